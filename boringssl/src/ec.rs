@@ -183,6 +183,32 @@ impl crypto::Ec for BoringEc {
             }
         }
     }
+
+    fn verify_nist_signature(
+        &self,
+        curve: ec::NistCurve,
+        public_key: &[u8],
+        digest: Digest,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, Error> {
+        let group = nist_curve_to_group(curve)?;
+        let mut ctx = ossl!(openssl::bn::BigNumContext::new())?;
+        let point = ossl!(openssl::ec::EcPoint::from_bytes(&group, public_key, &mut ctx))?;
+        let ec_key = ossl!(openssl::ec::EcKey::from_public_key(&group, &point))?;
+        let sig = ossl!(openssl::ecdsa::EcdsaSig::from_der(signature))?;
+        match digest_into_openssl(digest) {
+            Some(digest) => {
+                let hash = ossl!(openssl::hash::hash(digest, data))?;
+                Ok(ossl!(sig.verify(&hash, &ec_key))?)
+            }
+            None => {
+                // As for signing, undigested input beyond the key size is ignored.
+                let len = core::cmp::min(curve.coord_len(), data.len());
+                Ok(ossl!(sig.verify(&data[..len], &ec_key))?)
+            }
+        }
+    }
 }
 
 /// ECDH operation based on BoringSSL.
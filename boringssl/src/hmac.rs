@@ -98,7 +98,7 @@ impl crypto::AccumulatingOperation for BoringHmacOperation {
     }
 
     fn finish(self: Box<Self>) -> Result<Vec<u8>, Error> {
-        let mut output_len = ffi::EVP_MAX_MD_SIZE as u32;
+        let mut output_len = ffi::EVP_MAX_MD_SIZE;
         let mut output = vec_try![0; ffi::EVP_MAX_MD_SIZE as usize]?;
 
         // Safety: `self.ctx` is non-null and valid; `output_len` is correct size of `output`
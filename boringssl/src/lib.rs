@@ -34,6 +34,10 @@ pub mod des;
 pub mod ec;
 pub mod eq;
 pub mod hmac;
+#[cfg(all(feature = "pqc", ossl350))]
+pub mod mldsa;
+#[cfg(all(feature = "pqc", ossl350))]
+pub mod mlkem;
 pub mod rng;
 pub mod rsa;
 pub mod sha256;
@@ -0,0 +1,91 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! OpenSSL-based implementation of ML-DSA functionality (experimental).  This relies on ML-DSA
+//! support from OpenSSL 3.5 or later, which is not available in BoringSSL, so is only built if
+//! `build.rs` sets `cfg(ossl350)`.
+use crate::{openssl_err, ossl};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use kmr_common::{
+    crypto,
+    crypto::{mldsa, OpaqueOr},
+    explicit, Error, FallibleAllocExt,
+};
+use openssl::pkey::{KeyType, PKey, Private};
+
+/// [`crypto::MlDsa`] implementation based on OpenSSL.
+pub struct BoringMlDsa {
+    /// Zero-sized private field to force use of [`default()`] for initialization.
+    _priv: core::marker::PhantomData<()>,
+}
+
+impl core::default::Default for BoringMlDsa {
+    fn default() -> Self {
+        ffi::init();
+        Self { _priv: core::marker::PhantomData }
+    }
+}
+
+impl crypto::MlDsa for BoringMlDsa {
+    fn public_key(
+        &self,
+        key: &OpaqueOr<mldsa::Key>,
+        param_set: mldsa::ParameterSet,
+    ) -> Result<Vec<u8>, Error> {
+        let pkey = pkey_from_seed(explicit!(key)?, param_set)?;
+        Ok(ossl!(pkey.raw_public_key())?)
+    }
+
+    fn begin_sign(
+        &self,
+        key: OpaqueOr<mldsa::Key>,
+        param_set: mldsa::ParameterSet,
+    ) -> Result<Box<dyn crypto::AccumulatingOperation>, Error> {
+        let key = explicit!(key)?;
+        let pkey = pkey_from_seed(&key, param_set)?;
+        Ok(Box::new(BoringMlDsaSignOperation { pkey, pending_input: Vec::new() }))
+    }
+}
+
+/// ML-DSA signing operation based on OpenSSL.
+pub struct BoringMlDsaSignOperation {
+    pkey: PKey<Private>,
+    pending_input: Vec<u8>,
+}
+
+impl crypto::AccumulatingOperation for BoringMlDsaSignOperation {
+    fn update(&mut self, data: &[u8]) -> Result<(), Error> {
+        // Pure ML-DSA signs the whole message in one go, so accumulate it.
+        self.pending_input.try_extend_from_slice(data)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<Vec<u8>, Error> {
+        let mut signer = ossl!(openssl::sign::Signer::new_without_digest(&self.pkey))?;
+        let sig = ossl!(signer.sign_oneshot_to_vec(&self.pending_input))?;
+        Ok(sig)
+    }
+}
+
+fn pkey_from_seed(
+    key: &mldsa::Key,
+    param_set: mldsa::ParameterSet,
+) -> Result<PKey<Private>, Error> {
+    let key_type = match param_set {
+        mldsa::ParameterSet::MlDsa65 => KeyType::ML_DSA_65,
+        mldsa::ParameterSet::MlDsa87 => KeyType::ML_DSA_87,
+    };
+    Ok(ossl!(PKey::private_key_from_seed(None, key_type, None, &key.0))?)
+}
@@ -0,0 +1,139 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! OpenSSL-based implementation of ML-KEM functionality (experimental).  This relies on ML-KEM
+//! support from OpenSSL 3.5 or later, which is not available in BoringSSL, so is only built if
+//! `build.rs` sets `cfg(ossl350)`.
+use crate::{cvt, openssl_err, ossl};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ptr;
+use foreign_types::ForeignType;
+use kmr_common::{
+    crypto,
+    crypto::{mlkem, OpaqueOr},
+    explicit, km_err, vec_try, Error, FallibleAllocExt,
+};
+use openssl::pkey::{KeyType, PKey, Private};
+use openssl::pkey_ctx::PkeyCtx;
+
+/// [`crypto::MlKem`] implementation based on OpenSSL.
+pub struct BoringMlKem {
+    /// Zero-sized private field to force use of [`default()`] for initialization.
+    _priv: core::marker::PhantomData<()>,
+}
+
+impl core::default::Default for BoringMlKem {
+    fn default() -> Self {
+        ffi::init();
+        Self { _priv: core::marker::PhantomData }
+    }
+}
+
+impl crypto::MlKem for BoringMlKem {
+    fn encapsulation_key(&self, key: &OpaqueOr<mlkem::Key>) -> Result<Vec<u8>, Error> {
+        let pkey = pkey_from_seed(explicit!(key)?)?;
+        Ok(ossl!(pkey.raw_public_key())?)
+    }
+
+    fn begin_decapsulate(
+        &self,
+        key: OpaqueOr<mlkem::Key>,
+    ) -> Result<Box<dyn crypto::AccumulatingOperation>, Error> {
+        let key = explicit!(key)?;
+        let pkey = pkey_from_seed(&key)?;
+        Ok(Box::new(BoringMlKemDecapsulateOperation { pkey, pending_input: Vec::new() }))
+    }
+}
+
+/// ML-KEM decapsulation operation based on OpenSSL.
+pub struct BoringMlKemDecapsulateOperation {
+    pkey: PKey<Private>,
+    pending_input: Vec<u8>, // Limited to `mlkem::CIPHERTEXT_LEN`.
+}
+
+impl crypto::AccumulatingOperation for BoringMlKemDecapsulateOperation {
+    fn max_input_size(&self) -> Option<usize> {
+        Some(mlkem::CIPHERTEXT_LEN)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.pending_input.try_extend_from_slice(data)?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<Vec<u8>, Error> {
+        if self.pending_input.len() != mlkem::CIPHERTEXT_LEN {
+            return Err(km_err!(
+                InvalidInputLength,
+                "ML-KEM ciphertext has {} bytes, want {}",
+                self.pending_input.len(),
+                mlkem::CIPHERTEXT_LEN
+            ));
+        }
+        let ctx = ossl!(PkeyCtx::new(&self.pkey))?;
+        let mut secret = vec_try![0; mlkem::SHARED_SECRET_LEN]?;
+        let mut secret_len = secret.len();
+        // Safety: `ctx` is a valid context for an ML-KEM private key, and the output buffer is
+        // large enough for the shared secret (with its length passed alongside it).
+        unsafe {
+            cvt(ffi::EVP_PKEY_decapsulate_init(ctx.as_ptr(), ptr::null()))?;
+            cvt(ffi::EVP_PKEY_decapsulate(
+                ctx.as_ptr(),
+                secret.as_mut_ptr(),
+                &mut secret_len,
+                self.pending_input.as_ptr(),
+                self.pending_input.len(),
+            ))?;
+        }
+        secret.truncate(secret_len);
+        Ok(secret)
+    }
+}
+
+/// Encapsulate a fresh shared secret to the given ML-KEM-768 encapsulation key, returning the
+/// ciphertext and the shared secret.  This is the peer side of an ML-KEM key agreement operation,
+/// and is not needed by the TA itself.
+pub fn encapsulate(encapsulation_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let pkey = ossl!(PKey::public_key_from_raw_bytes_ex(
+        None,
+        KeyType::ML_KEM_768,
+        None,
+        encapsulation_key
+    ))?;
+    let ctx = ossl!(PkeyCtx::new(&pkey))?;
+    let mut ciphertext = vec_try![0; mlkem::CIPHERTEXT_LEN]?;
+    let mut ciphertext_len = ciphertext.len();
+    let mut secret = vec_try![0; mlkem::SHARED_SECRET_LEN]?;
+    let mut secret_len = secret.len();
+    // Safety: `ctx` is a valid context for an ML-KEM public key, and the output buffers are large
+    // enough for the ciphertext and shared secret (with their lengths passed alongside them).
+    unsafe {
+        cvt(ffi::EVP_PKEY_encapsulate_init(ctx.as_ptr(), ptr::null()))?;
+        cvt(ffi::EVP_PKEY_encapsulate(
+            ctx.as_ptr(),
+            ciphertext.as_mut_ptr(),
+            &mut ciphertext_len,
+            secret.as_mut_ptr(),
+            &mut secret_len,
+        ))?;
+    }
+    ciphertext.truncate(ciphertext_len);
+    secret.truncate(secret_len);
+    Ok((ciphertext, secret))
+}
+
+fn pkey_from_seed(key: &mlkem::Key) -> Result<PKey<Private>, Error> {
+    Ok(ossl!(PKey::private_key_from_seed(None, KeyType::ML_KEM_768, None, &key.0))?)
+}
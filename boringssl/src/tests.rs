@@ -65,3 +65,8 @@ fn test_des() {
 fn test_sha256() {
     kmr_tests::test_sha256(sha256::BoringSha256 {});
 }
+
+#[test]
+fn test_hmac_drbg() {
+    kmr_tests::test_hmac_drbg(hmac::BoringHmac {});
+}
@@ -95,6 +95,7 @@ fn main() {
     show_schema::<DestroyAttestationIdsResponse>();
     show_schema::<BeginRequest>();
     show_schema::<InternalBeginResult>(); // Special case
+    show_schema::<BeginResponse>();
     show_schema::<EarlyBootEndedRequest>();
     show_schema::<EarlyBootEndedResponse>();
     show_schema::<ConvertStorageKeyToEphemeralRequest>();
@@ -107,6 +108,8 @@ fn main() {
     show_schema::<UpdateResponse>();
     show_schema::<FinishRequest>();
     show_schema::<FinishResponse>();
+    show_schema::<FinishDeriveKeyRequest>();
+    show_schema::<FinishDeriveKeyResponse>();
     show_schema::<AbortRequest>();
     show_schema::<AbortResponse>();
 
@@ -127,6 +130,30 @@ fn main() {
     show_schema::<GenerateTimeStampRequest>();
     show_schema::<GenerateTimeStampResponse>();
 
+    show_schema::<GetRootOfTrustChallengeRequest>();
+    show_schema::<GetRootOfTrustChallengeResponse>();
+    show_schema::<GetRootOfTrustRequest>();
+    show_schema::<GetRootOfTrustResponse>();
+    show_schema::<SendRootOfTrustRequest>();
+    show_schema::<SendRootOfTrustResponse>();
+
+    show_schema::<SetHalInfoRequest>();
+    show_schema::<SetHalInfoResponse>();
+    show_schema::<SetBootInfoRequest>();
+    show_schema::<SetBootInfoResponse>();
+    show_schema::<AttestationIdInfo>();
+    show_schema::<SetAttestationIdsRequest>();
+    show_schema::<SetAttestationIdsResponse>();
+    show_schema::<SetHalVersionRequest>();
+    show_schema::<SetHalVersionResponse>();
+    show_schema::<SetAdditionalAttestationInfoRequest>();
+    show_schema::<SetAdditionalAttestationInfoResponse>();
+
+    show_schema::<audit::AuditEvent>();
+    show_schema::<audit::AuditRecord>();
+    show_schema::<DrainAuditLogRequest>();
+    show_schema::<DrainAuditLogResponse>();
+
     // Autogenerated enums
     show_schema::<PerformOpReq>();
     show_schema::<PerformOpRsp>();
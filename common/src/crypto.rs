@@ -35,8 +35,13 @@ use zeroize::ZeroizeOnDrop;
 
 pub mod aes;
 pub mod des;
+pub mod drbg;
 pub mod ec;
 pub mod hmac;
+#[cfg(feature = "pqc")]
+pub mod mldsa;
+#[cfg(feature = "pqc")]
+pub mod mlkem;
 pub mod rsa;
 mod traits;
 pub use traits::*;
@@ -81,6 +86,12 @@ pub enum KeyGenInfo {
     Ed25519,
     /// Generate an X25519 keypair.
     X25519,
+    /// Generate an ML-DSA keypair with the given parameter set.
+    #[cfg(feature = "pqc")]
+    MlDsa(mldsa::ParameterSet),
+    /// Generate an ML-KEM-768 keypair.
+    #[cfg(feature = "pqc")]
+    MlKem768,
 }
 
 /// Type of elliptic curve.
@@ -130,6 +141,10 @@ opaque_from_key!(des::Key);
 opaque_from_key!(hmac::Key);
 opaque_from_key!(rsa::Key);
 opaque_from_key!(ec::Key);
+#[cfg(feature = "pqc")]
+opaque_from_key!(mldsa::Key);
+#[cfg(feature = "pqc")]
+opaque_from_key!(mlkem::Key);
 
 impl<T> From<OpaqueKeyMaterial> for OpaqueOr<T> {
     fn from(k: OpaqueKeyMaterial) -> Self {
@@ -151,6 +166,12 @@ pub enum KeyMaterial {
     Rsa(OpaqueOr<rsa::Key>),
     /// Elliptic curve asymmetric key.
     Ec(EcCurve, CurveType, OpaqueOr<ec::Key>),
+    /// ML-DSA asymmetric key (experimental vendor extension).
+    #[cfg(feature = "pqc")]
+    MlDsa(mldsa::ParameterSet, OpaqueOr<mldsa::Key>),
+    /// ML-KEM-768 asymmetric key (experimental vendor extension).
+    #[cfg(feature = "pqc")]
+    MlKem(OpaqueOr<mlkem::Key>),
 }
 
 /// Macro that extracts the explicit key from an [`OpaqueOr`] wrapper.
@@ -171,6 +192,8 @@ impl KeyMaterial {
         match self {
             Self::Aes(_) | Self::TripleDes(_) | Self::Hmac(_) => false,
             Self::Ec(_, _, _) | Self::Rsa(_) => true,
+            #[cfg(feature = "pqc")]
+            Self::MlDsa(_, _) | Self::MlKem(_) => true,
         }
     }
 
@@ -196,14 +219,19 @@ impl KeyMaterial {
     pub fn subject_public_key_info<'a>(
         &'a self,
         buf: &'a mut Vec<u8>,
-        ec: &dyn Ec,
-        rsa: &dyn Rsa,
+        imp: &Implementation,
     ) -> Result<Option<SubjectPublicKeyInfoRef<'a>>, Error> {
         Ok(match self {
-            Self::Rsa(key) => Some(key.subject_public_key_info(buf, rsa)?),
+            Self::Rsa(key) => Some(key.subject_public_key_info(buf, &*imp.rsa)?),
             Self::Ec(curve, curve_type, key) => {
-                Some(key.subject_public_key_info(buf, ec, curve, curve_type)?)
+                Some(key.subject_public_key_info(buf, &*imp.ec, curve, curve_type)?)
             }
+            #[cfg(feature = "pqc")]
+            Self::MlDsa(param_set, key) => {
+                Some(key.subject_public_key_info(buf, &*imp.mldsa, *param_set)?)
+            }
+            #[cfg(feature = "pqc")]
+            Self::MlKem(key) => Some(key.subject_public_key_info(buf, &*imp.mlkem)?),
             _ => None,
         })
     }
@@ -223,6 +251,10 @@ impl core::fmt::Debug for KeyMaterial {
             Self::Hmac(_) => f.write_str("Hmac(...)"),
             Self::Rsa(_) => f.write_str("Rsa(...)"),
             Self::Ec(c, _, _) => f.write_fmt(format_args!("Ec({:?}, ...)", c)),
+            #[cfg(feature = "pqc")]
+            Self::MlDsa(p, _) => f.write_fmt(format_args!("MlDsa({:?}, ...)", p)),
+            #[cfg(feature = "pqc")]
+            Self::MlKem(_) => f.write_str("MlKem(...)"),
         }
     }
 }
@@ -325,6 +357,33 @@ impl AsCborValue for KeyMaterial {
                     Ok(Self::Ec(curve, curve_type, key.into()))
                 }
             }
+            #[cfg(feature = "pqc")]
+            x if x == Algorithm::MlDsa as i32 => {
+                let mut a = match raw_key_value {
+                    cbor::value::Value::Array(a) if a.len() == 2 => a,
+                    _ => return cbor_type_error(&raw_key_value, "arr len 2"),
+                };
+                let raw_key = <Vec<u8>>::from_cbor_value(a.remove(1))?;
+                let param_set = mldsa::ParameterSet::from_cbor_value(a.remove(0))?;
+                if opaque {
+                    Ok(Self::MlDsa(param_set, OpaqueKeyMaterial(raw_key).into()))
+                } else {
+                    let key = mldsa::Key::new_from_slice(&raw_key)
+                        .map_err(|_e| CborError::UnexpectedItem("bstr", "bstr len 32"))?;
+                    Ok(Self::MlDsa(param_set, key.into()))
+                }
+            }
+            #[cfg(feature = "pqc")]
+            x if x == Algorithm::MlKem as i32 => {
+                let raw_key = <Vec<u8>>::from_cbor_value(raw_key_value)?;
+                if opaque {
+                    Ok(Self::MlKem(OpaqueKeyMaterial(raw_key).into()))
+                } else {
+                    let key = mlkem::Key::new_from_slice(&raw_key)
+                        .map_err(|_e| CborError::UnexpectedItem("bstr", "bstr len 64"))?;
+                    Ok(Self::MlKem(key.into()))
+                }
+            }
             _ => Err(CborError::UnexpectedItem("unknown enum", "algo enum")),
         }
     }
@@ -369,6 +428,26 @@ impl AsCborValue for KeyMaterial {
                 ),
             ]
             .map_err(cbor_alloc_err)?,
+            #[cfg(feature = "pqc")]
+            Self::MlDsa(param_set, OpaqueOr::Opaque(OpaqueKeyMaterial(k))) => vec_try![
+                cbor::value::Value::Integer((Algorithm::MlDsa as i32).into()),
+                cbor::value::Value::Bool(true),
+                cbor::value::Value::Array(
+                    vec_try![
+                        cbor::value::Value::Integer((param_set as i32).into()),
+                        cbor::value::Value::Bytes(try_to_vec(&k)?),
+                    ]
+                    .map_err(cbor_alloc_err)?
+                ),
+            ]
+            .map_err(cbor_alloc_err)?,
+            #[cfg(feature = "pqc")]
+            Self::MlKem(OpaqueOr::Opaque(OpaqueKeyMaterial(k))) => vec_try![
+                cbor::value::Value::Integer((Algorithm::MlKem as i32).into()),
+                cbor::value::Value::Bool(true),
+                cbor::value::Value::Bytes(try_to_vec(&k)?),
+            ]
+            .map_err(cbor_alloc_err)?,
 
             Self::Aes(OpaqueOr::Explicit(k)) => vec_try![
                 cbor::value::Value::Integer((Algorithm::Aes as i32).into()),
@@ -412,6 +491,26 @@ impl AsCborValue for KeyMaterial {
                 ),
             ]
             .map_err(cbor_alloc_err)?,
+            #[cfg(feature = "pqc")]
+            Self::MlDsa(param_set, OpaqueOr::Explicit(k)) => vec_try![
+                cbor::value::Value::Integer((Algorithm::MlDsa as i32).into()),
+                cbor::value::Value::Bool(false),
+                cbor::value::Value::Array(
+                    vec_try![
+                        cbor::value::Value::Integer((param_set as i32).into()),
+                        cbor::value::Value::Bytes(try_to_vec(&k.0)?),
+                    ]
+                    .map_err(cbor_alloc_err)?,
+                ),
+            ]
+            .map_err(cbor_alloc_err)?,
+            #[cfg(feature = "pqc")]
+            Self::MlKem(OpaqueOr::Explicit(k)) => vec_try![
+                cbor::value::Value::Integer((Algorithm::MlKem as i32).into()),
+                cbor::value::Value::Bool(false),
+                cbor::value::Value::Bytes(try_to_vec(&k.0)?),
+            ]
+            .map_err(cbor_alloc_err)?,
         }))
     }
 
@@ -420,6 +519,22 @@ impl AsCborValue for KeyMaterial {
     }
 
     fn cddl_schema() -> Option<String> {
+        #[cfg(feature = "pqc")]
+        let pqc_choices = format!(
+            "
+  ; An ML-DSA key (experimental vendor extension, only present if the `pqc` feature is enabled)
+  ; holds the parameter set (65 or 87), and an explicit key is the 32-byte seed.
+  [{}, bool, [int, bstr]], ; {}
+  ; An explicit ML-KEM-768 key (experimental vendor extension, only present if the `pqc` feature
+  ; is enabled) is the 64-byte seed.
+  [{}, bool, bstr], ; {}",
+            Algorithm::MlDsa as i32,
+            "Algorithm_MlDsa",
+            Algorithm::MlKem as i32,
+            "Algorithm_MlKem",
+        );
+        #[cfg(not(feature = "pqc"))]
+        let pqc_choices = String::new();
         Some(format!(
             "&(
   ; For each variant the `bool` second entry indicates whether the bstr for the key material
@@ -433,7 +548,7 @@ impl AsCborValue for KeyMaterial {
   ; An explicit EC key for a NIST curve is in the form of an ASN.1 DER encoding of a
   ; `ECPrivateKey` structure, as specified by RFC 5915 section 3.
   ; An explicit EC key for curve 25519 is the raw key bytes.
-  [{}, bool, [EcCurve, CurveType, bstr]], ; {}
+  [{}, bool, [EcCurve, CurveType, bstr]], ; {}{}
 )",
             Algorithm::Aes as i32,
             "Algorithm_Aes",
@@ -445,6 +560,7 @@ impl AsCborValue for KeyMaterial {
             "Algorithm_Rsa",
             Algorithm::Ec as i32,
             "Algorithm_Ec",
+            pqc_choices,
         ))
     }
 }
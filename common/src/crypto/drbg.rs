@@ -0,0 +1,348 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic random bit generator (HMAC_DRBG with SHA-256, as per NIST SP 800-90A) that is
+//! seeded from a raw entropy source, with the source continuously monitored by the health tests
+//! described in NIST SP 800-90B section 4.4.
+
+use super::{hmac_sha256, Hmac, Rng, SHA256_DIGEST_LEN};
+use crate::{km_err, vec_try, Error, FallibleAllocExt};
+use alloc::{boxed::Box, vec::Vec};
+use log::error;
+use zeroize::ZeroizeOnDrop;
+
+/// Security strength of the DRBG, in bits.
+pub const SECURITY_STRENGTH_BITS: usize = 256;
+
+/// Number of generate requests allowed before the DRBG reseeds from the entropy source.
+pub const RESEED_INTERVAL: u64 = 1 << 16;
+
+/// Maximum number of bytes produced by a single generate request (SP 800-90A section 10.1).
+pub const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+
+/// Number of raw samples run through the health tests (and discarded) on start-up, as per SP
+/// 800-90B section 4.3.
+pub const STARTUP_SAMPLES: usize = 1024;
+
+/// Window size for the adaptive proportion test for non-binary sources (SP 800-90B section 4.4.2).
+pub const APT_WINDOW_SIZE: usize = 512;
+
+/// Adaptive proportion test cutoff values for a window of [`APT_WINDOW_SIZE`] samples, indexed by
+/// (min-entropy per sample - 1).  Each value is `1 + CRITBINOM(512, 2^-H, 1 - 2^-20)`, giving a
+/// false positive probability of 2^-20.
+const APT_CUTOFFS: [usize; 8] = [311, 177, 103, 62, 39, 25, 18, 13];
+
+/// Exponent of the false positive probability (2^-20) for the repetition count test.
+const RCT_ALPHA_EXPONENT: usize = 20;
+
+/// Source of raw entropy, such as a hardware noise source.  The samples that it produces are one
+/// byte each, and are not expected to be full entropy.
+pub trait EntropySource: Send {
+    /// Fill `dest` with raw samples from the noise source.
+    fn fill_raw(&mut self, dest: &mut [u8]) -> Result<(), Error>;
+}
+
+/// Continuous health tests for a noise source, as per SP 800-90B section 4.4.
+#[derive(Debug)]
+pub struct HealthTests {
+    /// Cutoff for the repetition count test.
+    rct_cutoff: usize,
+    /// Most recent sample, and the number of times it has been repeated.
+    rct_state: Option<(u8, usize)>,
+    /// Cutoff for the adaptive proportion test.
+    apt_cutoff: usize,
+    /// First sample of the current window, the number of times it has been seen in the window, and
+    /// the number of samples seen in the window.
+    apt_state: Option<(u8, usize, usize)>,
+}
+
+impl HealthTests {
+    /// Create a set of health tests for a noise source with the given assessed min-entropy per
+    /// (byte) sample, which must be between 1 and 8 bits.
+    pub fn new(min_entropy_bits: usize) -> Result<Self, Error> {
+        if !(1..=8).contains(&min_entropy_bits) {
+            return Err(km_err!(
+                InvalidArgument,
+                "min-entropy of {} bits per sample not in range 1..=8",
+                min_entropy_bits
+            ));
+        }
+        Ok(Self {
+            rct_cutoff: 1 + RCT_ALPHA_EXPONENT.div_ceil(min_entropy_bits),
+            rct_state: None,
+            apt_cutoff: APT_CUTOFFS[min_entropy_bits - 1],
+            apt_state: None,
+        })
+    }
+
+    /// Run the health tests on the next sample from the noise source.
+    pub fn check(&mut self, sample: u8) -> Result<(), Error> {
+        // Repetition count test (section 4.4.1).
+        self.rct_state = match self.rct_state {
+            Some((prev, count)) if prev == sample => {
+                if count + 1 >= self.rct_cutoff {
+                    return Err(km_err!(
+                        UnknownError,
+                        "noise source failed repetition count test: {} repeats",
+                        count + 1
+                    ));
+                }
+                Some((prev, count + 1))
+            }
+            _ => Some((sample, 1)),
+        };
+
+        // Adaptive proportion test (section 4.4.2).
+        self.apt_state = match self.apt_state {
+            Some((first, count, seen)) if seen < APT_WINDOW_SIZE => {
+                let count = if sample == first { count + 1 } else { count };
+                if count >= self.apt_cutoff {
+                    return Err(km_err!(
+                        UnknownError,
+                        "noise source failed adaptive proportion test: {} of {} samples",
+                        count,
+                        seen + 1
+                    ));
+                }
+                Some((first, count, seen + 1))
+            }
+            _ => Some((sample, 1, 1)),
+        };
+        Ok(())
+    }
+}
+
+/// Working state of the DRBG.
+#[derive(ZeroizeOnDrop)]
+struct State {
+    key: [u8; SHA256_DIGEST_LEN],
+    v: [u8; SHA256_DIGEST_LEN],
+    reseed_counter: u64,
+}
+
+/// HMAC_DRBG instance with SHA-256, seeded from a health-tested [`EntropySource`].  Any failure of
+/// the entropy source (including a health test failure) is latched, and all subsequent requests for
+/// random data fail (with [`Rng::fill_bytes`] producing zeros, and [`Rng::failed`] returning
+/// `true`).
+pub struct HmacDrbg<S: EntropySource> {
+    hmac: Box<dyn Hmac>,
+    source: S,
+    health: HealthTests,
+    min_entropy_bits: usize,
+    state: Option<State>,
+}
+
+impl<S: EntropySource> HmacDrbg<S> {
+    /// Create a DRBG that is seeded from `source`, where `min_entropy_bits` gives the assessed
+    /// min-entropy per sample produced by the source.  The optional `personalization` string is
+    /// mixed into the initial seed.
+    pub fn new(
+        hmac: Box<dyn Hmac>,
+        source: S,
+        min_entropy_bits: usize,
+        personalization: &[u8],
+    ) -> Result<Self, Error> {
+        let health = HealthTests::new(min_entropy_bits)?;
+        let mut drbg = Self { hmac, source, health, min_entropy_bits, state: None };
+
+        // Start-up health testing of the noise source.
+        drbg.raw_samples(STARTUP_SAMPLES)?;
+
+        // Instantiate (SP 800-90A section 10.1.2.3), using entropy input together with a nonce of
+        // half the security strength, both taken from the noise source.
+        let mut seed_material =
+            drbg.raw_samples(drbg.samples_for_bits(SECURITY_STRENGTH_BITS * 3 / 2))?;
+        seed_material.try_extend_from_slice(personalization)?;
+        let mut state =
+            State { key: [0; SHA256_DIGEST_LEN], v: [0x01; SHA256_DIGEST_LEN], reseed_counter: 1 };
+        drbg.update(&mut state, &[&seed_material])?;
+        drbg.state = Some(state);
+        Ok(drbg)
+    }
+
+    /// Fill `dest` with random data, reseeding from the entropy source as needed.  On failure,
+    /// `dest` is zeroed.
+    pub fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        for chunk in dest.chunks_mut(MAX_BYTES_PER_REQUEST) {
+            let result = self.generate(chunk);
+            if result.is_err() {
+                // Fail closed, and stay failed.
+                self.state = None;
+                dest.fill(0);
+                return result;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reseed from the entropy source (SP 800-90A section 10.1.2.4), also mixing in the provided
+    /// `additional_input`.
+    pub fn reseed(&mut self, additional_input: &[u8]) -> Result<(), Error> {
+        let result = self.reseed_state(additional_input);
+        if result.is_err() {
+            self.state = None;
+        }
+        result
+    }
+
+    fn reseed_state(&mut self, additional_input: &[u8]) -> Result<(), Error> {
+        let mut state =
+            self.state.take().ok_or_else(|| km_err!(UnknownError, "DRBG in failed state"))?;
+        let entropy = self.raw_samples(self.samples_for_bits(SECURITY_STRENGTH_BITS))?;
+        self.update(&mut state, &[&entropy, additional_input])?;
+        state.reseed_counter = 1;
+        self.state = Some(state);
+        Ok(())
+    }
+
+    /// Generate random data (SP 800-90A section 10.1.2.5).
+    fn generate(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        if self.state.as_ref().map(|s| s.reseed_counter > RESEED_INTERVAL).unwrap_or(false) {
+            self.reseed_state(&[])?;
+        }
+        let mut state =
+            self.state.take().ok_or_else(|| km_err!(UnknownError, "DRBG in failed state"))?;
+        for chunk in dest.chunks_mut(SHA256_DIGEST_LEN) {
+            state.v = self.hmac(&state.key, &[&state.v])?;
+            chunk.copy_from_slice(&state.v[..chunk.len()]);
+        }
+        self.update(&mut state, &[])?;
+        state.reseed_counter += 1;
+        self.state = Some(state);
+        Ok(())
+    }
+
+    /// HMAC_DRBG update function (SP 800-90A section 10.1.2.2), where the provided data is the
+    /// concatenation of `provided`.
+    fn update(&self, state: &mut State, provided: &[&[u8]]) -> Result<(), Error> {
+        let empty = provided.iter().all(|data| data.is_empty());
+        for round in [0x00u8, 0x01] {
+            if round == 0x01 && empty {
+                break;
+            }
+            let mut data = Vec::new();
+            data.try_extend_from_slice(&state.v)?;
+            data.try_push(round)?;
+            for input in provided {
+                data.try_extend_from_slice(input)?;
+            }
+            state.key = self.hmac(&state.key, &[&data])?;
+            state.v = self.hmac(&state.key, &[&state.v])?;
+        }
+        Ok(())
+    }
+
+    fn hmac(&self, key: &[u8], data: &[&[u8]]) -> Result<[u8; SHA256_DIGEST_LEN], Error> {
+        let mut input = Vec::new();
+        for d in data {
+            input.try_extend_from_slice(d)?;
+        }
+        let mac = hmac_sha256(&*self.hmac, key, &input)?;
+        mac.try_into().map_err(|_e| km_err!(UnknownError, "unexpected HMAC-SHA256 length"))
+    }
+
+    /// Number of raw samples needed to provide `bits` of min-entropy.
+    fn samples_for_bits(&self, bits: usize) -> usize {
+        bits.div_ceil(self.min_entropy_bits)
+    }
+
+    /// Retrieve `count` raw samples from the noise source, running the health tests on each.
+    fn raw_samples(&mut self, count: usize) -> Result<Vec<u8>, Error> {
+        let mut samples = vec_try![0; count]?;
+        self.source.fill_raw(&mut samples)?;
+        for sample in &samples {
+            self.health.check(*sample)?;
+        }
+        Ok(samples)
+    }
+}
+
+impl<S: EntropySource> Rng for HmacDrbg<S> {
+    fn add_entropy(&mut self, data: &[u8]) {
+        // Mix the data into the working state without requiring fresh input from the source.
+        if let Some(mut state) = self.state.take() {
+            match self.update(&mut state, &[data]) {
+                Ok(()) => self.state = Some(state),
+                Err(e) => error!("failed to add entropy to DRBG: {:?}", e),
+            }
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // The `Rng` trait has no way of reporting errors, so the failure is latched for the caller
+        // to check with `failed()`.
+        if let Err(e) = self.try_fill_bytes(dest) {
+            error!("DRBG failure: {:?}", e);
+        }
+    }
+
+    fn failed(&self) -> bool {
+        self.state.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_tests_pass() {
+        for min_entropy_bits in 1..=8 {
+            let mut health = HealthTests::new(min_entropy_bits).unwrap();
+            for i in 0..10 * APT_WINDOW_SIZE {
+                health.check((i * 37) as u8).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_health_tests_invalid() {
+        assert!(HealthTests::new(0).is_err());
+        assert!(HealthTests::new(9).is_err());
+    }
+
+    #[test]
+    fn test_repetition_count_test() {
+        // H=8 gives a cutoff of 1 + ceil(20/8) = 4.
+        let mut health = HealthTests::new(8).unwrap();
+        health.check(1).unwrap();
+        health.check(2).unwrap();
+        health.check(2).unwrap();
+        health.check(2).unwrap();
+        assert!(health.check(2).is_err());
+    }
+
+    #[test]
+    fn test_adaptive_proportion_test() {
+        // H=8 gives a cutoff of 13 per window of 512 samples; alternate the first sample with
+        // distinct values so that the repetition count test doesn't fire.
+        let mut health = HealthTests::new(8).unwrap();
+        for i in 0..12 {
+            health.check(0).unwrap();
+            health.check(i + 1).unwrap();
+        }
+        assert!(health.check(0).is_err());
+
+        // A new window resets the count.
+        let mut health = HealthTests::new(8).unwrap();
+        for i in 0..APT_WINDOW_SIZE {
+            let sample = if i % 64 == 0 { 0 } else { (i % 255) as u8 + 1 };
+            health.check(sample).unwrap();
+        }
+        for i in 0..12 {
+            health.check(0).unwrap();
+            health.check(i + 1).unwrap();
+        }
+    }
+}
@@ -395,6 +395,59 @@ pub fn import_sec1_private_key(data: &[u8]) -> Result<KeyMaterial, Error> {
     import_pkcs8_key_impl(&pkcs8_key)
 }
 
+/// Import an NIST EC key in SEC1 ECPrivateKey format, paired with a `SubjectPublicKeyInfo` that
+/// holds the corresponding public key.  Any curve parameters or public key that are missing from
+/// the ECPrivateKey are filled in from the `SubjectPublicKeyInfo`; any that are present must match.
+pub fn import_sec1_key_with_spki(data: &[u8], spki: &[u8]) -> Result<KeyMaterial, Error> {
+    let mut ec_key = sec1::EcPrivateKey::from_der(data)
+        .map_err(|e| der_err!(e, "failed to parse ECPrivateKey"))?;
+    let spki = SubjectPublicKeyInfoRef::from_der(spki)
+        .map_err(|e| der_err!(e, "failed to parse SubjectPublicKeyInfo"))?;
+    if spki.algorithm.oid != X509_NIST_OID {
+        return Err(km_err!(
+            ImportParameterMismatch,
+            "unexpected OID {:?} in SubjectPublicKeyInfo for SEC1 import",
+            spki.algorithm.oid
+        ));
+    }
+    let spki_curve_oid: pkcs8::ObjectIdentifier = spki
+        .algorithm
+        .parameters
+        .ok_or_else(|| km_err!(InvalidArgument, "SubjectPublicKeyInfo has no curve parameters"))?
+        .decode_as()
+        .map_err(|e| der_err!(e, "failed to parse SubjectPublicKeyInfo curve OID"))?;
+    match ec_key.parameters.and_then(|params| params.named_curve()) {
+        Some(curve_oid) if curve_oid != spki_curve_oid => {
+            return Err(km_err!(
+                ImportParameterMismatch,
+                "ECPrivateKey curve {:?} doesn't match SubjectPublicKeyInfo curve {:?}",
+                curve_oid,
+                spki_curve_oid
+            ));
+        }
+        Some(_) => {}
+        None => ec_key.parameters = Some(sec1::EcParameters::NamedCurve(spki_curve_oid)),
+    }
+
+    let spki_public_key = spki
+        .subject_public_key
+        .as_bytes()
+        .ok_or_else(|| km_err!(InvalidArgument, "SubjectPublicKeyInfo key has unused bits"))?;
+    match ec_key.public_key {
+        Some(public_key) if public_key != spki_public_key => {
+            return Err(km_err!(
+                ImportParameterMismatch,
+                "ECPrivateKey public key doesn't match SubjectPublicKeyInfo"
+            ));
+        }
+        Some(_) => {}
+        None => ec_key.public_key = Some(spki_public_key),
+    }
+
+    let data = ec_key.to_der().map_err(|e| der_err!(e, "failed to encode ECPrivateKey"))?;
+    import_sec1_private_key(&data)
+}
+
 /// Import an EC key in PKCS#8 format.
 pub fn import_pkcs8_key(data: &[u8]) -> Result<KeyMaterial, Error> {
     let key_info = pkcs8::PrivateKeyInfo::try_from(data)
@@ -671,4 +724,58 @@ mod tests {
             panic!("unexpected key type");
         }
     }
+
+    #[test]
+    fn test_sec1_ec_import_with_spki() {
+        // As above, but without the optional parameters and public key.
+        let key_data = hex::decode(concat!(
+            "3025",   // SEQUENCE len x25 (ECPrivateKey)
+            "020101", // INTEGER 1 = (ecPrivkeyVer1)
+            "0420",   // OCTET STRING len x20 (privateKey)
+            "a6a30ca3dc87b58763736400e7e86260",
+            "9e8311f41e6b89888c33753218168517",
+        ))
+        .unwrap();
+        let spki_data = |curve_oid: &str| {
+            hex::decode(
+                [
+                    "3059",           // SEQUENCE len x59 (SubjectPublicKeyInfo)
+                    "3013",           // SEQUENCE len x13 (AlgorithmIdentifier)
+                    "0607",           // OBJECT IDENTIFIER len 7
+                    "2a8648ce3d0201", // 1.2.840.10045.2.1=ecPublicKey
+                    "0608",           // OBJECT IDENTIFIER len 8 (NamedCurve)
+                    curve_oid,
+                    "0342", // BIT STRING len x42
+                    "00",   // no pad bits
+                    "0481e4ce20d8be3dd40b940b3a3ba3e8",
+                    "cf5a3f2156eceb4debb8fce83cbe4a48",
+                    "bd576a03eebf77d329a438fcdc509f37",
+                    "1f092cad41e2ecf9f25cd82f31500f33",
+                    "8e",
+                ]
+                .concat(),
+            )
+            .unwrap()
+        };
+        assert!(import_sec1_private_key(&key_data).is_err());
+
+        let key = import_sec1_key_with_spki(&key_data, &spki_data("2a8648ce3d030107"))
+            .expect("SEC1+SPKI parse failed");
+        let KeyMaterial::Ec(curve, curve_type, key) = key else {
+            panic!("unexpected key type");
+        };
+        assert_eq!(curve, EcCurve::P256);
+        assert_eq!(curve_type, CurveType::Nist);
+        // Curve parameters and public key have been filled in from the SPKI.
+        let OpaqueOr::Explicit(Key::P256(NistKey(der))) = &key else {
+            panic!("unexpected key variant");
+        };
+        let ec_key = sec1::EcPrivateKey::from_der(der).unwrap();
+        assert_eq!(ec_key.parameters.unwrap().named_curve(), Some(ALGO_PARAM_P256_OID));
+        assert_eq!(ec_key.public_key.unwrap()[0], SEC1_UNCOMPRESSED_PREFIX);
+
+        // An SPKI for a different curve (here 1.2.840.10045.3.1.1=prime192v1) is rejected.
+        let result = import_sec1_key_with_spki(&key_data, &spki_data("2a8648ce3d030101"));
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,163 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Functionality related to ML-DSA (FIPS 204) support, an experimental vendor extension.
+
+use super::{KeyMaterial, OpaqueOr};
+use crate::{der_err, km_err, Error, FallibleAllocExt};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use der::{asn1::BitStringRef, asn1::OctetStringRef, Decode, Sequence};
+use enumn::N;
+use kmr_derive::AsCborValue;
+use kmr_wire::{cbor_type_error, AsCborValue, CborError, KeySizeInBits};
+use spki::{AlgorithmIdentifier, SubjectPublicKeyInfo, SubjectPublicKeyInfoRef};
+use zeroize::ZeroizeOnDrop;
+
+/// Size (in bytes) of the seed from which an ML-DSA key pair is derived.
+pub const SEED_LEN: usize = 32;
+
+/// OID value for ML-DSA-65 keys and signatures in PKCS#8 and X.509; see RFC 9881 s2.
+pub const ML_DSA_65_OID: pkcs8::ObjectIdentifier =
+    pkcs8::ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.18");
+
+/// OID value for ML-DSA-87 keys and signatures in PKCS#8 and X.509; see RFC 9881 s2.
+pub const ML_DSA_87_OID: pkcs8::ObjectIdentifier =
+    pkcs8::ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.19");
+
+/// ML-DSA parameter set.  The discriminant is also the `KEY_SIZE` value used to select the
+/// parameter set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, AsCborValue, N)]
+#[repr(i32)]
+pub enum ParameterSet {
+    /// ML-DSA-65 (NIST security category 3).
+    MlDsa65 = 65,
+    /// ML-DSA-87 (NIST security category 5).
+    MlDsa87 = 87,
+}
+
+impl ParameterSet {
+    /// Determine the parameter set from a `KEY_SIZE` value.
+    pub fn new(key_size: KeySizeInBits) -> Result<Self, Error> {
+        i32::try_from(key_size.0).ok().and_then(Self::n).ok_or_else(|| {
+            km_err!(UnsupportedKeySize, "unsupported KEY_SIZE {:?} for ML-DSA", key_size)
+        })
+    }
+
+    /// Return the `KEY_SIZE` value that corresponds to the parameter set.
+    pub fn key_size(&self) -> KeySizeInBits {
+        KeySizeInBits(*self as u32)
+    }
+
+    /// Return the OID that identifies keys and signatures for the parameter set.
+    pub fn oid(&self) -> pkcs8::ObjectIdentifier {
+        match self {
+            Self::MlDsa65 => ML_DSA_65_OID,
+            Self::MlDsa87 => ML_DSA_87_OID,
+        }
+    }
+
+    /// Determine the parameter set from an OID.
+    pub fn from_oid(oid: &pkcs8::ObjectIdentifier) -> Result<Self, Error> {
+        match *oid {
+            ML_DSA_65_OID => Ok(Self::MlDsa65),
+            ML_DSA_87_OID => Ok(Self::MlDsa87),
+            _ => Err(km_err!(UnsupportedAlgorithm, "unexpected OID {:?} for ML-DSA key", oid)),
+        }
+    }
+}
+
+/// An ML-DSA private key, held as the seed from which the key pair is derived (FIPS 204 s6.1).
+#[derive(Clone, PartialEq, Eq, ZeroizeOnDrop)]
+pub struct Key(pub [u8; SEED_LEN]);
+
+impl Key {
+    /// Create a key from a seed.
+    pub fn new_from_slice(data: &[u8]) -> Result<Self, Error> {
+        let seed = data.try_into().map_err(|_e| {
+            km_err!(InvalidInputLength, "ML-DSA seed has {} bytes, want {}", data.len(), SEED_LEN)
+        })?;
+        Ok(Self(seed))
+    }
+}
+
+impl OpaqueOr<Key> {
+    /// Encode into `buf` the public key information as an ASN.1 DER encodable
+    /// `SubjectPublicKeyInfo`, as described in RFC 5280 section 4.1.
+    ///
+    /// For ML-DSA keys, the contents are described in RFC 9881 section 4.
+    /// - The `AlgorithmIdentifier` has an `algorithm` OID of 2.16.840.1.101.3.4.3.18 (ML-DSA-65)
+    ///   or 2.16.840.1.101.3.4.3.19 (ML-DSA-87).
+    /// - The `parameters` are absent.
+    /// - The `subjectPublicKey` holds the raw encoded public key.
+    pub fn subject_public_key_info<'a>(
+        &'a self,
+        buf: &'a mut Vec<u8>,
+        mldsa: &dyn super::MlDsa,
+        param_set: ParameterSet,
+    ) -> Result<SubjectPublicKeyInfoRef<'a>, Error> {
+        buf.try_extend_from_slice(&mldsa.public_key(self, param_set)?)?;
+        Ok(SubjectPublicKeyInfo {
+            algorithm: AlgorithmIdentifier { oid: param_set.oid(), parameters: None },
+            subject_public_key: BitStringRef::from_bytes(buf)
+                .map_err(|e| km_err!(UnknownError, "invalid bitstring: {e:?}"))?,
+        })
+    }
+}
+
+/// Both forms of an ML-DSA private key, as described in RFC 9881 section 6.
+///
+/// ```asn1
+/// both SEQUENCE {
+///     seed OCTET STRING (SIZE (32)),
+///     expandedKey OCTET STRING }
+/// ```
+#[derive(Sequence)]
+struct BothForms<'a> {
+    seed: OctetStringRef<'a>,
+    #[allow(dead_code)]
+    expanded_key: OctetStringRef<'a>,
+}
+
+/// DER tag for the `seed [0] IMPLICIT OCTET STRING` choice of an ML-DSA private key.
+const SEED_CHOICE_TAG: u8 = 0x80;
+
+/// Import an ML-DSA key from a DER-encoded PKCS#8 `PrivateKeyInfo`.  The private key must include
+/// the seed form (RFC 9881 section 6); keys that only have the expanded form cannot be imported,
+/// and the expanded form is ignored if both are present.
+pub fn import_pkcs8_key(data: &[u8]) -> Result<KeyMaterial, Error> {
+    let key_info = pkcs8::PrivateKeyInfo::try_from(data)
+        .map_err(|_| km_err!(InvalidArgument, "failed to parse PKCS#8 ML-DSA key"))?;
+    if key_info.algorithm.parameters.is_some() {
+        return Err(km_err!(InvalidArgument, "unexpected parameters for ML-DSA key"));
+    }
+    let param_set = ParameterSet::from_oid(&key_info.algorithm.oid)?;
+    let seed = match key_info.private_key {
+        [SEED_CHOICE_TAG, len, seed @ ..] if *len as usize == SEED_LEN => seed,
+        both @ [0x30, ..] => BothForms::from_der(both)
+            .map_err(|e| der_err!(e, "failed to parse ML-DSA private key"))?
+            .seed
+            .as_bytes(),
+        _ => {
+            return Err(km_err!(
+                UnsupportedKeyFormat,
+                "ML-DSA private key without seed not supported"
+            ))
+        }
+    };
+    Ok(KeyMaterial::MlDsa(param_set, Key::new_from_slice(seed)?.into()))
+}
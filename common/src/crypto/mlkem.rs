@@ -0,0 +1,131 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Functionality related to ML-KEM (FIPS 203) support, an experimental vendor extension.
+//!
+//! Only the ML-KEM-768 parameter set is supported.  A key agreement operation takes an ML-KEM
+//! ciphertext as input, and emits the shared secret that results from decapsulating it.
+
+use super::{KeyMaterial, OpaqueOr};
+use crate::{der_err, km_err, Error, FallibleAllocExt};
+use alloc::vec::Vec;
+use der::{asn1::BitStringRef, asn1::OctetStringRef, Decode, Sequence};
+use kmr_wire::KeySizeInBits;
+use spki::{AlgorithmIdentifier, SubjectPublicKeyInfo, SubjectPublicKeyInfoRef};
+use zeroize::ZeroizeOnDrop;
+
+/// Size (in bytes) of the seed `d || z` from which an ML-KEM key pair is derived.
+pub const SEED_LEN: usize = 64;
+
+/// Size (in bytes) of an ML-KEM-768 encapsulation key.
+pub const ENCAPSULATION_KEY_LEN: usize = 1184;
+
+/// Size (in bytes) of an ML-KEM-768 ciphertext.
+pub const CIPHERTEXT_LEN: usize = 1088;
+
+/// Size (in bytes) of an ML-KEM shared secret.
+pub const SHARED_SECRET_LEN: usize = 32;
+
+/// `KEY_SIZE` value that indicates ML-KEM-768.
+pub const KEY_SIZE: KeySizeInBits = KeySizeInBits(768);
+
+/// OID value for ML-KEM-768 keys in PKCS#8 and X.509; see draft-ietf-lamps-kyber-certificates.
+pub const ML_KEM_768_OID: pkcs8::ObjectIdentifier =
+    pkcs8::ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.2");
+
+/// An ML-KEM-768 private key, held as the seed from which the key pair is derived (FIPS 203
+/// s7.1).
+#[derive(Clone, PartialEq, Eq, ZeroizeOnDrop)]
+pub struct Key(pub [u8; SEED_LEN]);
+
+impl Key {
+    /// Create a key from a seed.
+    pub fn new_from_slice(data: &[u8]) -> Result<Self, Error> {
+        let seed = data.try_into().map_err(|_e| {
+            km_err!(InvalidInputLength, "ML-KEM seed has {} bytes, want {}", data.len(), SEED_LEN)
+        })?;
+        Ok(Self(seed))
+    }
+}
+
+impl OpaqueOr<Key> {
+    /// Encode into `buf` the public key information as an ASN.1 DER encodable
+    /// `SubjectPublicKeyInfo`, as described in RFC 5280 section 4.1.
+    ///
+    /// For ML-KEM keys, the contents are described in draft-ietf-lamps-kyber-certificates.
+    /// - The `AlgorithmIdentifier` has an `algorithm` OID of 2.16.840.1.101.3.4.4.2.
+    /// - The `parameters` are absent.
+    /// - The `subjectPublicKey` holds the raw encapsulation key.
+    pub fn subject_public_key_info<'a>(
+        &'a self,
+        buf: &'a mut Vec<u8>,
+        mlkem: &dyn super::MlKem,
+    ) -> Result<SubjectPublicKeyInfoRef<'a>, Error> {
+        buf.try_extend_from_slice(&mlkem.encapsulation_key(self)?)?;
+        Ok(SubjectPublicKeyInfo {
+            algorithm: AlgorithmIdentifier { oid: ML_KEM_768_OID, parameters: None },
+            subject_public_key: BitStringRef::from_bytes(buf)
+                .map_err(|e| km_err!(UnknownError, "invalid bitstring: {e:?}"))?,
+        })
+    }
+}
+
+/// Both forms of an ML-KEM private key, as described in draft-ietf-lamps-kyber-certificates.
+///
+/// ```asn1
+/// both SEQUENCE {
+///     seed OCTET STRING (SIZE (64)),
+///     expandedKey OCTET STRING }
+/// ```
+#[derive(Sequence)]
+struct BothForms<'a> {
+    seed: OctetStringRef<'a>,
+    #[allow(dead_code)]
+    expanded_key: OctetStringRef<'a>,
+}
+
+/// DER tag for the `seed [0] IMPLICIT OCTET STRING` choice of an ML-KEM private key.
+const SEED_CHOICE_TAG: u8 = 0x80;
+
+/// Import an ML-KEM-768 key from a DER-encoded PKCS#8 `PrivateKeyInfo`.  The private key must
+/// include the seed form; keys that only have the expanded form cannot be imported, and the
+/// expanded form is ignored if both are present.
+pub fn import_pkcs8_key(data: &[u8]) -> Result<KeyMaterial, Error> {
+    let key_info = pkcs8::PrivateKeyInfo::try_from(data)
+        .map_err(|_| km_err!(InvalidArgument, "failed to parse PKCS#8 ML-KEM key"))?;
+    if key_info.algorithm.oid != ML_KEM_768_OID {
+        return Err(km_err!(
+            UnsupportedAlgorithm,
+            "unexpected OID {:?} for ML-KEM key",
+            key_info.algorithm.oid
+        ));
+    }
+    if key_info.algorithm.parameters.is_some() {
+        return Err(km_err!(InvalidArgument, "unexpected parameters for ML-KEM key"));
+    }
+    let seed = match key_info.private_key {
+        [SEED_CHOICE_TAG, len, seed @ ..] if *len as usize == SEED_LEN => seed,
+        both @ [0x30, ..] => BothForms::from_der(both)
+            .map_err(|e| der_err!(e, "failed to parse ML-KEM private key"))?
+            .seed
+            .as_bytes(),
+        _ => {
+            return Err(km_err!(
+                UnsupportedKeyFormat,
+                "ML-KEM private key without seed not supported"
+            ))
+        }
+    };
+    Ok(KeyMaterial::MlKem(Key::new_from_slice(seed)?.into()))
+}
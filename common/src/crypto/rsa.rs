@@ -248,3 +248,36 @@ pub fn import_pkcs1_key(
 
     Ok((KeyMaterial::Rsa(key.into()), KeySizeInBits(key_size), RsaExponent(pub_exponent)))
 }
+
+/// Import an RSA key in PKCS#1 format, paired with a `SubjectPublicKeyInfo` that holds the
+/// corresponding public key (which must match).  Also returns the key size in bits and public
+/// exponent.
+pub fn import_pkcs1_key_with_spki(
+    private_key: &[u8],
+    spki: &[u8],
+) -> Result<(KeyMaterial, KeySizeInBits, RsaExponent), Error> {
+    let parsed_key = RsaPrivateKey::from_der(private_key)
+        .map_err(|e| der_err!(e, "failed to parse RsaPrivateKey"))?;
+    let spki = SubjectPublicKeyInfoRef::from_der(spki)
+        .map_err(|e| der_err!(e, "failed to parse SubjectPublicKeyInfo"))?;
+    if spki.algorithm.oid != X509_OID {
+        return Err(km_err!(
+            ImportParameterMismatch,
+            "unexpected OID {:?} in SubjectPublicKeyInfo for PKCS#1 import",
+            spki.algorithm.oid
+        ));
+    }
+    let spki_public_key = spki
+        .subject_public_key
+        .as_bytes()
+        .ok_or_else(|| km_err!(InvalidArgument, "SubjectPublicKeyInfo key has unused bits"))?;
+    let spki_public_key = pkcs1::RsaPublicKey::from_der(spki_public_key)
+        .map_err(|e| der_err!(e, "failed to parse RSAPublicKey"))?;
+    if spki_public_key != parsed_key.public_key() {
+        return Err(km_err!(
+            ImportParameterMismatch,
+            "RsaPrivateKey doesn't match SubjectPublicKeyInfo public key"
+        ));
+    }
+    import_pkcs1_key(private_key)
+}
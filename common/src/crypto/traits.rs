@@ -47,6 +47,14 @@ pub struct Implementation {
     /// EC implementation.
     pub ec: Box<dyn Ec>,
 
+    /// ML-DSA implementation (experimental).
+    #[cfg(feature = "pqc")]
+    pub mldsa: Box<dyn MlDsa>,
+
+    /// ML-KEM implementation (experimental).
+    #[cfg(feature = "pqc")]
+    pub mlkem: Box<dyn MlKem>,
+
     /// CKDF implementation.
     pub ckdf: Box<dyn Ckdf>,
 
@@ -64,6 +72,12 @@ pub trait Rng: Send {
     fn add_entropy(&mut self, data: &[u8]);
     /// Generate random data.
     fn fill_bytes(&mut self, dest: &mut [u8]);
+    /// Indicate whether the generator has failed, in which case the output of
+    /// [`Rng::fill_bytes`] must not be used.  Implementations that can fail should latch the
+    /// failure and return zeros from `fill_bytes`, rather than panicking.
+    fn failed(&self) -> bool {
+        false
+    }
     /// Return a random `u64` value.
     fn next_u64(&mut self) -> u64 {
         let mut buf = [0u8; 8];
@@ -261,6 +275,25 @@ pub trait Rsa: Send {
         rsa::import_pkcs8_key(data)
     }
 
+    /// Import an RSA key in PKCS#1 format, optionally paired with a DER-encoded
+    /// `SubjectPublicKeyInfo` that holds the corresponding public key, also returning the key size
+    /// in bits and public exponent.  Key import parameters are passed in for reference, to allow
+    /// for implementations that might have parameter-specific behaviour.
+    ///
+    /// This backs the vendor-extension [`keymint::KeyFormat::Pkcs1`] import format, which is only
+    /// reached if enabled by `KeyPolicy::vendor_import_formats`.
+    fn import_pkcs1_key(
+        &self,
+        data: &[u8],
+        spki: Option<&[u8]>,
+        _params: &[keymint::KeyParam],
+    ) -> Result<(KeyMaterial, KeySizeInBits, RsaExponent), Error> {
+        match spki {
+            Some(spki) => rsa::import_pkcs1_key_with_spki(data, spki),
+            None => rsa::import_pkcs1_key(data),
+        }
+    }
+
     /// Return the public key data corresponds to the provided private `key`,
     /// as an ASN.1 DER-encoded `SEQUENCE` as per RFC 3279 section 2.3.1:
     ///     ```asn1
@@ -330,6 +363,25 @@ pub trait Ec: Send {
         ec::import_pkcs8_key(data)
     }
 
+    /// Import a NIST EC key in SEC1 ECPrivateKey format, optionally paired with a DER-encoded
+    /// `SubjectPublicKeyInfo` that holds the corresponding public key.  Key import parameters are
+    /// passed in for reference, to allow for implementations that might have parameter-specific
+    /// behaviour.
+    ///
+    /// This backs the vendor-extension [`keymint::KeyFormat::Sec1`] import format, which is only
+    /// reached if enabled by `KeyPolicy::vendor_import_formats`.
+    fn import_sec1_key(
+        &self,
+        data: &[u8],
+        spki: Option<&[u8]>,
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        match spki {
+            Some(spki) => ec::import_sec1_key_with_spki(data, spki),
+            None => ec::import_sec1_private_key(data),
+        }
+    }
+
     /// Import a 32-byte raw Ed25519 key.  Key import parameters are passed in for reference, to
     /// allow for implementations that might have parameter-specific behaviour.
     fn import_raw_ed25519_key(
@@ -403,6 +455,127 @@ pub trait Ec: Send {
         key: OpaqueOr<ec::Key>,
         digest: Digest,
     ) -> Result<Box<dyn AccumulatingOperation>, Error>;
+
+    /// Verify an ECDSA `signature` over `data`, using the NIST curve public key `public_key`
+    /// (provided as a SEC-1 encoded uncompressed point).  The `signature` is an ASN.1 DER-encoded
+    /// `ECDSA-Sig-Value`.  Return `Ok(false)` if the signature is well-formed but does not verify.
+    /// KeyMint itself only verifies signatures as part of its self tests, which skip the check if
+    /// this returns `ErrorCode::Unimplemented` (as the default implementation does).
+    fn verify_nist_signature(
+        &self,
+        _curve: ec::NistCurve,
+        _public_key: &[u8],
+        _digest: Digest,
+        _data: &[u8],
+        _signature: &[u8],
+    ) -> Result<bool, Error> {
+        Err(crate::km_err!(Unimplemented, "ECDSA signature verification not implemented"))
+    }
+}
+
+/// Abstraction of ML-DSA functionality (experimental).
+#[cfg(feature = "pqc")]
+pub trait MlDsa: Send {
+    /// Generate an ML-DSA key.  The default implementation generates a random seed.  Key
+    /// generation parameters are passed in for reference, to allow for implementations that might
+    /// have parameter-specific behaviour.
+    fn generate_key(
+        &self,
+        rng: &mut dyn Rng,
+        param_set: mldsa::ParameterSet,
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        let mut seed = [0u8; mldsa::SEED_LEN];
+        rng.fill_bytes(&mut seed);
+        Ok(KeyMaterial::MlDsa(param_set, mldsa::Key(seed).into()))
+    }
+
+    /// Import an ML-DSA key in PKCS#8 format.  Key import parameters are passed in for reference,
+    /// to allow for implementations that might have parameter-specific behaviour.
+    fn import_pkcs8_key(
+        &self,
+        data: &[u8],
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        mldsa::import_pkcs8_key(data)
+    }
+
+    /// Import an ML-DSA key as a raw 32-byte seed.  Key import parameters are passed in for
+    /// reference, to allow for implementations that might have parameter-specific behaviour.
+    fn import_raw_key(
+        &self,
+        data: &[u8],
+        param_set: mldsa::ParameterSet,
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        Ok(KeyMaterial::MlDsa(param_set, mldsa::Key::new_from_slice(data)?.into()))
+    }
+
+    /// Return the raw encoded public key that corresponds to the provided private `key`, which is
+    /// the `subjectPublicKey` to be included in `SubjectPublicKeyInfo`.
+    fn public_key(
+        &self,
+        key: &OpaqueOr<mldsa::Key>,
+        param_set: mldsa::ParameterSet,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Create an ML-DSA signing operation, which signs the accumulated message using pure ML-DSA
+    /// with an empty context string.
+    fn begin_sign(
+        &self,
+        key: OpaqueOr<mldsa::Key>,
+        param_set: mldsa::ParameterSet,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error>;
+}
+
+/// Abstraction of ML-KEM-768 functionality (experimental).
+#[cfg(feature = "pqc")]
+pub trait MlKem: Send {
+    /// Generate an ML-KEM-768 key.  The default implementation generates a random seed.  Key
+    /// generation parameters are passed in for reference, to allow for implementations that might
+    /// have parameter-specific behaviour.
+    fn generate_key(
+        &self,
+        rng: &mut dyn Rng,
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        let mut seed = [0u8; mlkem::SEED_LEN];
+        rng.fill_bytes(&mut seed);
+        Ok(KeyMaterial::MlKem(mlkem::Key(seed).into()))
+    }
+
+    /// Import an ML-KEM-768 key in PKCS#8 format.  Key import parameters are passed in for
+    /// reference, to allow for implementations that might have parameter-specific behaviour.
+    fn import_pkcs8_key(
+        &self,
+        data: &[u8],
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        mlkem::import_pkcs8_key(data)
+    }
+
+    /// Import an ML-KEM-768 key as a raw 64-byte seed.  Key import parameters are passed in for
+    /// reference, to allow for implementations that might have parameter-specific behaviour.
+    fn import_raw_key(
+        &self,
+        data: &[u8],
+        _params: &[keymint::KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        Ok(KeyMaterial::MlKem(mlkem::Key::new_from_slice(data)?.into()))
+    }
+
+    /// Return the raw encapsulation key that corresponds to the provided private `key`, which is
+    /// the `subjectPublicKey` to be included in `SubjectPublicKeyInfo`.
+    fn encapsulation_key(&self, key: &OpaqueOr<mlkem::Key>) -> Result<Vec<u8>, Error>;
+
+    /// Create an ML-KEM decapsulation operation.  The accumulated input for the operation is
+    /// expected to be an ML-KEM-768 ciphertext, and the output is the shared secret.  The
+    /// implementation should reject (with `ErrorCode::InvalidInputLength`) accumulated input that
+    /// is not [`mlkem::CIPHERTEXT_LEN`] bytes long.
+    fn begin_decapsulate(
+        &self,
+        key: OpaqueOr<mlkem::Key>,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error>;
 }
 
 /// Abstraction of an in-progress operation that emits data as it progresses.
@@ -705,6 +878,45 @@ impl Ec for NoOpEc {
     }
 }
 
+/// Stub implementation of [`MlDsa`].
+#[cfg(feature = "pqc")]
+pub struct NoOpMlDsa;
+#[cfg(feature = "pqc")]
+impl MlDsa for NoOpMlDsa {
+    fn public_key(
+        &self,
+        _key: &OpaqueOr<mldsa::Key>,
+        _param_set: mldsa::ParameterSet,
+    ) -> Result<Vec<u8>, Error> {
+        unimpl!();
+    }
+
+    fn begin_sign(
+        &self,
+        _key: OpaqueOr<mldsa::Key>,
+        _param_set: mldsa::ParameterSet,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        unimpl!();
+    }
+}
+
+/// Stub implementation of [`MlKem`].
+#[cfg(feature = "pqc")]
+pub struct NoOpMlKem;
+#[cfg(feature = "pqc")]
+impl MlKem for NoOpMlKem {
+    fn encapsulation_key(&self, _key: &OpaqueOr<mlkem::Key>) -> Result<Vec<u8>, Error> {
+        unimpl!();
+    }
+
+    fn begin_decapsulate(
+        &self,
+        _key: OpaqueOr<mlkem::Key>,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        unimpl!();
+    }
+}
+
 /// Stub implementation of [`keyblob::SecureDeletionSecretManager`].
 pub struct NoOpSdsManager;
 impl keyblob::SecureDeletionSecretManager for NoOpSdsManager {
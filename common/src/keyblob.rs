@@ -15,8 +15,7 @@
 //! Key blob manipulation functionality.
 
 use crate::{
-    contains_tag_value, crypto, crypto::aes, km_err, tag, try_to_vec, vec_try, Error,
-    FallibleAllocExt,
+    contains_tag_value, crypto, crypto::aes, km_err, tag, try_to_vec, Error, FallibleAllocExt,
 };
 use alloc::{
     format,
@@ -27,7 +26,7 @@ use kmr_derive::AsCborValue;
 use kmr_wire::keymint::{
     BootInfo, KeyCharacteristics, KeyParam, KeyPurpose, SecurityLevel, VerifiedBootState,
 };
-use kmr_wire::{cbor, cbor_type_error, AsCborValue, CborError};
+use kmr_wire::{cbor_type_error, AsCborValue, CborError};
 use log::{error, info};
 use zeroize::ZeroizeOnDrop;
 
@@ -53,9 +52,10 @@ pub enum Version {
 }
 
 /// Encrypted key material, as translated to/from CBOR.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, AsCborValue)]
 pub enum EncryptedKeyBlob {
     /// Version 1 key blob.
+    #[cbor(tag = Version::V1)]
     V1(EncryptedKeyBlobV1),
     // Future versions go here...
 }
@@ -66,6 +66,12 @@ impl EncryptedKeyBlob {
         Self::from_slice(data)
             .map_err(|e| km_err!(InvalidKeyBlob, "failed to parse keyblob: {:?}", e))
     }
+    /// Construct from serialized data that must be deterministically encoded CBOR, mapping failure
+    /// to `ErrorCode::InvalidKeyBlob`.
+    pub fn new_strict(data: &[u8]) -> Result<Self, Error> {
+        Self::from_slice_strict(data)
+            .map_err(|e| km_err!(InvalidKeyBlob, "failed to parse keyblob: {:?}", e))
+    }
     /// Return the secure deletion slot for the key, if present.
     pub fn secure_deletion_slot(&self) -> Option<SecureDeletionSlot> {
         match self {
@@ -80,40 +86,6 @@ impl EncryptedKeyBlob {
     }
 }
 
-impl AsCborValue for EncryptedKeyBlob {
-    fn from_cbor_value(value: cbor::value::Value) -> Result<Self, CborError> {
-        let mut a = match value {
-            cbor::value::Value::Array(a) if a.len() == 2 => a,
-            _ => return cbor_type_error(&value, "arr len 2"),
-        };
-        let inner = a.remove(1);
-        let version = Version::from_cbor_value(a.remove(0))?;
-        match version {
-            Version::V1 => Ok(Self::V1(EncryptedKeyBlobV1::from_cbor_value(inner)?)),
-        }
-    }
-    fn to_cbor_value(self) -> Result<cbor::value::Value, CborError> {
-        Ok(match self {
-            EncryptedKeyBlob::V1(inner) => cbor::value::Value::Array(
-                vec_try![Version::V1.to_cbor_value()?, inner.to_cbor_value()?]
-                    .map_err(|_e| CborError::AllocationFailed)?,
-            ),
-        })
-    }
-    fn cddl_typename() -> Option<String> {
-        Some("EncryptedKeyBlob".to_string())
-    }
-    fn cddl_schema() -> Option<String> {
-        Some(format!(
-            "&(
-    [{}, {}] ; Version::V1
-)",
-            Version::V1 as i32,
-            EncryptedKeyBlobV1::cddl_ref()
-        ))
-    }
-}
-
 /// Encrypted key material, as translated to/from CBOR.
 #[derive(Clone, Debug, AsCborValue)]
 pub struct EncryptedKeyBlobV1 {
@@ -196,6 +168,8 @@ pub enum SlotPurpose {
     KeyImport,
     /// Secure deletion slot needed for upgrade of an existing key.
     KeyUpgrade,
+    /// Secure deletion slot needed for derivation of a key from the output of a key agreement.
+    KeyDerivation,
 }
 
 /// Manager for the mapping between secure deletion slots and the corresponding
@@ -281,8 +255,8 @@ pub struct RootOfTrustInfo {
 /// Derive a key encryption key used for key blob encryption. The key is an AES-256 key derived
 /// from `root_key` using HKDF (RFC 5869) with HMAC-SHA256:
 /// - input keying material = a root key held in hardware. If it contains explicit key material,
-///                           perform full HKDF. If the root key is an opaque one, we assume that
-///                           the key is able to be directly used on the HKDF expand step.
+///   perform full HKDF. If the root key is an opaque one, we assume that
+///   the key is able to be directly used on the HKDF expand step.
 /// - salt = absent
 /// - info = the following three or four chunks of context data concatenated:
 ///    - content of `key_derivation_input` (which is random data)
@@ -17,9 +17,10 @@
 use crate::{
     crypto,
     crypto::{rsa::DecryptionMode, *},
-    km_err, try_to_vec, vec_try_with_capacity, Error, FallibleAllocExt,
+    der_err, km_err, try_to_vec, vec_try_with_capacity, Error, FallibleAllocExt,
 };
 use alloc::vec::Vec;
+use der::Reader;
 use kmr_wire::{
     keymint::{
         Algorithm, BlockMode, Digest, EcCurve, ErrorCode, KeyCharacteristics, KeyFormat, KeyParam,
@@ -32,6 +33,8 @@ use log::{info, warn};
 mod info;
 pub use info::*;
 pub mod legacy;
+mod policy;
+pub use policy::*;
 #[cfg(test)]
 mod tests;
 
@@ -173,7 +176,7 @@ pub fn transcribe_tags(
     for param in src {
         let tag = param.tag();
         dup_checker.add(tag)?;
-        if tags.iter().any(|t| *t == tag) {
+        if tags.contains(&tag) {
             dest.try_push(param.clone())?;
         }
     }
@@ -265,34 +268,51 @@ pub fn hidden(params: &[KeyParam], rot: &[u8]) -> Result<Vec<KeyParam>, Error> {
 }
 
 /// Build the set of key characteristics for a key that is about to be generated,
-/// checking parameter validity along the way. Also return the information needed for key
-/// generation.
+/// checking parameter validity and compliance with `policy` along the way. Also return the
+/// information needed for key generation.
 pub fn extract_key_gen_characteristics(
+    policy: &KeyPolicy,
     secure_storage: SecureStorage,
     params: &[KeyParam],
     sec_level: SecurityLevel,
 ) -> Result<(Vec<KeyCharacteristics>, KeyGenInfo), Error> {
-    let keygen_info = match get_algorithm(params)? {
+    let algo = get_algorithm(params)?;
+    policy.check_algorithm(algo)?;
+    let keygen_info = match algo {
         Algorithm::Rsa => check_rsa_gen_params(params, sec_level),
         Algorithm::Ec => check_ec_gen_params(params, sec_level),
         Algorithm::Aes => check_aes_gen_params(params, sec_level),
         Algorithm::TripleDes => check_3des_gen_params(params),
         Algorithm::Hmac => check_hmac_gen_params(params, sec_level),
+        #[cfg(feature = "pqc")]
+        Algorithm::MlDsa => check_mldsa_gen_params(params),
+        #[cfg(feature = "pqc")]
+        Algorithm::MlKem => check_mlkem_gen_params(params),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlDsa => Err(km_err!(UnsupportedAlgorithm, "ML-DSA not supported")),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlKem => Err(km_err!(UnsupportedAlgorithm, "ML-KEM not supported")),
     }?;
-    Ok((extract_key_characteristics(secure_storage, params, &[], sec_level)?, keygen_info))
+    let chars = extract_key_characteristics(secure_storage, params, &[], sec_level)?;
+    policy.check_key_characteristics(&chars)?;
+    Ok((chars, keygen_info))
 }
 
 /// Build the set of key characteristics for a key that is about to be imported,
-/// checking parameter validity along the way.
+/// checking parameter validity and compliance with `policy` along the way.
 pub fn extract_key_import_characteristics(
     imp: &crypto::Implementation,
+    policy: &KeyPolicy,
     secure_storage: SecureStorage,
     params: &[KeyParam],
     sec_level: SecurityLevel,
     key_format: KeyFormat,
     key_data: &[u8],
 ) -> Result<(Vec<KeyCharacteristics>, KeyMaterial), Error> {
-    let (deduced_params, key_material) = match get_algorithm(params)? {
+    let algo = get_algorithm(params)?;
+    policy.check_algorithm(algo)?;
+    policy.check_import_format(key_format)?;
+    let (deduced_params, key_material) = match algo {
         Algorithm::Rsa => {
             check_rsa_import_params(&*imp.rsa, params, sec_level, key_format, key_data)
         }
@@ -304,11 +324,18 @@ pub fn extract_key_import_characteristics(
         Algorithm::Hmac => {
             check_hmac_import_params(&*imp.hmac, params, sec_level, key_format, key_data)
         }
+        #[cfg(feature = "pqc")]
+        Algorithm::MlDsa => check_mldsa_import_params(&*imp.mldsa, params, key_format, key_data),
+        #[cfg(feature = "pqc")]
+        Algorithm::MlKem => check_mlkem_import_params(&*imp.mlkem, params, key_format, key_data),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlDsa => Err(km_err!(UnsupportedAlgorithm, "ML-DSA not supported")),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlKem => Err(km_err!(UnsupportedAlgorithm, "ML-KEM not supported")),
     }?;
-    Ok((
-        extract_key_characteristics(secure_storage, params, &deduced_params, sec_level)?,
-        key_material,
-    ))
+    let chars = extract_key_characteristics(secure_storage, params, &deduced_params, sec_level)?;
+    policy.check_key_characteristics(&chars)?;
+    Ok((chars, key_material))
 }
 
 /// Build the set of key characteristics for a key that is about to be generated or imported,
@@ -415,14 +442,20 @@ fn check_rsa_import_params(
     key_data: &[u8],
 ) -> Result<(Vec<KeyParam>, KeyMaterial), Error> {
     // Deduce key size and exponent from import data.
-    if key_format != KeyFormat::Pkcs8 {
-        return Err(km_err!(
-            UnsupportedKeyFormat,
-            "unsupported import format {:?}, expect PKCS8",
-            key_format
-        ));
-    }
-    let (key, key_size, public_exponent) = rsa.import_pkcs8_key(key_data, params)?;
+    let (key, key_size, public_exponent) = match key_format {
+        KeyFormat::Pkcs8 => rsa.import_pkcs8_key(key_data, params)?,
+        KeyFormat::Pkcs1 => {
+            let (key_data, spki) = split_spki_pair(key_data)?;
+            rsa.import_pkcs1_key(key_data, spki, params)?
+        }
+        _ => {
+            return Err(km_err!(
+                UnsupportedKeyFormat,
+                "unsupported import format {:?}, expect PKCS8 or PKCS1",
+                key_format
+            ))
+        }
+    };
 
     // If key size or exponent are explicitly specified, they must match. If they were not
     // specified, we emit them.
@@ -432,9 +465,10 @@ fn check_rsa_import_params(
             if *param_key_size != key_size {
                 return Err(km_err!(
                     ImportParameterMismatch,
-                    "specified KEY_SIZE {:?} bits != actual key size {:?} for PKCS8 import",
+                    "specified KEY_SIZE {:?} bits != actual key size {:?} for {:?} import",
                     param_key_size,
-                    key_size
+                    key_size,
+                    key_format
                 ));
             }
         }
@@ -445,9 +479,10 @@ fn check_rsa_import_params(
             if *param_public_exponent != public_exponent {
                 return Err(km_err!(
                     ImportParameterMismatch,
-                    "specified RSA_PUBLIC_EXPONENT {:?} != actual exponent {:?} for PKCS8 import",
+                    "specified RSA_PUBLIC_EXPONENT {:?} != actual exponent {:?} for {:?} import",
                     param_public_exponent,
                     public_exponent,
+                    key_format
                 ));
             }
         }
@@ -459,6 +494,17 @@ fn check_rsa_import_params(
     Ok((deduced_chars, key))
 }
 
+/// Split the key data for one of the vendor-extension private key import formats into the
+/// DER-encoded private key and the optional DER-encoded `SubjectPublicKeyInfo` that follows it.
+fn split_spki_pair(key_data: &[u8]) -> Result<(&[u8], Option<&[u8]>), Error> {
+    let mut reader = der::SliceReader::new(key_data)
+        .map_err(|e| der_err!(e, "failed to create reader for key data"))?;
+    let private_key =
+        reader.tlv_bytes().map_err(|e| der_err!(e, "failed to parse private key data"))?;
+    let spki = &key_data[private_key.len()..];
+    Ok((private_key, if spki.is_empty() { None } else { Some(spki) }))
+}
+
 /// Check the parameter validity for an RSA key that is about to be generated or imported.
 fn check_rsa_params(params: &[KeyParam]) -> Result<(), Error> {
     let mut seen_attest = false;
@@ -496,6 +542,7 @@ fn check_ec_gen_params(params: &[KeyParam], sec_level: SecurityLevel) -> Result<
         (EcCurve::Curve25519, Some(KeyPurpose::Sign)) => KeyGenInfo::Ed25519,
         (EcCurve::Curve25519, Some(KeyPurpose::AttestKey)) => KeyGenInfo::Ed25519,
         (EcCurve::Curve25519, Some(KeyPurpose::AgreeKey)) => KeyGenInfo::X25519,
+        (EcCurve::Curve25519, Some(KeyPurpose::WrapKey)) => KeyGenInfo::X25519,
         (EcCurve::Curve25519, _) => {
             return Err(km_err!(
                 IncompatiblePurpose,
@@ -531,14 +578,18 @@ fn check_ec_import_params(
     key_format: KeyFormat,
     key_data: &[u8],
 ) -> Result<(Vec<KeyParam>, KeyMaterial), Error> {
-    // Curve25519 can be imported as PKCS8 or raw; all other curves must be PKCS8.
+    // Curve25519 can be imported as PKCS8 or raw; all other curves must be PKCS8 (or SEC1, as a
+    // vendor extension).
     // If we need to disinguish between Ed25519 and X25519, we need to examine the purpose for the
-    // key -- look for `AgreeKey` as it cannot be combined with other purposes.
+    // key -- look for `AgreeKey` or `WrapKey` as they cannot be combined with other purposes.
+    let xdh_purpose = || -> Result<bool, Error> {
+        Ok(matches!(primary_purpose(params)?, KeyPurpose::AgreeKey | KeyPurpose::WrapKey))
+    };
     let (key, curve) = match key_format {
         KeyFormat::Raw if get_ec_curve(params)? == EcCurve::Curve25519 => {
             // Raw key import must specify the curve (and the only valid option is Curve25519
             // currently).
-            if primary_purpose(params)? == KeyPurpose::AgreeKey {
+            if xdh_purpose()? {
                 (ec.import_raw_x25519_key(key_data, params)?, EcCurve::Curve25519)
             } else {
                 (ec.import_raw_ed25519_key(key_data, params)?, EcCurve::Curve25519)
@@ -549,7 +600,7 @@ fn check_ec_import_params(
             let curve = match &key {
                 KeyMaterial::Ec(curve, CurveType::Nist, _) => *curve,
                 KeyMaterial::Ec(EcCurve::Curve25519, CurveType::EdDsa, _) => {
-                    if primary_purpose(params)? == KeyPurpose::AgreeKey {
+                    if xdh_purpose()? {
                         return Err(km_err!(
                             IncompatiblePurpose,
                             "can't use EdDSA key for key agreement"
@@ -558,7 +609,7 @@ fn check_ec_import_params(
                     EcCurve::Curve25519
                 }
                 KeyMaterial::Ec(EcCurve::Curve25519, CurveType::Xdh, _) => {
-                    if primary_purpose(params)? != KeyPurpose::AgreeKey {
+                    if !xdh_purpose()? {
                         return Err(km_err!(IncompatiblePurpose, "can't use XDH key for signing"));
                     }
                     EcCurve::Curve25519
@@ -572,6 +623,18 @@ fn check_ec_import_params(
             };
             (key, curve)
         }
+        KeyFormat::Sec1 => {
+            let (key_data, spki) = split_spki_pair(key_data)?;
+            match ec.import_sec1_key(key_data, spki, params)? {
+                key @ KeyMaterial::Ec(curve, CurveType::Nist, _) => (key, curve),
+                _ => {
+                    return Err(km_err!(
+                        ImportParameterMismatch,
+                        "unexpected key type from SEC1 import"
+                    ))
+                }
+            }
+        }
         _ => {
             return Err(km_err!(
                 UnsupportedKeyFormat,
@@ -606,9 +669,10 @@ fn check_ec_import_params(
             if *param_key_size != key_size {
                 return Err(km_err!(
                     ImportParameterMismatch,
-                    "specified KEY_SIZE {:?} bits != actual key size {:?} for PKCS8 import",
+                    "specified KEY_SIZE {:?} bits != actual key size {:?} for {:?} import",
                     param_key_size,
-                    key_size
+                    key_size,
+                    key_format
                 ));
             }
         }
@@ -651,6 +715,7 @@ fn check_ec_params(
     let mut seen_attest = false;
     let mut seen_sign = false;
     let mut seen_agree = false;
+    let mut seen_wrap = false;
     let mut primary_purpose = None;
     for param in params {
         if let KeyParam::Purpose(purpose) = param {
@@ -658,6 +723,7 @@ fn check_ec_params(
                 KeyPurpose::Sign => seen_sign = true,
                 KeyPurpose::AgreeKey => seen_agree = true,
                 KeyPurpose::AttestKey => seen_attest = true,
+                KeyPurpose::WrapKey => seen_wrap = true,
                 KeyPurpose::Verify => {}
                 _ => warn!("Generating EC key with invalid purpose {:?}", purpose),
             }
@@ -681,6 +747,23 @@ fn check_ec_params(
             "curve25519 keys must be either SIGN/ATTEST_KEY or AGREE_KEY, not both"
         ));
     }
+    // EC keys with Purpose::WRAP_KEY (a vendor extension) must have no other purpose, and must be
+    // P-256 or X25519 keys.
+    if seen_wrap {
+        if seen_sign || seen_agree || seen_attest {
+            return Err(km_err!(
+                IncompatiblePurpose,
+                "EC keys with WRAP_KEY must have no other purpose"
+            ));
+        }
+        if curve != EcCurve::P256 && curve != EcCurve::Curve25519 {
+            return Err(km_err!(
+                UnsupportedEcCurve,
+                "invalid curve ({:?}) for EC wrapping key",
+                curve
+            ));
+        }
+    }
 
     Ok(primary_purpose)
 }
@@ -738,7 +821,7 @@ fn check_aes_import_params(
 
 /// Check the parameter validity for an AES key that is about to be generated or imported.
 fn check_aes_params(params: &[KeyParam]) -> Result<(), Error> {
-    let gcm_support = params.iter().any(|p| *p == KeyParam::BlockMode(BlockMode::Gcm));
+    let gcm_support = params.contains(&KeyParam::BlockMode(BlockMode::Gcm));
     if gcm_support {
         let min_mac_len = get_tag_value!(params, MinMacLength, ErrorCode::MissingMinMacLength)?;
         if (min_mac_len % 8 != 0) || !(96..=128).contains(&min_mac_len) {
@@ -839,6 +922,145 @@ fn check_hmac_params(
     Ok(())
 }
 
+/// Check ML-DSA key generation parameter validity.
+#[cfg(feature = "pqc")]
+fn check_mldsa_gen_params(params: &[KeyParam]) -> Result<KeyGenInfo, Error> {
+    // For key generation the parameter set must be explicitly specified, via the key size.
+    let key_size = get_tag_value!(params, KeySize, ErrorCode::UnsupportedKeySize)?;
+    let param_set = mldsa::ParameterSet::new(key_size)?;
+    check_mldsa_params(params)?;
+    Ok(KeyGenInfo::MlDsa(param_set))
+}
+
+/// Check ML-DSA key import parameter validity. Return the key material along with any key
+/// generation parameters that have been deduced from the key material (but which are not present
+/// in the input key parameters).
+#[cfg(feature = "pqc")]
+fn check_mldsa_import_params(
+    mldsa: &dyn MlDsa,
+    params: &[KeyParam],
+    key_format: KeyFormat,
+    key_data: &[u8],
+) -> Result<(Vec<KeyParam>, KeyMaterial), Error> {
+    let key = match key_format {
+        KeyFormat::Pkcs8 => mldsa.import_pkcs8_key(key_data, params)?,
+        KeyFormat::Raw => {
+            // A raw seed doesn't identify the parameter set, so it must be specified.
+            let key_size = get_tag_value!(params, KeySize, ErrorCode::UnsupportedKeySize)?;
+            mldsa.import_raw_key(key_data, mldsa::ParameterSet::new(key_size)?, params)?
+        }
+        _ => {
+            return Err(km_err!(
+                UnsupportedKeyFormat,
+                "invalid import format ({:?}) for ML-DSA key",
+                key_format,
+            ))
+        }
+    };
+    let param_set = match &key {
+        KeyMaterial::MlDsa(param_set, _) => *param_set,
+        _ => {
+            return Err(km_err!(ImportParameterMismatch, "unexpected key type from ML-DSA import"))
+        }
+    };
+    let deduced_chars = require_matching_key_size(params, param_set.key_size())?;
+
+    check_mldsa_params(params)?;
+    Ok((deduced_chars, key))
+}
+
+/// Check the parameter validity for an ML-DSA key that is about to be generated or imported.
+#[cfg(feature = "pqc")]
+fn check_mldsa_params(params: &[KeyParam]) -> Result<(), Error> {
+    let mut seen_attest = false;
+    let mut seen_sign = false;
+    for param in params {
+        if let KeyParam::Purpose(purpose) = param {
+            match purpose {
+                KeyPurpose::Sign => seen_sign = true,
+                KeyPurpose::AttestKey => seen_attest = true,
+                KeyPurpose::Verify => {}
+                _ => {
+                    return Err(km_err!(
+                        IncompatiblePurpose,
+                        "invalid purpose {:?} for ML-DSA key",
+                        purpose
+                    ))
+                }
+            }
+        }
+    }
+    // Keys with Purpose::ATTEST_KEY must have no other purpose.
+    if seen_attest && seen_sign {
+        return Err(km_err!(
+            IncompatiblePurpose,
+            "keys with ATTEST_KEY must have no other purpose"
+        ));
+    }
+    // ML-DSA signs the message directly, so there is no digest or padding.
+    reject_some_digest(params)?;
+    reject_some_padding(params)?;
+    Ok(())
+}
+
+/// Check ML-KEM key generation parameter validity.
+#[cfg(feature = "pqc")]
+fn check_mlkem_gen_params(params: &[KeyParam]) -> Result<KeyGenInfo, Error> {
+    // For key generation the size must be explicitly specified.
+    let key_size = get_tag_value!(params, KeySize, ErrorCode::UnsupportedKeySize)?;
+    if key_size != mlkem::KEY_SIZE {
+        return Err(km_err!(UnsupportedKeySize, "unsupported KEY_SIZE {:?} for ML-KEM", key_size));
+    }
+    check_mlkem_params(params)?;
+    Ok(KeyGenInfo::MlKem768)
+}
+
+/// Check ML-KEM key import parameter validity. Return the key material along with any key
+/// generation parameters that have been deduced from the key material (but which are not present
+/// in the input key parameters).
+#[cfg(feature = "pqc")]
+fn check_mlkem_import_params(
+    mlkem: &dyn MlKem,
+    params: &[KeyParam],
+    key_format: KeyFormat,
+    key_data: &[u8],
+) -> Result<(Vec<KeyParam>, KeyMaterial), Error> {
+    let key = match key_format {
+        KeyFormat::Pkcs8 => mlkem.import_pkcs8_key(key_data, params)?,
+        KeyFormat::Raw => mlkem.import_raw_key(key_data, params)?,
+        _ => {
+            return Err(km_err!(
+                UnsupportedKeyFormat,
+                "invalid import format ({:?}) for ML-KEM key",
+                key_format,
+            ))
+        }
+    };
+    let deduced_chars = require_matching_key_size(params, mlkem::KEY_SIZE)?;
+
+    check_mlkem_params(params)?;
+    Ok((deduced_chars, key))
+}
+
+/// Check the parameter validity for an ML-KEM key that is about to be generated or imported.
+#[cfg(feature = "pqc")]
+fn check_mlkem_params(params: &[KeyParam]) -> Result<(), Error> {
+    for param in params {
+        if let KeyParam::Purpose(purpose) = param {
+            if *purpose != KeyPurpose::AgreeKey {
+                return Err(km_err!(
+                    IncompatiblePurpose,
+                    "invalid purpose {:?} for ML-KEM key",
+                    purpose
+                ));
+            }
+        }
+    }
+    reject_some_digest(params)?;
+    reject_some_padding(params)?;
+    Ok(())
+}
+
 /// Check for `KeyFormat::RAW`.
 fn require_raw(key_format: KeyFormat) -> Result<(), Error> {
     if key_format != KeyFormat::Raw {
@@ -941,8 +1163,9 @@ struct BeginParamsToCheck {
 }
 
 /// Check that an operation with the given `purpose` and `params` can validly be started
-/// using a key with characteristics `chars`.
+/// using a key with characteristics `chars`, in compliance with `policy`.
 pub fn check_begin_params(
+    policy: &KeyPolicy,
     chars: &[KeyParam],
     purpose: KeyPurpose,
     params: &[KeyParam],
@@ -963,6 +1186,9 @@ pub fn check_begin_params(
             | (Algorithm::Rsa, KeyPurpose::Sign)
             | (Algorithm::Rsa, KeyPurpose::Decrypt)
             | (Algorithm::Rsa, KeyPurpose::AttestKey)
+            | (Algorithm::MlDsa, KeyPurpose::Sign)
+            | (Algorithm::MlDsa, KeyPurpose::AttestKey)
+            | (Algorithm::MlKem, KeyPurpose::AgreeKey)
     );
     if !valid_purpose {
         return Err(km_err!(
@@ -972,6 +1198,7 @@ pub fn check_begin_params(
             algo
         ));
     }
+    policy.check_begin(chars, purpose, params)?;
     if !contains_tag_value!(chars, Purpose, purpose) {
         return Err(km_err!(
             IncompatiblePurpose,
@@ -996,6 +1223,14 @@ pub fn check_begin_params(
         Algorithm::Aes => check_begin_aes_params(chars, params, nonce.map(|v| v.as_ref())),
         Algorithm::TripleDes => check_begin_3des_params(params, nonce.map(|v| v.as_ref())),
         Algorithm::Hmac => check_begin_hmac_params(chars, purpose, params),
+        #[cfg(feature = "pqc")]
+        Algorithm::MlDsa => check_begin_mldsa_params(params),
+        #[cfg(feature = "pqc")]
+        Algorithm::MlKem => check_begin_mlkem_params(params),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlDsa => Err(km_err!(UnsupportedAlgorithm, "ML-DSA not supported")),
+        #[cfg(not(feature = "pqc"))]
+        Algorithm::MlKem => Err(km_err!(UnsupportedAlgorithm, "ML-KEM not supported")),
     }?;
 
     // For various parameters, if they are specified in the begin parameters and they
@@ -1173,6 +1408,28 @@ fn check_begin_ec_params(
     Ok(BeginParamsToCheck { block_mode: false, padding: false, digest: true, mgf_digest: false })
 }
 
+/// Check that an ML-DSA operation with the given `params` can validly be started.
+#[cfg(feature = "pqc")]
+fn check_begin_mldsa_params(params: &[KeyParam]) -> Result<BeginParamsToCheck, Error> {
+    reject_tags(params, &[Tag::RsaOaepMgfDigest])?;
+    if let Some(digest) = get_opt_tag_value!(params, Digest)? {
+        if *digest != Digest::None {
+            return Err(km_err!(UnsupportedDigest, "ML-DSA only supports Digest::None"));
+        }
+    }
+    reject_some_padding(params)?;
+    Ok(BeginParamsToCheck { block_mode: false, padding: false, digest: false, mgf_digest: false })
+}
+
+/// Check that an ML-KEM operation with the given `params` can validly be started.
+#[cfg(feature = "pqc")]
+fn check_begin_mlkem_params(params: &[KeyParam]) -> Result<BeginParamsToCheck, Error> {
+    reject_tags(params, &[Tag::RsaOaepMgfDigest])?;
+    reject_some_digest(params)?;
+    reject_some_padding(params)?;
+    Ok(BeginParamsToCheck { block_mode: false, padding: false, digest: false, mgf_digest: false })
+}
+
 /// Check that an AES operation with the given `purpose` and `params` can validly be started
 /// using a key with characteristics `chars`.
 fn check_begin_aes_params(
@@ -1317,6 +1574,27 @@ pub fn digest_len(digest: Digest) -> Result<u32, Error> {
     }
 }
 
+/// Check the required key characteristics for an EC wrapping key used in secure import (a vendor
+/// extension), given the curve and curve type of the key material.
+pub fn check_ec_wrapping_key_params(
+    chars: &[KeyParam],
+    curve: EcCurve,
+    curve_type: CurveType,
+) -> Result<(), Error> {
+    if !contains_tag_value!(chars, Purpose, KeyPurpose::WrapKey) {
+        return Err(km_err!(IncompatiblePurpose, "no wrap key purpose for the wrapping key"));
+    }
+    match (curve, curve_type) {
+        (EcCurve::P256, CurveType::Nist) | (EcCurve::Curve25519, CurveType::Xdh) => Ok(()),
+        _ => Err(km_err!(
+            UnsupportedEcCurve,
+            "invalid curve {:?} ({:?}) for EC wrapping key",
+            curve,
+            curve_type
+        )),
+    }
+}
+
 /// Check the required key params for an RSA wrapping key used in secure import and return the
 /// [`DecryptionMode`] constructed from the processed key characteristics.
 pub fn check_rsa_wrapping_key_params(
@@ -0,0 +1,181 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Device-specific policy on the keys that can be created and used.
+
+use crate::{crypto::ec, km_err, Error};
+use alloc::vec::Vec;
+use kmr_wire::{
+    keymint::{
+        Algorithm, Digest, KeyCharacteristics, KeyFormat, KeyParam, KeyPurpose, PaddingMode,
+    },
+    KeySizeInBits,
+};
+
+/// Device-specific restrictions on the algorithms, key sizes and modes that are available, applied
+/// on top of the restrictions that KeyMint itself imposes.  The default policy imposes no extra
+/// restrictions.
+///
+/// The policy is checked when keys are generated or imported, and again whenever an operation is
+/// started, so tightening the policy also prevents the use of existing keys that no longer comply.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyPolicy {
+    /// Algorithms that are not supported.
+    pub disallowed_algorithms: Vec<Algorithm>,
+    /// Minimum key size for particular algorithms.  The size of an EC key is determined by its
+    /// curve.
+    pub min_key_sizes: Vec<(Algorithm, KeySizeInBits)>,
+    /// Digests that are not supported.
+    pub disallowed_digests: Vec<Digest>,
+    /// Padding modes that are not supported.
+    pub disallowed_padding_modes: Vec<PaddingMode>,
+    /// Purposes that are not supported, for keys of any algorithm.
+    pub disallowed_purposes: Vec<KeyPurpose>,
+    /// Minimum value for the `MIN_MAC_LENGTH` of HMAC and AES-GCM keys, in bits.
+    pub min_mac_length: Option<u32>,
+    /// Whether keys can be imported in the vendor-extension [`KeyFormat::Sec1`] and
+    /// [`KeyFormat::Pkcs1`] formats.  These formats are not accepted by default.
+    pub vendor_import_formats: bool,
+}
+
+impl KeyPolicy {
+    /// Check that the characteristics of a key that is about to be generated or imported comply
+    /// with the policy, whichever security level enforces them.
+    pub fn check_key_characteristics(&self, chars: &[KeyCharacteristics]) -> Result<(), Error> {
+        let params = || chars.iter().flat_map(|c| c.authorizations.iter());
+        self.check_key(params())?;
+        for param in params() {
+            match param {
+                KeyParam::Purpose(purpose) => self.check_purpose(*purpose)?,
+                KeyParam::Digest(digest) => self.check_digest(*digest)?,
+                KeyParam::Padding(padding) => self.check_padding(*padding)?,
+                KeyParam::MinMacLength(len) => {
+                    if let Some(min_len) = self.min_mac_length {
+                        if *len < min_len {
+                            return Err(km_err!(
+                                UnsupportedMinMacLength,
+                                "MIN_MAC_LENGTH {} below policy minimum {}",
+                                len,
+                                min_len
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that an operation with the given `purpose` and `params` on a key with characteristics
+    /// `chars` complies with the policy.
+    pub fn check_begin(
+        &self,
+        chars: &[KeyParam],
+        purpose: KeyPurpose,
+        params: &[KeyParam],
+    ) -> Result<(), Error> {
+        self.check_key(chars.iter())?;
+        self.check_purpose(purpose)?;
+        for param in params {
+            match param {
+                KeyParam::Digest(digest) => self.check_digest(*digest)?,
+                KeyParam::Padding(padding) => self.check_padding(*padding)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the algorithm and key size of a key.
+    fn check_key<'a>(
+        &self,
+        mut chars: impl Iterator<Item = &'a KeyParam> + Clone,
+    ) -> Result<(), Error> {
+        let algo = chars
+            .clone()
+            .find_map(|param| match param {
+                KeyParam::Algorithm(algo) => Some(*algo),
+                _ => None,
+            })
+            .ok_or_else(|| km_err!(UnsupportedAlgorithm, "algorithm not specified"))?;
+        self.check_algorithm(algo)?;
+        let Some(min_size) =
+            self.min_key_sizes.iter().find(|(a, _)| *a == algo).map(|(_, size)| *size)
+        else {
+            return Ok(());
+        };
+        let key_size = chars.find_map(|param| match param {
+            KeyParam::KeySize(size) => Some(*size),
+            KeyParam::EcCurve(curve) => Some(ec::curve_to_key_size(*curve)),
+            _ => None,
+        });
+        match key_size {
+            Some(size) if size.0 >= min_size.0 => Ok(()),
+            Some(size) => Err(km_err!(
+                UnsupportedKeySize,
+                "{:?} key size {:?} below policy minimum {:?}",
+                algo,
+                size,
+                min_size
+            )),
+            None => Err(km_err!(UnsupportedKeySize, "no key size for {:?} key", algo)),
+        }
+    }
+
+    /// Check that an algorithm is allowed by the policy.
+    pub fn check_algorithm(&self, algo: Algorithm) -> Result<(), Error> {
+        if self.disallowed_algorithms.contains(&algo) {
+            return Err(km_err!(UnsupportedAlgorithm, "algorithm {:?} disallowed by policy", algo));
+        }
+        Ok(())
+    }
+
+    /// Check that keys can be imported in the given format.
+    pub fn check_import_format(&self, format: KeyFormat) -> Result<(), Error> {
+        match format {
+            KeyFormat::Sec1 | KeyFormat::Pkcs1 if !self.vendor_import_formats => Err(km_err!(
+                UnsupportedKeyFormat,
+                "vendor-extension import format {:?} not enabled",
+                format
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_purpose(&self, purpose: KeyPurpose) -> Result<(), Error> {
+        if self.disallowed_purposes.contains(&purpose) {
+            return Err(km_err!(UnsupportedPurpose, "purpose {:?} disallowed by policy", purpose));
+        }
+        Ok(())
+    }
+
+    fn check_digest(&self, digest: Digest) -> Result<(), Error> {
+        if self.disallowed_digests.contains(&digest) {
+            return Err(km_err!(UnsupportedDigest, "digest {:?} disallowed by policy", digest));
+        }
+        Ok(())
+    }
+
+    fn check_padding(&self, padding: PaddingMode) -> Result<(), Error> {
+        if self.disallowed_padding_modes.contains(&padding) {
+            return Err(km_err!(
+                UnsupportedPaddingMode,
+                "padding mode {:?} disallowed by policy",
+                padding
+            ));
+        }
+        Ok(())
+    }
+}
@@ -89,7 +89,7 @@ fn test_check_begin_params_fail() {
         ),
     ];
     for (purpose, params, msg) in tests {
-        expect_err!(check_begin_params(&chars, purpose, &params), msg);
+        expect_err!(check_begin_params(&KeyPolicy::default(), &chars, purpose, &params), msg);
     }
 }
 
@@ -137,3 +137,158 @@ fn test_increment_imei() {
         assert_eq!(hex::encode(got), want, "mismatch for input IMEI {}", input);
     }
 }
+
+#[test]
+fn test_key_policy() {
+    let policy = KeyPolicy {
+        disallowed_algorithms: vec![Algorithm::TripleDes],
+        min_key_sizes: vec![
+            (Algorithm::Rsa, KeySizeInBits(3072)),
+            (Algorithm::Ec, KeySizeInBits(256)),
+        ],
+        disallowed_digests: vec![Digest::Md5],
+        disallowed_padding_modes: vec![PaddingMode::RsaPkcs115Encrypt],
+        disallowed_purposes: vec![KeyPurpose::AgreeKey],
+        min_mac_length: Some(128),
+        ..Default::default()
+    };
+    let tests = vec![
+        (vec![KeyParam::Algorithm(Algorithm::TripleDes)], Some(ErrorCode::UnsupportedAlgorithm)),
+        (
+            vec![KeyParam::Algorithm(Algorithm::Rsa), KeyParam::KeySize(KeySizeInBits(2048))],
+            Some(ErrorCode::UnsupportedKeySize),
+        ),
+        (vec![KeyParam::Algorithm(Algorithm::Rsa), KeyParam::KeySize(KeySizeInBits(3072))], None),
+        (vec![KeyParam::Algorithm(Algorithm::Rsa)], Some(ErrorCode::UnsupportedKeySize)),
+        (
+            vec![KeyParam::Algorithm(Algorithm::Ec), KeyParam::EcCurve(EcCurve::P224)],
+            Some(ErrorCode::UnsupportedKeySize),
+        ),
+        (vec![KeyParam::Algorithm(Algorithm::Ec), KeyParam::EcCurve(EcCurve::P256)], None),
+        (
+            vec![
+                KeyParam::Algorithm(Algorithm::Ec),
+                KeyParam::EcCurve(EcCurve::P256),
+                KeyParam::Purpose(KeyPurpose::AgreeKey),
+            ],
+            Some(ErrorCode::UnsupportedPurpose),
+        ),
+        (
+            vec![KeyParam::Algorithm(Algorithm::Hmac), KeyParam::Digest(Digest::Md5)],
+            Some(ErrorCode::UnsupportedDigest),
+        ),
+        (
+            vec![KeyParam::Algorithm(Algorithm::Hmac), KeyParam::MinMacLength(96)],
+            Some(ErrorCode::UnsupportedMinMacLength),
+        ),
+        (vec![KeyParam::Algorithm(Algorithm::Hmac), KeyParam::MinMacLength(128)], None),
+        (
+            vec![
+                KeyParam::Algorithm(Algorithm::Rsa),
+                KeyParam::KeySize(KeySizeInBits(4096)),
+                KeyParam::Padding(PaddingMode::RsaPkcs115Encrypt),
+            ],
+            Some(ErrorCode::UnsupportedPaddingMode),
+        ),
+    ];
+    for (authorizations, want) in tests {
+        let chars = vec![KeyCharacteristics {
+            security_level: SecurityLevel::TrustedEnvironment,
+            authorizations,
+        }];
+        let got = policy.check_key_characteristics(&chars).err().map(|e| match e {
+            Error::Hal(code, _) => code,
+            e => panic!("unexpected error {:?}", e),
+        });
+        assert_eq!(got, want, "mismatch for {:?}", chars);
+        assert!(KeyPolicy::default().check_key_characteristics(&chars).is_ok());
+    }
+
+    // Characteristics are checked at every security level.
+    let chars = vec![
+        KeyCharacteristics {
+            security_level: SecurityLevel::TrustedEnvironment,
+            authorizations: vec![KeyParam::Algorithm(Algorithm::Hmac)],
+        },
+        KeyCharacteristics {
+            security_level: SecurityLevel::Keystore,
+            authorizations: vec![KeyParam::Digest(Digest::Md5)],
+        },
+    ];
+    expect_err!(policy.check_key_characteristics(&chars), "UnsupportedDigest");
+
+    // Operations only need the requested digest and padding to be allowed, even if the key
+    // lists others.
+    let chars = vec![
+        KeyParam::Algorithm(Algorithm::Rsa),
+        KeyParam::KeySize(KeySizeInBits(3072)),
+        KeyParam::Purpose(KeyPurpose::Decrypt),
+        KeyParam::Padding(PaddingMode::RsaPkcs115Encrypt),
+        KeyParam::Padding(PaddingMode::RsaOaep),
+        KeyParam::Digest(Digest::Md5),
+        KeyParam::Digest(Digest::Sha256),
+    ];
+    let params = vec![KeyParam::Padding(PaddingMode::RsaOaep), KeyParam::Digest(Digest::Sha256)];
+    assert!(policy.check_begin(&chars, KeyPurpose::Decrypt, &params).is_ok());
+    let params = vec![KeyParam::Padding(PaddingMode::RsaPkcs115Encrypt)];
+    expect_err!(policy.check_begin(&chars, KeyPurpose::Decrypt, &params), "UnsupportedPaddingMode");
+    let params = vec![KeyParam::Padding(PaddingMode::RsaOaep), KeyParam::Digest(Digest::Md5)];
+    expect_err!(policy.check_begin(&chars, KeyPurpose::Decrypt, &params), "UnsupportedDigest");
+}
+
+#[test]
+fn test_key_policy_import_format() {
+    let policy = KeyPolicy::default();
+    assert!(policy.check_import_format(KeyFormat::Pkcs8).is_ok());
+    assert!(policy.check_import_format(KeyFormat::Raw).is_ok());
+    expect_err!(policy.check_import_format(KeyFormat::Sec1), "UnsupportedKeyFormat");
+    expect_err!(policy.check_import_format(KeyFormat::Pkcs1), "UnsupportedKeyFormat");
+
+    let policy = KeyPolicy { vendor_import_formats: true, ..Default::default() };
+    assert!(policy.check_import_format(KeyFormat::Sec1).is_ok());
+    assert!(policy.check_import_format(KeyFormat::Pkcs1).is_ok());
+}
+
+#[test]
+fn test_mldsa_gen_params() {
+    let params = vec![
+        KeyParam::Algorithm(Algorithm::MlDsa),
+        KeyParam::KeySize(KeySizeInBits(65)),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::NoAuthRequired,
+    ];
+    let result = extract_key_gen_characteristics(
+        &KeyPolicy::default(),
+        SecureStorage::Unavailable,
+        &params,
+        SecurityLevel::TrustedEnvironment,
+    )
+    .map(|_| ());
+    if cfg!(feature = "pqc") {
+        assert!(result.is_ok());
+    } else {
+        expect_err!(result, "ML-DSA not supported");
+    }
+}
+
+#[test]
+fn test_mlkem_gen_params() {
+    let params = vec![
+        KeyParam::Algorithm(Algorithm::MlKem),
+        KeyParam::KeySize(KeySizeInBits(768)),
+        KeyParam::Purpose(KeyPurpose::AgreeKey),
+        KeyParam::NoAuthRequired,
+    ];
+    let result = extract_key_gen_characteristics(
+        &KeyPolicy::default(),
+        SecureStorage::Unavailable,
+        &params,
+        SecurityLevel::TrustedEnvironment,
+    )
+    .map(|_| ());
+    if cfg!(feature = "pqc") {
+        assert!(result.is_ok());
+    } else {
+        expect_err!(result, "ML-KEM not supported");
+    }
+}
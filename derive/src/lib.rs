@@ -13,16 +13,40 @@
 // limitations under the License.
 
 //! Derive macro for `AsCborValue`.
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned};
 use syn::{
-    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Fields, GenericParam,
-    Generics, Ident, Index,
+    parse_macro_input, parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Expr, Field,
+    Fields, GenericArgument, GenericParam, Generics, Ident, Index, LitStr, PathArguments, Type,
+    Variant,
 };
 
 /// Derive macro that implements the `AsCborValue` trait.  Using this macro requires
 /// that `AsCborValue`, `CborError` and `cbor_type_error` are locally `use`d.
-#[proc_macro_derive(AsCborValue)]
+///
+/// By default a struct is encoded as a fixed-length array of its fields.  For a struct with named
+/// fields, the encoding can be made forward-compatible with the following attributes:
+///
+/// - `#[cbor(map)]` on the struct: encode as a map whose (integer) keys are the positions of the
+///   fields, ignoring any unrecognized keys on decode.
+/// - `#[cbor(default)]` on a field: the field may be absent on decode, in which case it takes its
+///   `Default` value.  In the array encoding, default fields must come after all required fields.
+/// - `#[cbor(optional)]` on a field of type `Option<T>`: the field is encoded as a bare `T` when
+///   present, and is omitted when `None`.  In the array encoding, optional fields must come after
+///   all other fields, and an absent optional field cannot be followed by a present one.
+///
+/// New fields can then be added to a struct without breaking peers that use an older version of
+/// it, as long as the new fields are `default` or `optional` and are only ever appended.
+///
+/// A fieldless enum is encoded as an integer.  An enum whose variants carry data is instead encoded
+/// as an array holding a discriminant followed by the fields of the variant, and every variant
+/// needs the following attributes:
+///
+/// - `#[cbor(tag = N)]`: the discriminant for the variant, where `N` is an integer constant or a
+///   value of a fieldless enum (which is converted with `as i64`).
+/// - `#[cbor(flag)]` (optional, for a unit variant): encode the variant as `[N, true]` rather than
+///   as `[N]`.
+#[proc_macro_derive(AsCborValue, attributes(cbor))]
 pub fn derive_as_cbor_value(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     derive_as_cbor_value_internal(&input)
@@ -35,9 +59,26 @@ fn derive_as_cbor_value_internal(input: &DeriveInput) -> proc_macro::TokenStream
     let generics = add_trait_bounds(&input.generics);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let from_val = from_val_struct(&input.data);
-    let to_val = to_val_struct(&input.data);
-    let cddl = cddl_struct(name, &input.data);
+    // Types that use `#[cbor(..)]` attributes get different generated code.
+    let with_attrs = match cbor_tagged_enum(input) {
+        Ok(Some(variants)) => Ok(Some((
+            from_val_tagged(&variants),
+            to_val_tagged(&variants),
+            cddl_tagged(name, &variants),
+        ))),
+        Ok(None) => cbor_struct(input)
+            .map(|cs| cs.map(|cs| (from_val_attrs(&cs), to_val_attrs(&cs), cddl_attrs(&cs)))),
+        Err(e) => Err(e),
+    };
+    let (from_val, to_val, cddl) = match with_attrs {
+        Ok(Some(generated)) => generated,
+        Ok(None) => (
+            from_val_struct(&input.data),
+            to_val_struct(&input.data),
+            cddl_struct(name, &input.data),
+        ),
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let expanded = quote! {
         // The generated impl
@@ -449,6 +490,705 @@ fn cddl_struct(name: &Ident, data: &Data) -> TokenStream {
     }
 }
 
+/// How a field of a struct that uses `#[cbor(..)]` attributes is encoded.
+enum FieldKind<'a> {
+    /// Field is always present.
+    Required,
+    /// Field may be absent, in which case it takes its default value.
+    Default,
+    /// Field of type `Option<T>` (where `T` is the included type) that is omitted when `None`.
+    Optional(&'a Type),
+}
+
+/// Field of a struct that uses `#[cbor(..)]` attributes.
+struct CborField<'a> {
+    field: &'a Field,
+    kind: FieldKind<'a>,
+}
+
+/// Struct with named fields that uses `#[cbor(..)]` attributes.
+struct CborStruct<'a> {
+    /// Whether the struct is encoded as a map rather than an array.
+    map: bool,
+    fields: Vec<CborField<'a>>,
+}
+
+/// Return the names given in any `#[cbor(..)]` attributes.
+fn cbor_attr_names(attrs: &[Attribute]) -> syn::Result<Vec<Ident>> {
+    let mut names = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("cbor")) {
+        attr.parse_nested_meta(|meta| match meta.path.get_ident() {
+            Some(ident) => {
+                names.push(ident.clone());
+                Ok(())
+            }
+            None => Err(meta.error("unrecognized cbor attribute")),
+        })?;
+    }
+    Ok(names)
+}
+
+/// Return the `T` in a type of the form `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Parse the `#[cbor(..)]` attributes for the type, returning `None` if there are none.
+fn cbor_struct(input: &DeriveInput) -> syn::Result<Option<CborStruct<'_>>> {
+    let mut map = false;
+    for name in cbor_attr_names(&input.attrs)? {
+        if name != "map" {
+            return Err(syn::Error::new(name.span(), "unrecognized cbor attribute for type"));
+        }
+        map = true;
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+            Fields::Unit => Vec::new(),
+        },
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let mut cbor_fields = Vec::new();
+    let mut any_attrs = map;
+    for field in fields {
+        let mut kind = FieldKind::Required;
+        for name in cbor_attr_names(&field.attrs)? {
+            any_attrs = true;
+            if !matches!(kind, FieldKind::Required) {
+                return Err(syn::Error::new(name.span(), "conflicting cbor attributes for field"));
+            }
+            kind = if name == "default" {
+                FieldKind::Default
+            } else if name == "optional" {
+                FieldKind::Optional(option_inner(&field.ty).ok_or_else(|| {
+                    syn::Error::new(field.ty.span(), "optional field must be of type Option<T>")
+                })?)
+            } else {
+                return Err(syn::Error::new(name.span(), "unrecognized cbor attribute for field"));
+            };
+        }
+        cbor_fields.push(CborField { field, kind });
+    }
+    if !any_attrs {
+        return Ok(None);
+    }
+    if !matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Named(_))) {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "cbor attributes are only supported for structs with named fields",
+        ));
+    }
+    if !map {
+        // Fields that may be absent from an array have to be at the end, in the order required,
+        // default, optional.
+        let rank = |kind: &FieldKind| match kind {
+            FieldKind::Required => 0,
+            FieldKind::Default => 1,
+            FieldKind::Optional(_) => 2,
+        };
+        for pair in cbor_fields.windows(2) {
+            if rank(&pair[1].kind) < rank(&pair[0].kind) {
+                return Err(syn::Error::new(
+                    pair[1].field.span(),
+                    "array-encoded fields must be ordered required, then default, then optional",
+                ));
+            }
+        }
+    }
+    Ok(Some(CborStruct { map, fields: cbor_fields }))
+}
+
+/// Generate an expression to convert an instance of a struct that uses `#[cbor(..)]` attributes to
+/// `ciborium::value::Value`.
+fn to_val_attrs(cs: &CborStruct) -> TokenStream {
+    let nfields = cs.fields.len();
+    if cs.map {
+        // Expands to an expression like
+        //
+        //     {
+        //         let mut v = Vec::new();
+        //         v.try_reserve(3).map_err(|_e| CborError::AllocationFailed)?;
+        //         v.push((ciborium::value::Value::Integer(0.into()),
+        //                 AsCborValue::to_cbor_value(self.x)?));
+        //         v.push((ciborium::value::Value::Integer(1.into()),
+        //                 AsCborValue::to_cbor_value(self.y)?));
+        //         if let Some(val) = self.z {
+        //             v.push((ciborium::value::Value::Integer(2.into()),
+        //                     AsCborValue::to_cbor_value(val)?));
+        //         }
+        //         Ok(ciborium::value::Value::Map(v))
+        //     }
+        let recurse = cs.fields.iter().enumerate().map(|(i, f)| {
+            let name = &f.field.ident;
+            let key = quote! { ciborium::value::Value::Integer((#i as u64).into()) };
+            match f.kind {
+                FieldKind::Optional(_) => quote_spanned! {f.field.span()=>
+                    if let Some(val) = self.#name {
+                        v.push((#key, AsCborValue::to_cbor_value(val)?));
+                    }
+                },
+                _ => quote_spanned! {f.field.span()=>
+                    v.push((#key, AsCborValue::to_cbor_value(self.#name)?));
+                },
+            }
+        });
+        quote! {
+            {
+                let mut v = Vec::new();
+                v.try_reserve(#nfields).map_err(|_e| CborError::AllocationFailed)?;
+                #(#recurse)*
+                Ok(ciborium::value::Value::Map(v))
+            }
+        }
+    } else {
+        // Expands to an expression like
+        //
+        //     {
+        //         let mut v = Vec::new();
+        //         v.try_reserve(3).map_err(|_e| CborError::AllocationFailed)?;
+        //         let mut absent = false;
+        //         v.push(AsCborValue::to_cbor_value(self.x)?);
+        //         match self.y {
+        //             Some(val) if absent => return Err(CborError::EncodeFailed),
+        //             Some(val) => v.push(AsCborValue::to_cbor_value(val)?),
+        //             None => absent = true, // or {} for the last field
+        //         }
+        //         Ok(ciborium::value::Value::Array(v))
+        //     }
+        let recurse = cs.fields.iter().enumerate().map(|(i, f)| {
+            let name = &f.field.ident;
+            // Only a later field can observe that this field was absent.
+            let none = if i + 1 < nfields {
+                quote! { absent = true }
+            } else {
+                quote! { {} }
+            };
+            match f.kind {
+                FieldKind::Optional(_) => quote_spanned! {f.field.span()=>
+                    match self.#name {
+                        // An array can't skip over an absent field.
+                        Some(_) if absent => return Err(CborError::EncodeFailed),
+                        Some(val) => v.push(AsCborValue::to_cbor_value(val)?),
+                        None => #none,
+                    }
+                },
+                _ => quote_spanned! {f.field.span()=>
+                    v.push(AsCborValue::to_cbor_value(self.#name)?);
+                },
+            }
+        });
+        quote! {
+            {
+                let mut v = Vec::new();
+                v.try_reserve(#nfields).map_err(|_e| CborError::AllocationFailed)?;
+                #[allow(unused_mut, unused_variables)]
+                let mut absent = false;
+                #(#recurse)*
+                Ok(ciborium::value::Value::Array(v))
+            }
+        }
+    }
+}
+
+/// Generate an expression to convert a `ciborium::value::Value` into an instance of a struct that
+/// uses `#[cbor(..)]` attributes.
+fn from_val_attrs(cs: &CborStruct) -> TokenStream {
+    // Expression that converts `val` (which may not be present) to the type of the field.
+    let convert = |f: &CborField, val: TokenStream, missing: TokenStream| {
+        let typ = &f.field.ty;
+        match f.kind {
+            FieldKind::Required => quote_spanned! {f.field.span()=>
+                match #val {
+                    Some(val) => <#typ>::from_cbor_value(val)?,
+                    None => return Err(#missing),
+                }
+            },
+            FieldKind::Default => quote_spanned! {f.field.span()=>
+                match #val {
+                    Some(val) => <#typ>::from_cbor_value(val)?,
+                    None => Default::default(),
+                }
+            },
+            FieldKind::Optional(inner) => quote_spanned! {f.field.span()=>
+                match #val {
+                    Some(val) => Some(<#inner>::from_cbor_value(val)?),
+                    None => None,
+                }
+            },
+        }
+    };
+    if cs.map {
+        // Expands to an expression like
+        //
+        //     let m = match value {
+        //         ciborium::value::Value::Map(m) => m,
+        //         _ => return cbor_type_error(&value, "map"),
+        //     };
+        //     let mut vals = [None, None, None];
+        //     for (key, val) in m {
+        //         let key: i64 = match key { /* int, or error */ };
+        //         match usize::try_from(key) {
+        //             Ok(idx) if idx < 3 => {
+        //                 if vals[idx].is_some() { /* duplicate key error */ }
+        //                 vals[idx] = Some(val);
+        //             }
+        //             _ => {} // Ignore unrecognized keys.
+        //         }
+        //     }
+        //     let [val_0, val_1, val_2] = vals;
+        //     Ok(Self {
+        //         x: match val_0 { Some(val) => <XType>::from_cbor_value(val)?, None => ... },
+        //         y: match val_1 { Some(val) => <YType>::from_cbor_value(val)?, None => ... },
+        //         z: match val_2 { Some(val) => Some(<ZType>::from_cbor_value(val)?), None => None },
+        //     })
+        let nfields = cs.fields.len();
+        let nones = cs.fields.iter().map(|_| quote! { None });
+        let varnames: Vec<_> = (0..nfields).map(|i| format_ident!("val_{}", i)).collect();
+        let recurse = cs.fields.iter().zip(varnames.iter()).enumerate().map(|(i, (f, var))| {
+            let name = &f.field.ident;
+            let missing = LitStr::new(&format!("map with key {}", i), f.field.span());
+            let value = convert(
+                f,
+                quote! { #var },
+                quote! { CborError::UnexpectedItem("map without key", #missing) },
+            );
+            quote_spanned! {f.field.span()=>
+                #name: #value
+            }
+        });
+        quote! {
+            use core::convert::TryInto;
+            let m = match value {
+                ciborium::value::Value::Map(m) => m,
+                _ => return cbor_type_error(&value, "map"),
+            };
+            let mut vals: [Option<ciborium::value::Value>; #nfields] = [ #(#nones, )* ];
+            for (key, val) in m {
+                let key: i64 = match key {
+                    ciborium::value::Value::Integer(i) => i.try_into().map_err(|_| {
+                        CborError::OutOfRangeIntegerValue
+                    })?,
+                    k => return cbor_type_error(&k, "int"),
+                };
+                match usize::try_from(key) {
+                    Ok(idx) if idx < #nfields => {
+                        if vals[idx].is_some() {
+                            return Err(CborError::UnexpectedItem("dup map key", "unique keys"));
+                        }
+                        vals[idx] = Some(val);
+                    }
+                    // Keys from a newer version of the struct are ignored.
+                    _ => {}
+                }
+            }
+            let [ #(#varnames, )* ] = vals;
+            Ok(Self {
+                #(#recurse, )*
+            })
+        }
+    } else {
+        // Expands to an expression like
+        //
+        //     let mut a = match value {
+        //         ciborium::value::Value::Array(a) => a,
+        //         _ => return cbor_type_error(&value, "arr"),
+        //     };
+        //     if a.len() < 2 || a.len() > 3 {
+        //         return Err(CborError::UnexpectedItem("arr", "arr len 2..=3"));
+        //     }
+        //     let len = a.len();
+        //     // Fields specified in reverse order to reduce shifting.
+        //     Ok(Self {
+        //         z: match (len > 2).then(|| a.remove(2)) {
+        //             Some(val) => Some(<ZType>::from_cbor_value(val)?),
+        //             None => None,
+        //         },
+        //         y: match (len > 1).then(|| a.remove(1)) { .. },
+        //         x: match (len > 0).then(|| a.remove(0)) { .. },
+        //     })
+        let nfields = cs.fields.len();
+        let nrequired = cs.fields.iter().filter(|f| matches!(f.kind, FieldKind::Required)).count();
+        let want = LitStr::new(&format!("arr len {}..={}", nrequired, nfields), Span::call_site());
+        let recurse = cs.fields.iter().enumerate().rev().map(|(i, f)| {
+            let name = &f.field.ident;
+            let value = convert(
+                f,
+                quote! { (len > #i).then(|| a.remove(#i)) },
+                quote! { CborError::UnexpectedItem("arr", #want) },
+            );
+            quote_spanned! {f.field.span()=>
+                #name: #value
+            }
+        });
+        quote! {
+            let mut a = match value {
+                ciborium::value::Value::Array(a) => a,
+                _ => return cbor_type_error(&value, "arr"),
+            };
+            if a.len() < #nrequired || a.len() > #nfields {
+                return Err(CborError::UnexpectedItem("arr", #want));
+            }
+            let len = a.len();
+            // Fields specified in reverse order to reduce shifting.
+            Ok(Self {
+                #(#recurse, )*
+            })
+        }
+    }
+}
+
+/// Generate an expression that expresses the CDDL schema for a struct that uses `#[cbor(..)]`
+/// attributes.
+fn cddl_attrs(cs: &CborStruct) -> TokenStream {
+    // Expands to an expression like
+    //
+    //     format!("{{
+    //         0 => {}, ; x
+    //         ? 1 => {}, ; y
+    //         * int => any,
+    //     }}",
+    //         <TypeX>::cddl_ref(),
+    //         <TypeY>::cddl_ref(),
+    //     )
+    //
+    // for a map, or
+    //
+    //     format!("[
+    //         x: {},
+    //         ? y: {},
+    //     ]",
+    //         <TypeX>::cddl_ref(),
+    //         <TypeY>::cddl_ref(),
+    //     )
+    //
+    // for an array.
+    let mut fmt = String::from(if cs.map { "{{\n" } else { "[\n" });
+    for (i, f) in cs.fields.iter().enumerate() {
+        let name = f.field.ident.as_ref().unwrap();
+        let occur = if matches!(f.kind, FieldKind::Required) { "" } else { "? " };
+        if cs.map {
+            fmt += &format!("    {}{} => {{}}, ; {}\n", occur, i, name);
+        } else {
+            fmt += &format!("    {}{}: {{}},\n", occur, name);
+        }
+    }
+    fmt += if cs.map { "    * int => any,\n}}" } else { "]" };
+    let fmt = LitStr::new(&fmt, Span::call_site());
+    let recurse = cs.fields.iter().map(|f| {
+        let typ = match f.kind {
+            FieldKind::Optional(inner) => inner,
+            _ => &f.field.ty,
+        };
+        quote_spanned! {f.field.span()=>
+                        <#typ>::cddl_ref()
+        }
+    });
+    quote! {
+        Some(format!(
+            #fmt,
+            #(#recurse, )*
+        ))
+    }
+}
+
+/// Variant of an enum that is encoded as an array holding a discriminant and the variant's fields.
+struct TaggedVariant<'a> {
+    variant: &'a Variant,
+    /// Expression for the discriminant.
+    tag: Expr,
+    /// Whether a unit variant is encoded with a trailing `true`.
+    flag: bool,
+}
+
+impl TaggedVariant<'_> {
+    /// Return the `#[cfg(..)]` attributes for the variant, which also need to be applied to the
+    /// code that is generated for it.
+    fn cfg_attrs(&self) -> impl Iterator<Item = &Attribute> {
+        self.variant.attrs.iter().filter(|attr| attr.path().is_ident("cfg"))
+    }
+
+    /// Return an expression for the discriminant as an `i64`.  This uses the call site span, so
+    /// that lints about the cast are not reported against the type that uses the derive.
+    fn tag_value(&self) -> TokenStream {
+        let tag = &self.tag;
+        quote! { (#tag) as i64 }
+    }
+
+    /// Return the number of entries in the array encoding of the variant, after the discriminant.
+    fn payload_len(&self) -> usize {
+        if self.flag {
+            1
+        } else {
+            self.variant.fields.len()
+        }
+    }
+
+    /// Return a pattern that matches the variant, binding its fields to `field_0`, `field_1`, ...
+    fn pattern(&self) -> TokenStream {
+        let vname = &self.variant.ident;
+        let varnames = (0..self.variant.fields.len()).map(|i| format_ident!("field_{}", i));
+        match &self.variant.fields {
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote! { Self::#vname { #(#names: #varnames, )* } }
+            }
+            Fields::Unnamed(_) => quote! { Self::#vname( #(#varnames, )* ) },
+            Fields::Unit => quote! { Self::#vname },
+        }
+    }
+}
+
+/// Parse the `#[cbor(..)]` attributes for the variants of an enum that carries data, returning
+/// `None` for a fieldless enum that has no such attributes (and so is encoded as an integer).
+fn cbor_tagged_enum(input: &DeriveInput) -> syn::Result<Option<Vec<TaggedVariant<'_>>>> {
+    let Data::Enum(data) = &input.data else { return Ok(None) };
+    let tagged = data.variants.iter().any(|variant| {
+        !matches!(variant.fields, Fields::Unit)
+            || variant.attrs.iter().any(|attr| attr.path().is_ident("cbor"))
+    });
+    if !tagged {
+        return Ok(None);
+    }
+    if let Some(name) = cbor_attr_names(&input.attrs)?.first() {
+        return Err(syn::Error::new(name.span(), "unrecognized cbor attribute for type"));
+    }
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        let mut tag = None;
+        let mut flag = false;
+        for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("cbor")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("flag") {
+                    flag = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unrecognized cbor attribute for variant"))
+                }
+            })?;
+        }
+        let tag = tag.ok_or_else(|| {
+            syn::Error::new(variant.span(), "enum variant with data needs a #[cbor(tag = N)]")
+        })?;
+        if flag && !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "cbor(flag) is only valid on unit variants",
+            ));
+        }
+        for field in &variant.fields {
+            if let Some(name) = cbor_attr_names(&field.attrs)?.first() {
+                return Err(syn::Error::new(name.span(), "unrecognized cbor attribute for field"));
+            }
+        }
+        variants.push(TaggedVariant { variant, tag, flag });
+    }
+    Ok(Some(variants))
+}
+
+/// Generate an expression to convert an instance of an enum that carries data to
+/// `ciborium::value::Value`.
+fn to_val_tagged(variants: &[TaggedVariant]) -> TokenStream {
+    // Expands to an expression like
+    //
+    //     match self {
+    //         Self::Variant1(field_0, field_1) => {
+    //             let mut v = Vec::new();
+    //             v.try_reserve(3).map_err(|_e| CborError::AllocationFailed)?;
+    //             v.push(ciborium::value::Value::Integer(((TAG1) as i64).into()));
+    //             v.push(AsCborValue::to_cbor_value(field_0)?);
+    //             v.push(AsCborValue::to_cbor_value(field_1)?);
+    //             Ok(ciborium::value::Value::Array(v))
+    //         }
+    //         Self::Variant2 => { /* for a flag variant, push `true` after the tag */ }
+    //     }
+    let recurse = variants.iter().map(|tv| {
+        let cfgs = tv.cfg_attrs();
+        let pattern = tv.pattern();
+        let tag = tv.tag_value();
+        let len = tv.payload_len() + 1;
+        let payload = if tv.flag {
+            vec![quote! { v.push(ciborium::value::Value::Bool(true)); }]
+        } else {
+            (0..tv.variant.fields.len())
+                .map(|i| {
+                    let varname = format_ident!("field_{}", i);
+                    quote! { v.push(AsCborValue::to_cbor_value(#varname)?); }
+                })
+                .collect()
+        };
+        quote_spanned! {tv.variant.span()=>
+            #(#cfgs)*
+            #pattern => {
+                let mut v = Vec::new();
+                v.try_reserve(#len).map_err(|_e| CborError::AllocationFailed)?;
+                v.push(ciborium::value::Value::Integer((#tag).into()));
+                #(#payload)*
+                Ok(ciborium::value::Value::Array(v))
+            }
+        }
+    });
+    quote! {
+        match self {
+            #(#recurse)*
+        }
+    }
+}
+
+/// Generate an expression to convert a `ciborium::value::Value` into an instance of an enum that
+/// carries data.
+fn from_val_tagged(variants: &[TaggedVariant]) -> TokenStream {
+    // Expands to an expression like
+    //
+    //     use core::convert::TryInto;
+    //     let mut a = match value {
+    //         ciborium::value::Value::Array(a) if !a.is_empty() => a,
+    //         _ => return cbor_type_error(&value, "arr"),
+    //     };
+    //     let tag: i64 = match a.remove(0) { /* int, or error */ };
+    //     match tag {
+    //         x if x == (TAG1) as i64 => {
+    //             if a.len() != 2 {
+    //                 return Err(CborError::UnexpectedItem("arr", "arr len 3"));
+    //             }
+    //             // Fields specified in reverse order to reduce shifting.
+    //             let field_1 = <Type1>::from_cbor_value(a.remove(1))?;
+    //             let field_0 = <Type0>::from_cbor_value(a.remove(0))?;
+    //             Ok(Self::Variant1(field_0, field_1))
+    //         }
+    //         _ => Err(CborError::NonEnumValue),
+    //     }
+    let recurse = variants.iter().map(|tv| {
+        let cfgs = tv.cfg_attrs();
+        let pattern = tv.pattern();
+        let tag = tv.tag_value();
+        let len = tv.payload_len();
+        let want = LitStr::new(&format!("arr len {}", len + 1), Span::call_site());
+        let payload = if tv.flag {
+            vec![quote! {
+                match a.remove(0) {
+                    ciborium::value::Value::Bool(true) => {}
+                    v => return cbor_type_error(&v, "true"),
+                }
+            }]
+        } else {
+            tv.variant
+                .fields
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, f)| {
+                    let typ = &f.ty;
+                    let varname = format_ident!("field_{}", i);
+                    quote_spanned! {f.span()=>
+                        let #varname = <#typ>::from_cbor_value(a.remove(#i))?;
+                    }
+                })
+                .collect()
+        };
+        quote_spanned! {tv.variant.span()=>
+            #(#cfgs)*
+            x if x == #tag => {
+                if a.len() != #len {
+                    return Err(CborError::UnexpectedItem("arr", #want));
+                }
+                // Fields specified in reverse order to reduce shifting.
+                #(#payload)*
+                Ok(#pattern)
+            }
+        }
+    });
+    quote! {
+        use core::convert::TryInto;
+        let mut a = match value {
+            ciborium::value::Value::Array(a) if !a.is_empty() => a,
+            _ => return cbor_type_error(&value, "arr"),
+        };
+        let tag: i64 = match a.remove(0) {
+            ciborium::value::Value::Integer(i) => i.try_into().map_err(|_| {
+                CborError::OutOfRangeIntegerValue
+            })?,
+            v => return cbor_type_error(&v, "int"),
+        };
+        match tag {
+            #(#recurse)*
+            _ => Err(CborError::NonEnumValue),
+        }
+    }
+}
+
+/// Generate an expression that expresses the CDDL schema for an enum that carries data, as a choice
+/// between the array encodings of its variants.
+fn cddl_tagged(name: &Ident, variants: &[TaggedVariant]) -> TokenStream {
+    // Expands to an expression like
+    //
+    //     let mut result = String::from("&(\n");
+    //     result += &format!("    [{}, {}, {}], ; Tag_Variant1\n",
+    //                        (Tag::Variant1) as i64,
+    //                        <Type0>::cddl_ref(),
+    //                        <Type1>::cddl_ref());
+    //     result += &format!("    [{}, true], ; Tag_Variant2\n", (Tag::Variant2) as i64);
+    //     result += ")";
+    //     Some(result)
+    //
+    // where the comment is the path of the discriminant (or the variant name, if the discriminant
+    // is not a path), and where the fields of a struct-like variant are also named.
+    let recurse = variants.iter().map(|tv| {
+        let cfgs = tv.cfg_attrs();
+        let tag = tv.tag_value();
+        let comment = match &tv.tag {
+            Expr::Path(path) => {
+                path.path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>().join("_")
+            }
+            _ => format!("{}_{}", name, tv.variant.ident),
+        };
+        let mut fmt = String::from("    [{}");
+        if tv.flag {
+            fmt += ", true";
+        }
+        for f in &tv.variant.fields {
+            match &f.ident {
+                Some(fname) => fmt += &format!(", {}: {{}}", fname),
+                None => fmt += ", {}",
+            }
+        }
+        fmt += &format!("], ; {}\n", comment);
+        let fmt = LitStr::new(&fmt, Span::call_site());
+        let types = tv.variant.fields.iter().map(|f| {
+            let typ = &f.ty;
+            quote_spanned! {f.span()=>
+                <#typ>::cddl_ref()
+            }
+        });
+        quote_spanned! {tv.variant.span()=>
+            #(#cfgs)*
+            {
+                result += &format!(#fmt, #tag, #(#types, )*);
+            }
+        }
+    });
+    quote! {
+        let mut result = String::from("&(\n");
+        #(#recurse)*
+        result += ")";
+        Some(result)
+    }
+}
+
 /// Derive macro that implements a `from_raw_tag_value` method for the `Tag` enum.
 #[proc_macro_derive(FromRawTag)]
 pub fn derive_from_raw_tag(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -502,14 +1242,10 @@ pub fn derive_legacy_serialize(input: proc_macro::TokenStream) -> proc_macro::To
 fn derive_legacy_serialize_internal(input: &DeriveInput) -> proc_macro::TokenStream {
     let name = &input.ident;
 
-    let deserialize_val = deserialize_struct(&input.data);
     let serialize_val = serialize_struct(&input.data);
 
     let expanded = quote! {
         impl InnerSerialize for #name {
-            fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
-                #deserialize_val
-            }
             fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
                 #serialize_val
             }
@@ -519,6 +1255,30 @@ fn derive_legacy_serialize_internal(input: &DeriveInput) -> proc_macro::TokenStr
     expanded.into()
 }
 
+/// Derive macro that implements the `legacy::InnerDeserialize` trait.  Using this macro requires
+/// that `InnerDeserialize` and `Error` from `kmr_wire::legacy` be locally `use`d.
+#[proc_macro_derive(LegacyDeserialize)]
+pub fn derive_legacy_deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_legacy_deserialize_internal(&input)
+}
+
+fn derive_legacy_deserialize_internal(input: &DeriveInput) -> proc_macro::TokenStream {
+    let name = &input.ident;
+
+    let deserialize_val = deserialize_struct(&input.data);
+
+    let expanded = quote! {
+        impl InnerDeserialize for #name {
+            fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
+                #deserialize_val
+            }
+        }
+    };
+
+    expanded.into()
+}
+
 fn deserialize_struct(data: &Data) -> TokenStream {
     match data {
         Data::Struct(ref data) => {
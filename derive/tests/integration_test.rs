@@ -63,3 +63,168 @@ fn test_derive_numeric_enum_roundtrip() {
         "&(\n    NumericEnum_One: 1,\n    NumericEnum_Two: 2,\n    NumericEnum_Three: 3,\n)"
     );
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+struct ArrayV1 {
+    i: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+struct ArrayV2 {
+    i: i32,
+    #[cbor(default)]
+    u: u32,
+    #[cbor(optional)]
+    s: Option<String>,
+    #[cbor(optional)]
+    t: Option<String>,
+}
+
+#[test]
+fn test_derive_array_attrs() {
+    // Older encoding is accepted by newer version.
+    let v1 = ArrayV1 { i: 42 };
+    let got = ArrayV2::from_cbor_value(v1.to_cbor_value().unwrap()).unwrap();
+    assert_eq!(got, ArrayV2 { i: 42, u: 0, s: None, t: None });
+
+    let tests = [
+        ArrayV2 { i: 42, u: 1, s: None, t: None },
+        ArrayV2 { i: 42, u: 1, s: Some("a".to_string()), t: None },
+        ArrayV2 { i: 42, u: 1, s: Some("a".to_string()), t: Some("b".to_string()) },
+    ];
+    for (idx, want) in tests.into_iter().enumerate() {
+        let value = want.clone().to_cbor_value().unwrap();
+        match &value {
+            ciborium::value::Value::Array(a) => assert_eq!(a.len(), 2 + idx),
+            _ => panic!("not an array: {value:?}"),
+        }
+        let got = ArrayV2::from_cbor_value(value).unwrap();
+        assert_eq!(want, got);
+    }
+
+    // An absent optional field can't be followed by a present one.
+    let v2 = ArrayV2 { i: 42, u: 1, s: None, t: Some("b".to_string()) };
+    assert!(matches!(v2.to_cbor_value(), Err(CborError::EncodeFailed)));
+
+    assert_eq!(
+        ArrayV2::cddl_schema().unwrap(),
+        "[\n    i: int,\n    ? u: int,\n    ? s: tstr,\n    ? t: tstr,\n]"
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+#[cbor(map)]
+struct MapV1 {
+    i: i32,
+    s: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+#[cbor(map)]
+struct MapV2 {
+    i: i32,
+    s: String,
+    #[cbor(default)]
+    u: u32,
+    #[cbor(optional)]
+    o: Option<i64>,
+}
+
+#[test]
+fn test_derive_map_attrs() {
+    let v1 = MapV1 { i: 42, s: "a string".to_string() };
+    let v2 = MapV2 { i: 42, s: "a string".to_string(), u: 7, o: Some(-1) };
+
+    // Each version accepts the encoding of the other.
+    let got = MapV2::from_cbor_value(v1.clone().to_cbor_value().unwrap()).unwrap();
+    assert_eq!(got, MapV2 { i: 42, s: "a string".to_string(), u: 0, o: None });
+    let got = MapV1::from_cbor_value(v2.clone().to_cbor_value().unwrap()).unwrap();
+    assert_eq!(got, v1);
+    let got = MapV2::from_cbor_value(v2.clone().to_cbor_value().unwrap()).unwrap();
+    assert_eq!(got, v2);
+
+    // Keys are field positions, and absent optional fields are omitted.
+    let v2 = MapV2 { i: 42, s: "a string".to_string(), u: 7, o: None };
+    let data = v2.into_vec().unwrap();
+    assert_eq!(
+        data,
+        [
+            0xa3, 0x00, 0x18, 0x2a, 0x01, 0x68, b'a', b' ', b's', b't', b'r', b'i', b'n', b'g',
+            0x02, 0x07
+        ]
+    );
+    assert!(kmr_wire::check_canonical(&data).is_ok());
+
+    // Required fields must be present, and keys must be unique integers.
+    let tests: [&[u8]; 4] = [
+        &[0xa1, 0x01, 0x18, 0x18],             // {1: 24}
+        &[0xa2, 0x00, 0x00, 0x00, 0x01],       // {0: 0, 0: 1}
+        &[0xa2, 0x00, 0x00, 0x61, b'a', 0x00], // {0: 0, "a": 0}
+        &[0x81, 0x00],                         // [0]
+    ];
+    for data in tests {
+        assert!(MapV1::from_slice(data).is_err(), "for {data:?}");
+    }
+
+    assert_eq!(
+        MapV2::cddl_schema().unwrap(),
+        "{\n    0 => int, ; i\n    1 => tstr, ; s\n    ? 2 => int, ; u\n    ? 3 => int, ; o\n    * int => any,\n}"
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+enum TaggedEnum {
+    #[cbor(tag = 1)]
+    Int(i32),
+    #[cbor(tag = NumericEnum::Two)]
+    Pair(String, Vec<u8>),
+    #[cbor(tag = -3)]
+    Named { i: i32, e: NumericEnum },
+    #[cbor(tag = 4)]
+    Unit,
+    #[cbor(tag = 5, flag)]
+    Flag,
+}
+
+#[test]
+fn test_derive_tagged_enum_roundtrip() {
+    let tests: [(TaggedEnum, &[u8]); 5] = [
+        (TaggedEnum::Int(42), &[0x82, 0x01, 0x18, 0x2a]),
+        (TaggedEnum::Pair("a".to_string(), vec![0xff]), &[0x83, 0x02, 0x61, b'a', 0x41, 0xff]),
+        (TaggedEnum::Named { i: -1, e: NumericEnum::Three }, &[0x83, 0x22, 0x20, 0x03]),
+        (TaggedEnum::Unit, &[0x81, 0x04]),
+        (TaggedEnum::Flag, &[0x82, 0x05, 0xf5]),
+    ];
+    for (want, data) in tests {
+        assert_eq!(want.clone().into_vec().unwrap(), data, "for {want:?}");
+        let got = TaggedEnum::from_slice(data).unwrap();
+        assert_eq!(want, got);
+    }
+
+    // Unknown discriminants, wrong payload lengths and false flags are rejected.
+    let tests: [&[u8]; 6] = [
+        &[0x82, 0x06, 0x00],       // [6, 0]
+        &[0x83, 0x01, 0x00, 0x00], // [1, 0, 0]
+        &[0x81, 0x01],             // [1]
+        &[0x82, 0x04, 0x00],       // [4, 0]
+        &[0x82, 0x05, 0xf4],       // [5, false]
+        &[0x80],                   // []
+    ];
+    for data in tests {
+        assert!(TaggedEnum::from_slice(data).is_err(), "for {data:?}");
+    }
+
+    assert_eq!(TaggedEnum::cddl_typename().unwrap(), "TaggedEnum");
+    assert_eq!(
+        TaggedEnum::cddl_schema().unwrap(),
+        concat!(
+            "&(\n",
+            "    [1, int], ; TaggedEnum_Int\n",
+            "    [2, tstr, bstr], ; NumericEnum_Two\n",
+            "    [-3, i: int, e: NumericEnum], ; TaggedEnum_Named\n",
+            "    [4], ; TaggedEnum_Unit\n",
+            "    [5, true], ; TaggedEnum_Flag\n",
+            ")",
+        )
+    );
+}
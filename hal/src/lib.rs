@@ -41,6 +41,7 @@ pub use binder;
 pub mod env;
 pub mod hal;
 pub mod keymint;
+pub mod record;
 pub mod rpc;
 pub mod secureclock;
 pub mod sharedsecret;
@@ -191,23 +192,33 @@ where
     // Send in request bytes, get back response bytes.
     let rsp_data = channel.execute(&req_data)?;
 
-    // Convert the raw response data to an array of [error code, opt_response].
+    // Convert the raw response data to an array of [error code, opt_response, ?diagnostics].
     let rsp_value = kmr_wire::read_to_value(&rsp_data).map_err(failed_cbor)?;
     let mut rsp_array = match rsp_value {
-        cbor::value::Value::Array(a) if a.len() == 2 => a,
+        cbor::value::Value::Array(a) if a.len() == 2 || a.len() == 3 => a,
         _ => {
             error!("HAL: failed to parse response data 2-array!");
-            return cbor_type_error(&rsp_value, "arr of len 2").map_err(failed_cbor);
+            return cbor_type_error(&rsp_value, "arr of len 2 or 3").map_err(failed_cbor);
         }
     };
+    // A TA built with the `diagnostics` feature describes any error in an extra trailing element.
+    let diagnostics = if rsp_array.len() == 3 {
+        Some(<String>::from_cbor_value(rsp_array.remove(2)).map_err(failed_cbor)?)
+    } else {
+        None
+    };
     let opt_response = rsp_array.remove(1);
     let error_code = <i32>::from_cbor_value(rsp_array.remove(0)).map_err(failed_cbor)?;
     // The error code is in a numbering space that depends on the specific HAL being
     // invoked (IRemotelyProvisionedComponent vs. the rest). However, the OK value is
     // the same in all spaces.
     if error_code != ErrorCode::Ok as i32 {
-        warn!("HAL: command {:?} failed: {:?}", <R>::CODE, error_code);
-        return Err(binder::Status::new_service_specific_error(error_code, None));
+        warn!("HAL: command {:?} failed: {:?} {:?}", <R>::CODE, error_code, diagnostics);
+        let msg = diagnostics.map(|msg| {
+            // Binder status messages can't hold embedded NUL characters.
+            CString::new(msg.replace('\0', " ")).unwrap(/* safe: NULs removed */)
+        });
+        return Err(binder::Status::new_service_specific_error(error_code, msg.as_deref()));
     }
 
     // The optional response should be an array of exactly 1 element (because the 0-element case
@@ -298,6 +309,18 @@ pub fn send_boot_info<T: SerializedChannel>(
     Ok(())
 }
 
+/// Retrieve (and clear) the TA's log of security-relevant events (a vendor extension).
+pub fn drain_audit_log<T: SerializedChannel>(
+    channel: &mut T,
+) -> binder::Result<kmr_wire::DrainAuditLogResponse> {
+    let rsp: kmr_wire::DrainAuditLogResponse =
+        channel_execute(channel, kmr_wire::DrainAuditLogRequest {})?;
+    if rsp.dropped > 0 {
+        warn!("TA->HAL: {} audit records were dropped because the audit log was full", rsp.dropped);
+    }
+    Ok(rsp)
+}
+
 /// Provision the TA with attestation ID information.
 pub fn send_attest_ids<T: SerializedChannel>(
     channel: &mut T,
@@ -0,0 +1,78 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of the traffic on a [`SerializedChannel`], so that it can be inspected or replayed
+//! against a TA later on.
+//!
+//! A recording holds key material and other secrets that pass between HAL and TA, so a
+//! [`RecordingChannel`] should only be used to reproduce problems on development devices.
+
+use crate::{binder, SerializedChannel};
+use core::fmt::Debug;
+use log::warn;
+use std::{io::Write, time::Instant};
+
+/// [`SerializedChannel`] decorator that records every request and response that passes through the
+/// wrapped channel, one per line, as:
+///
+/// ```text
+/// <elapsed_ms> > <request hex>
+/// <elapsed_ms> < <response hex>
+/// ```
+///
+/// where `<elapsed_ms>` is the time since the channel was created.  If the wrapped channel fails,
+/// the request is followed by a `#` comment line that describes the error.  This is the text log
+/// format understood by the `kmr_message_inspect` and `kmr_replay` host tools.
+///
+/// Failure to write the recording is logged but does not affect the channel.
+#[derive(Debug)]
+pub struct RecordingChannel<T: SerializedChannel, W: Write + Debug + Send> {
+    inner: T,
+    out: W,
+    start: Instant,
+}
+
+impl<T: SerializedChannel, W: Write + Debug + Send> RecordingChannel<T, W> {
+    /// Wrap `inner`, writing the recording to `out`.
+    pub fn new(inner: T, out: W) -> Self {
+        Self { inner, out, start: Instant::now() }
+    }
+
+    /// Return the wrapped channel and the recording destination.
+    pub fn into_inner(self) -> (T, W) {
+        (self.inner, self.out)
+    }
+
+    fn record(&mut self, line: std::fmt::Arguments) {
+        if let Err(e) = self.out.write_fmt(line).and_then(|_| self.out.flush()) {
+            warn!("Failed to record channel traffic: {:?}", e);
+        }
+    }
+}
+
+impl<T: SerializedChannel, W: Write + Debug + Send> SerializedChannel for RecordingChannel<T, W> {
+    const MAX_SIZE: usize = T::MAX_SIZE;
+
+    fn execute(&mut self, serialized_req: &[u8]) -> binder::Result<Vec<u8>> {
+        let elapsed = self.start.elapsed().as_millis();
+        self.record(format_args!("{} > {}\n", elapsed, hex::encode(serialized_req)));
+        let result = self.inner.execute(serialized_req);
+        let elapsed = self.start.elapsed().as_millis();
+        match &result {
+            Ok(rsp) => self.record(format_args!("{} < {}\n", elapsed, hex::encode(rsp))),
+            Err(e) => self.record(format_args!("# {} channel error: {:?}\n", elapsed, e)),
+        }
+        result
+    }
+}
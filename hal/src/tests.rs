@@ -109,6 +109,52 @@ fn test_method_err_roundtrip() {
     assert_eq!(status.service_specific_error(), ErrorCode::UNSUPPORTED_PURPOSE.0);
 }
 
+#[test]
+fn test_method_err_diagnostics() {
+    let channel = TestChannel::new(concat!(
+        "83", // 3-arr (PerformOpResponse)
+        "21", // (PerformOpResponse.error_code = ErrorCode::UNSUPPORTED_PURPOSE)
+        "80", // 0-arr (PerformOpResponse.rsp)
+        "63", "626164", // 3-tstr (PerformOpResponse.diagnostics = "bad")
+    ));
+    let imp = keymint::Device::new(Arc::new(Mutex::new(channel.clone())));
+
+    let status = imp.generateKey(&[], None).unwrap_err();
+    assert_eq!(status.exception_code(), binder::ExceptionCode::SERVICE_SPECIFIC);
+    assert_eq!(status.service_specific_error(), ErrorCode::UNSUPPORTED_PURPOSE.0);
+    assert!(status.get_description().contains("bad"), "{}", status.get_description());
+}
+
+#[test]
+fn test_recording_channel() {
+    let channel = TestChannel::new(concat!(
+        "82", // 2-arr (PerformOpResponse)
+        "00", // int   (PerformOpResponse.error_code == ErrorCode::Ok)
+        "81", // 1-arr (PerformOpResponse.rsp)
+        "82", // 2-arr (PerformOpResponse.rsp.0 : PerformOpRsp)
+        "1c", // 0x1c = KeyMintOperation::DEVICE_EARLY_BOOT_ENDED
+        "80", // 0-arr (EarlyBootEndedResponse)
+    ));
+    let mut recorder = record::RecordingChannel::new(channel.clone(), Vec::new());
+
+    early_boot_ended(&mut recorder).unwrap();
+    early_boot_ended(&mut recorder).unwrap();
+
+    let (_channel, recording) = recorder.into_inner();
+    let recording = String::from_utf8(recording).unwrap();
+    let lines: Vec<Vec<&str>> = recording.lines().map(|line| line.split(' ').collect()).collect();
+    assert_eq!(lines.len(), 4, "{recording}");
+    for (idx, line) in lines.iter().enumerate() {
+        assert_eq!(line.len(), 3, "{recording}");
+        assert!(line[0].parse::<u64>().is_ok(), "{recording}");
+        if idx % 2 == 0 {
+            assert_eq!(&line[1..], &[">", "821c80"]);
+        } else {
+            assert_eq!(&line[1..], &["<", "820081821c80"]);
+        }
+    }
+}
+
 #[test]
 fn test_overhead_size() {
     let largest_op_req = PerformOpReq::OperationFinish(FinishRequest {
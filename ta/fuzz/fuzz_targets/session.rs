@@ -0,0 +1,490 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stateful fuzzer that drives a [`KeyMintTa`] (with BoringSSL crypto) through sequences of
+//! requests, feeding keyblobs and operation handles from earlier responses into later requests.
+
+#![no_main]
+use arbitrary::Arbitrary;
+use kmr_common::keyblob::sdd_mem::InMemorySlotManager;
+use kmr_ta::{KeyMintTa, MAX_TEE_OPERATIONS};
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, DateTime, Digest, EcCurve, ErrorCode, KeyParam, KeyPurpose, PaddingMode,
+};
+use kmr_wire::*;
+use libfuzzer_sys::fuzz_target;
+
+/// Maximum number of requests in a single session, to keep individual runs fast.
+const MAX_ACTIONS: usize = 64;
+
+/// Key sizes that can be requested.  Large RSA keys are omitted as they are slow to generate.
+const KEY_SIZES: &[u32] = &[0, 56, 64, 128, 168, 192, 224, 256, 384, 512, 521, 1024, 2048];
+
+const ALGORITHMS: &[Algorithm] =
+    &[Algorithm::Rsa, Algorithm::Ec, Algorithm::Aes, Algorithm::TripleDes, Algorithm::Hmac];
+const BLOCK_MODES: &[BlockMode] = &[BlockMode::Ecb, BlockMode::Cbc, BlockMode::Ctr, BlockMode::Gcm];
+const DIGESTS: &[Digest] = &[
+    Digest::None,
+    Digest::Md5,
+    Digest::Sha1,
+    Digest::Sha224,
+    Digest::Sha256,
+    Digest::Sha384,
+    Digest::Sha512,
+];
+const CURVES: &[EcCurve] =
+    &[EcCurve::P224, EcCurve::P256, EcCurve::P384, EcCurve::P521, EcCurve::Curve25519];
+const PADDINGS: &[PaddingMode] = &[
+    PaddingMode::None,
+    PaddingMode::RsaOaep,
+    PaddingMode::RsaPss,
+    PaddingMode::RsaPkcs115Encrypt,
+    PaddingMode::RsaPkcs115Sign,
+    PaddingMode::Pkcs7,
+];
+const PURPOSES: &[KeyPurpose] = &[
+    KeyPurpose::Encrypt,
+    KeyPurpose::Decrypt,
+    KeyPurpose::Sign,
+    KeyPurpose::Verify,
+    KeyPurpose::WrapKey,
+    KeyPurpose::AgreeKey,
+    KeyPurpose::AttestKey,
+];
+
+/// Pick an entry from a (non-empty) list of values.
+fn pick<T: Copy>(values: &[T], idx: u8) -> T {
+    values[idx as usize % values.len()]
+}
+
+/// A key parameter, restricted to the tags (and values) that affect key generation and use, so
+/// that generated sequences are likely to produce usable keys.
+#[derive(Arbitrary, Debug)]
+enum Param {
+    Purpose(u8),
+    Algorithm(u8),
+    KeySize(u8),
+    BlockMode(u8),
+    Digest(u8),
+    Padding(u8),
+    CallerNonce,
+    MinMacLength(u16),
+    EcCurve(u8),
+    RsaPublicExponent(bool),
+    RsaOaepMgfDigest(u8),
+    RollbackResistance,
+    EarlyBootOnly,
+    ActiveDatetime(i64),
+    UsageExpireDatetime(i64),
+    MaxUsesPerBoot(u8),
+    UsageCountLimit(u8),
+    NoAuthRequired,
+    ApplicationId(Vec<u8>),
+    ApplicationData(Vec<u8>),
+    Nonce(Vec<u8>),
+    MacLength(u16),
+    AttestationChallenge(Vec<u8>),
+    StorageKey,
+}
+
+impl Param {
+    fn key_param(&self) -> KeyParam {
+        match self {
+            Param::Purpose(v) => KeyParam::Purpose(pick(PURPOSES, *v)),
+            Param::Algorithm(v) => KeyParam::Algorithm(pick(ALGORITHMS, *v)),
+            Param::KeySize(v) => KeyParam::KeySize(KeySizeInBits(pick(KEY_SIZES, *v))),
+            Param::BlockMode(v) => KeyParam::BlockMode(pick(BLOCK_MODES, *v)),
+            Param::Digest(v) => KeyParam::Digest(pick(DIGESTS, *v)),
+            Param::Padding(v) => KeyParam::Padding(pick(PADDINGS, *v)),
+            Param::CallerNonce => KeyParam::CallerNonce,
+            Param::MinMacLength(v) => KeyParam::MinMacLength(*v as u32),
+            Param::EcCurve(v) => KeyParam::EcCurve(pick(CURVES, *v)),
+            Param::RsaPublicExponent(f4) => {
+                KeyParam::RsaPublicExponent(RsaExponent(if *f4 { 65537 } else { 3 }))
+            }
+            Param::RsaOaepMgfDigest(v) => KeyParam::RsaOaepMgfDigest(pick(DIGESTS, *v)),
+            Param::RollbackResistance => KeyParam::RollbackResistance,
+            Param::EarlyBootOnly => KeyParam::EarlyBootOnly,
+            Param::ActiveDatetime(ms) => KeyParam::ActiveDatetime(DateTime { ms_since_epoch: *ms }),
+            Param::UsageExpireDatetime(ms) => {
+                KeyParam::UsageExpireDatetime(DateTime { ms_since_epoch: *ms })
+            }
+            Param::MaxUsesPerBoot(v) => KeyParam::MaxUsesPerBoot(*v as u32),
+            Param::UsageCountLimit(v) => KeyParam::UsageCountLimit(*v as u32),
+            Param::NoAuthRequired => KeyParam::NoAuthRequired,
+            Param::ApplicationId(v) => KeyParam::ApplicationId(v.clone()),
+            Param::ApplicationData(v) => KeyParam::ApplicationData(v.clone()),
+            Param::Nonce(v) => KeyParam::Nonce(v.clone()),
+            Param::MacLength(v) => KeyParam::MacLength(*v as u32),
+            Param::AttestationChallenge(v) => KeyParam::AttestationChallenge(v.clone()),
+            Param::StorageKey => KeyParam::StorageKey,
+        }
+    }
+}
+
+fn key_params(params: &[Param]) -> Vec<KeyParam> {
+    params.iter().map(Param::key_param).collect()
+}
+
+/// Return a set of key generation parameters that produces a usable key of the given algorithm,
+/// authorized for most of the modes that the algorithm supports.
+fn base_key_params(algorithm: Algorithm) -> Vec<KeyParam> {
+    let mut params = vec![
+        KeyParam::Algorithm(algorithm),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(keymint::UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(keymint::UNDEFINED_NOT_AFTER),
+    ];
+    let (key_size, purposes, modes): (u32, &[KeyPurpose], Vec<KeyParam>) = match algorithm {
+        Algorithm::Aes => (
+            256,
+            &[KeyPurpose::Encrypt, KeyPurpose::Decrypt],
+            BLOCK_MODES
+                .iter()
+                .map(|m| KeyParam::BlockMode(*m))
+                .chain([PaddingMode::None, PaddingMode::Pkcs7].map(KeyParam::Padding))
+                .chain([KeyParam::MinMacLength(128), KeyParam::CallerNonce])
+                .collect(),
+        ),
+        Algorithm::TripleDes => (
+            168,
+            &[KeyPurpose::Encrypt, KeyPurpose::Decrypt],
+            vec![
+                KeyParam::BlockMode(BlockMode::Ecb),
+                KeyParam::BlockMode(BlockMode::Cbc),
+                KeyParam::Padding(PaddingMode::None),
+                KeyParam::Padding(PaddingMode::Pkcs7),
+            ],
+        ),
+        Algorithm::Hmac => (
+            256,
+            &[KeyPurpose::Sign, KeyPurpose::Verify],
+            vec![KeyParam::Digest(Digest::Sha256), KeyParam::MinMacLength(128)],
+        ),
+        Algorithm::Ec => (
+            256,
+            &[KeyPurpose::Sign, KeyPurpose::Verify, KeyPurpose::AgreeKey],
+            vec![
+                KeyParam::EcCurve(EcCurve::P256),
+                KeyParam::Digest(Digest::None),
+                KeyParam::Digest(Digest::Sha256),
+            ],
+        ),
+        _ => (
+            2048,
+            &[KeyPurpose::Sign, KeyPurpose::Verify, KeyPurpose::Encrypt, KeyPurpose::Decrypt],
+            DIGESTS[..5]
+                .iter()
+                .map(|d| KeyParam::Digest(*d))
+                .chain(PADDINGS[..5].iter().map(|p| KeyParam::Padding(*p)))
+                .chain([KeyParam::RsaPublicExponent(RsaExponent(65537))])
+                .collect(),
+        ),
+    };
+    params.push(KeyParam::KeySize(KeySizeInBits(key_size)));
+    params.extend(purposes.iter().map(|p| KeyParam::Purpose(*p)));
+    params.extend(modes);
+    params
+}
+
+/// Return one of a selection of `begin()` parameter sets that are valid for keys generated with
+/// `base_key_params()`.
+fn base_begin_params(idx: u8) -> Vec<KeyParam> {
+    let sets: [&[KeyParam]; 7] = [
+        &[KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::None)],
+        &[KeyParam::BlockMode(BlockMode::Cbc), KeyParam::Padding(PaddingMode::Pkcs7)],
+        &[
+            KeyParam::BlockMode(BlockMode::Gcm),
+            KeyParam::Padding(PaddingMode::None),
+            KeyParam::MacLength(128),
+        ],
+        &[KeyParam::Digest(Digest::Sha256), KeyParam::MacLength(256)],
+        &[KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaPss)],
+        &[KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaOaep)],
+        &[KeyParam::Digest(Digest::None), KeyParam::Padding(PaddingMode::None)],
+    ];
+    sets[idx as usize % sets.len()].to_vec()
+}
+
+/// A single step in a session.  Keys and operations are referred to by index into the keyblobs and
+/// operation handles returned earlier in the session.
+#[derive(Arbitrary, Debug)]
+enum Action {
+    SetBootInfo {
+        locked: bool,
+        state: i32,
+        patchlevel: u32,
+    },
+    SetHalInfo {
+        os_version: u32,
+        os_patchlevel: u32,
+        vendor_patchlevel: u32,
+    },
+    AddRngEntropy {
+        data: Vec<u8>,
+    },
+    /// Generate a key with the parameters given by `base_key_params()` (if `algorithm` is
+    /// present), followed by `params`.
+    GenerateKey {
+        algorithm: Option<u8>,
+        params: Vec<Param>,
+    },
+    GetKeyCharacteristics {
+        key: u8,
+        app_id: Vec<u8>,
+        app_data: Vec<u8>,
+    },
+    UpgradeKey {
+        key: u8,
+        params: Vec<Param>,
+    },
+    DeleteKey {
+        key: u8,
+    },
+    DeleteAllKeys,
+    EarlyBootEnded,
+    /// Begin an operation with the parameters given by `base_begin_params()` (if `mode` is
+    /// present), followed by `params`.
+    Begin {
+        key: u8,
+        purpose: u8,
+        mode: Option<u8>,
+        params: Vec<Param>,
+    },
+    UpdateAad {
+        op: u8,
+        input: Vec<u8>,
+    },
+    Update {
+        op: u8,
+        input: Vec<u8>,
+    },
+    Finish {
+        op: u8,
+        input: Option<Vec<u8>>,
+        signature: Option<Vec<u8>>,
+    },
+    Abort {
+        op: u8,
+    },
+}
+
+/// State of a fuzzing session.
+struct Session {
+    ta: KeyMintTa,
+    /// Keyblobs returned by the TA.
+    keys: Vec<Vec<u8>>,
+    /// Handles of operations that the TA has started and that have not been finished or aborted.
+    /// Operations that fail on `update()` are also removed, so this is a subset of the operations
+    /// that the TA regards as live.
+    ops: Vec<i64>,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut dev = host::device_impl();
+        dev.sdd_mgr = Some(Box::<InMemorySlotManager<10>>::default());
+        Self {
+            ta: host::keymint_ta(host::crypto_impl(None), dev),
+            keys: Vec::new(),
+            ops: Vec::new(),
+        }
+    }
+
+    fn key(&self, idx: u8) -> Vec<u8> {
+        if self.keys.is_empty() {
+            vec![idx]
+        } else {
+            self.keys[idx as usize % self.keys.len()].clone()
+        }
+    }
+
+    fn op(&self, idx: u8) -> i64 {
+        if self.ops.is_empty() {
+            idx as i64
+        } else {
+            self.ops[idx as usize % self.ops.len()]
+        }
+    }
+
+    fn forget_op(&mut self, op_handle: i64) {
+        self.ops.retain(|h| *h != op_handle);
+    }
+
+    /// Send a request to the TA.  A response that cannot be parsed causes a panic.
+    fn exec(&mut self, req: PerformOpReq) -> Result<PerformOpRsp, ErrorCode> {
+        exec(&mut self.ta, req)
+    }
+
+    fn run(&mut self, action: &Action) {
+        match action {
+            Action::SetBootInfo { locked, state, patchlevel } => {
+                let _ = self.exec(PerformOpReq::SetBootInfo(SetBootInfoRequest {
+                    verified_boot_key: vec![0; 32],
+                    device_boot_locked: *locked,
+                    verified_boot_state: *state,
+                    verified_boot_hash: vec![0; 32],
+                    boot_patchlevel: *patchlevel,
+                }));
+            }
+            Action::SetHalInfo { os_version, os_patchlevel, vendor_patchlevel } => {
+                let _ = self.exec(PerformOpReq::SetHalInfo(SetHalInfoRequest {
+                    os_version: *os_version,
+                    os_patchlevel: *os_patchlevel,
+                    vendor_patchlevel: *vendor_patchlevel,
+                }));
+            }
+            Action::AddRngEntropy { data } => {
+                let _ = self.exec(PerformOpReq::DeviceAddRngEntropy(AddRngEntropyRequest {
+                    data: data.clone(),
+                }));
+            }
+            Action::GenerateKey { algorithm, params } => {
+                let mut key_params = match algorithm {
+                    Some(v) => base_key_params(pick(ALGORITHMS, *v)),
+                    None => Vec::new(),
+                };
+                key_params.extend(params.iter().map(Param::key_param));
+                if let Ok(PerformOpRsp::DeviceGenerateKey(rsp)) =
+                    self.exec(PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+                        key_params,
+                        attestation_key: None,
+                    }))
+                {
+                    self.keys.push(rsp.ret.key_blob);
+                }
+            }
+            Action::GetKeyCharacteristics { key, app_id, app_data } => {
+                let _ = self.exec(PerformOpReq::DeviceGetKeyCharacteristics(
+                    GetKeyCharacteristicsRequest {
+                        key_blob: self.key(*key),
+                        app_id: app_id.clone(),
+                        app_data: app_data.clone(),
+                    },
+                ));
+            }
+            Action::UpgradeKey { key, params } => {
+                if let Ok(PerformOpRsp::DeviceUpgradeKey(rsp)) =
+                    self.exec(PerformOpReq::DeviceUpgradeKey(UpgradeKeyRequest {
+                        key_blob_to_upgrade: self.key(*key),
+                        upgrade_params: key_params(params),
+                    }))
+                {
+                    if !rsp.ret.is_empty() {
+                        self.keys.push(rsp.ret);
+                    }
+                }
+            }
+            Action::DeleteKey { key } => {
+                let _ = self.exec(PerformOpReq::DeviceDeleteKey(DeleteKeyRequest {
+                    key_blob: self.key(*key),
+                }));
+            }
+            Action::DeleteAllKeys => {
+                let _ = self.exec(PerformOpReq::DeviceDeleteAllKeys(DeleteAllKeysRequest {}));
+            }
+            Action::EarlyBootEnded => {
+                let _ = self.exec(PerformOpReq::DeviceEarlyBootEnded(EarlyBootEndedRequest {}));
+            }
+            Action::Begin { key, purpose, mode, params } => {
+                let mut begin_params = match mode {
+                    Some(v) => base_begin_params(*v),
+                    None => Vec::new(),
+                };
+                begin_params.extend(params.iter().map(Param::key_param));
+                if let Ok(PerformOpRsp::DeviceBegin(rsp)) =
+                    self.exec(PerformOpReq::DeviceBegin(BeginRequest {
+                        purpose: pick(PURPOSES, *purpose),
+                        key_blob: self.key(*key),
+                        params: begin_params,
+                        auth_token: None,
+                    }))
+                {
+                    let op_handle = rsp.ret.op_handle;
+                    assert!(!self.ops.contains(&op_handle), "duplicate op handle {op_handle}");
+                    assert!(
+                        self.ops.len() < MAX_TEE_OPERATIONS,
+                        "operation started with {} operations already live",
+                        self.ops.len()
+                    );
+                    self.ops.push(op_handle);
+                }
+            }
+            Action::UpdateAad { op, input } => {
+                let op_handle = self.op(*op);
+                if self
+                    .exec(PerformOpReq::OperationUpdateAad(UpdateAadRequest {
+                        op_handle,
+                        input: input.clone(),
+                        auth_token: None,
+                        timestamp_token: None,
+                    }))
+                    .is_err()
+                {
+                    self.forget_op(op_handle);
+                }
+            }
+            Action::Update { op, input } => {
+                let op_handle = self.op(*op);
+                if self
+                    .exec(PerformOpReq::OperationUpdate(UpdateRequest {
+                        op_handle,
+                        input: input.clone(),
+                        auth_token: None,
+                        timestamp_token: None,
+                    }))
+                    .is_err()
+                {
+                    self.forget_op(op_handle);
+                }
+            }
+            Action::Finish { op, input, signature } => {
+                let op_handle = self.op(*op);
+                let _ = self.exec(PerformOpReq::OperationFinish(FinishRequest {
+                    op_handle,
+                    input: input.clone(),
+                    signature: signature.clone(),
+                    auth_token: None,
+                    timestamp_token: None,
+                    confirmation_token: None,
+                }));
+                self.forget_op(op_handle);
+                // The operation is gone whatever the outcome.
+                assert!(matches!(self.abort(op_handle), Err(ErrorCode::InvalidOperation)));
+            }
+            Action::Abort { op } => {
+                let op_handle = self.op(*op);
+                let _ = self.abort(op_handle);
+                self.forget_op(op_handle);
+                assert!(matches!(self.abort(op_handle), Err(ErrorCode::InvalidOperation)));
+            }
+        }
+    }
+
+    fn abort(&mut self, op_handle: i64) -> Result<PerformOpRsp, ErrorCode> {
+        self.exec(PerformOpReq::OperationAbort(AbortRequest { op_handle }))
+    }
+}
+
+fuzz_target!(|actions: Vec<Action>| {
+    let mut session = Session::new();
+    for action in actions.iter().take(MAX_ACTIONS) {
+        session.run(action);
+    }
+    // All remaining operations can be aborted.
+    for op_handle in session.ops.clone() {
+        assert!(session.abort(op_handle).is_ok(), "failed to abort live operation {op_handle}");
+    }
+});
@@ -0,0 +1,116 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounded in-TA log of security-relevant events, drained by the HAL.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use kmr_common::{vec_try_with_capacity, Error, FallibleAllocExt};
+use kmr_wire::{
+    audit::{AuditEvent, AuditRecord},
+    keymint::{ErrorCode, KeyCharacteristics, KeyParam},
+};
+
+/// Maximum number of records held in the audit log before the oldest records are discarded.
+pub(crate) const MAX_AUDIT_RECORDS: usize = 32;
+
+/// Build an empty record for the given event, for the caller to fill in.
+pub(crate) fn record(event: AuditEvent) -> AuditRecord {
+    AuditRecord {
+        sequence: 0,
+        timestamp_ms: None,
+        event,
+        key_id: None,
+        params: Vec::new(),
+        slot: None,
+        error: None,
+    }
+}
+
+/// Return the error code for an [`Error`], if it has one.
+pub(crate) fn error_code(err: &Error) -> Option<ErrorCode> {
+    match err {
+        Error::Hal(code, _) => Some(*code),
+        _ => None,
+    }
+}
+
+/// Summarize key characteristics for an audit record, omitting anything that identifies the
+/// user or application.
+pub(crate) fn key_summary(chars: &[KeyCharacteristics]) -> Result<Vec<KeyParam>, Error> {
+    let mut summary = Vec::new();
+    for param in chars.iter().flat_map(|c| c.authorizations.iter()) {
+        if matches!(
+            param,
+            KeyParam::Algorithm(_)
+                | KeyParam::KeySize(_)
+                | KeyParam::EcCurve(_)
+                | KeyParam::Purpose(_)
+                | KeyParam::Origin(_)
+                | KeyParam::RollbackResistance
+                | KeyParam::UsageCountLimit(_)
+                | KeyParam::MaxUsesPerBoot(_)
+        ) {
+            summary.try_push(param.clone())?;
+        }
+    }
+    Ok(summary)
+}
+
+/// Ring buffer of audit records.  When full, the oldest record is discarded to make room, and the
+/// number of discarded records is reported on the next drain.
+pub(crate) struct AuditLog {
+    records: VecDeque<AuditRecord>,
+    capacity: usize,
+    next_sequence: u64,
+    dropped: u64,
+}
+
+impl AuditLog {
+    /// Create an audit log holding at most `capacity` records.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { records: VecDeque::new(), capacity, next_sequence: 0, dropped: 0 }
+    }
+
+    /// Assign the next sequence number to `record`.
+    pub(crate) fn sequence(&mut self, record: &mut AuditRecord) {
+        record.sequence = self.next_sequence;
+        self.next_sequence += 1;
+    }
+
+    /// Add a record to the log, discarding the oldest record if the log is full.
+    pub(crate) fn push(&mut self, record: AuditRecord) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+            self.dropped += 1;
+        }
+        if self.records.try_reserve(1).is_err() {
+            self.dropped += 1;
+            return;
+        }
+        self.records.push_back(record);
+    }
+
+    /// Remove and return all records in the log, together with the number of records that were
+    /// discarded since the last drain.  On allocation failure the log is left unchanged.
+    pub(crate) fn drain(&mut self) -> Result<(Vec<AuditRecord>, u64), Error> {
+        let mut records = vec_try_with_capacity!(self.records.len())?;
+        records.extend(self.records.drain(..));
+        Ok((records, core::mem::take(&mut self.dropped)))
+    }
+}
@@ -87,6 +87,8 @@ pub(crate) fn tbs_certificate<'a>(
         Some(info) => match info.signing_key {
             KeyMaterial::Rsa(_) => crypto::rsa::SHA256_PKCS1_SIGNATURE_OID,
             KeyMaterial::Ec(curve, _, _) => crypto::ec::curve_to_signing_oid(curve),
+            #[cfg(feature = "pqc")]
+            KeyMaterial::MlDsa(param_set, _) => param_set.oid(),
             _ => {
                 return Err(km_err!(UnsupportedAlgorithm, "unexpected cert signing key type"));
             }
@@ -98,6 +100,16 @@ pub(crate) fn tbs_certificate<'a>(
                 keymint::Algorithm::Ec => {
                     crypto::ec::curve_to_signing_oid(tag::get_ec_curve(chars)?)
                 }
+                #[cfg(feature = "pqc")]
+                keymint::Algorithm::MlDsa => {
+                    let key_size = get_tag_value!(chars, KeySize, ErrorCode::InvalidArgument)?;
+                    crypto::mldsa::ParameterSet::new(key_size)?.oid()
+                }
+                // ML-KEM keys can't sign, so use the same placeholder as for a P-256 key.
+                #[cfg(feature = "pqc")]
+                keymint::Algorithm::MlKem => {
+                    crypto::ec::curve_to_signing_oid(keymint::EcCurve::P256)
+                }
                 alg => {
                     return Err(km_err!(
                         UnsupportedAlgorithm,
@@ -207,9 +219,17 @@ fn validity_time_from_datetime(when: DateTime) -> Result<Time, Error> {
     }
 }
 
-pub(crate) fn asn1_der_encode<T: Encode>(obj: &T) -> Result<Vec<u8>, der::Error> {
-    let mut encoded_data = Vec::<u8>::new();
-    obj.encode_to_vec(&mut encoded_data)?;
+/// Encode an object as DER into a freshly allocated vector.  [`der_alloc_err`] reports allocation
+/// failure as an [`ErrorKind::Overlength`] error, so any error of that kind is reported as an
+/// allocation failure, even though it may also indicate an object that is too long to encode.
+pub(crate) fn asn1_der_encode<T: Encode>(obj: &T) -> Result<Vec<u8>, Error> {
+    let encode_err = |e: der::Error| match e.kind() {
+        ErrorKind::Overlength => Error::Alloc("failed allocation during DER encoding"),
+        _ => der_err!(e, "failed to encode {}", core::any::type_name::<T>()),
+    };
+    let len = obj.encoded_len().map_err(encode_err)?;
+    let mut encoded_data = vec_try_with_capacity!(u32::from(len) as usize)?;
+    obj.encode_to_vec(&mut encoded_data).map_err(encode_err)?;
     Ok(encoded_data)
 }
 
@@ -1282,7 +1302,7 @@ struct RootOfTrust<'a> {
 }
 
 impl<'a> From<&'a keymint::BootInfo> for RootOfTrust<'a> {
-    fn from(info: &keymint::BootInfo) -> RootOfTrust {
+    fn from(info: &keymint::BootInfo) -> RootOfTrust<'_> {
         let verified_boot_key: &[u8] = if info.verified_boot_key.is_empty() {
             // If an empty verified boot key was passed by the boot loader, set the verified boot
             // key in the attestation to all zeroes.
@@ -0,0 +1,195 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ConfirmationUI-style companion TA, which shows a prompt to the user via a trusted UI and (if
+//! the user approves) issues a confirmation token over the formatted prompt message.  KeyMint
+//! accepts such tokens for keys with `TRUSTED_CONFIRMATION_REQUIRED`.
+
+use crate::device::RetrieveKeyMaterial;
+use crate::operation::{
+    CONFIRMATION_DATA_PREFIX, CONFIRMATION_MESSAGE_MAX_LEN, CONFIRMATION_TOKEN_SIZE,
+};
+use crate::secret::CompanionSecret;
+use alloc::{boxed::Box, collections::VecDeque, string::ToString, vec::Vec};
+use kmr_common::{crypto, km_err, try_to_vec, vec_try, Error, FallibleAllocExt};
+use kmr_wire::{cbor::value::Value, sharedsecret::SharedSecretParameters};
+use log::info;
+
+/// Options that affect how the trusted UI renders a prompt, matching `UIOption` in the
+/// `IConfirmationUI` AIDL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum UiOption {
+    /// Use inverted colors.
+    AccessibilityInverted = 0,
+    /// Use magnified text.
+    AccessibilityMagnified = 1,
+}
+
+/// Response from the user to a confirmation prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserResponse {
+    /// The user approved the prompt.
+    Confirmed,
+    /// The user cancelled the prompt.
+    Cancelled,
+}
+
+/// Trusted UI that displays a prompt and reports whether the user approved it.
+pub trait ConfirmationUi {
+    /// Display `prompt_text` and wait for the user to respond.  An error indicates that the prompt
+    /// could not be displayed.
+    fn prompt(
+        &mut self,
+        prompt_text: &str,
+        locale: &str,
+        ui_options: &[UiOption],
+    ) -> Result<UserResponse, Error>;
+}
+
+/// Headless stand-in for a trusted UI, which answers prompts with a scripted sequence of
+/// responses (and cancels any prompt once the script is exhausted).  Only suitable for testing.
+#[derive(Default)]
+pub struct HeadlessConfirmationUi {
+    responses: VecDeque<UserResponse>,
+}
+
+impl HeadlessConfirmationUi {
+    /// Create a headless UI that gives the provided responses, in order.
+    pub fn new<I: IntoIterator<Item = UserResponse>>(responses: I) -> Self {
+        Self { responses: responses.into_iter().collect() }
+    }
+}
+
+impl ConfirmationUi for HeadlessConfirmationUi {
+    fn prompt(
+        &mut self,
+        prompt_text: &str,
+        _locale: &str,
+        _ui_options: &[UiOption],
+    ) -> Result<UserResponse, Error> {
+        let response = self.responses.pop_front().unwrap_or(UserResponse::Cancelled);
+        info!("Headless confirmation of prompt {:?}: {:?}", prompt_text, response);
+        Ok(response)
+    }
+}
+
+/// Outcome of a confirmation prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfirmationResult {
+    /// The user approved the prompt.
+    Confirmed {
+        /// Formatted message that was confirmed, which is the data to be signed by a key that
+        /// requires trusted confirmation.
+        message: Vec<u8>,
+        /// Confirmation token to pass to KeyMint alongside the signing operation.
+        token: Vec<u8>,
+    },
+    /// The user cancelled the prompt.
+    Cancelled,
+}
+
+/// Build the formatted message for a prompt, which is the CBOR encoding of the map
+/// `{"prompt": prompt_text, "extra": extra_data}`.
+pub fn format_message(prompt_text: &str, extra_data: &[u8]) -> Result<Vec<u8>, Error> {
+    let map = Value::Map(vec_try![
+        (Value::Text("prompt".to_string()), Value::Text(prompt_text.to_string())),
+        (Value::Text("extra".to_string()), Value::Bytes(try_to_vec(extra_data)?)),
+    ]?);
+    let message = crate::rkp::serialize_cbor(&map)?;
+    if message.len() > CONFIRMATION_MESSAGE_MAX_LEN {
+        return Err(km_err!(
+            InvalidInputLength,
+            "formatted confirmation message of size {} too big",
+            message.len()
+        ));
+    }
+    Ok(message)
+}
+
+/// ConfirmationUI TA.
+pub struct ConfirmationTa {
+    /// Cryptographic primitives.
+    imp: crypto::Implementation,
+
+    /// Retrieval of device key material.
+    keys: Box<dyn RetrieveKeyMaterial>,
+
+    /// Trusted UI.
+    ui: Box<dyn ConfirmationUi>,
+
+    /// Shared secret negotiation state, holding the HMAC key agreed with KeyMint.
+    secret: CompanionSecret,
+}
+
+impl ConfirmationTa {
+    /// Create a new ConfirmationUI TA.
+    pub fn new(
+        imp: crypto::Implementation,
+        keys: Box<dyn RetrieveKeyMaterial>,
+        ui: Box<dyn ConfirmationUi>,
+    ) -> Self {
+        Self { imp, keys, ui, secret: CompanionSecret::default() }
+    }
+
+    /// Return the local shared secret parameters, generating them if necessary.
+    pub fn get_shared_secret_params(&mut self) -> Result<SharedSecretParameters, Error> {
+        self.secret.get_params(&mut *self.imp.rng)
+    }
+
+    /// Agree the HMAC key used for confirmation tokens from the full set of shared secret
+    /// parameters, returning the key check value.
+    pub fn compute_shared_secret(
+        &mut self,
+        params: &[SharedSecretParameters],
+    ) -> Result<Vec<u8>, Error> {
+        info!("ConfirmationUI setting HMAC key from {} shared secret parameters", params.len());
+        self.secret.compute(&self.imp, &*self.keys, params)
+    }
+
+    /// Show `prompt_text` to the user, and if they approve it return the formatted message
+    /// together with a confirmation token for it.
+    pub fn prompt_user_confirmation(
+        &mut self,
+        prompt_text: &str,
+        extra_data: &[u8],
+        locale: &str,
+        ui_options: &[UiOption],
+    ) -> Result<ConfirmationResult, Error> {
+        let message = format_message(prompt_text, extra_data)?;
+
+        // Check that a token can be generated before bothering the user.
+        self.confirmation_token(&[])?;
+
+        match self.ui.prompt(prompt_text, locale, ui_options)? {
+            UserResponse::Confirmed => {
+                let token = self.confirmation_token(&message)?;
+                Ok(ConfirmationResult::Confirmed { message, token })
+            }
+            UserResponse::Cancelled => Ok(ConfirmationResult::Cancelled),
+        }
+    }
+
+    /// Calculate the confirmation token for a formatted message.
+    fn confirmation_token(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        data.try_extend_from_slice(CONFIRMATION_DATA_PREFIX)?;
+        data.try_extend_from_slice(message)?;
+        let token = self.secret.hmac(&*self.imp.hmac, &data)?;
+        if token.len() != CONFIRMATION_TOKEN_SIZE {
+            return Err(km_err!(UnknownError, "unexpected HMAC length {}", token.len()));
+        }
+        Ok(token)
+    }
+}
@@ -18,9 +18,9 @@ use crate::coset::{iana, AsCborValue, CoseSign1Builder, HeaderBuilder};
 use alloc::{boxed::Box, vec::Vec};
 use kmr_common::{
     crypto, crypto::aes, crypto::hmac, crypto::KeyMaterial, crypto::OpaqueOr, keyblob, log_unimpl,
-    unimpl, Error,
+    tag, unimpl, Error,
 };
-use kmr_wire::{keymint, rpc, secureclock::TimeStampToken, CborError};
+use kmr_wire::{audit::AuditRecord, keymint, rpc, secureclock::TimeStampToken, CborError};
 use log::error;
 
 use crate::rkp::serialize_cbor;
@@ -63,6 +63,12 @@ pub struct Implementation {
     /// Retrieval of artifacts related to the device implementation of IRemotelyProvisionedComponent
     /// (IRPC) HAL.
     pub rpc: Box<dyn RetrieveRpcArtifacts>,
+
+    /// Device-specific restrictions on the keys that can be generated, imported and used.
+    pub policy: tag::KeyPolicy,
+
+    /// Recipient of security-relevant audit events, in addition to the TA's own audit log.
+    pub audit: Option<Box<dyn AuditSink>>,
 }
 
 /// Functionality related to retrieval of device-specific key material, and its subsequent use.
@@ -95,6 +101,14 @@ pub trait RetrieveKeyMaterial {
         ckdf.ckdf(&self.kak()?, unique_id_label, &[], 32).map(crypto::hmac::Key::new)
     }
 
+    /// Retrieve the hardware backed secret used by the Gatekeeper companion to sign password
+    /// handles.
+    fn gatekeeper_password_key(&self, ckdf: &dyn crypto::Ckdf) -> Result<crypto::hmac::Key, Error> {
+        // By default, use CKDF on the key agreement secret to derive a key.
+        let password_key_label = b"Gatekeeper password key";
+        ckdf.ckdf(&self.kak()?, password_key_label, &[], 32).map(crypto::hmac::Key::new)
+    }
+
     /// Build the HMAC input for a [`TimeStampToken`].  The default implementation produces
     /// data that matches the `ISecureClock` AIDL specification; this method should only be
     /// overridden for back-compatibility reasons.
@@ -325,6 +339,19 @@ pub struct RpcV2TestCDIPriv {
 pub struct BootloaderDone;
 impl BootloaderStatus for BootloaderDone {}
 
+/// Time (in milliseconds) allowed after `begin()` for the user to provide a trusted presence event
+/// for an operation on a key with `TRUSTED_USER_PRESENCE_REQUIRED`.
+pub const TRUSTED_PRESENCE_TIMEOUT_MS: u32 = 15_000;
+
+/// Outcome of waiting for trusted user presence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresenceEvent {
+    /// A presence event was detected, at the given time (if known).
+    Detected(Option<crypto::MillisecondsSinceEpoch>),
+    /// No presence event was detected before the timeout expired.
+    TimedOut,
+}
+
 /// Trusted user presence indicator.
 pub trait TrustedUserPresence {
     /// Indication of whether user presence is detected, via a mechanism in the current secure
@@ -333,12 +360,43 @@ pub trait TrustedUserPresence {
         // By default assume that trusted user presence is not supported.
         false
     }
+
+    /// Start waiting (asynchronously) for a user presence event, such as a button press, that
+    /// occurs within `timeout_ms` of now.  Invoked on `begin()` of an operation that requires
+    /// trusted user presence; at most one wait is outstanding at a time.
+    fn start_wait(&mut self, _timeout_ms: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Complete the wait started by `start_wait()`, blocking until a presence event occurs or the
+    /// timeout expires.  The timestamp of a detected event (if provided) is checked by the caller
+    /// against the window of the wait.
+    fn finish_wait(&mut self) -> PresenceEvent {
+        // By default, fall back to a one-off check of current presence.
+        if self.available() {
+            PresenceEvent::Detected(None)
+        } else {
+            PresenceEvent::TimedOut
+        }
+    }
+
+    /// Cancel any outstanding wait, because the operation that required it has been abandoned.
+    fn cancel_wait(&mut self) {}
 }
 
 /// Marker implementation to indicate that trusted user presence is not supported.
 pub struct TrustedPresenceUnsupported;
 impl TrustedUserPresence for TrustedPresenceUnsupported {}
 
+/// Recipient of security-relevant audit events, for example to forward them to secure storage or
+/// a compliance log.  Events are delivered as they happen, with their sequence number already
+/// assigned, so any gaps in the sequence seen by the HAL can be reconciled against this sink.
+pub trait AuditSink {
+    /// Record an audit event.  This must not fail or block for long, as it is invoked in the middle
+    /// of processing a request.
+    fn record(&self, record: &AuditRecord);
+}
+
 /// Storage key wrapping.
 pub trait StorageKeyWrapper {
     /// Wrap the provided key material using an ephemeral storage key.
@@ -0,0 +1,430 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Gatekeeper-style companion TA, which enrolls and verifies passwords and mints
+//! [`HardwareAuthToken`]s that are authenticated with the HMAC key agreed via `ISharedSecret`.
+//!
+//! Password handles use the same layout as the AOSP reference Gatekeeper implementation, and
+//! failed verification attempts are throttled according to the same schedule.
+
+use crate::device::RetrieveKeyMaterial;
+use crate::secret::CompanionSecret;
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::mem::size_of;
+use kmr_common::{crypto, crypto::hmac, km_err, vec_try_with_capacity, Error};
+use kmr_wire::{
+    keymint::{Digest, HardwareAuthToken, HardwareAuthenticatorType},
+    secureclock::Timestamp,
+    sharedsecret::SharedSecretParameters,
+};
+use log::{info, warn};
+
+/// Version of the password handle format.
+pub const HANDLE_VERSION: u8 = 2;
+
+/// Length of the signature held in a password handle.
+pub const SIGNATURE_LEN: usize = 32;
+
+/// Length of an encoded password handle.
+pub const HANDLE_LEN: usize = size_of::<u8>() + // version
+    size_of::<u64>() + // secure user ID
+    size_of::<u64>() + // flags
+    size_of::<u64>() + // salt
+    SIGNATURE_LEN + // signature
+    size_of::<u8>(); // hardware-backed
+
+/// Retry timeout (in milliseconds) used for most throttled attempts.
+const FAILURE_TIMEOUT_MS: u32 = 30_000;
+
+/// Retry timeout (in milliseconds) used once the failure count gets large.
+const DAY_IN_MS: u32 = 24 * 60 * 60 * 1000;
+
+/// Outcome of a Gatekeeper request that involves checking a password.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response<T> {
+    /// Request succeeded.
+    Ok(T),
+    /// The password was wrong, but no retry timeout applies.
+    Invalid,
+    /// The request was throttled (or the password was wrong and subsequent attempts will be
+    /// throttled); retry after the given number of milliseconds.
+    Retry(u32),
+}
+
+/// Successful result of an enroll request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnrollResponse {
+    /// Encoded password handle for the newly enrolled password.
+    pub handle: Vec<u8>,
+    /// Secure user ID associated with the password.
+    pub secure_user_id: u64,
+}
+
+/// Successful result of a verify request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyResponse {
+    /// Auth token asserting that the user has just presented their password.
+    pub auth_token: HardwareAuthToken,
+}
+
+/// Password handle, as stored by the non-secure side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordHandle {
+    /// Secure user ID associated with the password.
+    pub secure_user_id: u64,
+    /// Flags (currently unused).
+    pub flags: u64,
+    /// Random salt mixed into the signature.
+    pub salt: u64,
+    /// HMAC-SHA256 signature over the handle metadata and password.
+    pub signature: [u8; SIGNATURE_LEN],
+    /// Whether the handle was created by a hardware-backed implementation.
+    pub hardware_backed: bool,
+}
+
+impl PasswordHandle {
+    /// Parse an encoded password handle.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != HANDLE_LEN {
+            return Err(km_err!(
+                InvalidArgument,
+                "password handle len {} not {HANDLE_LEN}",
+                data.len()
+            ));
+        }
+        if data[0] != HANDLE_VERSION {
+            return Err(km_err!(InvalidArgument, "unexpected password handle version {}", data[0]));
+        }
+        let u64_at = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(buf)
+        };
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&data[25..25 + SIGNATURE_LEN]);
+        Ok(Self {
+            secure_user_id: u64_at(1),
+            flags: u64_at(9),
+            salt: u64_at(17),
+            signature,
+            hardware_backed: data[HANDLE_LEN - 1] != 0,
+        })
+    }
+
+    /// Encode the password handle.
+    pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut result = vec_try_with_capacity!(HANDLE_LEN)?;
+        result.push(HANDLE_VERSION);
+        result.extend_from_slice(&self.secure_user_id.to_le_bytes());
+        result.extend_from_slice(&self.flags.to_le_bytes());
+        result.extend_from_slice(&self.salt.to_le_bytes());
+        result.extend_from_slice(&self.signature);
+        result.push(self.hardware_backed as u8);
+        Ok(result)
+    }
+}
+
+/// Record of failed password attempts for a user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FailureRecord {
+    /// Secure user ID that the record applies to.
+    pub secure_user_id: u64,
+    /// Time of the most recent failed attempt, in milliseconds.
+    pub last_checked_timestamp: i64,
+    /// Number of consecutive failed attempts.
+    pub failure_counter: u32,
+}
+
+impl FailureRecord {
+    /// Calculate the retry timeout (in milliseconds) that applies after the recorded number of
+    /// failures, following the schedule used by the AOSP reference implementation.
+    pub fn retry_timeout(&self) -> u32 {
+        match self.failure_counter {
+            0 => 0,
+            c @ 1..=10 if c.is_multiple_of(5) => FAILURE_TIMEOUT_MS,
+            1..=10 => 0,
+            11..=29 => FAILURE_TIMEOUT_MS,
+            c @ 30..=139 => FAILURE_TIMEOUT_MS << ((c - 30) / 10),
+            _ => DAY_IN_MS,
+        }
+    }
+}
+
+/// Persistent storage for [`FailureRecord`]s, indexed by Android user ID.  Implementations
+/// should store records in secure storage that survives a reboot, so that throttling cannot be
+/// bypassed by restarting the device.
+pub trait FailureRecordStorage {
+    /// Retrieve the failure record for a user, if any.
+    fn read(&self, uid: u32) -> Result<Option<FailureRecord>, Error>;
+
+    /// Store the failure record for a user.
+    fn write(&mut self, uid: u32, record: FailureRecord) -> Result<(), Error>;
+
+    /// Delete the failure record for a user.
+    fn delete(&mut self, uid: u32) -> Result<(), Error>;
+
+    /// Delete all failure records.
+    fn delete_all(&mut self) -> Result<(), Error>;
+}
+
+/// Failure record storage that holds records in memory.  Only suitable for testing, as
+/// throttling state is lost on restart.
+#[derive(Default)]
+pub struct InMemoryFailureRecords {
+    records: BTreeMap<u32, FailureRecord>,
+}
+
+impl FailureRecordStorage for InMemoryFailureRecords {
+    fn read(&self, uid: u32) -> Result<Option<FailureRecord>, Error> {
+        Ok(self.records.get(&uid).copied())
+    }
+    fn write(&mut self, uid: u32, record: FailureRecord) -> Result<(), Error> {
+        self.records.insert(uid, record);
+        Ok(())
+    }
+    fn delete(&mut self, uid: u32) -> Result<(), Error> {
+        self.records.remove(&uid);
+        Ok(())
+    }
+    fn delete_all(&mut self) -> Result<(), Error> {
+        self.records.clear();
+        Ok(())
+    }
+}
+
+/// Gatekeeper TA.
+pub struct GatekeeperTa {
+    /// Cryptographic primitives.  A clock is required.
+    imp: crypto::Implementation,
+
+    /// Retrieval of device key material.
+    keys: Box<dyn RetrieveKeyMaterial>,
+
+    /// Storage of failure records.
+    records: Box<dyn FailureRecordStorage>,
+
+    /// Key used to sign password handles; derived on first use.
+    password_key: Option<hmac::Key>,
+
+    /// Shared secret negotiation state, holding the HMAC key agreed with KeyMint.
+    secret: CompanionSecret,
+}
+
+impl GatekeeperTa {
+    /// Create a new Gatekeeper TA.
+    pub fn new(
+        imp: crypto::Implementation,
+        keys: Box<dyn RetrieveKeyMaterial>,
+        records: Box<dyn FailureRecordStorage>,
+    ) -> Self {
+        Self { imp, keys, records, password_key: None, secret: CompanionSecret::default() }
+    }
+
+    /// Return the local shared secret parameters, generating them if necessary.
+    pub fn get_shared_secret_params(&mut self) -> Result<SharedSecretParameters, Error> {
+        self.secret.get_params(&mut *self.imp.rng)
+    }
+
+    /// Agree the HMAC key used for auth tokens from the full set of shared secret parameters,
+    /// returning the key check value.
+    pub fn compute_shared_secret(
+        &mut self,
+        params: &[SharedSecretParameters],
+    ) -> Result<Vec<u8>, Error> {
+        info!("Gatekeeper setting HMAC key from {} shared secret parameters", params.len());
+        self.secret.compute(&self.imp, &*self.keys, params)
+    }
+
+    /// Enroll `desired_password` for user `uid`.  If `current_handle` is provided, the
+    /// `current_password` must match it, and the existing secure user ID is retained; otherwise a
+    /// new secure user ID is generated (which invalidates any keys bound to the old one).
+    pub fn enroll(
+        &mut self,
+        uid: u32,
+        current_handle: Option<&[u8]>,
+        current_password: Option<&[u8]>,
+        desired_password: &[u8],
+    ) -> Result<Response<EnrollResponse>, Error> {
+        let secure_user_id = match (current_handle, current_password) {
+            (Some(handle), Some(password)) => {
+                let handle = PasswordHandle::from_slice(handle)?;
+                match self.check_password(uid, &handle, password)? {
+                    Response::Ok(()) => handle.secure_user_id,
+                    Response::Invalid => return Ok(Response::Invalid),
+                    Response::Retry(timeout) => return Ok(Response::Retry(timeout)),
+                }
+            }
+            (Some(_), None) => {
+                return Err(km_err!(InvalidArgument, "current handle without current password"))
+            }
+            (None, _) => {
+                let secure_user_id = self.new_secure_user_id()?;
+                self.records.delete(uid)?;
+                secure_user_id
+            }
+        };
+
+        let mut salt = [0u8; 8];
+        self.fill_random(&mut salt)?;
+        let mut handle = PasswordHandle {
+            secure_user_id,
+            flags: 0,
+            salt: u64::from_le_bytes(salt),
+            signature: [0; SIGNATURE_LEN],
+            hardware_backed: true,
+        };
+        handle.signature = self.password_signature(&handle, desired_password)?;
+        Ok(Response::Ok(EnrollResponse { handle: handle.to_vec()?, secure_user_id }))
+    }
+
+    /// Verify `password` against the enrolled `handle` for user `uid`, returning an auth token
+    /// that includes `challenge` on success.
+    pub fn verify(
+        &mut self,
+        uid: u32,
+        challenge: i64,
+        handle: &[u8],
+        password: &[u8],
+    ) -> Result<Response<VerifyResponse>, Error> {
+        let handle = PasswordHandle::from_slice(handle)?;
+        match self.check_password(uid, &handle, password)? {
+            Response::Ok(()) => {}
+            Response::Invalid => return Ok(Response::Invalid),
+            Response::Retry(timeout) => return Ok(Response::Retry(timeout)),
+        }
+
+        let mut auth_token = HardwareAuthToken {
+            challenge,
+            user_id: handle.secure_user_id as i64,
+            authenticator_id: 0,
+            authenticator_type: HardwareAuthenticatorType::Password,
+            timestamp: Timestamp { milliseconds: self.now()? },
+            mac: Vec::new(),
+        };
+        let mac_input = crate::hardware_auth_token_mac_input(&auth_token)?;
+        auth_token.mac = self.secret.hmac(&*self.imp.hmac, &mac_input)?;
+        Ok(Response::Ok(VerifyResponse { auth_token }))
+    }
+
+    /// Remove any throttling state for user `uid`.
+    pub fn delete_user(&mut self, uid: u32) -> Result<(), Error> {
+        self.records.delete(uid)
+    }
+
+    /// Remove throttling state for all users.
+    pub fn delete_all_users(&mut self) -> Result<(), Error> {
+        self.records.delete_all()
+    }
+
+    /// Check `password` against `handle`, applying (and updating) the throttling state for `uid`.
+    fn check_password(
+        &mut self,
+        uid: u32,
+        handle: &PasswordHandle,
+        password: &[u8],
+    ) -> Result<Response<()>, Error> {
+        let now = self.now()?;
+        let mut record = match self.records.read(uid)? {
+            Some(record) if record.secure_user_id == handle.secure_user_id => record,
+            _ => FailureRecord { secure_user_id: handle.secure_user_id, ..Default::default() },
+        };
+
+        let timeout = record.retry_timeout();
+        if timeout > 0 {
+            let last_checked = record.last_checked_timestamp;
+            if now > last_checked && now < last_checked + timeout as i64 {
+                // Attempt before the timeout expired.
+                return Ok(Response::Retry(timeout - (now - last_checked) as u32));
+            } else if now <= last_checked {
+                // Clock has been reset; don't count this as a failure but restart the timeout.
+                record.last_checked_timestamp = now;
+                self.records.write(uid, record)?;
+                return Ok(Response::Retry(timeout));
+            }
+        }
+
+        // Record the failure before checking the password, so that an attacker can't avoid the
+        // failure being counted by interrupting the check.
+        record.failure_counter = record.failure_counter.saturating_add(1);
+        record.last_checked_timestamp = now;
+        self.records.write(uid, record)?;
+
+        let expected = self.password_signature(handle, password)?;
+        if self.imp.compare.eq(&expected, &handle.signature) {
+            self.records.delete(uid)?;
+            Ok(Response::Ok(()))
+        } else {
+            warn!("Gatekeeper password mismatch for uid {}", uid);
+            match record.retry_timeout() {
+                0 => Ok(Response::Invalid),
+                timeout => Ok(Response::Retry(timeout)),
+            }
+        }
+    }
+
+    /// Calculate the signature for a password handle, as
+    /// HMAC-SHA256(salt || version || secure_user_id || flags || password).
+    fn password_signature(
+        &mut self,
+        handle: &PasswordHandle,
+        password: &[u8],
+    ) -> Result<[u8; SIGNATURE_LEN], Error> {
+        if self.password_key.is_none() {
+            self.password_key = Some(self.keys.gatekeeper_password_key(&*self.imp.ckdf)?);
+        }
+        let key = self.password_key.clone().unwrap(); // safe: filled above
+        let mut op = self.imp.hmac.begin(key.into(), Digest::Sha256)?;
+        op.update(&handle.salt.to_le_bytes())?;
+        op.update(&[HANDLE_VERSION])?;
+        op.update(&handle.secure_user_id.to_le_bytes())?;
+        op.update(&handle.flags.to_le_bytes())?;
+        op.update(password)?;
+        let tag = op.finish()?;
+        if tag.len() != SIGNATURE_LEN {
+            return Err(km_err!(UnknownError, "unexpected HMAC length {}", tag.len()));
+        }
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&tag);
+        Ok(signature)
+    }
+
+    /// Generate a new random, non-zero, secure user ID.
+    fn new_secure_user_id(&mut self) -> Result<u64, Error> {
+        loop {
+            let mut buf = [0u8; 8];
+            self.fill_random(&mut buf)?;
+            let sid = u64::from_le_bytes(buf);
+            if sid != 0 {
+                return Ok(sid);
+            }
+        }
+    }
+
+    /// Fill `dest` with random data, failing if the random number generator has failed.
+    fn fill_random(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.imp.rng.fill_bytes(dest);
+        if self.imp.rng.failed() {
+            return Err(km_err!(HardwareNotYetAvailable, "random number generator failed"));
+        }
+        Ok(())
+    }
+
+    /// Return the current time in milliseconds.
+    fn now(&self) -> Result<i64, Error> {
+        match &self.imp.clock {
+            Some(clock) => Ok(clock.now().0),
+            None => Err(km_err!(HardwareNotYetAvailable, "Gatekeeper requires a clock")),
+        }
+    }
+}
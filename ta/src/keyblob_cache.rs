@@ -0,0 +1,147 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounded cache of decrypted keyblobs, to avoid repeating the KEK derivation and keyblob
+//! decryption for keys that are used frequently.
+
+use crate::KeyId;
+use alloc::vec::Vec;
+use kmr_common::{crypto::ConstTimeEq, keyblob, keyblob::SecureDeletionSlot};
+
+/// Default number of cached keyblobs when running as TEE.
+pub(crate) const DEFAULT_TEE_CACHE_SIZE: usize = 8;
+
+/// Default number of cached keyblobs when running as StrongBox.  StrongBox memory is scarce, so
+/// caching is disabled unless explicitly configured.
+pub(crate) const DEFAULT_STRONGBOX_CACHE_SIZE: usize = 0;
+
+/// A decrypted keyblob, together with the information needed to match it to a later request.
+struct Entry {
+    /// Identifier of the encrypted keyblob.
+    key_id: KeyId,
+    /// SHA-256 digest of the hidden parameters that the keyblob was decrypted with.
+    hidden_digest: [u8; 32],
+    /// Decrypted keyblob; key material is zeroized when the entry is dropped.
+    keyblob: keyblob::PlaintextKeyBlob,
+    /// Secure deletion slot that the keyblob is bound to, if any.
+    slot: Option<SecureDeletionSlot>,
+    /// Value of [`KeyblobCache::counter`] when the entry was last used.
+    last_used: u64,
+}
+
+/// Least-recently-used cache of decrypted keyblobs.  Entries are held in fixed slots (rather than
+/// being shuffled around a `Vec`) so that no stray copies of key material are left behind when the
+/// cache contents change.
+pub(crate) struct KeyblobCache {
+    entries: Vec<Option<Entry>>,
+    counter: u64,
+}
+
+impl KeyblobCache {
+    /// Create a cache holding at most `size` decrypted keyblobs.
+    pub(crate) fn new(size: usize) -> Self {
+        // Work around Rust limitation that `vec![None; n]` doesn't work.
+        Self { entries: (0..size).map(|_| None).collect(), counter: 0 }
+    }
+
+    /// Indicate whether the cache is able to hold any keyblobs.
+    pub(crate) fn enabled(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Return a copy of the cached keyblob matching `key_id` and `hidden_digest`, if present.
+    pub(crate) fn get(
+        &mut self,
+        compare: &dyn ConstTimeEq,
+        key_id: &KeyId,
+        hidden_digest: &[u8; 32],
+    ) -> Option<(keyblob::PlaintextKeyBlob, Option<SecureDeletionSlot>)> {
+        self.counter += 1;
+        let counter = self.counter;
+        let entry = self.entries.iter_mut().flatten().find(|e| e.key_id == *key_id)?;
+        if !compare.eq(&entry.hidden_digest, hidden_digest) {
+            return None;
+        }
+        entry.last_used = counter;
+        Some((entry.keyblob.clone(), entry.slot))
+    }
+
+    /// Add a decrypted keyblob to the cache, evicting the least recently used entry if necessary.
+    pub(crate) fn insert(
+        &mut self,
+        key_id: KeyId,
+        hidden_digest: [u8; 32],
+        keyblob: &keyblob::PlaintextKeyBlob,
+        slot: Option<SecureDeletionSlot>,
+    ) {
+        if !self.enabled() {
+            return;
+        }
+        // Prefer a slot that already holds this keyblob (perhaps with different hidden
+        // parameters), then an empty slot, then the least recently used slot.
+        let idx = match self.entries.iter().position(|e| matches!(e, Some(e) if e.key_id == key_id))
+        {
+            Some(idx) => idx,
+            None => match self.entries.iter().position(|e| e.is_none()) {
+                Some(idx) => idx,
+                None => self
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, e)| e.as_ref().map(|e| e.last_used))
+                    .map(|(idx, _)| idx)
+                    .unwrap(/* safe: cache is enabled so there is at least one slot */),
+            },
+        };
+        self.counter += 1;
+        self.entries[idx] = Some(Entry {
+            key_id,
+            hidden_digest,
+            keyblob: keyblob.clone(),
+            slot,
+            last_used: self.counter,
+        });
+    }
+
+    /// Drop any cached copy of the keyblob with the given `key_id`.
+    pub(crate) fn remove(&mut self, key_id: &KeyId) {
+        for entry in self.entries.iter_mut() {
+            if matches!(entry, Some(e) if e.key_id == *key_id) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Drop any cached keyblobs that are bound to the given secure deletion `slot`.
+    pub(crate) fn remove_slot(&mut self, slot: SecureDeletionSlot) {
+        for entry in self.entries.iter_mut() {
+            if matches!(entry, Some(e) if e.slot == Some(slot)) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Drop all cached keyblobs.
+    pub(crate) fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+
+    /// Number of keyblobs currently cached.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+}
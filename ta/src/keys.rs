@@ -14,7 +14,7 @@
 
 //! TA functionality related to key generation/import/upgrade.
 
-use crate::{cert, device, AttestationChainInfo};
+use crate::{audit, cert, device, AttestationChainInfo};
 use alloc::collections::btree_map::Entry;
 use alloc::vec::Vec;
 use core::{borrow::Borrow, cmp::Ordering, convert::TryFrom};
@@ -25,6 +25,7 @@ use kmr_common::{
     try_to_vec, vec_try_with_capacity, Error, FallibleAllocExt,
 };
 use kmr_wire::{
+    audit::{AuditEvent, AuditRecord},
     keymint::{
         AttestationKey, Digest, EcCurve, ErrorCode, HardwareAuthenticatorType, KeyCharacteristics,
         KeyCreationResult, KeyFormat, KeyOrigin, KeyParam, KeyPurpose, SecurityLevel,
@@ -55,7 +56,8 @@ const MAX_ATTESTATION_CHALLENGE_LEN: usize = 128;
 pub struct SecureKeyWrapper<'a> {
     /// Version of this structure.
     pub version: i32,
-    /// Encrypted transport key.
+    /// Encrypted transport key.  For an EC wrapping key (a vendor extension), this instead holds
+    /// the sender's ephemeral public key, as a DER-encoded `SubjectPublicKeyInfo`.
     #[asn1(type = "OCTET STRING")]
     pub encrypted_transport_key: &'a [u8],
     /// IV to use for decryption.
@@ -73,6 +75,24 @@ pub struct SecureKeyWrapper<'a> {
 
 const SECURE_KEY_WRAPPER_VERSION: i32 = 0;
 
+/// Label at the start of the HKDF info used to derive the masked transport key from the ECDH
+/// shared secret, when importing a key wrapped under an EC wrapping key.
+pub const ECDH_WRAPPING_HKDF_INFO: &[u8] = b"KeyMint ECDH wrapped key import";
+
+/// Build the HKDF info used to derive the masked transport key for a key wrapped under an EC
+/// wrapping key: [`ECDH_WRAPPING_HKDF_INFO`] followed by the DER-encoded `SubjectPublicKeyInfo`s of
+/// the ephemeral key and of the wrapping key, so that the derived key is bound to both.
+pub fn ecdh_wrapping_hkdf_info(
+    ephemeral_spki: &[u8],
+    wrapping_spki: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut info = Vec::new();
+    info.try_extend_from_slice(ECDH_WRAPPING_HKDF_INFO)?;
+    info.try_extend_from_slice(ephemeral_spki)?;
+    info.try_extend_from_slice(wrapping_spki)?;
+    Ok(info)
+}
+
 /// Contents of key description.
 ///
 /// ```asn1
@@ -112,7 +132,7 @@ impl crate::KeyMintTa {
     pub(crate) fn get_signing_info(
         &self,
         key_type: device::SigningKeyType,
-    ) -> Result<SigningInfo, Error> {
+    ) -> Result<SigningInfo<'_>, Error> {
         let sign_info = self.dev.sign_info.as_ref().ok_or_else(|| {
             km_err!(AttestationKeysNotProvisioned, "batch attestation keys not available")
         })?;
@@ -151,17 +171,14 @@ impl crate::KeyMintTa {
     ) -> Result<keymint::Certificate, Error> {
         // Build and encode key usage extension value
         let key_usage_ext_bits = cert::key_usage_extension_bits(params);
-        let key_usage_ext_val = cert::asn1_der_encode(&key_usage_ext_bits)
-            .map_err(|e| der_err!(e, "failed to encode KeyUsage {:?}", key_usage_ext_bits))?;
+        let key_usage_ext_val = cert::asn1_der_encode(&key_usage_ext_bits)?;
 
         // Build and encode basic constraints extension value, based on the key usage extension
         // value
         let basic_constraints_ext_val =
             if (key_usage_ext_bits.0 & KeyUsages::KeyCertSign).bits().count_ones() != 0 {
                 let basic_constraints = cert::basic_constraints_ext_value(true);
-                Some(cert::asn1_der_encode(&basic_constraints).map_err(|e| {
-                    der_err!(e, "failed to encode basic constraints {:?}", basic_constraints)
-                })?)
+                Some(cert::asn1_der_encode(&basic_constraints)?)
             } else {
                 None
             };
@@ -184,10 +201,7 @@ impl crate::KeyMintTa {
                     &boot_info,
                     &self.additional_attestation_info,
                 )?;
-                Some(
-                    cert::asn1_der_encode(&attest_ext)
-                        .map_err(|e| der_err!(e, "failed to encode attestation extension"))?,
-                )
+                Some(cert::asn1_der_encode(&attest_ext)?)
             } else {
                 None
             };
@@ -201,8 +215,7 @@ impl crate::KeyMintTa {
             tag::characteristics_at(chars, self.hw_info.security_level)?,
             params,
         )?;
-        let tbs_data = cert::asn1_der_encode(&tbs_cert)
-            .map_err(|e| der_err!(e, "failed to encode tbsCert"))?;
+        let tbs_data = cert::asn1_der_encode(&tbs_cert)?;
         // If key does not have ATTEST_KEY or SIGN purpose, the certificate has empty signature
         let sig_data = match info.as_ref() {
             Some(info) => self.sign_cert_data(info.signing_key.clone(), tbs_data.as_slice())?,
@@ -210,8 +223,7 @@ impl crate::KeyMintTa {
         };
 
         let cert = cert::certificate(tbs_cert, &sig_data)?;
-        let cert_data = cert::asn1_der_encode(&cert)
-            .map_err(|e| der_err!(e, "failed to encode certificate"))?;
+        let cert_data = cert::asn1_der_encode(&cert)?;
         Ok(keymint::Certificate { encoded_certificate: cert_data })
     }
 
@@ -237,6 +249,12 @@ impl crate::KeyMintTa {
                 op.update(tbs_data)?;
                 op.finish()
             }
+            #[cfg(feature = "pqc")]
+            KeyMaterial::MlDsa(param_set, key) => {
+                let mut op = self.imp.mldsa.begin_sign(key, param_set)?;
+                op.update(tbs_data)?;
+                op.finish()
+            }
             _ => Err(km_err!(IncompatibleAlgorithm, "unexpected cert signing key type")),
         }
     }
@@ -284,6 +302,7 @@ impl crate::KeyMintTa {
         params: &[KeyParam],
     ) -> Result<(KeyMaterial, Vec<KeyCharacteristics>), Error> {
         let (mut chars, keygen_info) = tag::extract_key_gen_characteristics(
+            &self.dev.policy,
             self.secure_storage_available(),
             params,
             self.hw_info.security_level,
@@ -311,6 +330,14 @@ impl crate::KeyMintTa {
             crypto::KeyGenInfo::X25519 => {
                 self.imp.ec.generate_x25519_key(&mut *self.imp.rng, params)?
             }
+            #[cfg(feature = "pqc")]
+            crypto::KeyGenInfo::MlDsa(param_set) => {
+                self.imp.mldsa.generate_key(&mut *self.imp.rng, param_set, params)?
+            }
+            #[cfg(feature = "pqc")]
+            crypto::KeyGenInfo::MlKem768 => {
+                self.imp.mlkem.generate_key(&mut *self.imp.rng, params)?
+            }
         };
         Ok((key_material, chars))
     }
@@ -329,6 +356,7 @@ impl crate::KeyMintTa {
 
         let (mut chars, key_material) = tag::extract_key_import_characteristics(
             &self.imp,
+            &self.dev.policy,
             self.secure_storage_available(),
             params,
             self.hw_info.security_level,
@@ -353,6 +381,47 @@ impl crate::KeyMintTa {
         )
     }
 
+    /// Derive a new symmetric key from the `shared_secret` output of a key agreement operation (a
+    /// vendor extension), using HKDF-SHA256 with the given `salt` and `info`.
+    pub(crate) fn derive_key(
+        &mut self,
+        params: &[KeyParam],
+        shared_secret: &[u8],
+        salt: &[u8],
+        info: &[u8],
+    ) -> Result<KeyCreationResult, Error> {
+        let (mut chars, keygen_info) = tag::extract_key_gen_characteristics(
+            &self.dev.policy,
+            self.secure_storage_available(),
+            params,
+            self.hw_info.security_level,
+        )?;
+        self.add_keymint_tags(&mut chars, KeyOrigin::Derived)?;
+        let key_material = match keygen_info {
+            crypto::KeyGenInfo::Aes(variant) => {
+                KeyMaterial::Aes(self.imp.hkdf.hkdf_aes(salt, shared_secret, info, variant)?)
+            }
+            crypto::KeyGenInfo::Hmac(key_size) => {
+                let data =
+                    self.imp.hkdf.hkdf(salt, shared_secret, info, key_size.0 as usize / 8)?;
+                KeyMaterial::Hmac(crypto::hmac::Key::new(data).into())
+            }
+            _ => {
+                return Err(km_err!(
+                    UnsupportedAlgorithm,
+                    "only AES and HMAC keys can be derived from a shared secret"
+                ))
+            }
+        };
+        self.finish_keyblob_creation(
+            params,
+            None,
+            chars,
+            key_material,
+            keyblob::SlotPurpose::KeyDerivation,
+        )
+    }
+
     /// Perform common processing for keyblob creation (for both generation and import).
     pub fn finish_keyblob_creation(
         &mut self,
@@ -374,11 +443,9 @@ impl crate::KeyMintTa {
         };
         let attest_keyblob;
         let mut certificate_chain = Vec::new();
-        if let Some(spki) = keyblob.key_material.subject_public_key_info(
-            &mut Vec::<u8>::new(),
-            &*self.imp.ec,
-            &*self.imp.rsa,
-        )? {
+        if let Some(spki) =
+            keyblob.key_material.subject_public_key_info(&mut Vec::<u8>::new(), &self.imp)?
+        {
             // Asymmetric keys return the public key inside an X.509 certificate.
             // Need to determine:
             // - a key to sign the cert with (may be absent), together with any associated
@@ -440,6 +507,12 @@ impl crate::KeyMintTa {
                     let algo_hint = match &keyblob.key_material {
                         crypto::KeyMaterial::Rsa(_) => device::SigningAlgorithm::Rsa,
                         crypto::KeyMaterial::Ec(_, _, _) => device::SigningAlgorithm::Ec,
+                        // There are no post-quantum batch keys, so ML-DSA and ML-KEM keys are
+                        // attested by the EC batch key.
+                        #[cfg(feature = "pqc")]
+                        crypto::KeyMaterial::MlDsa(_, _) | crypto::KeyMaterial::MlKem(_) => {
+                            device::SigningAlgorithm::Ec
+                        }
                         _ => return Err(km_err!(InvalidArgument, "unexpected key type!")),
                     };
 
@@ -509,8 +582,24 @@ impl crate::KeyMintTa {
             hidden,
             purpose,
         )?;
-        let serialized_keyblob = encrypted_keyblob.into_vec()?;
+        let sdd_slot = encrypted_keyblob.secure_deletion_slot();
+        let serialized_keyblob = encrypted_keyblob.into_vec().map_err(Error::from).and_then(|kb| {
+            self.audit_new_keyblob(purpose, &kb, audit::key_summary(&chars)?, sdd_slot)?;
+            Ok(kb)
+        });
+        let serialized_keyblob = match serialized_keyblob {
+            Ok(kb) => kb,
+            Err(e) => {
+                // The keyblob will never reach the caller, so its secure deletion slot must not
+                // outlive this call.
+                if let Some(slot) = sdd_slot {
+                    self.release_secure_deletion_slot(slot);
+                }
+                return Err(e);
+            }
+        };
 
+        self.new_keyblob_slot = sdd_slot;
         Ok(KeyCreationResult {
             key_blob: serialized_keyblob,
             key_characteristics: chars,
@@ -540,7 +629,8 @@ impl crate::KeyMintTa {
         }
 
         // Decrypt the masked transport key, using an RSA key. (Only RSA wrapping keys are supported
-        // by the spec, as RSA is the only algorithm supporting asymmetric decryption.)
+        // by the spec, as RSA is the only algorithm supporting asymmetric decryption.)  As a vendor
+        // extension, the masked transport key can instead be derived using an EC wrapping key.
         let masked_transport_key = match key_material {
             KeyMaterial::Rsa(key) => {
                 // Check the requirements on the wrapping key characterisitcs
@@ -554,6 +644,33 @@ impl crate::KeyMintTa {
                 crypto_op.as_mut().update(secure_key_wrapper.encrypted_transport_key)?;
                 crypto_op.finish()?
             }
+            KeyMaterial::Ec(curve, curve_type, key) => {
+                tag::check_ec_wrapping_key_params(
+                    tag::characteristics_at(&characteristics, self.hw_info.security_level)?,
+                    curve,
+                    curve_type,
+                )?;
+
+                // Agree a shared secret with the ephemeral public key, and expand it into the
+                // masked transport key, bound to both public keys.
+                let mut buf = Vec::new();
+                let wrapping_spki = cert::asn1_der_encode(&key.subject_public_key_info(
+                    &mut buf,
+                    &*self.imp.ec,
+                    &curve,
+                    &curve_type,
+                )?)?;
+                let ephemeral_spki = secure_key_wrapper.encrypted_transport_key;
+                let mut crypto_op = self.imp.ec.begin_agree(key)?;
+                crypto_op.update(ephemeral_spki)?;
+                let shared_secret = crypto::RawKeyMaterial(crypto_op.finish()?);
+                self.imp.hkdf.hkdf(
+                    &[],
+                    &shared_secret.0,
+                    &ecdh_wrapping_hkdf_info(ephemeral_spki, &wrapping_spki)?,
+                    aes::Variant::Aes256.key_size(),
+                )?
+            }
             _ => {
                 return Err(km_err!(InvalidArgument, "invalid key algorithm for transport key"));
             }
@@ -628,10 +745,7 @@ impl crate::KeyMintTa {
             gcm_mode,
             crypto::SymmetricOperation::Decrypt,
         )?;
-        op.update_aad(
-            &cert::asn1_der_encode(&secure_key_wrapper.key_description)
-                .map_err(|e| der_err!(e, "failed to re-encode SecureKeyWrapper"))?,
-        )?;
+        op.update_aad(&cert::asn1_der_encode(&secure_key_wrapper.key_description)?)?;
 
         let mut imported_key_data = op.update(secure_key_wrapper.encrypted_key)?;
         imported_key_data.try_extend_from_slice(&op.update(secure_key_wrapper.tag)?)?;
@@ -799,6 +913,7 @@ impl crate::KeyMintTa {
         let kek_context = self.dev.keys.kek_context()?;
         let root_kek = self.root_kek(&kek_context)?;
         let hidden = tag::hidden(&upgrade_params, self.root_of_trust()?)?;
+        let summary = audit::key_summary(&keyblob.characteristics)?;
         let encrypted_keyblob = keyblob::encrypt(
             self.hw_info.security_level,
             match &mut self.dev.sdd_mgr {
@@ -814,6 +929,70 @@ impl crate::KeyMintTa {
             hidden,
             keyblob::SlotPurpose::KeyUpgrade,
         )?;
-        Ok(encrypted_keyblob.into_vec()?)
+        let sdd_slot = encrypted_keyblob.secure_deletion_slot();
+        let serialized_keyblob = encrypted_keyblob.into_vec().map_err(Error::from).and_then(|kb| {
+            self.audit_new_keyblob(keyblob::SlotPurpose::KeyUpgrade, &kb, summary, sdd_slot)?;
+            Ok(kb)
+        });
+        let serialized_keyblob = match serialized_keyblob {
+            Ok(kb) => kb,
+            Err(e) => {
+                // The upgraded keyblob will never reach the caller, so its secure deletion slot
+                // must not outlive this call.
+                if let Some(slot) = sdd_slot {
+                    self.release_secure_deletion_slot(slot);
+                }
+                return Err(e);
+            }
+        };
+        self.new_keyblob_slot = sdd_slot;
+        Ok(serialized_keyblob)
+    }
+
+    /// Record the creation of a new keyblob (and of any secure deletion slot that it is bound to)
+    /// in the audit log.
+    fn audit_new_keyblob(
+        &self,
+        purpose: keyblob::SlotPurpose,
+        keyblob: &[u8],
+        summary: Vec<KeyParam>,
+        sdd_slot: Option<keyblob::SecureDeletionSlot>,
+    ) -> Result<(), Error> {
+        let key_id = try_to_vec(&self.key_id(keyblob)?.0)?;
+        let slot = sdd_slot.map(|slot| slot.0);
+        if slot.is_some() {
+            self.audit(AuditRecord {
+                key_id: Some(try_to_vec(&key_id)?),
+                slot,
+                ..audit::record(AuditEvent::SecureDeletionSlotCreated)
+            });
+        }
+        let event = match purpose {
+            keyblob::SlotPurpose::KeyGeneration => AuditEvent::KeyGenerated,
+            keyblob::SlotPurpose::KeyImport => AuditEvent::KeyImported,
+            keyblob::SlotPurpose::KeyUpgrade => AuditEvent::KeyUpgraded,
+            keyblob::SlotPurpose::KeyDerivation => AuditEvent::KeyDerived,
+        };
+        self.audit(AuditRecord {
+            key_id: Some(key_id),
+            params: summary,
+            slot,
+            ..audit::record(event)
+        });
+        Ok(())
+    }
+
+    /// Delete the secure deletion slot of a newly created keyblob that could not be returned to
+    /// the caller.
+    pub(crate) fn release_secure_deletion_slot(&mut self, slot: keyblob::SecureDeletionSlot) {
+        if let Some(sdd_mgr) = &mut self.dev.sdd_mgr {
+            match sdd_mgr.delete_secret(slot) {
+                Ok(()) => self.audit(AuditRecord {
+                    slot: Some(slot.0),
+                    ..audit::record(AuditEvent::SecureDeletionSlotDeleted)
+                }),
+                Err(e) => error!("failed to release secure deletion slot {:?}: {:?}", slot, e),
+            }
+        }
     }
 }
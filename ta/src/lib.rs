@@ -32,6 +32,7 @@ use kmr_common::{
     km_err, tag, try_to_vec, vec_try, vec_try_with_capacity, Error, FallibleAllocExt,
 };
 use kmr_wire::{
+    audit::{AuditEvent, AuditRecord},
     coset::TaggedCborSerializable,
     keymint::{
         Digest, ErrorCode, HardwareAuthToken, KeyCharacteristics, KeyMintHardwareInfo, KeyOrigin,
@@ -45,16 +46,21 @@ use kmr_wire::{
 };
 use log::{debug, error, info, trace, warn};
 
+mod audit;
 mod cert;
 mod clock;
+pub mod confirmation;
 pub mod device;
+pub mod gatekeeper;
+mod keyblob_cache;
 pub mod keys;
 mod operation;
 pub mod rkp;
 mod secret;
+pub mod self_test;
 
 use keys::KeyImport;
-use operation::{OpHandle, Operation};
+use operation::{OpHandle, Operation, PresenceWait};
 
 #[cfg(test)]
 mod tests;
@@ -77,10 +83,10 @@ pub enum KeyMintHalVersion {
 pub const KEYMINT_CURRENT_VERSION: KeyMintHalVersion = KeyMintHalVersion::V4;
 
 /// Maximum number of parallel operations supported when running as TEE.
-const MAX_TEE_OPERATIONS: usize = 16;
+pub const MAX_TEE_OPERATIONS: usize = 16;
 
 /// Maximum number of parallel operations supported when running as StrongBox.
-const MAX_STRONGBOX_OPERATIONS: usize = 4;
+pub const MAX_STRONGBOX_OPERATIONS: usize = 4;
 
 /// Maximum number of keys whose use count can be tracked.
 const MAX_USE_COUNTED_KEYS: usize = 32;
@@ -139,6 +145,12 @@ pub struct KeyMintTa {
     /// Information provided by the HAL service once at start of day.
     hal_info: Option<HalInfo>,
 
+    /// Whether the power-on self tests of the crypto implementation have failed.
+    self_test_failed: bool,
+
+    /// Whether incoming requests and keyblobs must be deterministically encoded CBOR.
+    strict_cbor: bool,
+
     /// Additional information to attest to, provided by Android. Refer to
     /// `IKeyMintDevice::setAdditionalAttestationInfo()`.
     additional_attestation_info: Vec<KeyParam>,
@@ -176,8 +188,18 @@ pub struct KeyMintTa {
     /// Use counts for keys where this is tracked.
     use_count: [Option<UseCount>; MAX_USE_COUNTED_KEYS],
 
-    /// Operation handle of the (single) in-flight operation that requires trusted user presence.
-    presence_required_op: Option<OpHandle>,
+    /// Cache of recently decrypted keyblobs.
+    keyblob_cache: RefCell<keyblob_cache::KeyblobCache>,
+
+    /// Log of security-relevant events, waiting to be drained by the HAL.
+    audit_log: RefCell<audit::AuditLog>,
+
+    /// Details of the (single) in-flight operation that requires trusted user presence.
+    presence_required_op: Option<PresenceWait>,
+
+    /// Secure deletion slot of a keyblob created by the request currently being processed, which
+    /// must be released if the keyblob cannot be returned.
+    new_keyblob_slot: Option<keyblob::SecureDeletionSlot>,
 }
 
 /// A helper method that can be used by the TA for processing the responses to be sent to the
@@ -197,10 +219,7 @@ pub fn split_rsp(mut rsp_data: &[u8], max_size: usize) -> Result<Vec<Vec<u8>>, E
     }
     // Need to allocate one byte for the more_msg_signal.
     let allowed_msg_length = max_size - 1;
-    let mut num_of_splits = rsp_data.len() / allowed_msg_length;
-    if rsp_data.len() % allowed_msg_length > 0 {
-        num_of_splits += 1;
-    }
+    let num_of_splits = rsp_data.len().div_ceil(allowed_msg_length);
     let mut split_rsp = vec_try_with_capacity!(num_of_splits)?;
     while rsp_data.len() > allowed_msg_length {
         let mut rsp = vec_try_with_capacity!(allowed_msg_length + 1)?;
@@ -311,12 +330,14 @@ impl KeyMintTa {
         imp: crypto::Implementation,
         dev: device::Implementation,
     ) -> Self {
-        let max_operations = if hw_info.security_level == SecurityLevel::Strongbox {
-            MAX_STRONGBOX_OPERATIONS
-        } else {
-            MAX_TEE_OPERATIONS
-        };
-        Self {
+        let (max_operations, keyblob_cache_size) =
+            if hw_info.security_level == SecurityLevel::Strongbox {
+                (MAX_STRONGBOX_OPERATIONS, keyblob_cache::DEFAULT_STRONGBOX_CACHE_SIZE)
+            } else {
+                (MAX_TEE_OPERATIONS, keyblob_cache::DEFAULT_TEE_CACHE_SIZE)
+            };
+        #[allow(unused_mut)]
+        let mut ta = Self {
             imp,
             dev,
             in_early_boot: true,
@@ -325,7 +346,10 @@ impl KeyMintTa {
             // Work around Rust limitation that `vec![None; n]` doesn't work.
             operations: (0..max_operations).map(|_| None).collect(),
             use_count: Default::default(),
+            keyblob_cache: RefCell::new(keyblob_cache::KeyblobCache::new(keyblob_cache_size)),
+            audit_log: RefCell::new(audit::AuditLog::new(audit::MAX_AUDIT_RECORDS)),
             presence_required_op: None,
+            new_keyblob_slot: None,
             shared_secret_params: None,
             hw_info,
             rpc_info,
@@ -333,10 +357,53 @@ impl KeyMintTa {
             boot_info: None,
             rot_data: None,
             hal_info: None,
+            self_test_failed: false,
+            strict_cbor: false,
             attestation_chain_info: RefCell::new(BTreeMap::new()),
             attestation_id_info: RefCell::new(None),
             dice_info: RefCell::new(None),
             additional_attestation_info: Vec::new(),
+        };
+        #[cfg(feature = "self-test")]
+        if let Err(e) = ta.run_self_tests() {
+            error!("crypto self tests failed: {:?}", e);
+        }
+        ta
+    }
+
+    /// Run the known-answer self tests against the crypto implementation.  A failure is latched,
+    /// after which all requests fail with `HardwareNotYetAvailable`.
+    ///
+    /// This is invoked automatically on construction if the `self-test` feature is enabled.
+    pub fn run_self_tests(&mut self) -> Result<(), Error> {
+        let result = self_test::run(&self.imp);
+        if result.is_err() {
+            self.self_test_failed = true;
+        }
+        result
+    }
+
+    /// Set the number of decrypted keyblobs that are cached, replacing the default for the
+    /// security level.  A size of zero disables caching.  Any currently cached keyblobs are
+    /// dropped.
+    pub fn set_keyblob_cache_size(&mut self, size: usize) {
+        *self.keyblob_cache.get_mut() = keyblob_cache::KeyblobCache::new(size);
+    }
+
+    /// Set whether incoming request messages and keyblobs are required to be deterministically
+    /// encoded CBOR (RFC 8949 section 4.2.1).  Strict decoding is off by default; turning it on
+    /// means that any encoding that differs from what this codebase emits is rejected, rather than
+    /// being interpreted in a way that another CBOR parser might not agree with.
+    pub fn set_strict_cbor(&mut self, strict: bool) {
+        self.strict_cbor = strict;
+    }
+
+    /// Parse an encrypted keyblob, honouring the strict CBOR setting.
+    fn parse_keyblob(&self, keyblob: &[u8]) -> Result<keyblob::EncryptedKeyBlob, Error> {
+        if self.strict_cbor {
+            keyblob::EncryptedKeyBlob::new_strict(keyblob)
+        } else {
+            keyblob::EncryptedKeyBlob::new(keyblob)
         }
     }
 
@@ -389,7 +456,7 @@ impl KeyMintTa {
         key_blob: &[u8],
         params: &[KeyParam],
     ) -> Result<(keyblob::PlaintextKeyBlob, Option<SecureDeletionSlot>), Error> {
-        let encrypted_keyblob = match keyblob::EncryptedKeyBlob::new(key_blob) {
+        let encrypted_keyblob = match self.parse_keyblob(key_blob) {
             Ok(k) => k,
             Err(e) => {
                 // We might have failed to parse the keyblob because it is in some prior format.
@@ -405,6 +472,22 @@ impl KeyMintTa {
             }
         };
         let hidden = tag::hidden(params, self.root_of_trust()?)?;
+
+        // Decryption depends on the hidden parameters as well as the keyblob, so both are needed
+        // to find a cached copy.
+        let cache_key = if self.keyblob_cache.borrow().enabled() {
+            let key_id = self.key_id(key_blob)?;
+            let hidden_digest = self.imp.sha256.hash(&try_to_vec(&hidden)?.into_vec()?)?;
+            if let Some(cached) =
+                self.keyblob_cache.borrow_mut().get(&*self.imp.compare, &key_id, &hidden_digest)
+            {
+                return Ok(cached);
+            }
+            Some((key_id, hidden_digest))
+        } else {
+            None
+        };
+
         let sdd_slot = encrypted_keyblob.secure_deletion_slot();
         let root_kek = self.root_kek(encrypted_keyblob.kek_context())?;
         let keyblob = keyblob::decrypt(
@@ -418,6 +501,9 @@ impl KeyMintTa {
             encrypted_keyblob,
             hidden,
         )?;
+        if let Some((key_id, hidden_digest)) = cache_key {
+            self.keyblob_cache.borrow_mut().insert(key_id, hidden_digest, &keyblob, sdd_slot);
+        }
         Ok((keyblob, sdd_slot))
     }
 
@@ -514,6 +600,29 @@ impl KeyMintTa {
         })?))
     }
 
+    /// Record a security-relevant event in the audit log, and pass it on to the device's audit
+    /// sink (if any).
+    fn audit(&self, mut record: AuditRecord) {
+        if let Some(clock) = &self.imp.clock {
+            record.timestamp_ms = Some(clock.now().0);
+        }
+        let mut audit_log = self.audit_log.borrow_mut();
+        audit_log.sequence(&mut record);
+        if let Some(sink) = &self.dev.audit {
+            sink.record(&record);
+        }
+        audit_log.push(record);
+    }
+
+    /// Record a failure to authenticate an operation, returning the error.
+    fn audit_auth_failure(&self, err: Error) -> Error {
+        self.audit(AuditRecord {
+            error: audit::error_code(&err),
+            ..audit::record(AuditEvent::AuthFailed)
+        });
+        err
+    }
+
     /// Increment the use count for the given key ID, failing if `max_uses` is reached.
     fn update_use_count(&mut self, key_id: KeyId, max_uses: u32) -> Result<(), Error> {
         let mut free_idx = None;
@@ -540,7 +649,15 @@ impl KeyMintTa {
         if let Some(idx) = slot_idx {
             let c = self.use_count[idx].as_mut().unwrap(); // safe: code above guarantees
             if c.count >= max_uses as u64 {
-                Err(km_err!(KeyMaxOpsExceeded, "use count {} >= limit {}", c.count, max_uses))
+                let err = km_err!(KeyMaxOpsExceeded, "use count {} >= limit {}", c.count, max_uses);
+                let key_id = try_to_vec(&c.key_id.0)?;
+                self.audit(AuditRecord {
+                    key_id: Some(key_id),
+                    params: vec_try![KeyParam::MaxUsesPerBoot(max_uses)]?,
+                    error: audit::error_code(&err),
+                    ..audit::record(AuditEvent::UseCountExhausted)
+                });
+                Err(err)
             } else {
                 c.count += 1;
                 Ok(())
@@ -556,10 +673,15 @@ impl KeyMintTa {
     pub fn set_boot_info(&mut self, boot_info: keymint::BootInfo) -> Result<(), Error> {
         if !self.in_early_boot {
             error!("Rejecting attempt to set boot info {:?} after early boot", boot_info);
-            return Err(km_err!(
+            let err = km_err!(
                 EarlyBootEnded,
                 "attempt to set boot info to {boot_info:?} after early boot"
-            ));
+            );
+            self.audit(AuditRecord {
+                error: audit::error_code(&err),
+                ..audit::record(AuditEvent::BootInfoChanged)
+            });
+            return Err(err);
         }
         if let Some(existing_boot_info) = &self.boot_info {
             if *existing_boot_info == boot_info {
@@ -568,12 +690,17 @@ impl KeyMintTa {
                     boot_info
                 );
             } else {
-                return Err(km_err!(
+                let err = km_err!(
                     RootOfTrustAlreadySet,
                     "attempt to set boot info to {:?} but already set to {:?}",
                     boot_info,
                     existing_boot_info
-                ));
+                );
+                self.audit(AuditRecord {
+                    error: audit::error_code(&err),
+                    ..audit::record(AuditEvent::BootInfoChanged)
+                });
+                return Err(err);
             }
         } else {
             info!("Setting boot_info to {:?}", boot_info);
@@ -582,6 +709,10 @@ impl KeyMintTa {
                 device_boot_locked: boot_info.device_boot_locked,
                 verified_boot_state: boot_info.verified_boot_state,
             };
+            self.audit(AuditRecord {
+                params: vec_try![KeyParam::BootPatchlevel(boot_info.boot_patchlevel)]?,
+                ..audit::record(AuditEvent::BootInfoChanged)
+            });
             self.boot_info = Some(boot_info);
             self.rot_data =
                 Some(rot_info.into_vec().map_err(|e| {
@@ -677,10 +808,26 @@ impl KeyMintTa {
 
     /// Process a single serialized request, returning a serialized response.
     pub fn process(&mut self, req_data: &[u8]) -> Vec<u8> {
-        let (req_code, rsp) = match PerformOpReq::from_slice(req_data) {
+        self.new_keyblob_slot = None;
+        let req = if self.strict_cbor {
+            PerformOpReq::from_slice_strict(req_data)
+        } else {
+            PerformOpReq::from_slice(req_data)
+        };
+        let (req_code, rsp) = match req {
             Ok(req) => {
                 trace!("-> TA: received request {:?}", req.code());
-                (Some(req.code()), self.process_req(req))
+                let code = req.code();
+                let mut rsp = self.process_req(req);
+                if self.imp.rng.failed() {
+                    // Any random data used for this request may be predictable, so discard the
+                    // response (and any keyblob in it).
+                    if let Some(slot) = self.new_keyblob_slot.take() {
+                        self.release_secure_deletion_slot(slot);
+                    }
+                    rsp = op_error_rsp(code, rng_failed_err());
+                }
+                (Some(code), rsp)
             }
             Err(e) => {
                 error!("failed to decode CBOR request: {:?}", e);
@@ -694,6 +841,19 @@ impl KeyMintTa {
         trace!("<- TA: send response {:?} rc {}", req_code, rsp.error_code);
         match rsp.into_vec() {
             Ok(rsp_data) => rsp_data,
+            Err(CborError::AllocationFailed) => {
+                error!("failed to allocate memory for CBOR response");
+                if let Some(slot) = self.new_keyblob_slot.take() {
+                    // The newly created keyblob is lost along with the response.
+                    self.release_secure_deletion_slot(slot);
+                }
+                match req_code {
+                    Some(code) if kmr_wire::is_rpc_operation(code) => {
+                        rpc_failed_rsp_data().to_vec()
+                    }
+                    _ => alloc_failed_rsp_data().to_vec(),
+                }
+            }
             Err(e) => {
                 error!("failed to encode CBOR response: {:?}", e);
                 invalid_cbor_rsp_data().to_vec()
@@ -707,6 +867,15 @@ impl KeyMintTa {
     /// request fields as parameters to the method.  In the opposite direction,
     /// build a response message from the values returned by the method.
     fn process_req(&mut self, req: PerformOpReq) -> PerformOpResponse {
+        if self.self_test_failed {
+            return op_error_rsp(
+                req.code(),
+                km_err!(HardwareNotYetAvailable, "crypto self tests failed"),
+            );
+        }
+        if self.imp.rng.failed() {
+            return op_error_rsp(req.code(), rng_failed_err());
+        }
         match req {
             // Internal messages.
             PerformOpReq::SetBootInfo(req) => {
@@ -742,6 +911,15 @@ impl KeyMintTa {
                 Ok(_) => op_ok_rsp(PerformOpRsp::SetHalVersion(SetHalVersionResponse {})),
                 Err(e) => op_error_rsp(SetHalVersionRequest::CODE, e),
             },
+            PerformOpReq::DrainAuditLog(_req) => match self.audit_log.get_mut().drain() {
+                Ok((records, dropped)) => {
+                    op_ok_rsp(PerformOpRsp::DrainAuditLog(DrainAuditLogResponse {
+                        records,
+                        dropped,
+                    }))
+                }
+                Err(e) => op_error_rsp(DrainAuditLogRequest::CODE, e),
+            },
 
             // ISharedSecret messages.
             PerformOpReq::SharedSecretGetSharedSecretParameters(_req) => {
@@ -932,6 +1110,24 @@ impl KeyMintTa {
                     Err(e) => op_error_rsp(FinishRequest::CODE, e),
                 }
             }
+            PerformOpReq::OperationFinishDeriveKey(req) => {
+                match self.op_finish_derive_key(
+                    OpHandle(req.op_handle),
+                    req.input.as_deref(),
+                    req.auth_token,
+                    req.timestamp_token,
+                    &req.hkdf_salt,
+                    &req.hkdf_info,
+                    &req.key_params,
+                ) {
+                    Ok(ret) => {
+                        op_ok_rsp(PerformOpRsp::OperationFinishDeriveKey(FinishDeriveKeyResponse {
+                            ret,
+                        }))
+                    }
+                    Err(e) => op_error_rsp(FinishDeriveKeyRequest::CODE, e),
+                }
+            }
             PerformOpReq::OperationAbort(req) => match self.op_abort(OpHandle(req.op_handle)) {
                 Ok(_ret) => op_ok_rsp(PerformOpRsp::OperationAbort(AbortResponse {})),
                 Err(e) => op_error_rsp(AbortRequest::CODE, e),
@@ -991,6 +1187,7 @@ impl KeyMintTa {
     fn early_boot_ended(&mut self) -> Result<(), Error> {
         info!("early boot ended");
         self.in_early_boot = false;
+        self.keyblob_cache.get_mut().clear();
         Ok(())
     }
 
@@ -1005,18 +1202,32 @@ impl KeyMintTa {
     }
 
     fn delete_key(&mut self, keyblob: &[u8]) -> Result<(), Error> {
+        let mut record = audit::record(AuditEvent::KeyDeleted);
+        if let Ok(key_id) = self.key_id(keyblob) {
+            self.keyblob_cache.get_mut().remove(&key_id);
+            record.key_id = Some(try_to_vec(&key_id.0)?);
+        }
+
         // Parse the keyblob. It cannot be decrypted, because hidden parameters are not available
         // (there is no `params` for them to arrive in).
-        if let Ok(keyblob::EncryptedKeyBlob::V1(encrypted_keyblob)) =
-            keyblob::EncryptedKeyBlob::new(keyblob)
-        {
+        if let Ok(keyblob::EncryptedKeyBlob::V1(encrypted_keyblob)) = self.parse_keyblob(keyblob) {
             // We have to trust that any secure deletion slot in the keyblob is valid, because the
             // key can't be decrypted.
             if let (Some(sdd_mgr), Some(slot)) =
                 (&mut self.dev.sdd_mgr, encrypted_keyblob.secure_deletion_slot)
             {
-                if let Err(e) = sdd_mgr.delete_secret(slot) {
-                    error!("failed to delete secure deletion slot: {:?}", e);
+                self.keyblob_cache.get_mut().remove_slot(slot);
+                record.slot = Some(slot.0);
+                match sdd_mgr.delete_secret(slot) {
+                    Ok(()) => self.audit(AuditRecord {
+                        key_id: record.key_id.clone(),
+                        slot: Some(slot.0),
+                        ..audit::record(AuditEvent::SecureDeletionSlotDeleted)
+                    }),
+                    Err(e) => {
+                        error!("failed to delete secure deletion slot: {:?}", e);
+                        record.error = audit::error_code(&e);
+                    }
                 }
             }
         } else {
@@ -1029,11 +1240,14 @@ impl KeyMintTa {
                 error!("failed to parse keyblob, ignoring");
             }
         }
+        self.audit(record);
 
         Ok(())
     }
 
     fn delete_all_keys(&mut self) -> Result<(), Error> {
+        self.keyblob_cache.get_mut().clear();
+        self.audit(audit::record(AuditEvent::AllKeysDeleted));
         if let Some(sdd_mgr) = &mut self.dev.sdd_mgr {
             error!("secure deleting all keys -- device likely to need factory reset!");
             sdd_mgr.delete_all();
@@ -1042,7 +1256,7 @@ impl KeyMintTa {
     }
 
     fn destroy_attestation_ids(&mut self) -> Result<(), Error> {
-        match self.dev.attest_ids.as_mut() {
+        let result = match self.dev.attest_ids.as_mut() {
             Some(attest_ids) => {
                 // Drop any cached copies too.
                 *self.attestation_id_info.borrow_mut() = None;
@@ -1053,7 +1267,12 @@ impl KeyMintTa {
                 error!("destroying device attestation IDs requested but not supported");
                 Err(km_err!(Unimplemented, "no attestation ID functionality available"))
             }
-        }
+        };
+        self.audit(AuditRecord {
+            error: result.as_ref().err().and_then(audit::error_code),
+            ..audit::record(AuditEvent::AttestationIdsDestroyed)
+        });
+        result
     }
 
     fn get_root_of_trust_challenge(&mut self) -> Result<[u8; 16], Error> {
@@ -1252,18 +1471,31 @@ impl KeyMintTa {
 /// Create an OK response structure with the given inner response message.
 fn op_ok_rsp(rsp: PerformOpRsp) -> PerformOpResponse {
     // Zero is OK in any context.
-    PerformOpResponse { error_code: 0, rsp: Some(rsp) }
+    PerformOpResponse { error_code: 0, rsp: Some(rsp), diagnostics: None }
 }
 
 /// Create a response structure with the given error code.
 fn error_rsp(error_code: i32) -> PerformOpResponse {
-    PerformOpResponse { error_code, rsp: None }
+    PerformOpResponse { error_code, rsp: None, diagnostics: None }
 }
 
-/// Create a response structure with the given error.
+/// Error for requests that arrive after the random number generator has failed.
+fn rng_failed_err() -> Error {
+    km_err!(HardwareNotYetAvailable, "random number generator failed")
+}
+
+/// Create a response structure with the given error.  If the `diagnostics` feature is enabled, the
+/// response also includes a description of the error.
 fn op_error_rsp(op: KeyMintOperation, err: Error) -> PerformOpResponse {
     warn!("failing {:?} request with error {:?}", op, err);
-    if kmr_wire::is_rpc_operation(op) {
+    #[cfg(feature = "diagnostics")]
+    let diagnostics = Some(match &err {
+        Error::Hal(_, msg) | Error::Rpc(_, msg) => msg.clone(),
+        _ => format!("{:?}", err),
+    });
+    #[cfg(not(feature = "diagnostics"))]
+    let diagnostics = None;
+    let mut rsp = if kmr_wire::is_rpc_operation(op) {
         // The IRemotelyProvisionedComponent HAL uses a different error space than the
         // other HALs.
         let rpc_err: rpc::ErrorCode = match err {
@@ -1277,6 +1509,7 @@ fn op_error_rsp(op: KeyMintOperation, err: Error) -> PerformOpResponse {
         error_rsp(rpc_err as i32)
     } else {
         let hal_err = match err {
+            Error::Cbor(CborError::AllocationFailed) => ErrorCode::MemoryAllocationFailed,
             Error::Cbor(_) | Error::Der(_) => ErrorCode::InvalidArgument,
             Error::Hal(e, _) => e,
             Error::Rpc(_, _) => {
@@ -1286,7 +1519,9 @@ fn op_error_rsp(op: KeyMintOperation, err: Error) -> PerformOpResponse {
             Error::Alloc(_) => ErrorCode::MemoryAllocationFailed,
         };
         error_rsp(hal_err as i32)
-    }
+    };
+    rsp.diagnostics = diagnostics;
+    rsp
 }
 
 /// Create an Error for [`KeyMintTa::set_additional_attestation_info`] failure that corresponds to
@@ -1311,6 +1546,28 @@ fn invalid_cbor_rsp_data() -> [u8; 5] {
     ]
 }
 
+/// Hand-encoded [`PerformOpResponse`] data for [`ErrorCode::MEMORY_ALLOCATION_FAILED`].
+/// Does not perform CBOR serialization (and so is suitable for error reporting if/when
+/// CBOR serialization runs out of memory).
+fn alloc_failed_rsp_data() -> [u8; 4] {
+    [
+        0x82, // 2-arr
+        0x38, // nint, len 1
+        0x28, // 0x28(40) = -41
+        0x80, // 0-arr
+    ]
+}
+
+/// Hand-encoded [`PerformOpResponse`] data for [`rpc::ErrorCode::FAILED`], for use in place of
+/// [`alloc_failed_rsp_data`] for `IRemotelyProvisionedComponent` requests.
+fn rpc_failed_rsp_data() -> [u8; 3] {
+    [
+        0x82, // 2-arr
+        0x01, // int, value 1
+        0x80, // 0-arr
+    ]
+}
+
 /// Build the HMAC input for a [`HardwareAuthToken`]
 pub fn hardware_auth_token_mac_input(token: &HardwareAuthToken) -> Result<Vec<u8>, Error> {
     let mut result = vec_try_with_capacity!(
@@ -14,29 +14,31 @@
 
 //! TA functionality related to in-progress crypto operations.
 
+use crate::{audit, device};
 use alloc::{boxed::Box, vec::Vec};
 use kmr_common::{
     crypto,
     crypto::{aes, AadOperation, AccumulatingOperation, EmittingOperation, KeyMaterial},
-    get_bool_tag_value, get_opt_tag_value, get_tag_value, keyblob, km_err, tag, try_to_vec, Error,
-    FallibleAllocExt,
+    get_bool_tag_value, get_opt_tag_value, get_tag_value, keyblob, km_err, tag, try_to_vec,
+    vec_try, Error, FallibleAllocExt,
 };
 use kmr_wire::{
-    keymint::{ErrorCode, HardwareAuthToken, KeyParam, KeyPurpose},
+    audit::{AuditEvent, AuditRecord},
+    keymint::{ErrorCode, HardwareAuthToken, KeyCreationResult, KeyParam, KeyPurpose},
     secureclock::{TimeStampToken, Timestamp},
     InternalBeginResult,
 };
 use log::{error, info, warn};
 
 /// A trusted confirmation token should be the size of HMAC-SHA256 output.
-const CONFIRMATION_TOKEN_SIZE: usize = 32;
+pub(crate) const CONFIRMATION_TOKEN_SIZE: usize = 32;
 
 /// Trusted confirmation data prefix, from IConfirmationResultCallback.hal.
-const CONFIRMATION_DATA_PREFIX: &[u8] = b"confirmation token";
+pub(crate) const CONFIRMATION_DATA_PREFIX: &[u8] = b"confirmation token";
 
 /// Maximum size of messages with `Tag::TrustedConfirmationRequired` set.
 /// See <https://source.android.com/security/protected-confirmation/implementation>
-const CONFIRMATION_MESSAGE_MAX_LEN: usize = 6144;
+pub(crate) const CONFIRMATION_MESSAGE_MAX_LEN: usize = 6144;
 
 /// Union holder for in-progress cryptographic operations, each of which is an instance
 /// of the relevant trait.
@@ -50,6 +52,10 @@ pub(crate) enum CryptoOperation {
     RsaSign(Box<dyn AccumulatingOperation>),
     EcAgree(Box<dyn AccumulatingOperation>),
     EcSign(Box<dyn AccumulatingOperation>),
+    #[cfg(feature = "pqc")]
+    MlDsaSign(Box<dyn AccumulatingOperation>),
+    #[cfg(feature = "pqc")]
+    MlKemDecapsulate(Box<dyn AccumulatingOperation>),
 }
 
 /// Current state of an operation.
@@ -88,6 +94,10 @@ impl Operation {
             | CryptoOperation::RsaSign(op)
             | CryptoOperation::EcAgree(op)
             | CryptoOperation::EcSign(op) => op.max_input_size(),
+            #[cfg(feature = "pqc")]
+            CryptoOperation::MlDsaSign(op) | CryptoOperation::MlKemDecapsulate(op) => {
+                op.max_input_size()
+            }
             _ => None,
         };
         if let Some(max_size) = max_size {
@@ -106,6 +116,15 @@ impl Operation {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct OpHandle(pub i64);
 
+/// Details of the (single) in-flight operation that requires trusted user presence.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PresenceWait {
+    /// Operation that requires trusted user presence.
+    pub op_handle: OpHandle,
+    /// Time at which the wait for presence started, if a clock is available.
+    pub started: Option<crypto::MillisecondsSinceEpoch>,
+}
+
 /// Authentication requirements associated with an operation.
 pub(crate) struct AuthInfo {
     secure_ids: Vec<u64>,
@@ -175,7 +194,7 @@ impl crate::KeyMintTa {
         // Validate parameters.
         let key_chars =
             kmr_common::tag::characteristics_at(&characteristics, self.hw_info.security_level)?;
-        tag::check_begin_params(key_chars, purpose, &params)?;
+        tag::check_begin_params(&self.dev.policy, key_chars, purpose, &params)?;
         self.check_begin_auths(key_chars, key_blob)?;
 
         let trusted_conf_data = if purpose == KeyPurpose::Sign
@@ -212,7 +231,10 @@ impl crate::KeyMintTa {
                 if let Some(clock) = &self.imp.clock {
                     let now: Timestamp = clock.now().into();
                     let auth_token = auth_token.ok_or_else(|| {
-                        km_err!(KeyUserNotAuthenticated, "no auth token on begin()")
+                        self.audit_auth_failure(km_err!(
+                            KeyUserNotAuthenticated,
+                            "no auth token on begin()"
+                        ))
                     })?;
                     self.check_auth_token(
                         auth_token,
@@ -418,12 +440,56 @@ impl crate::KeyMintTa {
                     }
                 },
             },
+            #[cfg(feature = "pqc")]
+            KeyMaterial::MlDsa(param_set, key) => Operation {
+                handle: op_handle,
+                aad_allowed: false,
+                input_size: 0,
+                slot_to_delete,
+                trusted_conf_data,
+                auth_info: op_auth_info,
+                crypto_op: match purpose {
+                    KeyPurpose::Sign => {
+                        CryptoOperation::MlDsaSign(self.imp.mldsa.begin_sign(key, param_set)?)
+                    }
+                    _ => {
+                        return Err(km_err!(
+                            IncompatiblePurpose,
+                            "invalid purpose {:?} for ML-DSA key",
+                            purpose
+                        ))
+                    }
+                },
+            },
+            #[cfg(feature = "pqc")]
+            KeyMaterial::MlKem(key) => Operation {
+                handle: op_handle,
+                aad_allowed: false,
+                input_size: 0,
+                slot_to_delete,
+                trusted_conf_data,
+                auth_info: op_auth_info,
+                crypto_op: match purpose {
+                    KeyPurpose::AgreeKey => {
+                        CryptoOperation::MlKemDecapsulate(self.imp.mlkem.begin_decapsulate(key)?)
+                    }
+                    _ => {
+                        return Err(km_err!(
+                            IncompatiblePurpose,
+                            "invalid purpose {:?} for ML-KEM key",
+                            purpose
+                        ))
+                    }
+                },
+            },
         };
-        self.operations[op_idx] = Some(op);
         if presence_required {
             info!("this operation requires proof-of-presence");
-            self.presence_required_op = Some(op_handle);
+            self.dev.tup.start_wait(device::TRUSTED_PRESENCE_TIMEOUT_MS)?;
+            let started = self.imp.clock.as_ref().map(|clock| clock.now());
+            self.presence_required_op = Some(PresenceWait { op_handle, started });
         }
+        self.operations[op_idx] = Some(op);
         Ok(InternalBeginResult { challenge, params: ret_params, op_handle: op_handle.0 })
     }
 
@@ -452,20 +518,7 @@ impl crate::KeyMintTa {
         auth_token: Option<HardwareAuthToken>,
         timestamp_token: Option<TimeStampToken>,
     ) -> Result<Vec<u8>, Error> {
-        let check_presence = if self.presence_required_op == Some(op_handle) {
-            self.presence_required_op = None;
-            true
-        } else {
-            false
-        };
-        let tup_available = self.dev.tup.available();
         self.with_authed_operation(op_handle, auth_token, timestamp_token, |op| {
-            if check_presence && !tup_available {
-                return Err(km_err!(
-                    ProofOfPresenceRequired,
-                    "trusted proof of presence required but not available"
-                ));
-            }
             if let Some(trusted_conf_data) = &mut op.trusted_conf_data {
                 if trusted_conf_data.len() + data.len()
                     > CONFIRMATION_DATA_PREFIX.len() + CONFIRMATION_MESSAGE_MAX_LEN
@@ -505,6 +558,11 @@ impl crate::KeyMintTa {
                     op.update(data)?;
                     Ok(Vec::new())
                 }
+                #[cfg(feature = "pqc")]
+                CryptoOperation::MlDsaSign(op) | CryptoOperation::MlKemDecapsulate(op) => {
+                    op.update(data)?;
+                    Ok(Vec::new())
+                }
             }
         })
     }
@@ -519,17 +577,15 @@ impl crate::KeyMintTa {
         confirmation_token: Option<&[u8]>,
     ) -> Result<Vec<u8>, Error> {
         let mut op = self.take_operation(op_handle)?;
-        self.check_subsequent_auth(&op, auth_token, timestamp_token)?;
-
-        if self.presence_required_op == Some(op_handle) {
-            self.presence_required_op = None;
-            if !self.dev.tup.available() {
-                return Err(km_err!(
-                    ProofOfPresenceRequired,
-                    "trusted proof of presence required but not available"
-                ));
-            }
+        let checked = self
+            .check_subsequent_auth(&op, auth_token, timestamp_token)
+            .and_then(|_| self.check_presence(op_handle));
+        if checked.is_err() {
+            // The operation is gone, so any wait for presence is no longer needed.
+            self.cancel_presence_wait(op_handle);
         }
+        checked?;
+
         if let (Some(trusted_conf_data), Some(data)) = (&mut op.trusted_conf_data, data) {
             if trusted_conf_data.len() + data.len()
                 > CONFIRMATION_DATA_PREFIX.len() + CONFIRMATION_MESSAGE_MAX_LEN
@@ -616,6 +672,13 @@ impl crate::KeyMintTa {
                 };
                 op.finish()
             }
+            #[cfg(feature = "pqc")]
+            CryptoOperation::MlDsaSign(mut op) | CryptoOperation::MlKemDecapsulate(mut op) => {
+                if let Some(data) = data {
+                    op.update(data)?;
+                };
+                op.finish()
+            }
         };
         if result.is_ok() {
             if let Some(trusted_conf_data) = op.trusted_conf_data {
@@ -625,18 +688,67 @@ impl crate::KeyMintTa {
             if let (Some(slot), Some(sdd_mgr)) = (op.slot_to_delete, &mut self.dev.sdd_mgr) {
                 // A successful use of a key with UsageCountLimit(1) triggers deletion.
                 warn!("Deleting single-use key after use");
-                if let Err(e) = sdd_mgr.delete_secret(slot) {
+                self.keyblob_cache.get_mut().remove_slot(slot);
+                let result = sdd_mgr.delete_secret(slot);
+                if let Err(e) = &result {
                     error!("Failed to delete single-use key after use: {:?}", e);
                 }
+                self.audit(AuditRecord {
+                    params: vec_try![KeyParam::UsageCountLimit(1)]?,
+                    slot: Some(slot.0),
+                    ..audit::record(AuditEvent::UseCountExhausted)
+                });
+                if result.is_ok() {
+                    self.audit(AuditRecord {
+                        slot: Some(slot.0),
+                        ..audit::record(AuditEvent::SecureDeletionSlotDeleted)
+                    });
+                }
             }
         }
         result
     }
 
-    pub(crate) fn op_abort(&mut self, op_handle: OpHandle) -> Result<(), Error> {
-        if self.presence_required_op == Some(op_handle) {
-            self.presence_required_op = None;
+    /// Finish a key agreement operation (a vendor extension), using HKDF-SHA256 to derive a new
+    /// key with characteristics from `params` from the shared secret.  The shared secret never
+    /// leaves the TA; instead the new key is returned as a keyblob, with `KeyOrigin::Derived`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn op_finish_derive_key(
+        &mut self,
+        op_handle: OpHandle,
+        data: Option<&[u8]>,
+        auth_token: Option<HardwareAuthToken>,
+        timestamp_token: Option<TimeStampToken>,
+        hkdf_salt: &[u8],
+        hkdf_info: &[u8],
+        params: &[KeyParam],
+    ) -> Result<KeyCreationResult, Error> {
+        let op_idx = self.op_index(op_handle)?;
+        if !matches!(
+            self.operations[op_idx].as_ref().map(|op| &op.crypto_op),
+            Some(CryptoOperation::EcAgree(_))
+        ) {
+            // As for `finish()`, any failure terminates the operation.
+            self.cancel_presence_wait(op_handle);
+            let _op = self.take_operation(op_handle)?;
+            return Err(km_err!(
+                IncompatiblePurpose,
+                "key derivation only supported for key agreement operations"
+            ));
         }
+        let shared_secret = crypto::RawKeyMaterial(self.op_finish(
+            op_handle,
+            data,
+            None,
+            auth_token,
+            timestamp_token,
+            None,
+        )?);
+        self.derive_key(params, &shared_secret.0, hkdf_salt, hkdf_info)
+    }
+
+    pub(crate) fn op_abort(&mut self, op_handle: OpHandle) -> Result<(), Error> {
+        self.cancel_presence_wait(op_handle);
         let _op = self.take_operation(op_handle)?;
         Ok(())
     }
@@ -661,7 +773,7 @@ impl crate::KeyMintTa {
         Ok(())
     }
 
-    /// Validate a `[keymint::HardwareAuthToken`].
+    /// Validate a `[keymint::HardwareAuthToken`], recording any failure in the audit log.
     fn check_auth_token(
         &self,
         auth_token: HardwareAuthToken,
@@ -669,6 +781,19 @@ impl crate::KeyMintTa {
         now: Option<Timestamp>,
         timeout_secs: Option<u32>,
         challenge: Option<i64>,
+    ) -> Result<(), Error> {
+        self.verify_auth_token(auth_token, auth_info, now, timeout_secs, challenge)
+            .map_err(|e| self.audit_auth_failure(e))
+    }
+
+    /// Validate a `[keymint::HardwareAuthToken`].
+    fn verify_auth_token(
+        &self,
+        auth_token: HardwareAuthToken,
+        auth_info: &AuthInfo,
+        now: Option<Timestamp>,
+        timeout_secs: Option<u32>,
+        challenge: Option<i64>,
     ) -> Result<(), Error> {
         // Common check: confirm the HMAC tag in the token is valid.
         let mac_input = crate::hardware_auth_token_mac_input(&auth_token)?;
@@ -719,6 +844,47 @@ impl crate::KeyMintTa {
         Ok(())
     }
 
+    /// Check for a trusted user presence event, if `op_handle` identifies the in-flight operation
+    /// that requires one.  The event must have occurred during the window that started when the
+    /// operation began.
+    fn check_presence(&mut self, op_handle: OpHandle) -> Result<(), Error> {
+        let wait = match &self.presence_required_op {
+            Some(wait) if wait.op_handle == op_handle => *wait,
+            _ => return Ok(()),
+        };
+        self.presence_required_op = None;
+        match self.dev.tup.finish_wait() {
+            device::PresenceEvent::Detected(Some(when)) => {
+                if let Some(started) = wait.started {
+                    let deadline = started.0 + device::TRUSTED_PRESENCE_TIMEOUT_MS as i64;
+                    if when.0 < started.0 || when.0 > deadline {
+                        return Err(km_err!(
+                            ProofOfPresenceRequired,
+                            "presence event at {} outside window {}..={}",
+                            when.0,
+                            started.0,
+                            deadline
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            device::PresenceEvent::Detected(None) => Ok(()),
+            device::PresenceEvent::TimedOut => Err(km_err!(
+                ProofOfPresenceRequired,
+                "trusted proof of presence required but not available"
+            )),
+        }
+    }
+
+    /// Cancel any outstanding wait for trusted user presence for `op_handle`.
+    fn cancel_presence_wait(&mut self, op_handle: OpHandle) {
+        if matches!(&self.presence_required_op, Some(wait) if wait.op_handle == op_handle) {
+            self.presence_required_op = None;
+            self.dev.tup.cancel_wait();
+        }
+    }
+
     /// Verify that an optional confirmation token matches the provided `data`.
     fn verify_confirmation_token(&self, data: &[u8], token: Option<&[u8]>) -> Result<(), Error> {
         if let Some(token) = token {
@@ -773,7 +939,8 @@ impl crate::KeyMintTa {
     }
 
     /// Execute the provided lambda over the associated [`Operation`], handling
-    /// errors.
+    /// errors.  Any trusted user presence event that the operation needs is checked once the
+    /// authentication checks have passed.
     fn with_authed_operation<F, T>(
         &mut self,
         op_handle: OpHandle,
@@ -790,16 +957,15 @@ impl crate::KeyMintTa {
             auth_token,
             timestamp_token,
         )?;
+        let presence = self.check_presence(op_handle);
         let op = self.operations[op_idx].as_mut().unwrap(/* safe: op_index() checks */);
         if !check_again {
             op.auth_info = None;
         }
-        let result = f(op);
+        let result = presence.and_then(|_| f(op));
         if result.is_err() {
             // A failure destroys the operation.
-            if self.presence_required_op == Some(op_handle) {
-                self.presence_required_op = None;
-            }
+            self.cancel_presence_wait(op_handle);
             self.operations[op_idx] = None;
         }
         result
@@ -821,7 +987,10 @@ impl crate::KeyMintTa {
     ) -> Result<bool, Error> {
         if let Some(auth_info) = &op.auth_info {
             let auth_token = auth_token.ok_or_else(|| {
-                km_err!(KeyUserNotAuthenticated, "no auth token on subsequent op")
+                self.audit_auth_failure(km_err!(
+                    KeyUserNotAuthenticated,
+                    "no auth token on subsequent op"
+                ))
             })?;
 
             // Most auth checks happen on begin(), but there are two exceptions.
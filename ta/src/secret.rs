@@ -14,7 +14,7 @@
 
 //! TA functionality for shared secret negotiation.
 
-use crate::device::DeviceHmac;
+use crate::device::{DeviceHmac, RetrieveKeyMaterial};
 use alloc::{boxed::Box, vec::Vec};
 use kmr_common::{crypto, crypto::hmac, km_err, vec_try, Error, FallibleAllocExt};
 use kmr_wire::{keymint::Digest, sharedsecret::SharedSecretParameters};
@@ -23,9 +23,7 @@ use log::info;
 impl crate::KeyMintTa {
     pub(crate) fn get_shared_secret_params(&mut self) -> Result<SharedSecretParameters, Error> {
         if self.shared_secret_params.is_none() {
-            let mut nonce = vec_try![0u8; 32]?;
-            self.imp.rng.fill_bytes(&mut nonce);
-            self.shared_secret_params = Some(SharedSecretParameters { seed: Vec::new(), nonce });
+            self.shared_secret_params = Some(new_shared_secret_params(&mut *self.imp.rng)?);
         }
         Ok(self.shared_secret_params.as_ref().unwrap().clone()) // safe: filled above
     }
@@ -40,23 +38,94 @@ impl crate::KeyMintTa {
             None => return Err(km_err!(HardwareNotYetAvailable, "no local shared secret params")),
         };
 
-        let context = shared_secret_context(params, local_params)?;
-        let key = hmac::Key(self.imp.ckdf.ckdf(
-            &self.dev.keys.kak()?,
-            kmr_wire::sharedsecret::KEY_AGREEMENT_LABEL.as_bytes(),
-            &[&context],
-            kmr_common::crypto::SHA256_DIGEST_LEN,
-        )?);
-
-        // Potentially hand the negotiated HMAC key off to hardware.
-        self.device_hmac = Some(self.dev.keys.hmac_key_agreed(&key).unwrap_or_else(|| {
-            // Key not installed into hardware, so build & use a local impl.
-            Box::new(SoftDeviceHmac { key })
-        }));
+        self.device_hmac =
+            Some(agree_device_hmac(&*self.imp.ckdf, &*self.dev.keys, params, local_params)?);
         self.device_hmac(kmr_wire::sharedsecret::KEY_CHECK_LABEL.as_bytes())
     }
 }
 
+/// Generate a fresh set of local shared secret parameters, with an empty seed and a random nonce.
+pub(crate) fn new_shared_secret_params(
+    rng: &mut dyn crypto::Rng,
+) -> Result<SharedSecretParameters, Error> {
+    let mut nonce = vec_try![0u8; 32]?;
+    rng.fill_bytes(&mut nonce);
+    if rng.failed() {
+        return Err(km_err!(HardwareNotYetAvailable, "random number generator failed"));
+    }
+    Ok(SharedSecretParameters { seed: Vec::new(), nonce })
+}
+
+/// Derive the agreed HMAC key from the full set of shared secret `params` (which must include
+/// `local_params`), and return the [`DeviceHmac`] implementation that uses it.
+pub(crate) fn agree_device_hmac(
+    ckdf: &dyn crypto::Ckdf,
+    keys: &dyn RetrieveKeyMaterial,
+    params: &[SharedSecretParameters],
+    local_params: &SharedSecretParameters,
+) -> Result<Box<dyn DeviceHmac>, Error> {
+    let context = shared_secret_context(params, local_params)?;
+    let key = hmac::Key(ckdf.ckdf(
+        &keys.kak()?,
+        kmr_wire::sharedsecret::KEY_AGREEMENT_LABEL.as_bytes(),
+        &[&context],
+        kmr_common::crypto::SHA256_DIGEST_LEN,
+    )?);
+
+    // Potentially hand the negotiated HMAC key off to hardware.
+    Ok(keys.hmac_key_agreed(&key).unwrap_or_else(|| {
+        // Key not installed into hardware, so build & use a local impl.
+        Box::new(SoftDeviceHmac { key })
+    }))
+}
+
+/// Shared secret negotiation state for a companion TA that is not KeyMint itself, but which needs
+/// to share the agreed HMAC key with KeyMint.
+#[derive(Default)]
+pub(crate) struct CompanionSecret {
+    /// Local shared secret parameters.
+    params: Option<SharedSecretParameters>,
+    /// HMAC implementation using the agreed key.
+    device_hmac: Option<Box<dyn DeviceHmac>>,
+}
+
+impl CompanionSecret {
+    /// Return the local shared secret parameters, generating them if necessary.
+    pub(crate) fn get_params(
+        &mut self,
+        rng: &mut dyn crypto::Rng,
+    ) -> Result<SharedSecretParameters, Error> {
+        if self.params.is_none() {
+            self.params = Some(new_shared_secret_params(rng)?);
+        }
+        Ok(self.params.as_ref().unwrap().clone()) // safe: filled above
+    }
+
+    /// Agree the HMAC key from the full set of shared secret `params`, returning the key check
+    /// value.
+    pub(crate) fn compute(
+        &mut self,
+        imp: &crypto::Implementation,
+        keys: &dyn RetrieveKeyMaterial,
+        params: &[SharedSecretParameters],
+    ) -> Result<Vec<u8>, Error> {
+        let local_params = match &self.params {
+            Some(params) => params,
+            None => return Err(km_err!(HardwareNotYetAvailable, "no local shared secret params")),
+        };
+        self.device_hmac = Some(agree_device_hmac(&*imp.ckdf, keys, params, local_params)?);
+        self.hmac(&*imp.hmac, kmr_wire::sharedsecret::KEY_CHECK_LABEL.as_bytes())
+    }
+
+    /// Calculate an HMAC-SHA256 value over the data using the agreed HMAC key.
+    pub(crate) fn hmac(&self, imp: &dyn crypto::Hmac, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match &self.device_hmac {
+            Some(traitobj) => traitobj.hmac(imp, data),
+            None => Err(km_err!(HardwareNotYetAvailable, "HMAC key not agreed")),
+        }
+    }
+}
+
 /// Build the shared secret context from the given `params`, which
 /// is required to include `must_include` (our own parameters).
 pub fn shared_secret_context(
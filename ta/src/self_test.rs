@@ -0,0 +1,705 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Power-on known-answer self tests for a [`crypto::Implementation`], in the style of FIPS 140.
+//!
+//! The test vectors are public so that the `kmr_tests` smoke tests for individual trait
+//! implementations can use the same data.
+
+use alloc::vec::Vec;
+use kmr_common::{
+    crypto::{self, aes, des, ec, hmac, rsa, KeyMaterial, SymmetricOperation},
+    km_err, vec_try_with_capacity, Error, FallibleAllocExt,
+};
+use kmr_wire::keymint::{Digest, EcCurve, ErrorCode};
+use log::{error, info, warn};
+
+/// HKDF test case.
+#[allow(missing_docs)]
+pub struct HkdfTestCase {
+    pub ikm: &'static str,
+    pub salt: &'static str,
+    pub info: &'static str,
+    pub out_len: usize,
+    pub want: &'static str,
+}
+
+/// HKDF test vectors.
+pub const HKDF_TESTS: &[HkdfTestCase] = &[
+    // RFC 5869 section A.1
+    HkdfTestCase {
+        ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+        salt: "000102030405060708090a0b0c",
+        info: "f0f1f2f3f4f5f6f7f8f9",
+        out_len: 42,
+        want: concat!(
+            "3cb25f25faacd57a90434f64d0362f2a",
+            "2d2d0a90cf1a5a4c5db02d56ecc4c5bf",
+            "34007208d5b887185865",
+        ),
+    },
+    // RFC 5869 section A.2
+    HkdfTestCase {
+        ikm: concat!(
+            "000102030405060708090a0b0c0d0e0f",
+            "101112131415161718191a1b1c1d1e1f",
+            "202122232425262728292a2b2c2d2e2f",
+            "303132333435363738393a3b3c3d3e3f",
+            "404142434445464748494a4b4c4d4e4f",
+        ),
+        salt: concat!(
+            "606162636465666768696a6b6c6d6e6f",
+            "707172737475767778797a7b7c7d7e7f",
+            "808182838485868788898a8b8c8d8e8f",
+            "909192939495969798999a9b9c9d9e9f",
+            "a0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+        ),
+        info: concat!(
+            "b0b1b2b3b4b5b6b7b8b9babbbcbdbebf",
+            "c0c1c2c3c4c5c6c7c8c9cacbcccdcecf",
+            "d0d1d2d3d4d5d6d7d8d9dadbdcdddedf",
+            "e0e1e2e3e4e5e6e7e8e9eaebecedeeef",
+            "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+        ),
+        out_len: 82,
+        want: concat!(
+            "b11e398dc80327a1c8e7f78c596a4934",
+            "4f012eda2d4efad8a050cc4c19afa97c",
+            "59045a99cac7827271cb41c65e590e09",
+            "da3275600c2f09b8367793a9aca3db71",
+            "cc30c58179ec3e87c14c01d5c1f3434f",
+            "1d87",
+        ),
+    },
+    // RFC 5869 section A.3
+    HkdfTestCase {
+        ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+        salt: "",
+        info: "",
+        out_len: 42,
+        want: concat!(
+            "8da4e775a563c18f715f802a063c5a31",
+            "b8a11f5c5ee1879ec3454e5f3c738d2d",
+            "9d201395faa4b61a96c8",
+        ),
+    },
+];
+
+/// HMAC test case.
+#[allow(missing_docs)]
+pub struct HmacTestCase {
+    pub digest: Digest,
+    pub tag_size: usize,
+    pub key: &'static [u8],
+    pub data: &'static [u8],
+    pub expected_mac: &'static str,
+}
+
+/// HMAC test vectors.
+pub const HMAC_TESTS: &[HmacTestCase] = &[
+    HmacTestCase {
+        digest: Digest::Sha256,
+        tag_size: 32,
+        data: b"Hello",
+        key: b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f",
+        expected_mac: "e0ff02553d9a619661026c7aa1ddf59b7b44eac06a9908ff9e19961d481935d4",
+    },
+    HmacTestCase {
+        digest: Digest::Sha512,
+        tag_size: 64,
+        data: b"Hello",
+        key: b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f",
+        expected_mac: concat!(
+            "481e10d823ba64c15b94537a3de3f253c16642451ac45124dd4dde120bf1e5c1",
+            "5e55487d55ba72b43039f235226e7954cd5854b30abc4b5b53171a4177047c9b",
+        ),
+    },
+    // empty data
+    HmacTestCase {
+        digest: Digest::Sha256,
+        tag_size: 32,
+        data: &[],
+        key: b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f",
+        expected_mac: "07eff8b326b7798c9ccfcbdbe579489ac785a7995a04618b1a2813c26744777d",
+    },
+    // Test cases from RFC 4231 Section 4.2
+    HmacTestCase {
+        digest: Digest::Sha224,
+        tag_size: 224 / 8,
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        expected_mac: concat!("896fb1128abbdf196832107cd49df33f", "47b4b1169912ba4f53684b22"),
+    },
+    HmacTestCase {
+        digest: Digest::Sha256,
+        tag_size: 256 / 8,
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        expected_mac: concat!(
+            "b0344c61d8db38535ca8afceaf0bf12b",
+            "881dc200c9833da726e9376c2e32cff7"
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha384,
+        tag_size: 384 / 8,
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        expected_mac: concat!(
+            "afd03944d84895626b0825f4ab46907f",
+            "15f9dadbe4101ec682aa034c7cebc59c",
+            "faea9ea9076ede7f4af152e8b2fa9cb6",
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha512,
+        tag_size: 512 / 8,
+        key: &[0x0b; 20],
+        data: b"Hi There",
+        expected_mac: concat!(
+            "87aa7cdea5ef619d4ff0b4241a1d6cb0",
+            "2379f4e2ce4ec2787ad0b30545e17cde",
+            "daa833b7d6b8a702038b274eaea3f4e4",
+            "be9d914eeb61f1702e696c203a126854"
+        ),
+    },
+    // Test cases from RFC 4231 Section 4.3
+    HmacTestCase {
+        digest: Digest::Sha224,
+        tag_size: 224 / 8,
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        expected_mac: concat!("a30e01098bc6dbbf45690f3a7e9e6d0f", "8bbea2a39e6148008fd05e44"),
+    },
+    HmacTestCase {
+        digest: Digest::Sha256,
+        tag_size: 256 / 8,
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        expected_mac: concat!(
+            "5bdcc146bf60754e6a042426089575c7",
+            "5a003f089d2739839dec58b964ec3843"
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha384,
+        tag_size: 384 / 8,
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        expected_mac: concat!(
+            "af45d2e376484031617f78d2b58a6b1b",
+            "9c7ef464f5a01b47e42ec3736322445e",
+            "8e2240ca5e69e2c78b3239ecfab21649"
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha512,
+        tag_size: 512 / 8,
+        key: b"Jefe",
+        data: b"what do ya want for nothing?",
+        expected_mac: concat!(
+            "164b7a7bfcf819e2e395fbe73b56e0a3",
+            "87bd64222e831fd610270cd7ea250554",
+            "9758bf75c05a994a6d034f65f8f0e6fd",
+            "caeab1a34d4a6b4b636e070a38bce737"
+        ),
+    },
+    // Test cases from RFC 4231 Section 4.4
+    HmacTestCase {
+        digest: Digest::Sha224,
+        tag_size: 224 / 8,
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        expected_mac: concat!("7fb3cb3588c6c1f6ffa9694d7d6ad264", "9365b0c1f65d69d1ec8333ea"),
+    },
+    HmacTestCase {
+        digest: Digest::Sha256,
+        tag_size: 256 / 8,
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        expected_mac: concat!(
+            "773ea91e36800e46854db8ebd09181a7",
+            "2959098b3ef8c122d9635514ced565fe"
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha384,
+        tag_size: 384 / 8,
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        expected_mac: concat!(
+            "88062608d3e6ad8a0aa2ace014c8a86f",
+            "0aa635d947ac9febe83ef4e55966144b",
+            "2a5ab39dc13814b94e3ab6e101a34f27"
+        ),
+    },
+    HmacTestCase {
+        digest: Digest::Sha512,
+        tag_size: 512 / 8,
+        key: &[0xaa; 20],
+        data: &[0xdd; 50],
+        expected_mac: concat!(
+            "fa73b0089d56a284efb0f0756c890be9",
+            "b1b5dbdd8ee81a3655f83e33b2279d39",
+            "bf3e848279a722c806b485a47e67c807",
+            "b946a337bee8942674278859e13292fb"
+        ),
+    },
+];
+
+/// AES test case for the non-AEAD cipher modes.
+#[allow(missing_docs)]
+pub struct AesTestCase {
+    pub key: &'static str,
+    pub mode: aes::CipherMode,
+    pub msg: &'static str,
+    pub ct: &'static str,
+}
+
+/// AES test vectors, from NIST SP 800-38A appendix F.
+pub const AES_TESTS: &[AesTestCase] = &[
+    // F.1.1 ECB-AES128.Encrypt
+    AesTestCase {
+        key: "2b7e151628aed2a6abf7158809cf4f3c",
+        mode: aes::CipherMode::EcbNoPadding,
+        msg: "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+        ct: "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf",
+    },
+    // F.2.1 CBC-AES128.Encrypt
+    AesTestCase {
+        key: "2b7e151628aed2a6abf7158809cf4f3c",
+        mode: aes::CipherMode::CbcNoPadding {
+            nonce: [
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                0x0e, 0x0f,
+            ],
+        },
+        msg: "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+        ct: "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2",
+    },
+    // F.5.1 CTR-AES128.Encrypt
+    AesTestCase {
+        key: "2b7e151628aed2a6abf7158809cf4f3c",
+        mode: aes::CipherMode::Ctr {
+            nonce: [
+                0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd,
+                0xfe, 0xff,
+            ],
+        },
+        msg: "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+        ct: "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff",
+    },
+];
+
+/// AES-GCM test case.
+#[allow(missing_docs)]
+pub struct AesGcmTestCase {
+    pub key: &'static str,
+    pub iv: &'static str,
+    pub aad: &'static str,
+    pub msg: &'static str,
+    pub ct: &'static str,
+    pub tag: &'static str,
+}
+
+/// AES-GCM test vectors, from
+/// https://github.com/google/wycheproof/blob/master/testvectors/aes_gcm_test.json
+pub const AES_GCM_TESTS: &[AesGcmTestCase] = &[
+    AesGcmTestCase {
+        key: "5b9604fe14eadba931b0ccf34843dab9",
+        iv: "028318abc1824029138141a2",
+        aad: "",
+        msg: "001d0c231287c1182784554ca3a21908",
+        ct: "26073cc1d851beff176384dc9896d5ff",
+        tag: "0a3ea7a5487cb5f7d70fb6c58d038554",
+    },
+    AesGcmTestCase {
+        key: "5b9604fe14eadba931b0ccf34843dab9",
+        iv: "921d2507fa8007b7bd067d34",
+        aad: "00112233445566778899aabbccddeeff",
+        msg: "001d0c231287c1182784554ca3a21908",
+        ct: "49d8b9783e911913d87094d1f63cc765",
+        tag: "1e348ba07cca2cf04c618cb4d43a5b92",
+    },
+];
+
+/// Triple-DES test case.
+#[allow(missing_docs)]
+pub struct DesTestCase {
+    pub key: &'static str,
+    pub msg: &'static str,
+    pub ct: &'static str,
+}
+
+/// Triple-DES test vectors.
+pub const DES_TESTS: &[DesTestCase] = &[
+    DesTestCase {
+        key: "800000000000000000000000000000000000000000000000",
+        msg: "0000000000000000",
+        ct: "95a8d72813daa94d",
+    },
+    DesTestCase {
+        key: "000000000000000000000000000000002000000000000000",
+        msg: "0000000000000000",
+        ct: "7ad16ffb79c45926",
+    },
+];
+
+/// CKDF test case.
+#[allow(missing_docs)]
+pub struct CkdfTestCase {
+    pub key: &'static [u8],
+    pub label: &'static [u8],
+    pub chunks: &'static [&'static [u8]],
+    pub want: &'static str,
+}
+
+/// CKDF test vectors, manually generated from the Android C++ implementation.
+pub const CKDF_TESTS: &[CkdfTestCase] = &[CkdfTestCase {
+    key: &[0; 32],
+    label: b"KeymasterSharedMac",
+    chunks: &[&[0x00; 4], &[0x01; 4], &[0x02; 4], &[0x03; 4]],
+    want: concat!("ac9af88a02241f53d43056a4676c42ee", "f06825755e419e7bd20f4e57487717aa"),
+}];
+
+/// SHA-256 test case.
+#[allow(missing_docs)]
+pub struct Sha256TestCase {
+    pub msg: &'static [u8],
+    pub want: &'static str,
+}
+
+/// SHA-256 test vectors.
+pub const SHA256_TESTS: &[Sha256TestCase] = &[
+    Sha256TestCase {
+        msg: b"",
+        want: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    },
+    Sha256TestCase {
+        msg: b"abc",
+        want: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    },
+];
+
+/// Message used for the RSA known-answer tests.
+pub const RSA_MSG: &[u8] = b"KeyMint self test";
+
+/// RSA-2048 private key for the RSA known-answer tests, as a DER-encoded PKCS#8 `PrivateKeyInfo`.
+/// Generated (along with the signature and ciphertext below) with the Python `cryptography`
+/// package.
+pub const RSA_KEY: &str = concat!(
+    "308204be020100300d06092a864886f70d0101010500048204a8308204a40201",
+    "000282010100bec44414f48eddd9399ef6d8c1298f9d7445a99542eebcd36455",
+    "8e4f7ba2063c8eb2c9044ca89c765803ff41131b29593638a9964e1a0cf0df24",
+    "5cf2097c4cb2896aa1a5363f2212790b267c3fd5f4e69b7fd697b3fdffe5825f",
+    "7dedff6061efb258b0f242d23553b094004e869943df54bcc78909e1c9482999",
+    "ea271eb5f63b08dd6d1c131ace0a1abf4325117d9af1e11d27b6af605a89c8eb",
+    "f684ce8084422de9dd69394e0cc66872e08ab32de71284b68754b02dbec837bb",
+    "4e106944733efdda288b9c2ddd9909b10e9c6e62439fd41f96a10729fbabd2da",
+    "8587f8ee75e103aa95ced56d5ac10ac5b81c5751b3f9d14669fdd0472dfd673e",
+    "0c12abc023af02030100010282010058c8d49ce22359ea1f30b6573433692551",
+    "77989120e926d5c5c531ea1932d3ef3c44c65cab66f006fe38c20461e58acd7b",
+    "f84c2969a9232bb9d6dd3142abe8b0515760eb18ffb21661e288235cbc71f220",
+    "1e13dc60078fa11c17e06eb3a40280c203183b70511043a693833f08d2ed2d54",
+    "6d55fa98304ef338756b6e909304892f74bfe5efc4ce54babea891ef2a73ef08",
+    "37bf97d7c90fe475f7f111284aa4f641b2aebf84bc3fcdab1d0552dbea407d55",
+    "ae007c734599f04e6ab3c0ab2a6f22f0e5339e5b92a070bb3f9316581c0d09e4",
+    "ac3c0c60323cfc45f866bf7e0061dc0de17f13f2b99786ad4d911232db9993de",
+    "5d920d8d96a1704b937bb660e946b502818100f4bd355283ac988a27796622a5",
+    "33a99bbc282f4a5ab6ae744533ff84a7a47c7645c8a80645cbd4ae53d2674f96",
+    "0bf2a6efb481b7fb9109e3b1ae76c8f029c1077e9716ac572a18ab6b76a2b53a",
+    "238ebef3a3ffa34c2ead12f3e422d385c2cda7bfc53763d014b18d366572fd75",
+    "d02a051af20246ea5664ba2c5da928a1103d8b02818100c78b506fa4f3d00609",
+    "64c7d5443ad7c3584500f433c9bfb600c5791f00666cd973a6d9e8c1d95c0342",
+    "171e32699461c6c89377b0cac4f0e752ec87d1bb6cd61193bf88bce93ed6db5d",
+    "a876c189e8c70873d3564ae3c1097153dadac6c622fe1397516cdbd5500c2ef3",
+    "52c444c36f7b591d443b81dfc1efa1c9260bcddb5ebeed02818100b28f054361",
+    "8c607293a04ff34102b3614b632a359e7fa04276671caae540ee09c09a4874e3",
+    "2176d78592820b9fd6b1c0382d43633f68af856f927159e357235d5b6fd27c04",
+    "d052d5667f54f0d22d352d7e470df14f58d2af7c3fdb64e6379da221193b7358",
+    "5270c42d86d0e1b9be199a45f181d5c98d24ca6c2a1d8be60f1a470281800c99",
+    "6548c75785a8fbeadb1b42259cfdfe92ccce9e7e7c96575220a5ae76c924a9c2",
+    "bd3b8f11d4577852d15b49834ef12834f777b835db281ffa435120ce6c6dcbb0",
+    "6a47d34a5e7297654daa46b4e5ec48b3ed5ce928851a1cbb52d8fe25b3793532",
+    "b4f6e46f1b75d37344653b19cce6018d0f5cd1ee02dd6a3ff6bad6c112b90281",
+    "810097dd818f86c26ce4afc9babfe89fac759e6c01c7ce9fc686e47b422621cb",
+    "03b6644872d718bfbe86f958cf09c43813dcd3e21572df3d850156f1ced7d58d",
+    "5feed91cb51bc3b82e9d7f140d12c31b37a5c55f0ef80dbf236aaef8b55d5ed5",
+    "39a98bb8744618a3c08d62e404b057b66ffb84de01cc96d88c71d720bc04a6a5",
+    "76e2",
+);
+
+/// PKCS#1 v1.5 signature over [`RSA_MSG`] with SHA-256, using [`RSA_KEY`].
+pub const RSA_SIG: &str = concat!(
+    "9279291d318289efa84eacc89ff163e23a7352fd988fefb01cdb55a1ed141db1",
+    "fefab5a6ba757861f846b106102e85b56ade5cae0bac03a5a6be23f20ccaba32",
+    "daccdc6dd6003c3a9836d35655a232f141e941b2dead288ca401a296edacdd0c",
+    "1cb15e0b83b548201c7d21c85bcf64adb011c5682e902649673475e0f8eecef9",
+    "66a77b99188a0d191a9d23b40165815c10276013015073a70651076a88cfc1f5",
+    "39fe419d5f01d0902f634b3ff84d5b529e105245c1f4d8274694f50f64eff50a",
+    "4f75be0bdc42bd1ebb6e50685a6f600daf8697faa4c49b91f434113206c197be",
+    "dbb1703afb389f8cb71883bcddcb0124bd07f6d62527b52ad5147b7a47e65aea",
+);
+
+/// RSA-OAEP encryption of [`RSA_MSG`] with SHA-256 (for both message and MGF1 digests), using the
+/// public key for [`RSA_KEY`].
+pub const RSA_OAEP_CT: &str = concat!(
+    "0dd91e3b953a55eaa78a5ebb1fc8f437fe138a641282caf2806ecab955ec1c79",
+    "b1a8edcd737563e25339253b7d411fa35285c5d38700d00fa93b19b917c98b0a",
+    "c4e920a452dab16733649740485d0dbf52c6fa8e63732a463c2390a4f5f9eda1",
+    "a7db2f699d8ecec519dd2b296ea531b7053d9e4afe4a5242dbfb7b443e9623f4",
+    "9e01ede3c2f4f4a1e8aca19f62a860907f76f760e3098bea388b7f5ef7e344dd",
+    "37edb407e5e78b330eaf700bfcb65bbbeefdcf1b85c389a40dd155af76703025",
+    "aef516864ebec87732157db30e27833661d84b2687efa7fc23b06b677926f3c3",
+    "3539ea1ed3f6ecba316b13eb7a0fefd2a1eeea66d384968cf921e980758b2c5e",
+);
+
+/// P-256 private key for the EC known-answer tests, as a DER-encoded PKCS#8 `PrivateKeyInfo`
+/// whose SEC1 `ECPrivateKey` omits the optional public key (so the implementation has to compute
+/// it).  Generated (along with the other EC vectors below) with the Python `cryptography` package.
+pub const EC_KEY: &str = concat!(
+    "304d020100301306072a8648ce3d020106082a8648ce3d030107043330310201",
+    "010420bf487a438d7987337962c9f0355f119c8bc2dc183ce1ac9024b87afe20",
+    "ae00a8a00a06082a8648ce3d030107",
+);
+
+/// Public key for [`EC_KEY`], as an uncompressed point.
+pub const EC_PUBLIC_KEY: &str = concat!(
+    "044e5389911464f450a771de340646715d74531690441d4d23e957d0a2b71e83",
+    "79cd3c5104b0bdb719ac6d5b28a2635a2b988bca484a5d922b6cca445eea15d0",
+    "b1",
+);
+
+/// ECDSA signature over [`RSA_MSG`] with SHA-256, using [`EC_KEY`], as a DER-encoded
+/// `ECDSA-Sig-Value`.
+pub const EC_SIG: &str = concat!(
+    "3044022100c9847e8151865e8fcd3d97504b5034ce8334e5b5e5dae0a08dbd68",
+    "462acba166021f77ffd69c769c132fdb2f3982e5c599cfcf70494cf6024bc7a5",
+    "1f4e3dc294b7",
+);
+
+/// Peer P-256 public key for the ECDH known-answer test, as a DER-encoded `SubjectPublicKeyInfo`.
+pub const EC_PEER_SPKI: &str = concat!(
+    "3059301306072a8648ce3d020106082a8648ce3d03010703420004a2b2bba7ac",
+    "2698548b1216f4578caba02e9760e655d3e3eb3709f8d9d5d4bb26b5d03b683c",
+    "1f949f6bf078ab6a28c9595d8fd193678e0990b9d59f0a639b0257",
+);
+
+/// ECDH shared secret between [`EC_KEY`] and [`EC_PEER_SPKI`].
+pub const EC_SHARED_SECRET: &str =
+    "2ef84185532aef732e77bd454235718b1a67d186e990e550dd5babff39e3599f";
+
+/// Run the full set of known-answer self tests against the given implementation.
+pub fn run(imp: &crypto::Implementation) -> Result<(), Error> {
+    info!("running crypto self tests");
+    aes_kat(imp)?;
+    aes_gcm_kat(imp)?;
+    des_kat(imp)?;
+    hmac_kat(imp)?;
+    ckdf_kat(imp)?;
+    hkdf_kat(imp)?;
+    sha256_kat(imp)?;
+    rsa_kat(imp)?;
+    ec_kat(imp)?;
+    Ok(())
+}
+
+fn aes_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in AES_TESTS {
+        let key = aes::Key::new(hex_decode(test.key)?)?;
+        let mut op = imp.aes.begin(key.clone().into(), test.mode, SymmetricOperation::Encrypt)?;
+        let mut ct = op.update(&hex_decode(test.msg)?)?;
+        ct.try_extend_from_slice(&op.finish()?)?;
+        check("AES encrypt", &ct, test.ct)?;
+
+        let mut op = imp.aes.begin(key.into(), test.mode, SymmetricOperation::Decrypt)?;
+        let mut pt = op.update(&ct)?;
+        pt.try_extend_from_slice(&op.finish()?)?;
+        check("AES decrypt", &pt, test.msg)?;
+    }
+    Ok(())
+}
+
+fn aes_gcm_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in AES_GCM_TESTS {
+        let key = aes::Key::new(hex_decode(test.key)?)?;
+        let nonce = hex_decode(test.iv)?
+            .try_into()
+            .map_err(|_e| km_err!(UnknownError, "unexpected AES-GCM nonce size"))?;
+        let aad = hex_decode(test.aad)?;
+        let mode = aes::GcmMode::GcmTag16 { nonce };
+
+        let mut op = imp.aes.begin_aead(key.clone().into(), mode, SymmetricOperation::Encrypt)?;
+        op.update_aad(&aad)?;
+        let mut ct = op.update(&hex_decode(test.msg)?)?;
+        ct.try_extend_from_slice(&op.finish()?)?;
+        let mut want = hex_decode(test.ct)?;
+        want.try_extend_from_slice(&hex_decode(test.tag)?)?;
+        check_bytes("AES-GCM encrypt", &ct, &want)?;
+
+        let mut op = imp.aes.begin_aead(key.into(), mode, SymmetricOperation::Decrypt)?;
+        op.update_aad(&aad)?;
+        let mut pt = op.update(&ct)?;
+        pt.try_extend_from_slice(&op.finish()?)?;
+        check("AES-GCM decrypt", &pt, test.msg)?;
+    }
+    Ok(())
+}
+
+fn des_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in DES_TESTS {
+        let key = des::Key::new(hex_decode(test.key)?)?;
+        let mode = des::Mode::EcbNoPadding;
+        let mut op = imp.des.begin(key.clone().into(), mode, SymmetricOperation::Encrypt)?;
+        let mut ct = op.update(&hex_decode(test.msg)?)?;
+        ct.try_extend_from_slice(&op.finish()?)?;
+        check("3DES encrypt", &ct, test.ct)?;
+
+        let mut op = imp.des.begin(key.into(), mode, SymmetricOperation::Decrypt)?;
+        let mut pt = op.update(&ct)?;
+        pt.try_extend_from_slice(&op.finish()?)?;
+        check("3DES decrypt", &pt, test.msg)?;
+    }
+    Ok(())
+}
+
+fn hmac_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in HMAC_TESTS {
+        let key = hmac::Key::new_from(test.key)?;
+        let mut op = imp.hmac.begin(key.into(), test.digest)?;
+        op.update(test.data)?;
+        let mut mac = op.finish()?;
+        mac.truncate(test.tag_size);
+        check("HMAC", &mac, &test.expected_mac[..(test.tag_size * 2)])?;
+    }
+    Ok(())
+}
+
+fn ckdf_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in CKDF_TESTS {
+        let key = aes::Key::new_from(test.key)?;
+        let out = imp.ckdf.ckdf(&key.into(), test.label, test.chunks, test.want.len() / 2)?;
+        check("CKDF", &out, test.want)?;
+    }
+    Ok(())
+}
+
+fn hkdf_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in HKDF_TESTS {
+        let ikm = hex_decode(test.ikm)?;
+        let salt = hex_decode(test.salt)?;
+        let info = hex_decode(test.info)?;
+        let out = imp.hkdf.hkdf(&salt, &ikm, &info, test.out_len)?;
+        check("HKDF", &out, test.want)?;
+    }
+    Ok(())
+}
+
+fn sha256_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    for test in SHA256_TESTS {
+        let digest = imp.sha256.hash(test.msg)?;
+        check("SHA-256", &digest, test.want)?;
+    }
+    Ok(())
+}
+
+fn rsa_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    let (KeyMaterial::Rsa(key), _, _) = imp.rsa.import_pkcs8_key(&hex_decode(RSA_KEY)?, &[])?
+    else {
+        return Err(km_err!(UnknownError, "unexpected key type for RSA self test"));
+    };
+
+    let mut op =
+        imp.rsa.begin_sign(key.clone(), rsa::SignMode::Pkcs1_1_5Padding(Digest::Sha256))?;
+    op.update(RSA_MSG)?;
+    check("RSA sign", &op.finish()?, RSA_SIG)?;
+
+    let mode =
+        rsa::DecryptionMode::OaepPadding { msg_digest: Digest::Sha256, mgf_digest: Digest::Sha256 };
+    let mut op = imp.rsa.begin_decrypt(key, mode)?;
+    op.update(&hex_decode(RSA_OAEP_CT)?)?;
+    check_bytes("RSA decrypt", &op.finish()?, RSA_MSG)
+}
+
+fn ec_kat(imp: &crypto::Implementation) -> Result<(), Error> {
+    let KeyMaterial::Ec(EcCurve::P256, _, key) =
+        imp.ec.import_pkcs8_key(&hex_decode(EC_KEY)?, &[])?
+    else {
+        return Err(km_err!(UnknownError, "unexpected key type for EC self test"));
+    };
+    check("EC public key", &imp.ec.subject_public_key(&key)?, EC_PUBLIC_KEY)?;
+
+    let mut op = imp.ec.begin_agree(key.clone())?;
+    op.update(&hex_decode(EC_PEER_SPKI)?)?;
+    check("ECDH", &op.finish()?, EC_SHARED_SECRET)?;
+
+    // ECDSA signatures are randomized, so check that both a known signature and a fresh signature
+    // verify against the known public key.
+    let mut op = imp.ec.begin_sign(key, Digest::Sha256)?;
+    op.update(RSA_MSG)?;
+    let sig = op.finish()?;
+    let public_key = hex_decode(EC_PUBLIC_KEY)?;
+    let verify = |sig: &[u8]| {
+        imp.ec.verify_nist_signature(ec::NistCurve::P256, &public_key, Digest::Sha256, RSA_MSG, sig)
+    };
+    match verify(&hex_decode(EC_SIG)?) {
+        Ok(true) => {}
+        Ok(false) => {
+            error!("ECDSA verify self test failed: known signature rejected");
+            return Err(km_err!(UnknownError, "ECDSA verify self test failed"));
+        }
+        Err(Error::Hal(ErrorCode::Unimplemented, _)) => {
+            warn!("EC implementation cannot verify signatures, skipping ECDSA self test");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    }
+    if !verify(&sig)? {
+        error!("ECDSA sign self test failed: signature does not verify");
+        return Err(km_err!(UnknownError, "ECDSA sign self test failed"));
+    }
+    Ok(())
+}
+
+/// Check that `got` matches the hex-encoded `want`.
+fn check(name: &str, got: &[u8], want: &str) -> Result<(), Error> {
+    check_bytes(name, got, &hex_decode(want)?)
+}
+
+/// Check that `got` matches `want`.
+fn check_bytes(name: &str, got: &[u8], want: &[u8]) -> Result<(), Error> {
+    if got != want {
+        error!("{} self test failed: output mismatch", name);
+        return Err(km_err!(UnknownError, "{} self test failed", name));
+    }
+    Ok(())
+}
+
+/// Decode a hex-encoded test vector.
+fn hex_decode(data: &str) -> Result<Vec<u8>, Error> {
+    let nibble = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        _ => Err(km_err!(UnknownError, "invalid hex character {} in test vector", c)),
+    };
+    let data = data.as_bytes();
+    if !data.len().is_multiple_of(2) {
+        return Err(km_err!(UnknownError, "odd length {} for hex test vector", data.len()));
+    }
+    let mut result = vec_try_with_capacity!(data.len() / 2)?;
+    for pair in data.chunks(2) {
+        result.try_push((nibble(pair[0])? << 4) | nibble(pair[1])?)?;
+    }
+    Ok(result)
+}
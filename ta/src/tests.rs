@@ -14,11 +14,15 @@
 
 //! Tests
 
-use crate::{error_rsp, invalid_cbor_rsp_data, keys::SecureKeyWrapper, split_rsp};
+use crate::audit::{self, AuditLog};
+use crate::gatekeeper::{self, FailureRecord, PasswordHandle};
+use crate::keyblob_cache::KeyblobCache;
+use crate::{error_rsp, invalid_cbor_rsp_data, keys::SecureKeyWrapper, split_rsp, KeyId};
 use alloc::{vec, vec::Vec};
 use der::{Decode, Encode};
-use kmr_common::Error;
+use kmr_common::{crypto, keyblob, Error};
 use kmr_wire::{
+    audit::AuditEvent,
     keymint::{
         ErrorCode, KeyFormat, KeyParam, KeyPurpose, NEXT_MESSAGE_SIGNAL_FALSE,
         NEXT_MESSAGE_SIGNAL_TRUE,
@@ -343,3 +347,141 @@ fn test_split_rsp_larger_input_imperfect_split() {
     let msg3 = &inner_msg3[1..];
     assert_eq!(msg3, rsp3);
 }
+
+#[test]
+fn test_password_handle_roundtrip() {
+    let handle = PasswordHandle {
+        secure_user_id: 0x0102030405060708,
+        flags: 0,
+        salt: 0x1112131415161718,
+        signature: [0xaa; gatekeeper::SIGNATURE_LEN],
+        hardware_backed: true,
+    };
+    let data = handle.to_vec().unwrap();
+    assert_eq!(data.len(), gatekeeper::HANDLE_LEN);
+    assert_eq!(data[0], gatekeeper::HANDLE_VERSION);
+    assert_eq!(&data[1..9], &[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+    assert_eq!(PasswordHandle::from_slice(&data).unwrap(), handle);
+
+    assert!(PasswordHandle::from_slice(&data[1..]).is_err());
+    let mut bad_version = data.clone();
+    bad_version[0] = 1;
+    assert!(PasswordHandle::from_slice(&bad_version).is_err());
+}
+
+#[test]
+fn test_failure_record_retry_timeout() {
+    let tests = [
+        (0, 0),
+        (1, 0),
+        (4, 0),
+        (5, 30_000),
+        (6, 0),
+        (10, 30_000),
+        (11, 30_000),
+        (29, 30_000),
+        (30, 30_000),
+        (40, 60_000),
+        (139, 30_000 << 10),
+        (140, 24 * 60 * 60 * 1000),
+        (u32::MAX, 24 * 60 * 60 * 1000),
+    ];
+    for (failure_counter, want) in tests {
+        let record = FailureRecord { failure_counter, ..Default::default() };
+        assert_eq!(record.retry_timeout(), want, "for failure count {failure_counter}");
+    }
+}
+
+/// Non-constant-time comparison, good enough for tests.
+struct TestEq;
+impl crypto::ConstTimeEq for TestEq {
+    fn eq(&self, left: &[u8], right: &[u8]) -> bool {
+        left == right
+    }
+}
+
+fn cache_entry(id: u8) -> (KeyId, keyblob::PlaintextKeyBlob) {
+    let keyblob = keyblob::PlaintextKeyBlob {
+        characteristics: vec![],
+        key_material: crypto::KeyMaterial::Hmac(crypto::hmac::Key::new(vec![id; 32]).into()),
+    };
+    (KeyId([id; 32]), keyblob)
+}
+
+#[test]
+fn test_keyblob_cache() {
+    let mut cache = KeyblobCache::new(2);
+    let (id1, kb1) = cache_entry(1);
+    let (id2, kb2) = cache_entry(2);
+    let (id3, kb3) = cache_entry(3);
+    let hidden = [0xaa; 32];
+    let slot = keyblob::SecureDeletionSlot(7);
+
+    cache.insert(id1.clone(), hidden, &kb1, None);
+    cache.insert(id2.clone(), hidden, &kb2, Some(slot));
+    assert_eq!(cache.get(&TestEq, &id1, &hidden), Some((kb1.clone(), None)));
+    assert_eq!(cache.get(&TestEq, &id2, &hidden), Some((kb2.clone(), Some(slot))));
+
+    // Different hidden parameters don't match.
+    assert_eq!(cache.get(&TestEq, &id1, &[0xbb; 32]), None);
+
+    // Inserting a third entry evicts the least recently used one.
+    assert!(cache.get(&TestEq, &id1, &hidden).is_some());
+    cache.insert(id3.clone(), hidden, &kb3, None);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&TestEq, &id2, &hidden).is_none());
+    assert!(cache.get(&TestEq, &id1, &hidden).is_some());
+    assert!(cache.get(&TestEq, &id3, &hidden).is_some());
+
+    // Re-inserting an existing key replaces it rather than using another slot.
+    cache.insert(id3.clone(), [0xcc; 32], &kb3, Some(slot));
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&TestEq, &id3, &hidden).is_none());
+    assert!(cache.get(&TestEq, &id3, &[0xcc; 32]).is_some());
+
+    cache.remove_slot(slot);
+    assert_eq!(cache.len(), 1);
+    cache.remove(&id1);
+    assert_eq!(cache.len(), 0);
+
+    cache.insert(id1.clone(), hidden, &kb1, None);
+    cache.clear();
+    assert_eq!(cache.len(), 0);
+
+    // A zero-sized cache holds nothing.
+    let mut cache = KeyblobCache::new(0);
+    assert!(!cache.enabled());
+    cache.insert(id1.clone(), hidden, &kb1, None);
+    assert!(cache.get(&TestEq, &id1, &hidden).is_none());
+}
+
+#[test]
+fn test_audit_log() {
+    let mut log = AuditLog::new(2);
+    let events = [AuditEvent::KeyGenerated, AuditEvent::KeyDeleted, AuditEvent::AllKeysDeleted];
+    for event in events {
+        let mut record = audit::record(event);
+        log.sequence(&mut record);
+        log.push(record);
+    }
+
+    // The oldest record was discarded to make room.
+    let (records, dropped) = log.drain().unwrap();
+    assert_eq!(dropped, 1);
+    let got: Vec<_> = records.iter().map(|r| (r.sequence, r.event)).collect();
+    assert_eq!(got, vec![(1, AuditEvent::KeyDeleted), (2, AuditEvent::AllKeysDeleted)]);
+
+    // Draining empties the log and resets the dropped count, but sequence numbers continue.
+    assert_eq!(log.drain().unwrap(), (vec![], 0));
+    let mut record = audit::record(AuditEvent::AuthFailed);
+    log.sequence(&mut record);
+    log.push(record);
+    let (records, dropped) = log.drain().unwrap();
+    assert_eq!(dropped, 0);
+    assert_eq!(records[0].sequence, 3);
+
+    // A zero-sized log holds nothing.
+    let mut log = AuditLog::new(0);
+    log.push(audit::record(AuditEvent::KeyGenerated));
+    assert_eq!(log.drain().unwrap(), (vec![], 1));
+}
@@ -0,0 +1,26 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Build script that sets `cfg(ossl350)` when building against OpenSSL 3.5 or later, which is
+//! needed for the (experimental) ML-DSA and ML-KEM implementations.  Soong builds against
+//! BoringSSL, which has no such support, and does not run this script.
+
+fn main() {
+    if let Ok(version) = std::env::var("DEP_OPENSSL_VERSION_NUMBER") {
+        let version = u64::from_str_radix(&version, 16).expect("OpenSSL version is not hex");
+        if version >= 0x3050_0000 {
+            println!("cargo:rustc-cfg=ossl350");
+        }
+    }
+}
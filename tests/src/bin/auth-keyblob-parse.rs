@@ -138,6 +138,9 @@ fn process(filename: &str, hex: bool) {
             }
         }
         Algorithm::Rsa => KeyMaterial::Rsa(rsa::Key(raw_key).into()),
+        Algorithm::MlDsa | Algorithm::MlKem => {
+            panic!("legacy keyblobs do not hold post-quantum keys")
+        }
     };
 
     // Test the `tag::extract_key_characteristics()` entrypoint by comparing what it
@@ -164,6 +167,7 @@ fn process(filename: &str, hex: bool) {
     }
     keygen_params.sort_by(tag::legacy::param_compare);
     let (extracted, _) = tag::extract_key_gen_characteristics(
+        &tag::KeyPolicy::default(),
         kmr_common::tag::SecureStorage::Unavailable,
         &keygen_params,
         SecurityLevel::Software,
@@ -0,0 +1,131 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utility program to decode and pretty-print captured HAL<->TA messages.
+//!
+//! Usage: `kmr_message_inspect [--hex] [--log] [--req|--rsp|--keyblob] [--split] [--redact] FILE...`
+//!
+//! - `--hex`: files hold a single hex-encoded message (rather than binary).
+//! - `--log`: files hold a traffic log of `SerializedChannel` messages, as described in
+//!   `kmr_tests::inspect`.
+//! - `--req`, `--rsp`, `--keyblob`: the type of message (otherwise auto-detected).
+//! - `--split`: responses were split into multiple messages by the TA, and need reassembly.
+//! - `--redact`: hide secret values such as key material and operation data.
+//!
+//! Each option applies to the files that follow it on the command line.
+
+use kmr_tests::inspect::{describe, parse_log, Direction, Kind, Reassembler};
+
+#[derive(Default)]
+struct Options {
+    hex: bool,
+    log: bool,
+    kind: Option<Kind>,
+    split: bool,
+    redact: bool,
+}
+
+fn main() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let mut opts = Options::default();
+    let args: Vec<String> = std::env::args().collect();
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--hex" => opts.hex = !opts.hex,
+            "--log" => opts.log = !opts.log,
+            "--req" => opts.kind = Some(Kind::Request),
+            "--rsp" => opts.kind = Some(Kind::Response),
+            "--keyblob" => opts.kind = Some(Kind::KeyBlob),
+            "--split" => opts.split = !opts.split,
+            "--redact" => opts.redact = !opts.redact,
+            _ => process(arg, &opts),
+        }
+    }
+}
+
+fn process(filename: &str, opts: &Options) {
+    println!("File: {}", filename);
+    let data: Vec<u8> = match std::fs::read(filename) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("{}: Failed to read: {:?}", filename, e);
+            return;
+        }
+    };
+    if opts.log {
+        process_log(filename, &data, opts);
+        return;
+    }
+    let data = if opts.hex {
+        let hexdata = String::from_utf8_lossy(&data);
+        match hex::decode(hexdata.trim()) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("{}: Failed to parse hex ({:?})", filename, e);
+                return;
+            }
+        }
+    } else {
+        data
+    };
+    let data = if opts.split {
+        // A single capture can only hold the final part of a split response.
+        match Reassembler::default().push(&data) {
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                eprintln!("{}: Incomplete split response", filename);
+                return;
+            }
+            Err(e) => {
+                eprintln!("{}: Failed to reassemble: {}", filename, e);
+                return;
+            }
+        }
+    } else {
+        data
+    };
+    show(filename, opts.kind, &data, opts.redact);
+}
+
+fn process_log(filename: &str, data: &[u8], opts: &Options) {
+    let records = match parse_log(&String::from_utf8_lossy(data)) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("{}: Failed to parse log: {}", filename, e);
+            return;
+        }
+    };
+    let mut reassembler = Reassembler::default();
+    for record in records {
+        match record.direction {
+            Direction::Request => show(filename, Some(Kind::Request), &record.data, opts.redact),
+            Direction::Response if opts.split => match reassembler.push(&record.data) {
+                Ok(Some(data)) => show(filename, Some(Kind::Response), &data, opts.redact),
+                Ok(None) => {}
+                Err(e) => eprintln!("{}: Failed to reassemble: {}", filename, e),
+            },
+            Direction::Response => show(filename, Some(Kind::Response), &record.data, opts.redact),
+        }
+    }
+    if reassembler.is_pending() {
+        eprintln!("{}: Incomplete split response at end of log", filename);
+    }
+}
+
+fn show(filename: &str, kind: Option<Kind>, data: &[u8], redact: bool) {
+    match describe(kind, data, redact) {
+        Ok(text) => println!("{}", text),
+        Err(e) => eprintln!("{}: Failed to decode {}: {}", filename, hex::encode(data), e),
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utility program to replay recorded HAL<->TA traffic against a fresh host TA, and report any
+//! responses that differ from those recorded.
+//!
+//! Usage: `kmr_replay [--seed N] FILE...`, where `--seed` sets the seed for the random number
+//! generator of the TA used for subsequent files (default 0).
+
+use kmr_tests::replay::{parse_recording, replay, replay_ta};
+
+fn main() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let mut seed = 0;
+    let mut failed = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            seed = match args.next().map(|s| s.parse()) {
+                Some(Ok(seed)) => seed,
+                _ => {
+                    eprintln!("--seed needs a numeric argument");
+                    std::process::exit(2);
+                }
+            };
+        } else {
+            failed |= !process(&arg, seed);
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Replay the recording in `filename`, returning whether all responses matched.
+fn process(filename: &str, seed: u64) -> bool {
+    println!("File: {}", filename);
+    let text = match std::fs::read_to_string(filename) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: Failed to read: {:?}", filename, e);
+            return false;
+        }
+    };
+    let exchanges = match parse_recording(&text) {
+        Ok(exchanges) => exchanges,
+        Err(e) => {
+            eprintln!("{}: Failed to parse recording: {}", filename, e);
+            return false;
+        }
+    };
+    let (mut ta, clock) = replay_ta(seed);
+    let differences = replay(&mut ta, &clock, &exchanges);
+    for difference in &differences {
+        println!("{}", difference);
+    }
+    println!("{}: {} exchanges, {} differences", filename, exchanges.len(), differences.len());
+    differences.is_empty()
+}
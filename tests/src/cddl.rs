@@ -0,0 +1,605 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of CBOR data against a CDDL schema (RFC 8610).
+//!
+//! Only the subset of CDDL that is emitted by `#[derive(AsCborValue)]` (and so appears in
+//! `common/generated.cddl` and `common/src/keyblob/keyblob.cddl`) is supported:
+//! - the prelude types `any`, `bool`, `true`, `false`, `nil`, `null`, `int`, `uint`, `nint`,
+//!   `float`, `bstr`, `bytes`, `tstr` and `text`
+//! - integer literals (decimal or `0x` hex)
+//! - arrays `[ ... ]` and maps `{ ... }` holding group entries, each with an optional occurrence
+//!   indicator (`?`, `*` or `+`) and an optional `name:` label (a text key, in a map), and with map
+//!   entries of the form `key => value`
+//! - type choices `a / b`, and choices from a group `&( ... )`
+//! - the `.size` control operator and tagged items `#6.N(type)`.
+//!
+//! The members of a `&( ... )` group that are of the form `name: value` also define `name` as a
+//! type that matches `value` (unless the schema has a rule of the same name), so that the
+//! `KeyMintOperation` values can be referred to by name.
+//!
+//! Vendors that encode KeyMint messages outside of Rust (e.g. in a C++ HAL) can use this module to
+//! check their encodings against the generated schema.
+
+use kmr_wire::cbor::value::Value;
+use std::collections::BTreeMap;
+
+/// Maximum depth of rule references that are followed without consuming any data.
+const MAX_DEPTH: usize = 64;
+
+/// Occurrence indicator for a group entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Occur {
+    One,
+    Optional,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+/// Entry in a group.
+#[derive(Clone, Debug)]
+struct Entry {
+    occur: Occur,
+    /// Key for a map entry (`key => value` or `name: value`), or the label for an array entry.
+    key: Option<Type>,
+    value: Type,
+}
+
+/// CDDL type.
+#[derive(Clone, Debug)]
+enum Type {
+    Any,
+    Bool,
+    Literal(Value),
+    Null,
+    Int,
+    Uint,
+    Nint,
+    Float,
+    Bstr,
+    Tstr,
+    Ref(String),
+    Choice(Vec<Type>),
+    Array(Vec<Entry>),
+    Map(Vec<Entry>),
+    Size(Box<Type>, u64),
+    Tagged(u64, Box<Type>),
+}
+
+/// Parsed CDDL schema.
+#[derive(Debug, Default)]
+pub struct Schema {
+    rules: BTreeMap<String, Type>,
+    /// Values named by `name: value` entries of `&( ... )` groups.
+    consts: BTreeMap<String, Type>,
+}
+
+impl Schema {
+    /// Parse the CDDL in `text`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0, consts: BTreeMap::new() };
+        let mut rules = BTreeMap::new();
+        while parser.pos < parser.tokens.len() {
+            let name = match parser.next() {
+                Some(Token::Ident(name)) => name,
+                tok => return Err(format!("expected rule name, found {:?}", tok)),
+            };
+            parser.expect(Token::Punct("="))?;
+            let typ = parser.typ()?;
+            if rules.insert(name.clone(), typ).is_some() {
+                return Err(format!("duplicate rule {}", name));
+            }
+        }
+        Ok(Self { rules, consts: parser.consts })
+    }
+
+    /// Return the names that are referred to by the schema but not defined in it.
+    pub fn undefined_refs(&self) -> Vec<String> {
+        let mut refs = Vec::new();
+        for typ in self.rules.values().chain(self.consts.values()) {
+            collect_refs(typ, &mut refs);
+        }
+        refs.retain(|name| self.lookup(name).is_none());
+        refs.sort();
+        refs.dedup();
+        refs
+    }
+
+    /// Check that the encoded CBOR in `data` matches the rule `name`.
+    pub fn validate_slice(&self, name: &str, data: &[u8]) -> Result<(), String> {
+        let value = kmr_wire::read_to_value(data).map_err(|e| format!("invalid CBOR: {:?}", e))?;
+        self.validate(name, &value)
+    }
+
+    /// Check that `value` matches the rule `name`.
+    pub fn validate(&self, name: &str, value: &Value) -> Result<(), String> {
+        let typ = Type::Ref(name.to_string());
+        self.check(&typ, value, &mut Vec::new(), 0).map_err(|m| {
+            let path: String = m.path.iter().map(|step| step.to_string()).collect();
+            format!("{}{}: {}", name, path, m.msg)
+        })
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Type> {
+        self.rules.get(name).or_else(|| self.consts.get(name))
+    }
+
+    /// Check `value` against `typ`, where `path` locates the value within the top-level item.
+    fn check(
+        &self,
+        typ: &Type,
+        value: &Value,
+        path: &mut Vec<Step>,
+        depth: usize,
+    ) -> Result<(), Mismatch> {
+        let fail = |path: &[Step], msg: String| Err(Mismatch { path: path.to_vec(), msg });
+        let ok = match (typ, value) {
+            (Type::Any, _) => true,
+            (Type::Bool, Value::Bool(_)) => true,
+            (Type::Null, Value::Null) => true,
+            (Type::Int, Value::Integer(_)) => true,
+            (Type::Uint, Value::Integer(i)) => i128::from(*i) >= 0,
+            (Type::Nint, Value::Integer(i)) => i128::from(*i) < 0,
+            (Type::Float, Value::Float(_)) => true,
+            (Type::Bstr, Value::Bytes(_)) => true,
+            (Type::Tstr, Value::Text(_)) => true,
+            (Type::Literal(want), got) => want == got,
+            (Type::Ref(name), _) => {
+                let Some(inner) = self.lookup(name) else {
+                    return fail(path, format!("undefined type {}", name));
+                };
+                if depth > MAX_DEPTH {
+                    return fail(path, format!("recursion limit reached at {}", name));
+                }
+                return self.check(inner, value, path, depth + 1);
+            }
+            (Type::Choice(choices), _) => {
+                let mut best: Option<Mismatch> = None;
+                for choice in choices {
+                    match self.check(choice, value, path, depth + 1) {
+                        Ok(()) => return Ok(()),
+                        Err(e) => best = Some(deepest(best, e)),
+                    }
+                }
+                return Err(best.unwrap_or_else(|| Mismatch {
+                    path: path.clone(),
+                    msg: "empty choice".to_string(),
+                }));
+            }
+            (Type::Array(entries), Value::Array(items)) => {
+                return self.check_group(entries, items, 0, path);
+            }
+            (Type::Map(entries), Value::Map(items)) => return self.check_map(entries, items, path),
+            (Type::Size(inner, size), _) => {
+                self.check(inner, value, path, depth + 1)?;
+                let len = match value {
+                    Value::Bytes(b) => b.len(),
+                    Value::Text(t) => t.len(),
+                    _ => return fail(path, ".size on non-string".to_string()),
+                };
+                if len as u64 != *size {
+                    return fail(path, format!("expected size {}, found {}", size, len));
+                }
+                true
+            }
+            (Type::Tagged(tag, inner), Value::Tag(got, item)) => {
+                if tag != got {
+                    return fail(path, format!("expected tag {}, found tag {}", tag, got));
+                }
+                return self.check(inner, item, path, 0);
+            }
+            _ => false,
+        };
+        if ok {
+            Ok(())
+        } else {
+            fail(path, format!("expected {}, found {}", describe_type(typ), describe_value(value)))
+        }
+    }
+
+    /// Check that the array `items` (from index `idx` onwards) matches the group `entries`.
+    fn check_group(
+        &self,
+        entries: &[Entry],
+        items: &[Value],
+        idx: usize,
+        path: &mut Vec<Step>,
+    ) -> Result<(), Mismatch> {
+        let Some((entry, rest)) = entries.split_first() else {
+            if items.is_empty() {
+                return Ok(());
+            }
+            path.push(Step::Index(idx));
+            let m = Mismatch { path: path.clone(), msg: "unexpected extra array item".to_string() };
+            path.pop();
+            return Err(m);
+        };
+        let (min, max) = match entry.occur {
+            Occur::One => (1, 1),
+            Occur::Optional => (0, 1),
+            Occur::ZeroOrMore => (0, usize::MAX),
+            Occur::OneOrMore => (1, usize::MAX),
+        };
+
+        // Find how many leading items match the entry, then try the rest of the group after
+        // consuming as many of them as possible.
+        let mut count = 0;
+        let mut best: Option<Mismatch> = None;
+        while count < max && count < items.len() {
+            path.push(Step::Index(idx + count));
+            let result = self.check(&entry.value, &items[count], path, 0);
+            path.pop();
+            match result {
+                Ok(()) => count += 1,
+                Err(e) => {
+                    best = Some(deepest(best, e));
+                    break;
+                }
+            }
+        }
+        if count < min {
+            return Err(best.unwrap_or_else(|| {
+                let mut path = path.clone();
+                path.push(Step::Index(idx + count));
+                Mismatch { path, msg: format!("missing {}", describe_type(&entry.value)) }
+            }));
+        }
+        for n in (min..=count).rev() {
+            match self.check_group(rest, &items[n..], idx + n, path) {
+                Ok(()) => return Ok(()),
+                Err(e) => best = Some(deepest(best, e)),
+            }
+        }
+        Err(best.expect("at least one attempt made"))
+    }
+
+    /// Check that the map `items` matches the group `entries`.  Each item is matched against the
+    /// first entry whose key it matches.
+    fn check_map(
+        &self,
+        entries: &[Entry],
+        items: &[(Value, Value)],
+        path: &mut Vec<Step>,
+    ) -> Result<(), Mismatch> {
+        let mut counts = vec![0usize; entries.len()];
+        for (key, value) in items {
+            let found = entries.iter().position(|entry| match &entry.key {
+                Some(typ) => self.check(typ, key, &mut Vec::new(), 0).is_ok(),
+                None => false,
+            });
+            let Some(idx) = found else {
+                return Err(Mismatch {
+                    path: path.clone(),
+                    msg: format!("unexpected map key {}", describe_value(key)),
+                });
+            };
+            counts[idx] += 1;
+            path.push(Step::Key(describe_value(key)));
+            let result = self.check(&entries[idx].value, value, path, 0);
+            path.pop();
+            result?;
+        }
+        for (entry, count) in entries.iter().zip(counts) {
+            let ok = match entry.occur {
+                Occur::One => count == 1,
+                Occur::Optional => count <= 1,
+                Occur::ZeroOrMore => true,
+                Occur::OneOrMore => count >= 1,
+            };
+            if !ok {
+                let key = entry.key.as_ref().map(describe_type).unwrap_or_default();
+                return Err(Mismatch {
+                    path: path.clone(),
+                    msg: format!("map key {} occurs {} times", key, count),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Step in the path to a value within a CBOR item.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    Index(usize),
+    Key(String),
+}
+
+impl core::fmt::Display for Step {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Step::Index(idx) => write!(f, "[{}]", idx),
+            Step::Key(key) => write!(f, "{{{}}}", key),
+        }
+    }
+}
+
+/// Description of why a value does not match a type.
+#[derive(Debug)]
+struct Mismatch {
+    path: Vec<Step>,
+    msg: String,
+}
+
+/// Return whichever of two mismatches got further into the data, which is most likely to be the
+/// informative one when several alternatives fail.
+fn deepest(current: Option<Mismatch>, new: Mismatch) -> Mismatch {
+    match current {
+        Some(current) if (current.path.len(), &current.path) >= (new.path.len(), &new.path) => {
+            current
+        }
+        _ => new,
+    }
+}
+
+fn collect_refs(typ: &Type, refs: &mut Vec<String>) {
+    match typ {
+        Type::Ref(name) => refs.push(name.clone()),
+        Type::Choice(choices) => choices.iter().for_each(|t| collect_refs(t, refs)),
+        Type::Array(entries) | Type::Map(entries) => {
+            for entry in entries {
+                if let Some(key) = &entry.key {
+                    collect_refs(key, refs);
+                }
+                collect_refs(&entry.value, refs);
+            }
+        }
+        Type::Size(inner, _) | Type::Tagged(_, inner) => collect_refs(inner, refs),
+        _ => {}
+    }
+}
+
+fn describe_type(typ: &Type) -> String {
+    match typ {
+        Type::Any => "any".to_string(),
+        Type::Bool => "bool".to_string(),
+        Type::Literal(v) => describe_value(v),
+        Type::Null => "nil".to_string(),
+        Type::Int => "int".to_string(),
+        Type::Uint => "uint".to_string(),
+        Type::Nint => "nint".to_string(),
+        Type::Float => "float".to_string(),
+        Type::Bstr => "bstr".to_string(),
+        Type::Tstr => "tstr".to_string(),
+        Type::Ref(name) => name.clone(),
+        Type::Choice(_) => "choice".to_string(),
+        Type::Array(_) => "array".to_string(),
+        Type::Map(_) => "map".to_string(),
+        Type::Size(inner, size) => format!("{} .size {}", describe_type(inner), size),
+        Type::Tagged(tag, inner) => format!("#6.{}({})", tag, describe_type(inner)),
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i128::from(*i).to_string(),
+        Value::Bytes(b) => format!("bstr of length {}", b.len()),
+        Value::Float(f) => f.to_string(),
+        Value::Text(t) => format!("{:?}", t),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "nil".to_string(),
+        Value::Tag(tag, _) => format!("tag {}", tag),
+        Value::Array(a) => format!("array of length {}", a.len()),
+        Value::Map(m) => format!("map of length {}", m.len()),
+        _ => "unknown item".to_string(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    /// Tagged item `#6.N`.
+    Tag(u64),
+    /// Control operator, e.g. `.size`.
+    Control(String),
+    Punct(&'static str),
+}
+
+const PUNCTS: &[&str] =
+    &["=>", "=", "[", "]", "{", "}", "(", ")", ",", ":", "/", "?", "*", "+", "&"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.split(';').next().unwrap_or_default();
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            let err = || format!("line {}: unexpected input at {:?}", line_idx + 1, rest);
+            let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+            let (token, len) = if let Some(p) = PUNCTS.iter().find(|p| rest.starts_with(**p)) {
+                (Token::Punct(p), p.len())
+            } else if let Some(tail) = rest.strip_prefix("#6.") {
+                let len = tail.find(|c: char| !c.is_ascii_digit()).unwrap_or(tail.len());
+                (Token::Tag(tail[..len].parse().map_err(|_| err())?), 3 + len)
+            } else if let Some(tail) = rest.strip_prefix('.') {
+                let len = tail.find(|c: char| !is_ident_char(c)).unwrap_or(tail.len());
+                (Token::Control(tail[..len].to_string()), 1 + len)
+            } else if rest.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+                let len = 1 + rest[1..]
+                    .find(|c: char| !c.is_ascii_hexdigit() && c != 'x')
+                    .unwrap_or(rest.len() - 1);
+                (Token::Int(parse_int(&rest[..len]).ok_or_else(err)?), len)
+            } else if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                let len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+                (Token::Ident(rest[..len].to_string()), len)
+            } else {
+                return Err(err());
+            };
+            tokens.push(token);
+            rest = rest[len..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_int(text: &str) -> Option<i128> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    consts: BTreeMap<String, Type>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, tok: Token) -> Result<(), String> {
+        match self.next() {
+            Some(got) if got == tok => Ok(()),
+            got => Err(format!("expected {:?}, found {:?}", tok, got)),
+        }
+    }
+
+    /// Parse a type, which may be a choice between types.
+    fn typ(&mut self) -> Result<Type, String> {
+        let mut choices = vec![self.type1()?];
+        while self.eat("/") {
+            choices.push(self.type1()?);
+        }
+        Ok(if choices.len() == 1 { choices.remove(0) } else { Type::Choice(choices) })
+    }
+
+    /// Parse a type with an optional control operator.
+    fn type1(&mut self) -> Result<Type, String> {
+        let typ = self.type2()?;
+        match self.peek() {
+            Some(Token::Control(op)) if op == "size" => {
+                self.pos += 1;
+                match self.next() {
+                    Some(Token::Int(size)) if size >= 0 => {
+                        Ok(Type::Size(Box::new(typ), size as u64))
+                    }
+                    tok => Err(format!("expected size, found {:?}", tok)),
+                }
+            }
+            Some(Token::Control(op)) => Err(format!("unsupported control operator .{}", op)),
+            _ => Ok(typ),
+        }
+    }
+
+    fn type2(&mut self) -> Result<Type, String> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "any" => Type::Any,
+                "bool" => Type::Bool,
+                "true" => Type::Literal(Value::Bool(true)),
+                "false" => Type::Literal(Value::Bool(false)),
+                "nil" | "null" => Type::Null,
+                "int" => Type::Int,
+                "uint" => Type::Uint,
+                "nint" => Type::Nint,
+                "float" => Type::Float,
+                "bstr" | "bytes" => Type::Bstr,
+                "tstr" | "text" => Type::Tstr,
+                _ => Type::Ref(name),
+            }),
+            Some(Token::Int(i)) => {
+                let i = kmr_wire::cbor::value::Integer::try_from(i)
+                    .map_err(|_| format!("integer {} out of range", i))?;
+                Ok(Type::Literal(Value::Integer(i)))
+            }
+            Some(Token::Punct("[")) => Ok(Type::Array(self.group("]")?)),
+            Some(Token::Punct("{")) => Ok(Type::Map(self.group("}")?)),
+            Some(Token::Punct("(")) => {
+                let typ = self.typ()?;
+                self.expect(Token::Punct(")"))?;
+                Ok(typ)
+            }
+            Some(Token::Punct("&")) => {
+                self.expect(Token::Punct("("))?;
+                let entries = self.group(")")?;
+                let mut choices = Vec::new();
+                for entry in entries {
+                    if let Some(Type::Literal(Value::Text(name))) = entry.key {
+                        self.consts.entry(name).or_insert_with(|| entry.value.clone());
+                    }
+                    choices.push(entry.value);
+                }
+                Ok(Type::Choice(choices))
+            }
+            Some(Token::Tag(tag)) => {
+                self.expect(Token::Punct("("))?;
+                let typ = self.typ()?;
+                self.expect(Token::Punct(")"))?;
+                Ok(Type::Tagged(tag, Box::new(typ)))
+            }
+            tok => Err(format!("expected type, found {:?}", tok)),
+        }
+    }
+
+    /// Parse the entries of a group, up to and including the `close` punctuation.
+    fn group(&mut self, close: &'static str) -> Result<Vec<Entry>, String> {
+        let mut entries = Vec::new();
+        while !self.eat(close) {
+            let occur = if self.eat("?") {
+                Occur::Optional
+            } else if self.eat("*") {
+                Occur::ZeroOrMore
+            } else if self.eat("+") {
+                Occur::OneOrMore
+            } else {
+                Occur::One
+            };
+            let label = match (self.peek(), self.tokens.get(self.pos + 1)) {
+                (Some(Token::Ident(name)), Some(Token::Punct(":"))) => {
+                    let name = name.clone();
+                    self.pos += 2;
+                    Some(Type::Literal(Value::Text(name)))
+                }
+                _ => None,
+            };
+            let mut value = self.typ()?;
+            let mut key = label;
+            if key.is_none() && self.eat("=>") {
+                key = Some(value);
+                value = self.typ()?;
+            }
+            entries.push(Entry { occur, key, value });
+            if !self.eat(",") {
+                self.expect(Token::Punct(close))?;
+                break;
+            }
+        }
+        Ok(entries)
+    }
+}
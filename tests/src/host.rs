@@ -0,0 +1,649 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side harness for exercising TA functionality end to end, using BoringSSL-based crypto and
+//! software-only device implementations.  Not suitable for anything other than testing.
+
+use crate::opaque::{self, SeKeys, SecureElement};
+use kmr_common::crypto::{
+    self, aes, ec, hmac, AccumulatingOperation, Aes, AesCmac, MillisecondsSinceEpoch,
+    MonotonicClock, OpaqueOr, Sha256, SymmetricOperation,
+};
+use kmr_common::{keyblob::sdd_mem::InMemorySlotManager, Error};
+use kmr_crypto_boring::{
+    aes::BoringAes, des::BoringDes, ec::BoringEc, eq::BoringEq, hmac::BoringHmac, rng::BoringRng,
+    rsa::BoringRsa, sha256::BoringSha256,
+};
+use kmr_ta::{device, HardwareInfo, KeyMintTa, RpcInfo, RpcInfoV3};
+use kmr_wire::{
+    cbor::value::Value,
+    coset::{AsCborValue as _, CborSerializable},
+    keymint::{
+        Algorithm, Digest, EcCurve, ErrorCode, HardwareAuthToken, KeyCreationResult, KeyFormat,
+        KeyParam, KeyPurpose, SecurityLevel,
+    },
+    rpc,
+    sharedsecret::SharedSecretParameters,
+    AsCborValue, BeginRequest, ComputeSharedSecretRequest, FinishRequest, GenerateKeyRequest,
+    GetSharedSecretParametersRequest, ImportKeyRequest, InternalBeginResult, KeySizeInBits,
+    PerformOpReq, PerformOpResponse, PerformOpRsp, UpdateRequest,
+};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use x509_cert::der::{Decode, Encode};
+
+/// Fixed key agreement key shared by all TAs built by this harness.
+const TEST_KAK: [u8; 32] = [0x4b; 32];
+
+/// Fixed root key for keyblob encryption.
+const TEST_ROOT_KEK: [u8; 32] = [0x52; 32];
+
+/// Fixed hardware-backed key from which IRPC secrets are derived.
+const TEST_HBK: [u8; 32] = [0x48; 32];
+
+/// Fixed Ed25519 private key used as the DICE CDI_Leaf key.
+const TEST_CDI_KEY: [u8; 32] = [0x43; 32];
+
+/// Patchlevel used for the boot and HAL information.
+const TEST_PATCHLEVEL: u32 = 20260101;
+
+/// AES-CMAC (RFC 4493) implementation built on top of an arbitrary [`Aes`] implementation, for use
+/// where no native AES-CMAC implementation is available.
+pub struct SoftAesCmac(pub Arc<dyn Aes + Sync>);
+
+impl AesCmac for SoftAesCmac {
+    fn begin(&self, key: OpaqueOr<aes::Key>) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        Ok(Box::new(SoftAesCmacOperation { aes: self.0.clone(), key, data: Vec::new() }))
+    }
+}
+
+/// In-progress [`SoftAesCmac`] operation.
+struct SoftAesCmacOperation {
+    aes: Arc<dyn Aes + Sync>,
+    key: OpaqueOr<aes::Key>,
+    data: Vec<u8>,
+}
+
+impl SoftAesCmacOperation {
+    /// Encrypt `data` with AES-CBC under a zero IV, returning the final block.
+    fn cbc_mac(&self, data: &[u8]) -> Result<[u8; aes::BLOCK_SIZE], Error> {
+        let mut op = self.aes.begin(
+            self.key.clone(),
+            aes::CipherMode::CbcNoPadding { nonce: [0; aes::BLOCK_SIZE] },
+            SymmetricOperation::Encrypt,
+        )?;
+        let mut output = op.update(data)?;
+        output.extend_from_slice(&op.finish()?);
+        // Input is always a non-zero number of complete blocks, so is the output.
+        let last = output.len() - aes::BLOCK_SIZE;
+        let mut block = [0u8; aes::BLOCK_SIZE];
+        block.copy_from_slice(&output[last..]);
+        Ok(block)
+    }
+}
+
+/// Multiply a block by x in GF(2^128), as used for CMAC subkey generation.
+fn cmac_double(block: &[u8; aes::BLOCK_SIZE]) -> [u8; aes::BLOCK_SIZE] {
+    let value = u128::from_be_bytes(*block);
+    let doubled = (value << 1) ^ if value >> 127 == 1 { 0x87 } else { 0 };
+    doubled.to_be_bytes()
+}
+
+impl AccumulatingOperation for SoftAesCmacOperation {
+    fn update(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.data.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<Vec<u8>, Error> {
+        let k1 = cmac_double(&self.cbc_mac(&[0; aes::BLOCK_SIZE])?);
+        let k2 = cmac_double(&k1);
+
+        let mut data = self.data.clone();
+        let complete = !data.is_empty() && data.len().is_multiple_of(aes::BLOCK_SIZE);
+        let subkey = if complete {
+            k1
+        } else {
+            data.push(0x80);
+            data.resize(data.len().div_ceil(aes::BLOCK_SIZE) * aes::BLOCK_SIZE, 0);
+            k2
+        };
+        let last = data.len() - aes::BLOCK_SIZE;
+        for (b, k) in data[last..].iter_mut().zip(subkey.iter()) {
+            *b ^= k;
+        }
+        Ok(self.cbc_mac(&data)?.to_vec())
+    }
+}
+
+/// Controllable monotonic clock, whose clones all share the same notion of the current time.
+#[derive(Clone)]
+pub struct TestClock {
+    now: Arc<AtomicI64>,
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self { now: Arc::new(AtomicI64::new(1_000_000)) }
+    }
+}
+
+impl TestClock {
+    /// Move the clock forward by `ms` milliseconds.
+    pub fn advance(&self, ms: i64) {
+        self.now.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl MonotonicClock for TestClock {
+    fn now(&self) -> MillisecondsSinceEpoch {
+        MillisecondsSinceEpoch(self.now.load(Ordering::SeqCst))
+    }
+}
+
+/// Scripted behaviour of the user when a [`SoftPresence`] waits for trusted user presence.
+#[derive(Clone, Copy, Debug)]
+pub enum PresenceAction {
+    /// The user presses the button `delay_ms` after the wait starts.  Delays that are negative
+    /// or beyond the timeout simulate a misbehaving presence driver.
+    Press {
+        /// Delay after the start of the wait.
+        delay_ms: i64,
+    },
+    /// The user does nothing.
+    Ignore,
+}
+
+/// Scriptable trusted user presence implementation, which simulates blocking waits by advancing
+/// a [`TestClock`].  Clones share the same script and state.
+#[derive(Clone)]
+pub struct SoftPresence {
+    clock: TestClock,
+    state: Arc<Mutex<SoftPresenceState>>,
+}
+
+#[derive(Default)]
+struct SoftPresenceState {
+    /// Actions to take for successive waits.
+    script: VecDeque<PresenceAction>,
+    /// Start time and timeout of the outstanding wait, if any.
+    wait: Option<(i64, u32)>,
+    /// Number of waits that have been cancelled.
+    cancelled: usize,
+}
+
+impl SoftPresence {
+    /// Create a presence implementation that uses the given clock.
+    pub fn new(clock: TestClock) -> Self {
+        Self { clock, state: Arc::new(Mutex::new(SoftPresenceState::default())) }
+    }
+
+    /// Add an action to the end of the script.
+    pub fn push(&self, action: PresenceAction) {
+        self.state.lock().unwrap().script.push_back(action);
+    }
+
+    /// Return the number of waits that have been cancelled.
+    pub fn cancelled(&self) -> usize {
+        self.state.lock().unwrap().cancelled
+    }
+}
+
+impl device::TrustedUserPresence for SoftPresence {
+    fn available(&self) -> bool {
+        true
+    }
+
+    fn start_wait(&mut self, timeout_ms: u32) -> Result<(), Error> {
+        self.state.lock().unwrap().wait = Some((self.clock.now().0, timeout_ms));
+        Ok(())
+    }
+
+    fn finish_wait(&mut self) -> device::PresenceEvent {
+        let mut state = self.state.lock().unwrap();
+        let (started, timeout_ms) = match state.wait.take() {
+            Some(wait) => wait,
+            None => return device::PresenceEvent::TimedOut,
+        };
+        let now = self.clock.now().0;
+        match state.script.pop_front().unwrap_or(PresenceAction::Ignore) {
+            PresenceAction::Press { delay_ms } => {
+                let at = started + delay_ms;
+                self.clock.advance((at - now).max(0));
+                device::PresenceEvent::Detected(Some(MillisecondsSinceEpoch(at)))
+            }
+            PresenceAction::Ignore => {
+                self.clock.advance((started + timeout_ms as i64 - now).max(0));
+                device::PresenceEvent::TimedOut
+            }
+        }
+    }
+
+    fn cancel_wait(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.wait = None;
+        state.cancelled += 1;
+    }
+}
+
+/// Device key material that is fixed and held in memory.
+pub struct TestKeys;
+
+impl device::RetrieveKeyMaterial for TestKeys {
+    fn root_kek(&self, _context: &[u8]) -> Result<OpaqueOr<hmac::Key>, Error> {
+        Ok(hmac::Key::new(TEST_ROOT_KEK.to_vec()).into())
+    }
+
+    fn kak(&self) -> Result<OpaqueOr<aes::Key>, Error> {
+        Ok(aes::Key::Aes256(TEST_KAK).into())
+    }
+}
+
+/// IRPC artifacts built from fixed keys.  The DICE chain holds only the public key of the CDI_Leaf
+/// key, and there are no UDS certificates.
+pub struct TestRpcArtifacts;
+
+impl TestRpcArtifacts {
+    fn cdi_key() -> ec::Key {
+        ec::Key::Ed25519(ec::Ed25519Key(TEST_CDI_KEY))
+    }
+}
+
+impl device::RetrieveRpcArtifacts for TestRpcArtifacts {
+    fn derive_bytes_from_hbk(
+        &self,
+        hkdf: &dyn crypto::Hkdf,
+        context: &[u8],
+        output_len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        hkdf.hkdf(&[], &TEST_HBK, context, output_len)
+    }
+
+    fn get_dice_info(&self, _test_mode: rpc::TestMode) -> Result<device::DiceInfo, Error> {
+        let cdi_pub = OpaqueOr::Explicit(Self::cdi_key()).public_cose_key(
+            &BoringEc::default(),
+            EcCurve::Curve25519,
+            crypto::CurveType::EdDsa,
+            ec::CoseKeyPurpose::Sign,
+            None,
+            rpc::TestMode(false),
+        )?;
+        let cdi_pub = cdi_pub.to_cbor_value().map_err(kmr_wire::CborError::from)?;
+        let dice_cert_chain =
+            Value::Array(vec![cdi_pub]).to_vec().map_err(kmr_wire::CborError::from)?;
+        Ok(device::DiceInfo {
+            pub_dice_artifacts: device::PubDiceArtifacts {
+                uds_certs: vec![0xa0], // empty map
+                dice_cert_chain,
+            },
+            signing_algorithm: device::CsrSigningAlgorithm::EdDSA,
+            rpc_v2_test_cdi_priv: None,
+        })
+    }
+
+    fn sign_data(
+        &self,
+        ec: &dyn crypto::Ec,
+        data: &[u8],
+        _rpc_v2: Option<device::RpcV2Req>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut op = ec.begin_sign(Self::cdi_key().into(), Digest::None)?;
+        op.update(data)?;
+        op.finish()
+    }
+}
+
+/// Build a BoringSSL-based [`crypto::Implementation`] that uses the given clock.
+pub fn crypto_impl(clock: Option<TestClock>) -> crypto::Implementation {
+    crypto::Implementation {
+        rng: Box::new(BoringRng),
+        clock: clock.map(|c| Box::new(c) as Box<dyn MonotonicClock>),
+        compare: Box::new(BoringEq),
+        aes: Box::new(BoringAes),
+        des: Box::new(BoringDes),
+        hmac: Box::new(BoringHmac),
+        rsa: Box::<BoringRsa>::default(),
+        ec: Box::<BoringEc>::default(),
+        #[cfg(all(feature = "pqc", ossl350))]
+        mldsa: Box::<kmr_crypto_boring::mldsa::BoringMlDsa>::default(),
+        #[cfg(all(feature = "pqc", not(ossl350)))]
+        mldsa: Box::new(crypto::NoOpMlDsa),
+        #[cfg(all(feature = "pqc", ossl350))]
+        mlkem: Box::<kmr_crypto_boring::mlkem::BoringMlKem>::default(),
+        #[cfg(all(feature = "pqc", not(ossl350)))]
+        mlkem: Box::new(crypto::NoOpMlKem),
+        ckdf: Box::new(SoftAesCmac(Arc::new(BoringAes))),
+        hkdf: Box::new(BoringHmac),
+        sha256: Box::new(BoringSha256),
+    }
+}
+
+/// Build a software-only [`device::Implementation`] with the minimum of functionality.
+pub fn device_impl() -> device::Implementation {
+    device::Implementation {
+        keys: Box::new(TestKeys),
+        sign_info: None,
+        attest_ids: None,
+        sdd_mgr: None,
+        bootloader: Box::new(device::BootloaderDone),
+        sk_wrapper: None,
+        tup: Box::new(device::TrustedPresenceUnsupported),
+        legacy_key: None,
+        rpc: Box::new(device::NoOpRetrieveRpcArtifacts),
+        policy: kmr_common::tag::KeyPolicy::default(),
+        audit: None,
+    }
+}
+
+/// Build a [`device::Implementation`] as for [`device_impl`], with in-memory secure deletion
+/// storage.
+pub fn sdd_device_impl() -> device::Implementation {
+    device::Implementation {
+        sdd_mgr: Some(Box::<InMemorySlotManager<10>>::default()),
+        ..device_impl()
+    }
+}
+
+/// Build a [`crypto::Implementation`] that uses the given clock, and whose keys are all held
+/// in `se` as opaque handles.
+pub fn opaque_crypto_impl(
+    se: &Arc<SecureElement>,
+    clock: Option<TestClock>,
+) -> crypto::Implementation {
+    opaque::crypto_impl(se, crypto_impl(clock))
+}
+
+/// Build a software-only [`device::Implementation`] whose root KEK and key agreement key are held
+/// in `se` as opaque handles.
+pub fn opaque_device_impl(se: &SecureElement) -> device::Implementation {
+    let keys = SeKeys::new(se, hmac::Key::new(TEST_ROOT_KEK.to_vec()), aes::Key::Aes256(TEST_KAK))
+        .expect("failed to store device keys");
+    device::Implementation { keys: Box::new(keys), ..device_impl() }
+}
+
+/// Build a [`KeyMintTa`] from the given implementations, and populate its boot and HAL
+/// information so that it is ready for use.
+pub fn keymint_ta(imp: crypto::Implementation, dev: device::Implementation) -> KeyMintTa {
+    keymint_ta_at_patchlevel(imp, dev, TEST_PATCHLEVEL)
+}
+
+/// Build a [`KeyMintTa`] as for [`keymint_ta`], but with boot and HAL information that holds the
+/// given `patchlevel` (in YYYYMMDD form).  Keys created by a TA at an earlier patchlevel need to
+/// be upgraded before use.
+pub fn keymint_ta_at_patchlevel(
+    imp: crypto::Implementation,
+    dev: device::Implementation,
+    patchlevel: u32,
+) -> KeyMintTa {
+    let hw_info = HardwareInfo {
+        security_level: SecurityLevel::TrustedEnvironment,
+        version_number: 300,
+        impl_name: "host-test",
+        author_name: "Google",
+        unique_id: "host test KeyMint TA",
+    };
+    let rpc_info = RpcInfo::V3(RpcInfoV3 {
+        author_name: "Google",
+        unique_id: "host test KeyMint TA",
+        fused: false,
+        supported_num_of_keys_in_csr: kmr_wire::rpc::MINIMUM_SUPPORTED_KEYS_IN_CSR,
+    });
+    let mut ta = KeyMintTa::new(hw_info, rpc_info, imp, dev);
+    ta.set_boot_info(kmr_wire::keymint::BootInfo {
+        verified_boot_key: vec![0; 32],
+        device_boot_locked: false,
+        verified_boot_state: kmr_wire::keymint::VerifiedBootState::Unverified,
+        verified_boot_hash: vec![0; 32],
+        boot_patchlevel: patchlevel,
+    })
+    .expect("failed to set boot info");
+    ta.set_hal_info(kmr_ta::HalInfo {
+        os_version: 160000,
+        os_patchlevel: patchlevel / 100,
+        vendor_patchlevel: patchlevel,
+    });
+    ta
+}
+
+/// Send a request to the TA in serialized form, and return the deserialized response.
+pub fn exec(ta: &mut KeyMintTa, req: PerformOpReq) -> Result<PerformOpRsp, ErrorCode> {
+    let req_data = req.into_vec().expect("failed to serialize request");
+    let rsp_data = ta.process(&req_data);
+    let rsp = PerformOpResponse::from_slice(&rsp_data).expect("failed to parse response");
+    match (rsp.error_code, rsp.rsp) {
+        (0, Some(rsp)) => Ok(rsp),
+        (code, _) => Err(ErrorCode::try_from(code).unwrap_or(ErrorCode::UnknownError)),
+    }
+}
+
+/// Retrieve the local shared secret parameters from a [`KeyMintTa`].
+pub fn get_shared_secret_params(ta: &mut KeyMintTa) -> SharedSecretParameters {
+    match exec(
+        ta,
+        PerformOpReq::SharedSecretGetSharedSecretParameters(GetSharedSecretParametersRequest {}),
+    ) {
+        Ok(PerformOpRsp::SharedSecretGetSharedSecretParameters(rsp)) => rsp.ret,
+        Ok(_) => panic!("unexpected response"),
+        Err(e) => panic!("failed to get shared secret parameters: {e:?}"),
+    }
+}
+
+/// Complete shared secret negotiation for a [`KeyMintTa`], returning the key check value.
+pub fn compute_shared_secret(ta: &mut KeyMintTa, params: &[SharedSecretParameters]) -> Vec<u8> {
+    match exec(
+        ta,
+        PerformOpReq::SharedSecretComputeSharedSecret(ComputeSharedSecretRequest {
+            params: params.to_vec(),
+        }),
+    ) {
+        Ok(PerformOpRsp::SharedSecretComputeSharedSecret(rsp)) => rsp.ret,
+        Ok(_) => panic!("unexpected response"),
+        Err(e) => panic!("failed to compute shared secret: {e:?}"),
+    }
+}
+
+/// Generate a key on a [`KeyMintTa`].
+pub fn generate_key(
+    ta: &mut KeyMintTa,
+    key_params: Vec<KeyParam>,
+) -> Result<KeyCreationResult, ErrorCode> {
+    match exec(
+        ta,
+        PerformOpReq::DeviceGenerateKey(GenerateKeyRequest { key_params, attestation_key: None }),
+    )? {
+        PerformOpRsp::DeviceGenerateKey(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Import a key into a [`KeyMintTa`].
+pub fn import_key(
+    ta: &mut KeyMintTa,
+    key_params: Vec<KeyParam>,
+    key_format: KeyFormat,
+    key_data: Vec<u8>,
+) -> Result<KeyCreationResult, ErrorCode> {
+    match exec(
+        ta,
+        PerformOpReq::DeviceImportKey(ImportKeyRequest {
+            key_params,
+            key_format,
+            key_data,
+            attestation_key: None,
+        }),
+    )? {
+        PerformOpRsp::DeviceImportKey(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Begin an operation with the given key.
+pub fn begin(
+    ta: &mut KeyMintTa,
+    purpose: KeyPurpose,
+    key_blob: &[u8],
+    params: Vec<KeyParam>,
+) -> Result<InternalBeginResult, ErrorCode> {
+    begin_with_auth(ta, purpose, key_blob, params, None)
+}
+
+/// Begin an operation with the given key, providing an auth token.
+pub fn begin_with_auth(
+    ta: &mut KeyMintTa,
+    purpose: KeyPurpose,
+    key_blob: &[u8],
+    params: Vec<KeyParam>,
+    auth_token: Option<HardwareAuthToken>,
+) -> Result<InternalBeginResult, ErrorCode> {
+    match exec(
+        ta,
+        PerformOpReq::DeviceBegin(BeginRequest {
+            purpose,
+            key_blob: key_blob.to_vec(),
+            params,
+            auth_token,
+        }),
+    )? {
+        PerformOpRsp::DeviceBegin(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Provide more `input` to an operation.
+pub fn update(ta: &mut KeyMintTa, op_handle: i64, input: &[u8]) -> Result<Vec<u8>, ErrorCode> {
+    match exec(
+        ta,
+        PerformOpReq::OperationUpdate(UpdateRequest {
+            op_handle,
+            input: input.to_vec(),
+            auth_token: None,
+            timestamp_token: None,
+        }),
+    )? {
+        PerformOpRsp::OperationUpdate(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Finish an operation, providing the final `input` (and `signature`, for verification).
+pub fn finish(
+    ta: &mut KeyMintTa,
+    op_handle: i64,
+    input: &[u8],
+    signature: Option<Vec<u8>>,
+) -> Result<Vec<u8>, ErrorCode> {
+    finish_with_tokens(ta, op_handle, input, signature, None, None)
+}
+
+/// Finish an operation as for [`finish`], also providing an auth token and a confirmation token.
+pub fn finish_with_tokens(
+    ta: &mut KeyMintTa,
+    op_handle: i64,
+    input: &[u8],
+    signature: Option<Vec<u8>>,
+    auth_token: Option<HardwareAuthToken>,
+    confirmation_token: Option<Vec<u8>>,
+) -> Result<Vec<u8>, ErrorCode> {
+    match exec(
+        ta,
+        PerformOpReq::OperationFinish(FinishRequest {
+            op_handle,
+            input: Some(input.to_vec()),
+            signature,
+            auth_token,
+            timestamp_token: None,
+            confirmation_token,
+        }),
+    )? {
+        PerformOpRsp::OperationFinish(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Run a single-shot operation, returning the output and any parameters returned by `begin`.
+pub fn run_op(
+    ta: &mut KeyMintTa,
+    purpose: KeyPurpose,
+    key_blob: &[u8],
+    params: Vec<KeyParam>,
+    input: &[u8],
+    signature: Option<Vec<u8>>,
+) -> Result<(Vec<u8>, Vec<KeyParam>), ErrorCode> {
+    let begin_rsp = begin(ta, purpose, key_blob, params)?;
+    let output = finish(ta, begin_rsp.op_handle, input, signature)?;
+    Ok((output, begin_rsp.params))
+}
+
+/// Sign `data` with the given key.
+pub fn sign(
+    ta: &mut KeyMintTa,
+    key_blob: &[u8],
+    params: Vec<KeyParam>,
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorCode> {
+    run_op(ta, KeyPurpose::Sign, key_blob, params, data, None).map(|(sig, _)| sig)
+}
+
+/// Generate an HMAC-SHA256 signing key, with `extra_params` added to the key parameters.
+pub fn generate_hmac_key(
+    ta: &mut KeyMintTa,
+    extra_params: &[KeyParam],
+) -> Result<KeyCreationResult, ErrorCode> {
+    let mut key_params = vec![
+        KeyParam::Algorithm(Algorithm::Hmac),
+        KeyParam::KeySize(KeySizeInBits(256)),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::MinMacLength(256),
+    ];
+    key_params.extend_from_slice(extra_params);
+    generate_key(ta, key_params)
+}
+
+/// Sign `data` with a key from [`generate_hmac_key`], with `extra_params` added to the operation
+/// parameters.
+pub fn hmac_sign(
+    ta: &mut KeyMintTa,
+    key_blob: &[u8],
+    extra_params: &[KeyParam],
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorCode> {
+    let mut params = vec![KeyParam::Digest(Digest::Sha256), KeyParam::MacLength(256)];
+    params.extend_from_slice(extra_params);
+    sign(ta, key_blob, params, data)
+}
+
+/// Parse the leaf certificate of a newly created key.
+pub fn leaf_cert(result: &KeyCreationResult) -> x509_cert::Certificate {
+    x509_cert::Certificate::from_der(&result.certificate_chain[0].encoded_certificate)
+        .expect("failed to parse certificate")
+}
+
+/// Return the DER-encoded `SubjectPublicKeyInfo` from the leaf certificate of a newly created key.
+pub fn leaf_spki(result: &KeyCreationResult) -> Vec<u8> {
+    leaf_cert(result).tbs_certificate.subject_public_key_info.to_der().unwrap()
+}
+
+/// Return the algorithm OID and the raw public key from the leaf certificate of a newly created
+/// key.
+pub fn leaf_public_key(result: &KeyCreationResult) -> (String, Vec<u8>) {
+    let spki = leaf_cert(result).tbs_certificate.subject_public_key_info;
+    (spki.algorithm.oid.to_string(), spki.subject_public_key.raw_bytes().to_vec())
+}
+
+/// Return the hex-encoded SHA-256 digest of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(BoringSha256.hash(data).unwrap())
+}
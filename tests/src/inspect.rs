@@ -0,0 +1,334 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding and pretty-printing of captured HAL<->TA messages.
+//!
+//! Messages are shown as their CBOR structure, annotated with the operation involved, and with any
+//! [`KeyParam`] values shown symbolically.
+//!
+//! A capture of the traffic on a `SerializedChannel` can be held in a text log, with one message per
+//! line in the form:
+//!
+//! ```text
+//! [<anything> ...] <direction> <hex>
+//! ```
+//!
+//! where `<direction>` is `>` for a request sent to the TA and `<` for a response received from the
+//! TA.  Any leading fields are ignored, except that a first field holding an integer is taken to be
+//! the time of the message in milliseconds.  Blank lines and lines starting with `#` are skipped.
+//!
+//! This is the format written by `kmr_hal::record::RecordingChannel`.
+
+use kmr_common::keyblob::EncryptedKeyBlob;
+use kmr_wire::{
+    cbor::value::Value,
+    keymint::{ErrorCode, KeyParam, Tag, NEXT_MESSAGE_SIGNAL_FALSE, NEXT_MESSAGE_SIGNAL_TRUE},
+    AsCborValue, KeyMintOperation, PerformOpReq, PerformOpResponse,
+};
+
+/// Type of message held in a capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Encoded `PerformOpReq` sent from the HAL to the TA.
+    Request,
+    /// Encoded `PerformOpResponse` sent from the TA to the HAL.
+    Response,
+    /// Encoded `EncryptedKeyBlob`.
+    KeyBlob,
+}
+
+/// Direction of a message in a traffic log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Request from the HAL to the TA.
+    Request,
+    /// Response from the TA to the HAL.
+    Response,
+}
+
+/// Message from a traffic log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Time of the message, if the first field of the line is an integer number of milliseconds.
+    pub timestamp_ms: Option<i64>,
+    pub direction: Direction,
+    pub data: Vec<u8>,
+}
+
+/// Parse a traffic log.
+pub fn parse_log(text: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (direction, data) = match fields.as_slice() {
+            [.., ">", data] => (Direction::Request, data),
+            [.., "<", data] => (Direction::Response, data),
+            _ => return Err(format!("line {}: no direction and data", idx + 1)),
+        };
+        let data =
+            hex::decode(data).map_err(|e| format!("line {}: invalid hex: {:?}", idx + 1, e))?;
+        let timestamp_ms = match fields.as_slice() {
+            [first, _, _, ..] => first.parse().ok(),
+            _ => None,
+        };
+        records.push(Record { timestamp_ms, direction, data });
+    }
+    Ok(records)
+}
+
+/// Reassembler for responses that the TA has split into multiple messages with `split_rsp`, each
+/// of which starts with a byte that indicates whether more messages follow.
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    pending: Vec<u8>,
+}
+
+impl Reassembler {
+    /// Add a message, returning the complete response if this was its final message.
+    pub fn push(&mut self, msg: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        match msg.split_first() {
+            Some((&NEXT_MESSAGE_SIGNAL_TRUE, data)) => {
+                self.pending.extend_from_slice(data);
+                Ok(None)
+            }
+            Some((&NEXT_MESSAGE_SIGNAL_FALSE, data)) => {
+                self.pending.extend_from_slice(data);
+                Ok(Some(core::mem::take(&mut self.pending)))
+            }
+            Some((marker, _)) => Err(format!("unexpected next-message marker {:#04x}", marker)),
+            None => Err("empty message".to_string()),
+        }
+    }
+
+    /// Indicate whether there is a partially reassembled response.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+/// Return a description of an encoded message of the given kind, or of whichever kind it decodes
+/// as if `kind` is `None`.  With `redact` set, secret values (such as imported key material,
+/// operation data and application ID/data) are replaced by their length.
+pub fn describe(kind: Option<Kind>, data: &[u8], redact: bool) -> Result<String, String> {
+    match kind {
+        Some(Kind::Request) => describe_req(data, redact),
+        Some(Kind::Response) => describe_rsp(data, redact),
+        Some(Kind::KeyBlob) => describe_keyblob(data, redact),
+        None => describe_req(data, redact)
+            .or_else(|_| describe_rsp(data, redact))
+            .or_else(|_| describe_keyblob(data, redact))
+            .map_err(|_| "data is not a request, response or keyblob".to_string()),
+    }
+}
+
+fn describe_req(data: &[u8], redact: bool) -> Result<String, String> {
+    let req = PerformOpReq::from_slice(data).map_err(|e| format!("invalid request: {:?}", e))?;
+    let code = req.code();
+    let value = kmr_wire::read_to_value(data).map_err(|e| format!("{:?}", e))?;
+    let Value::Array(mut a) = value else { return Err("request is not an array".to_string()) };
+    let body = a.pop().ok_or("empty request")?;
+
+    let mut printer = Printer::new(redact);
+    printer.body(&body, secret_req_fields(code));
+    Ok(format!("request {:?} {}", code, printer.out))
+}
+
+fn describe_rsp(data: &[u8], redact: bool) -> Result<String, String> {
+    let rsp =
+        PerformOpResponse::from_slice(data).map_err(|e| format!("invalid response: {:?}", e))?;
+    let mut out = match ErrorCode::try_from(rsp.error_code) {
+        Ok(error_code) => format!("response error_code={:?}", error_code),
+        Err(_) => format!("response error_code={}", rsp.error_code),
+    };
+    if let Some(diagnostics) = &rsp.diagnostics {
+        out += &format!(" diagnostics={:?}", diagnostics);
+    }
+    let Some(inner) = rsp.rsp else { return Ok(out) };
+    let code = inner.code();
+
+    // The response is encoded as `[error_code, [[code, body]], ?diagnostics]`.
+    let value = kmr_wire::read_to_value(data).map_err(|e| format!("{:?}", e))?;
+    let body = match value {
+        Value::Array(mut a) if a.len() >= 2 => match a.swap_remove(1) {
+            Value::Array(mut a) if a.len() == 1 => match a.remove(0) {
+                Value::Array(mut a) if a.len() == 2 => a.remove(1),
+                _ => return Err("unexpected inner response structure".to_string()),
+            },
+            _ => return Err("unexpected response structure".to_string()),
+        },
+        _ => return Err("response is not an array".to_string()),
+    };
+
+    let mut printer = Printer::new(redact);
+    printer.body(&body, secret_rsp_fields(code));
+    Ok(format!("{} {:?} {}", out, code, printer.out))
+}
+
+fn describe_keyblob(data: &[u8], redact: bool) -> Result<String, String> {
+    EncryptedKeyBlob::new(data).map_err(|e| format!("invalid keyblob: {:?}", e))?;
+    let value = kmr_wire::read_to_value(data).map_err(|e| format!("{:?}", e))?;
+    let mut printer = Printer::new(redact);
+    printer.value(&value, 0, false);
+    Ok(format!("keyblob {}", printer.out))
+}
+
+/// Return the indices of the fields of a request that hold secret data.
+fn secret_req_fields(code: KeyMintOperation) -> &'static [usize] {
+    match code {
+        KeyMintOperation::DeviceAddRngEntropy => &[0], // data
+        KeyMintOperation::DeviceImportKey => &[2],     // key_data
+        KeyMintOperation::DeviceImportWrappedKey => &[2], // masking_key
+        KeyMintOperation::DeviceGetKeyCharacteristics => &[1, 2], // app_id, app_data
+        KeyMintOperation::OperationUpdateAad
+        | KeyMintOperation::OperationUpdate
+        | KeyMintOperation::OperationFinish
+        | KeyMintOperation::OperationFinishDeriveKey => &[1], // input
+        _ => &[],
+    }
+}
+
+/// Return the indices of the fields of a response that hold secret data.
+fn secret_rsp_fields(code: KeyMintOperation) -> &'static [usize] {
+    match code {
+        KeyMintOperation::OperationUpdate | KeyMintOperation::OperationFinish => &[0], // ret
+        _ => &[],
+    }
+}
+
+/// Indicate whether the value of a key parameter is secret.
+fn secret_tag(tag: Tag) -> bool {
+    matches!(tag, Tag::ApplicationId | Tag::ApplicationData)
+}
+
+/// Pretty-printer for CBOR values.
+struct Printer {
+    redact: bool,
+    out: String,
+}
+
+impl Printer {
+    fn new(redact: bool) -> Self {
+        Self { redact, out: String::new() }
+    }
+
+    /// Print the body of a message, which is an array when it has fields.
+    fn body(&mut self, value: &Value, secret_fields: &[usize]) {
+        match value {
+            Value::Array(a) if !a.is_empty() => {
+                self.out += "[\n";
+                for (idx, item) in a.iter().enumerate() {
+                    self.indent(1);
+                    self.value(item, 1, secret_fields.contains(&idx));
+                    self.out += ",\n";
+                }
+                self.out += "]";
+            }
+            _ => self.value(value, 0, false),
+        }
+    }
+
+    fn indent(&mut self, level: usize) {
+        self.out += &"  ".repeat(level);
+    }
+
+    fn bytes(&mut self, data: &[u8], secret: bool) {
+        if secret && self.redact {
+            self.out += &format!("<redacted {} bytes>", data.len());
+        } else {
+            self.out += &format!("h'{}'", hex::encode(data));
+        }
+    }
+
+    /// Print a value at the given indentation level, where `secret` indicates that any byte
+    /// strings within the value are secret.
+    fn value(&mut self, value: &Value, level: usize, secret: bool) {
+        match value {
+            Value::Integer(i) => self.out += &i128::from(*i).to_string(),
+            Value::Bytes(b) => self.bytes(b, secret),
+            Value::Text(t) => self.out += &format!("{:?}", t),
+            Value::Float(f) => self.out += &f.to_string(),
+            Value::Bool(b) => self.out += &b.to_string(),
+            Value::Null => self.out += "null",
+            Value::Tag(tag, inner) => {
+                self.out += &format!("{}(", tag);
+                self.value(inner, level, secret);
+                self.out += ")";
+            }
+            Value::Array(a) => {
+                if self.key_param(value) {
+                    return;
+                }
+                if a.iter().all(is_scalar) {
+                    self.out += "[";
+                    for (idx, item) in a.iter().enumerate() {
+                        if idx > 0 {
+                            self.out += ", ";
+                        }
+                        self.value(item, level, secret);
+                    }
+                    self.out += "]";
+                } else {
+                    self.out += "[\n";
+                    for item in a {
+                        self.indent(level + 1);
+                        self.value(item, level + 1, secret);
+                        self.out += ",\n";
+                    }
+                    self.indent(level);
+                    self.out += "]";
+                }
+            }
+            Value::Map(m) => {
+                self.out += "{\n";
+                for (k, v) in m {
+                    self.indent(level + 1);
+                    self.value(k, level + 1, secret);
+                    self.out += ": ";
+                    self.value(v, level + 1, secret);
+                    self.out += ",\n";
+                }
+                self.indent(level);
+                self.out += "}";
+            }
+            _ => self.out += "<unknown>",
+        }
+    }
+
+    /// Print a value symbolically if it is a [`KeyParam`], returning whether it was.
+    fn key_param(&mut self, value: &Value) -> bool {
+        let Value::Array(a) = value else { return false };
+        if a.len() != 2 || !matches!(a[0], Value::Integer(_)) {
+            return false;
+        }
+        let Ok(param) = KeyParam::from_cbor_value(value.clone()) else { return false };
+        let tag = param.tag();
+        match &a[1] {
+            Value::Bytes(b) => {
+                self.out += &format!("{:?}(", tag);
+                self.bytes(b, secret_tag(tag));
+                self.out += ")";
+            }
+            _ => self.out += &format!("{:?}", param),
+        }
+        true
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Map(_) | Value::Tag(_, _))
+}
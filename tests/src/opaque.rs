@@ -0,0 +1,538 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Software model of a secure element that holds key material internally and only ever hands out
+//! [`OpaqueKeyMaterial`] handles, for exercising the `OpaqueOr::Opaque` code paths in the TA.
+//!
+//! Each trait implementation here wraps an existing (explicit-key) implementation: keys that are
+//! generated, imported or derived are moved into a shared handle table, and operations on a handle
+//! look up the real key and pass it to the wrapped implementation.  Explicit keys (as created by
+//! the TA itself, e.g. for transport keys) are passed straight through.  Not suitable for anything
+//! other than testing.
+
+use kmr_common::crypto::{
+    self, aes, des, ec, hmac, rsa, AadOperation, AccumulatingOperation, Aes, Ckdf, Des,
+    EmittingOperation, Hkdf, Hmac, KeyMaterial, OpaqueKeyMaterial, OpaqueOr, Rng, Rsa,
+    SymmetricOperation,
+};
+use kmr_common::{km_err, Error};
+use kmr_ta::device;
+use kmr_wire::keymint::{Digest, KeyParam};
+use kmr_wire::{KeySizeInBits, RsaExponent};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Prefix for the opaque handles issued by a [`SecureElement`].
+const HANDLE_MAGIC: &[u8; 4] = b"SEK\x01";
+
+/// Handle table that holds the real key material behind each [`OpaqueKeyMaterial`] handle.
+#[derive(Default)]
+pub struct SecureElement {
+    keys: Mutex<HashMap<u64, KeyMaterial>>,
+}
+
+impl SecureElement {
+    /// Create an empty secure element.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Return the number of keys held.
+    pub fn key_count(&self) -> usize {
+        self.keys.lock().unwrap().len()
+    }
+
+    /// Move key material into the secure element, returning the equivalent opaque key.  Key
+    /// material that is already opaque is returned unchanged.
+    pub fn store(&self, key: KeyMaterial) -> Result<KeyMaterial, Error> {
+        Ok(match key {
+            KeyMaterial::Aes(OpaqueOr::Explicit(k)) => {
+                KeyMaterial::Aes(self.insert(KeyMaterial::Aes(k.into())).into())
+            }
+            KeyMaterial::TripleDes(OpaqueOr::Explicit(k)) => {
+                KeyMaterial::TripleDes(self.insert(KeyMaterial::TripleDes(k.into())).into())
+            }
+            KeyMaterial::Hmac(OpaqueOr::Explicit(k)) => {
+                KeyMaterial::Hmac(self.insert(KeyMaterial::Hmac(k.into())).into())
+            }
+            KeyMaterial::Rsa(OpaqueOr::Explicit(k)) => {
+                KeyMaterial::Rsa(self.insert(KeyMaterial::Rsa(k.into())).into())
+            }
+            KeyMaterial::Ec(curve, curve_type, OpaqueOr::Explicit(k)) => {
+                let handle = self.insert(KeyMaterial::Ec(curve, curve_type, k.into()));
+                KeyMaterial::Ec(curve, curve_type, handle.into())
+            }
+            key => key,
+        })
+    }
+
+    fn insert(&self, key: KeyMaterial) -> OpaqueKeyMaterial {
+        let mut keys = self.keys.lock().unwrap();
+        let id = keys.len() as u64;
+        keys.insert(id, key);
+        let mut handle = HANDLE_MAGIC.to_vec();
+        handle.extend_from_slice(&id.to_be_bytes());
+        OpaqueKeyMaterial(handle)
+    }
+
+    fn lookup(&self, handle: &OpaqueKeyMaterial) -> Result<KeyMaterial, Error> {
+        let id = match handle.0.strip_prefix(HANDLE_MAGIC.as_slice()) {
+            Some(id) => u64::from_be_bytes(
+                id.try_into()
+                    .map_err(|_e| km_err!(InvalidKeyBlob, "opaque handle of wrong length"))?,
+            ),
+            None => {
+                return Err(km_err!(InvalidKeyBlob, "opaque key is not a secure element handle"))
+            }
+        };
+        self.keys
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| km_err!(InvalidKeyBlob, "unknown opaque key handle {}", id))
+    }
+}
+
+/// Macro that generates a method on [`SecureElement`] that converts an `OpaqueOr<T>` into an
+/// explicit key of the appropriate type, looking up the handle if necessary.
+macro_rules! resolver {
+    { $name:ident, $t:ty, $pattern:pat => $key:ident } => {
+        impl SecureElement {
+            fn $name(&self, key: &OpaqueOr<$t>) -> Result<OpaqueOr<$t>, Error> {
+                match key {
+                    OpaqueOr::Explicit(_) => Ok(key.clone()),
+                    OpaqueOr::Opaque(handle) => match self.lookup(handle)? {
+                        $pattern => Ok($key),
+                        _ => Err(km_err!(IncompatibleAlgorithm, "opaque handle for wrong key type")),
+                    },
+                }
+            }
+        }
+    }
+}
+
+resolver!(aes_key, aes::Key, KeyMaterial::Aes(k) => k);
+resolver!(des_key, des::Key, KeyMaterial::TripleDes(k) => k);
+resolver!(hmac_key, hmac::Key, KeyMaterial::Hmac(k) => k);
+resolver!(rsa_key, rsa::Key, KeyMaterial::Rsa(k) => k);
+resolver!(ec_key, ec::Key, KeyMaterial::Ec(_, _, k) => k);
+
+/// Macro that generates a constructor for a trait implementation that holds its keys in a
+/// [`SecureElement`], wrapping an inner implementation of the trait.
+macro_rules! wrapper {
+    { $name:ident, $trait:path } => {
+        impl $name {
+            /// Wrap `inner`, holding its keys in `se`.
+            pub fn new(se: &Arc<SecureElement>, inner: Box<dyn $trait>) -> Self {
+                Self { se: se.clone(), inner }
+            }
+        }
+    }
+}
+
+wrapper!(SeAes, Aes);
+wrapper!(SeDes, Des);
+wrapper!(SeHmac, Hmac);
+wrapper!(SeRsa, Rsa);
+wrapper!(SeEc, crypto::Ec);
+wrapper!(SeCkdf, Ckdf);
+wrapper!(SeHkdf, Hkdf);
+
+/// Build a [`crypto::Implementation`] that holds all of its keys in `se`, using the cryptographic
+/// operations of `inner`.
+pub fn crypto_impl(
+    se: &Arc<SecureElement>,
+    inner: crypto::Implementation,
+) -> crypto::Implementation {
+    crypto::Implementation {
+        rng: inner.rng,
+        clock: inner.clock,
+        compare: inner.compare,
+        aes: Box::new(SeAes::new(se, inner.aes)),
+        des: Box::new(SeDes::new(se, inner.des)),
+        hmac: Box::new(SeHmac::new(se, inner.hmac)),
+        rsa: Box::new(SeRsa::new(se, inner.rsa)),
+        ec: Box::new(SeEc::new(se, inner.ec)),
+        // ML-DSA and ML-KEM keys are not held in the secure element.
+        #[cfg(feature = "pqc")]
+        mldsa: inner.mldsa,
+        #[cfg(feature = "pqc")]
+        mlkem: inner.mlkem,
+        ckdf: Box::new(SeCkdf::new(se, inner.ckdf)),
+        hkdf: Box::new(SeHkdf::new(se, inner.hkdf)),
+        sha256: inner.sha256,
+    }
+}
+
+/// AES implementation whose keys are held in a [`SecureElement`].
+pub struct SeAes {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Aes>,
+}
+
+impl Aes for SeAes {
+    fn generate_key(
+        &self,
+        rng: &mut dyn Rng,
+        variant: aes::Variant,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_key(rng, variant, params)?)
+    }
+
+    fn import_key(
+        &self,
+        data: &[u8],
+        params: &[KeyParam],
+    ) -> Result<(KeyMaterial, KeySizeInBits), Error> {
+        let (key, key_size) = self.inner.import_key(data, params)?;
+        Ok((self.se.store(key)?, key_size))
+    }
+
+    fn begin(
+        &self,
+        key: OpaqueOr<aes::Key>,
+        mode: aes::CipherMode,
+        dir: SymmetricOperation,
+    ) -> Result<Box<dyn EmittingOperation>, Error> {
+        self.inner.begin(self.se.aes_key(&key)?, mode, dir)
+    }
+
+    fn begin_aead(
+        &self,
+        key: OpaqueOr<aes::Key>,
+        mode: aes::GcmMode,
+        dir: SymmetricOperation,
+    ) -> Result<Box<dyn AadOperation>, Error> {
+        self.inner.begin_aead(self.se.aes_key(&key)?, mode, dir)
+    }
+}
+
+/// Triple-DES implementation whose keys are held in a [`SecureElement`].
+pub struct SeDes {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Des>,
+}
+
+impl Des for SeDes {
+    fn generate_key(&self, rng: &mut dyn Rng, params: &[KeyParam]) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_key(rng, params)?)
+    }
+
+    fn import_key(&self, data: &[u8], params: &[KeyParam]) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.import_key(data, params)?)
+    }
+
+    fn begin(
+        &self,
+        key: OpaqueOr<des::Key>,
+        mode: des::Mode,
+        dir: SymmetricOperation,
+    ) -> Result<Box<dyn EmittingOperation>, Error> {
+        self.inner.begin(self.se.des_key(&key)?, mode, dir)
+    }
+}
+
+/// HMAC implementation whose keys are held in a [`SecureElement`].
+pub struct SeHmac {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Hmac>,
+}
+
+impl Hmac for SeHmac {
+    fn generate_key(
+        &self,
+        rng: &mut dyn Rng,
+        key_size: KeySizeInBits,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_key(rng, key_size, params)?)
+    }
+
+    fn import_key(
+        &self,
+        data: &[u8],
+        params: &[KeyParam],
+    ) -> Result<(KeyMaterial, KeySizeInBits), Error> {
+        let (key, key_size) = self.inner.import_key(data, params)?;
+        Ok((self.se.store(key)?, key_size))
+    }
+
+    fn begin(
+        &self,
+        key: OpaqueOr<hmac::Key>,
+        digest: Digest,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.inner.begin(self.se.hmac_key(&key)?, digest)
+    }
+}
+
+/// RSA implementation whose keys are held in a [`SecureElement`].
+pub struct SeRsa {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Rsa>,
+}
+
+impl Rsa for SeRsa {
+    fn generate_key(
+        &self,
+        rng: &mut dyn Rng,
+        key_size: KeySizeInBits,
+        pub_exponent: RsaExponent,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_key(rng, key_size, pub_exponent, params)?)
+    }
+
+    fn import_pkcs8_key(
+        &self,
+        data: &[u8],
+        params: &[KeyParam],
+    ) -> Result<(KeyMaterial, KeySizeInBits, RsaExponent), Error> {
+        let (key, key_size, exponent) = self.inner.import_pkcs8_key(data, params)?;
+        Ok((self.se.store(key)?, key_size, exponent))
+    }
+
+    fn import_pkcs1_key(
+        &self,
+        data: &[u8],
+        spki: Option<&[u8]>,
+        params: &[KeyParam],
+    ) -> Result<(KeyMaterial, KeySizeInBits, RsaExponent), Error> {
+        let (key, key_size, exponent) = self.inner.import_pkcs1_key(data, spki, params)?;
+        Ok((self.se.store(key)?, key_size, exponent))
+    }
+
+    fn subject_public_key(&self, key: &OpaqueOr<rsa::Key>) -> Result<Vec<u8>, Error> {
+        self.inner.subject_public_key(&self.se.rsa_key(key)?)
+    }
+
+    fn begin_decrypt(
+        &self,
+        key: OpaqueOr<rsa::Key>,
+        mode: rsa::DecryptionMode,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.inner.begin_decrypt(self.se.rsa_key(&key)?, mode)
+    }
+
+    fn begin_sign(
+        &self,
+        key: OpaqueOr<rsa::Key>,
+        mode: rsa::SignMode,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.inner.begin_sign(self.se.rsa_key(&key)?, mode)
+    }
+}
+
+/// EC implementation whose keys are held in a [`SecureElement`].
+pub struct SeEc {
+    se: Arc<SecureElement>,
+    inner: Box<dyn crypto::Ec>,
+}
+
+impl crypto::Ec for SeEc {
+    fn generate_nist_key(
+        &self,
+        rng: &mut dyn Rng,
+        curve: ec::NistCurve,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_nist_key(rng, curve, params)?)
+    }
+
+    fn generate_ed25519_key(
+        &self,
+        rng: &mut dyn Rng,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_ed25519_key(rng, params)?)
+    }
+
+    fn generate_x25519_key(
+        &self,
+        rng: &mut dyn Rng,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.generate_x25519_key(rng, params)?)
+    }
+
+    fn import_pkcs8_key(&self, data: &[u8], params: &[KeyParam]) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.import_pkcs8_key(data, params)?)
+    }
+
+    fn import_sec1_key(
+        &self,
+        data: &[u8],
+        spki: Option<&[u8]>,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.import_sec1_key(data, spki, params)?)
+    }
+
+    fn import_raw_ed25519_key(
+        &self,
+        data: &[u8],
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.import_raw_ed25519_key(data, params)?)
+    }
+
+    fn import_raw_x25519_key(
+        &self,
+        data: &[u8],
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.se.store(self.inner.import_raw_x25519_key(data, params)?)
+    }
+
+    fn subject_public_key(&self, key: &OpaqueOr<ec::Key>) -> Result<Vec<u8>, Error> {
+        self.inner.subject_public_key(&self.se.ec_key(key)?)
+    }
+
+    fn nist_public_key(&self, key: &ec::NistKey, curve: ec::NistCurve) -> Result<Vec<u8>, Error> {
+        self.inner.nist_public_key(key, curve)
+    }
+
+    fn ed25519_public_key(&self, key: &ec::Ed25519Key) -> Result<Vec<u8>, Error> {
+        self.inner.ed25519_public_key(key)
+    }
+
+    fn x25519_public_key(&self, key: &ec::X25519Key) -> Result<Vec<u8>, Error> {
+        self.inner.x25519_public_key(key)
+    }
+
+    fn begin_agree(&self, key: OpaqueOr<ec::Key>) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.inner.begin_agree(self.se.ec_key(&key)?)
+    }
+
+    fn begin_sign(
+        &self,
+        key: OpaqueOr<ec::Key>,
+        digest: Digest,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.inner.begin_sign(self.se.ec_key(&key)?, digest)
+    }
+
+    fn verify_nist_signature(
+        &self,
+        curve: ec::NistCurve,
+        public_key: &[u8],
+        digest: Digest,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, Error> {
+        self.inner.verify_nist_signature(curve, public_key, digest, data, signature)
+    }
+}
+
+/// CKDF implementation that accepts keys held in a [`SecureElement`].
+pub struct SeCkdf {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Ckdf>,
+}
+
+impl Ckdf for SeCkdf {
+    fn ckdf(
+        &self,
+        key: &OpaqueOr<aes::Key>,
+        label: &[u8],
+        chunks: &[&[u8]],
+        out_len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        self.inner.ckdf(&self.se.aes_key(key)?, label, chunks, out_len)
+    }
+}
+
+/// HKDF implementation whose pseudo-random keys, and any derived AES keys, are held in a
+/// [`SecureElement`].
+pub struct SeHkdf {
+    se: Arc<SecureElement>,
+    inner: Box<dyn Hkdf>,
+}
+
+impl SeHkdf {
+    fn store_aes(&self, key: OpaqueOr<aes::Key>) -> Result<OpaqueOr<aes::Key>, Error> {
+        match self.se.store(KeyMaterial::Aes(key))? {
+            KeyMaterial::Aes(key) => Ok(key),
+            _ => Err(km_err!(UnknownError, "unexpected key type from secure element")),
+        }
+    }
+}
+
+impl Hkdf for SeHkdf {
+    fn extract(&self, salt: &[u8], ikm: &[u8]) -> Result<OpaqueOr<hmac::Key>, Error> {
+        match self.se.store(KeyMaterial::Hmac(self.inner.extract(salt, ikm)?))? {
+            KeyMaterial::Hmac(prk) => Ok(prk),
+            _ => Err(km_err!(UnknownError, "unexpected key type from secure element")),
+        }
+    }
+
+    fn expand(
+        &self,
+        prk: &OpaqueOr<hmac::Key>,
+        info: &[u8],
+        out_len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        self.inner.expand(&self.se.hmac_key(prk)?, info, out_len)
+    }
+
+    fn hkdf_aes(
+        &self,
+        salt: &[u8],
+        ikm: &[u8],
+        info: &[u8],
+        variant: aes::Variant,
+    ) -> Result<OpaqueOr<aes::Key>, Error> {
+        self.store_aes(self.inner.hkdf_aes(salt, ikm, info, variant)?)
+    }
+
+    fn expand_aes(
+        &self,
+        prk: &OpaqueOr<hmac::Key>,
+        info: &[u8],
+        variant: aes::Variant,
+    ) -> Result<OpaqueOr<aes::Key>, Error> {
+        self.store_aes(self.inner.expand_aes(&self.se.hmac_key(prk)?, info, variant)?)
+    }
+}
+
+/// Device key material whose root KEK and key agreement key are held in a [`SecureElement`].
+pub struct SeKeys {
+    root_kek: OpaqueOr<hmac::Key>,
+    kak: OpaqueOr<aes::Key>,
+}
+
+impl SeKeys {
+    /// Move the given device keys into `se`.
+    pub fn new(se: &SecureElement, root_kek: hmac::Key, kak: aes::Key) -> Result<Self, Error> {
+        match (
+            se.store(KeyMaterial::Hmac(root_kek.into()))?,
+            se.store(KeyMaterial::Aes(kak.into()))?,
+        ) {
+            (KeyMaterial::Hmac(root_kek), KeyMaterial::Aes(kak)) => Ok(Self { root_kek, kak }),
+            _ => Err(km_err!(UnknownError, "unexpected key type from secure element")),
+        }
+    }
+}
+
+impl device::RetrieveKeyMaterial for SeKeys {
+    fn root_kek(&self, _context: &[u8]) -> Result<OpaqueOr<hmac::Key>, Error> {
+        Ok(self.root_kek.clone())
+    }
+
+    fn kak(&self) -> Result<OpaqueOr<aes::Key>, Error> {
+        Ok(self.kak.clone())
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay of recorded HAL<->TA traffic against a TA, comparing the responses with those that were
+//! recorded.
+//!
+//! Recordings use the text log format described in [`crate::inspect`], as written by
+//! `kmr_hal::record::RecordingChannel`.  To get identical responses on replay, the TA needs to
+//! behave identically to the TA that was recorded: [`replay_ta`] builds a host TA whose random
+//! numbers come from a seeded [`DeterministicRng`], and whose clock follows the recorded
+//! timestamps.  (Note that some operations, such as generation of asymmetric keys, use randomness
+//! from BoringSSL directly and so will never be reproduced exactly.)
+
+use crate::host::{self, TestClock};
+use crate::inspect::{describe, parse_log, Direction, Kind};
+use kmr_common::crypto::Rng;
+use kmr_ta::KeyMintTa;
+
+/// Request sent to the TA, together with the response that was recorded for it (if any).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exchange {
+    /// Time of the request in milliseconds, if recorded.
+    pub timestamp_ms: Option<i64>,
+    pub req: Vec<u8>,
+    pub rsp: Option<Vec<u8>>,
+}
+
+/// Parse a recording into its request/response exchanges.
+pub fn parse_recording(text: &str) -> Result<Vec<Exchange>, String> {
+    let mut exchanges: Vec<Exchange> = Vec::new();
+    for record in parse_log(text)? {
+        match record.direction {
+            Direction::Request => exchanges.push(Exchange {
+                timestamp_ms: record.timestamp_ms,
+                req: record.data,
+                rsp: None,
+            }),
+            Direction::Response => match exchanges.last_mut() {
+                Some(exchange) if exchange.rsp.is_none() => exchange.rsp = Some(record.data),
+                _ => return Err(format!("response {} without request", hex::encode(record.data))),
+            },
+        }
+    }
+    Ok(exchanges)
+}
+
+/// Response from a replay that differs from the recorded response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    /// Index of the exchange in the recording.
+    pub index: usize,
+    pub req: Vec<u8>,
+    pub recorded: Vec<u8>,
+    pub replayed: Vec<u8>,
+}
+
+impl core::fmt::Display for Difference {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let show = |kind, data: &[u8]| {
+            describe(Some(kind), data, false).unwrap_or_else(|_| hex::encode(data))
+        };
+        writeln!(f, "exchange {}: {}", self.index, show(Kind::Request, &self.req))?;
+        writeln!(f, "recorded {}", show(Kind::Response, &self.recorded))?;
+        write!(f, "replayed {}", show(Kind::Response, &self.replayed))
+    }
+}
+
+/// Send the requests from a recording to `ta` in order, advancing `clock` in line with the
+/// recorded timestamps, and return the responses that differ from those recorded.
+pub fn replay(ta: &mut KeyMintTa, clock: &TestClock, exchanges: &[Exchange]) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    let mut last_ms = None;
+    for (index, exchange) in exchanges.iter().enumerate() {
+        if let Some(now_ms) = exchange.timestamp_ms {
+            if let Some(last_ms) = last_ms {
+                if now_ms > last_ms {
+                    clock.advance(now_ms - last_ms);
+                }
+            }
+            last_ms = Some(now_ms);
+        }
+        let rsp = ta.process(&exchange.req);
+        if let Some(recorded) = &exchange.rsp {
+            if *recorded != rsp {
+                differences.push(Difference {
+                    index,
+                    req: exchange.req.clone(),
+                    recorded: recorded.clone(),
+                    replayed: rsp,
+                });
+            }
+        }
+    }
+    differences
+}
+
+/// Build a host TA whose behaviour depends only on `seed` and on the requests sent to it, together
+/// with the clock that it uses.
+pub fn replay_ta(seed: u64) -> (KeyMintTa, TestClock) {
+    let clock = TestClock::default();
+    let mut imp = host::crypto_impl(Some(clock.clone()));
+    imp.rng = Box::new(DeterministicRng::new(seed));
+    (host::keymint_ta(imp, host::device_impl()), clock)
+}
+
+/// Insecure [`Rng`] implementation whose output depends only on its seed and on any entropy that
+/// is added, for reproducible tests.
+#[derive(Clone, Debug)]
+pub struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    /// Create a generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        // Xorshift needs a non-zero state.
+        Self { state: seed ^ 0x9e37_79b9_7f4a_7c15 }
+    }
+
+    fn next(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl Rng for DeterministicRng {
+    fn add_entropy(&mut self, data: &[u8]) {
+        for b in data {
+            self.state = (self.state.rotate_left(8) ^ *b as u64).max(1);
+            self.next();
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let value = self.next().to_le_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+    }
+}
@@ -0,0 +1,652 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sample values for every message that passes between HAL and TA, and for every [`KeyParam`],
+//! used to build a golden corpus of their CBOR encodings.
+//!
+//! The HAL and TA may be built and updated separately, so the encodings in the corpus (held in
+//! `tests/testdata/wire_corpus.txt`) must continue to be accepted.  If a new HAL version
+//! intentionally changes the wire format, regenerate the corpus with `scripts/wire-corpus-gen`.
+
+use kmr_wire::audit::{AuditEvent, AuditRecord};
+use kmr_wire::keymint::{
+    Algorithm, AttestationKey, BlockMode, Certificate, DateTime, Digest, EcCurve, ErrorCode,
+    HardwareAuthToken, HardwareAuthenticatorType, KeyCharacteristics, KeyCreationResult, KeyFormat,
+    KeyMintHardwareInfo, KeyOrigin, KeyParam, KeyPurpose, PaddingMode, SecurityLevel, Tag, TagType,
+};
+use kmr_wire::secureclock::{TimeStampToken, Timestamp};
+use kmr_wire::sharedsecret::SharedSecretParameters;
+use kmr_wire::*;
+
+/// Prefix for the names of request entries in the corpus.
+pub const REQ_PREFIX: &str = "req.";
+/// Prefix for the names of response entries in the corpus.
+pub const RSP_PREFIX: &str = "rsp.";
+/// Prefix for the names of key parameter entries in the corpus.
+pub const PARAM_PREFIX: &str = "param.";
+
+/// Header emitted at the start of the corpus.
+const HEADER: &str = "\
+# Golden CBOR encodings of the messages passed between HAL and TA, and of every key parameter.
+# Each line holds an entry name and the hex-encoded CBOR data for the entry.
+#
+# Generated by `scripts/wire-corpus-gen`; do not edit by hand.
+";
+
+/// Single entry in the corpus.
+pub struct Entry {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Return all of the [`KeyMintOperation`] values, in numeric order.
+pub fn all_codes() -> Vec<KeyMintOperation> {
+    // Operation codes are small values, or small values above 0x10000000 for vendor extensions.
+    (0..=0xff).chain(0x1000_0000..=0x1000_00ff).filter_map(KeyMintOperation::n).collect()
+}
+
+/// Return all of the [`Tag`] values, in numeric order.
+pub fn all_tags() -> Vec<Tag> {
+    let types = [
+        TagType::Invalid,
+        TagType::Enum,
+        TagType::EnumRep,
+        TagType::Uint,
+        TagType::UintRep,
+        TagType::Ulong,
+        TagType::Date,
+        TagType::Bool,
+        TagType::Bignum,
+        TagType::Bytes,
+        TagType::UlongRep,
+    ];
+    let mut tags: Vec<Tag> = types
+        .iter()
+        .flat_map(|tag_type| (0..0x1_0000).filter_map(move |n| Tag::n(*tag_type as i32 | n)))
+        .collect();
+    tags.sort();
+    tags
+}
+
+/// Build the entries of the corpus.
+pub fn entries() -> Vec<Entry> {
+    let mut result = Vec::new();
+    for code in all_codes() {
+        result.push(Entry {
+            name: format!("{REQ_PREFIX}{code:?}"),
+            data: sample_request(code).into_vec().unwrap(),
+        });
+    }
+    for code in all_codes() {
+        let rsp = PerformOpResponse {
+            error_code: 0,
+            rsp: Some(sample_response(code)),
+            diagnostics: None,
+        };
+        result.push(Entry { name: format!("{RSP_PREFIX}{code:?}"), data: rsp.into_vec().unwrap() });
+    }
+    let errors =
+        [("Error", None), ("ErrorWithDiagnostics", Some("unsupported key size".to_string()))];
+    for (name, diagnostics) in errors {
+        let rsp = PerformOpResponse {
+            error_code: ErrorCode::UnsupportedKeySize as i32,
+            rsp: None,
+            diagnostics,
+        };
+        result.push(Entry { name: format!("{RSP_PREFIX}{name}"), data: rsp.into_vec().unwrap() });
+    }
+    for tag in all_tags() {
+        if let Some(param) = sample_key_param(tag) {
+            result.push(Entry {
+                name: format!("{PARAM_PREFIX}{tag:?}"),
+                data: param.into_vec().unwrap(),
+            });
+        }
+    }
+    result
+}
+
+/// Return the text of the corpus.
+pub fn corpus_text() -> String {
+    let mut text = HEADER.to_string();
+    for entry in entries() {
+        text += &format!("{} {}\n", entry.name, hex::encode(&entry.data));
+    }
+    text
+}
+
+/// Parse the text of a corpus into its entries.
+pub fn parse(text: &str) -> Vec<Entry> {
+    text.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hexdata) =
+                line.split_once(' ').unwrap_or_else(|| panic!("malformed corpus line: {line}"));
+            let data =
+                hex::decode(hexdata).unwrap_or_else(|e| panic!("malformed hex for {name}: {e:?}"));
+            Entry { name: name.to_string(), data }
+        })
+        .collect()
+}
+
+/// Return a sample key parameter for the given tag, or `None` if the tag cannot be held in a
+/// [`KeyParam`].
+pub fn sample_key_param(tag: Tag) -> Option<KeyParam> {
+    // Deliberately no wildcard, so that a new tag needs an explicit decision.
+    Some(match tag {
+        Tag::Invalid
+        | Tag::HardwareType
+        | Tag::MinSecondsBetweenOps
+        | Tag::UniqueId
+        | Tag::IdentityCredentialKey
+        | Tag::AssociatedData
+        | Tag::ConfirmationToken => return None,
+        Tag::Purpose => KeyParam::Purpose(KeyPurpose::Sign),
+        Tag::Algorithm => KeyParam::Algorithm(Algorithm::Ec),
+        Tag::KeySize => KeyParam::KeySize(KeySizeInBits(256)),
+        Tag::BlockMode => KeyParam::BlockMode(BlockMode::Gcm),
+        Tag::Digest => KeyParam::Digest(Digest::Sha256),
+        Tag::Padding => KeyParam::Padding(PaddingMode::RsaPss),
+        Tag::CallerNonce => KeyParam::CallerNonce,
+        Tag::MinMacLength => KeyParam::MinMacLength(128),
+        Tag::EcCurve => KeyParam::EcCurve(EcCurve::P256),
+        Tag::RsaPublicExponent => KeyParam::RsaPublicExponent(RsaExponent(65537)),
+        Tag::IncludeUniqueId => KeyParam::IncludeUniqueId,
+        Tag::RsaOaepMgfDigest => KeyParam::RsaOaepMgfDigest(Digest::Sha1),
+        Tag::BootloaderOnly => KeyParam::BootloaderOnly,
+        Tag::RollbackResistance => KeyParam::RollbackResistance,
+        Tag::EarlyBootOnly => KeyParam::EarlyBootOnly,
+        Tag::ActiveDatetime => KeyParam::ActiveDatetime(date(1_700_000_000_000)),
+        Tag::OriginationExpireDatetime => {
+            KeyParam::OriginationExpireDatetime(date(1_800_000_000_000))
+        }
+        Tag::UsageExpireDatetime => KeyParam::UsageExpireDatetime(date(1_900_000_000_000)),
+        Tag::MaxUsesPerBoot => KeyParam::MaxUsesPerBoot(3),
+        Tag::UsageCountLimit => KeyParam::UsageCountLimit(1),
+        Tag::UserId => KeyParam::UserId(10),
+        Tag::UserSecureId => KeyParam::UserSecureId(0x0123_4567_89ab_cdef),
+        Tag::NoAuthRequired => KeyParam::NoAuthRequired,
+        Tag::UserAuthType => KeyParam::UserAuthType(HardwareAuthenticatorType::Password as u32),
+        Tag::AuthTimeout => KeyParam::AuthTimeout(300),
+        Tag::AllowWhileOnBody => KeyParam::AllowWhileOnBody,
+        Tag::TrustedUserPresenceRequired => KeyParam::TrustedUserPresenceRequired,
+        Tag::TrustedConfirmationRequired => KeyParam::TrustedConfirmationRequired,
+        Tag::UnlockedDeviceRequired => KeyParam::UnlockedDeviceRequired,
+        Tag::ApplicationId => KeyParam::ApplicationId(b"appid".to_vec()),
+        Tag::ApplicationData => KeyParam::ApplicationData(b"appdata".to_vec()),
+        Tag::CreationDatetime => KeyParam::CreationDatetime(date(1_600_000_000_000)),
+        Tag::Origin => KeyParam::Origin(KeyOrigin::Generated),
+        Tag::RootOfTrust => KeyParam::RootOfTrust(vec![0x30, 0x00]),
+        Tag::OsVersion => KeyParam::OsVersion(150000),
+        Tag::OsPatchlevel => KeyParam::OsPatchlevel(202601),
+        Tag::AttestationChallenge => KeyParam::AttestationChallenge(b"challenge".to_vec()),
+        Tag::AttestationApplicationId => KeyParam::AttestationApplicationId(b"attappid".to_vec()),
+        Tag::AttestationIdBrand => KeyParam::AttestationIdBrand(b"brand".to_vec()),
+        Tag::AttestationIdDevice => KeyParam::AttestationIdDevice(b"device".to_vec()),
+        Tag::AttestationIdProduct => KeyParam::AttestationIdProduct(b"product".to_vec()),
+        Tag::AttestationIdSerial => KeyParam::AttestationIdSerial(b"serial".to_vec()),
+        Tag::AttestationIdImei => KeyParam::AttestationIdImei(b"imei".to_vec()),
+        Tag::AttestationIdSecondImei => KeyParam::AttestationIdSecondImei(b"imei2".to_vec()),
+        Tag::AttestationIdMeid => KeyParam::AttestationIdMeid(b"meid".to_vec()),
+        Tag::AttestationIdManufacturer => {
+            KeyParam::AttestationIdManufacturer(b"manufacturer".to_vec())
+        }
+        Tag::AttestationIdModel => KeyParam::AttestationIdModel(b"model".to_vec()),
+        Tag::VendorPatchlevel => KeyParam::VendorPatchlevel(20260105),
+        Tag::BootPatchlevel => KeyParam::BootPatchlevel(20260101),
+        Tag::DeviceUniqueAttestation => KeyParam::DeviceUniqueAttestation,
+        Tag::StorageKey => KeyParam::StorageKey,
+        Tag::Nonce => KeyParam::Nonce(vec![0x4e; 12]),
+        Tag::MacLength => KeyParam::MacLength(128),
+        Tag::ResetSinceIdRotation => KeyParam::ResetSinceIdRotation,
+        Tag::CertificateSerial => KeyParam::CertificateSerial(vec![0x01, 0x02]),
+        Tag::CertificateSubject => KeyParam::CertificateSubject(vec![0x30, 0x00]),
+        Tag::CertificateNotBefore => KeyParam::CertificateNotBefore(date(0)),
+        Tag::CertificateNotAfter => KeyParam::CertificateNotAfter(date(253_402_300_799_000)),
+        Tag::MaxBootLevel => KeyParam::MaxBootLevel(2),
+        Tag::ModuleHash => KeyParam::ModuleHash(vec![0x4d; 32]),
+    })
+}
+
+/// Return a sample request for the given operation.
+pub fn sample_request(code: KeyMintOperation) -> PerformOpReq {
+    match code {
+        KeyMintOperation::DeviceGetHardwareInfo => {
+            PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {})
+        }
+        KeyMintOperation::DeviceAddRngEntropy => {
+            PerformOpReq::DeviceAddRngEntropy(AddRngEntropyRequest { data: vec![0xee; 8] })
+        }
+        KeyMintOperation::DeviceGenerateKey => {
+            PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+                key_params: key_params(),
+                attestation_key: Some(attestation_key()),
+            })
+        }
+        KeyMintOperation::DeviceImportKey => PerformOpReq::DeviceImportKey(ImportKeyRequest {
+            key_params: key_params(),
+            key_format: KeyFormat::Pkcs8,
+            key_data: vec![0x30, 0x00],
+            attestation_key: None,
+        }),
+        KeyMintOperation::DeviceImportWrappedKey => {
+            PerformOpReq::DeviceImportWrappedKey(ImportWrappedKeyRequest {
+                wrapped_key_data: vec![0x30, 0x00],
+                wrapping_key_blob: keyblob(),
+                masking_key: vec![0x00; 32],
+                unwrapping_params: vec![KeyParam::Digest(Digest::Sha256)],
+                password_sid: 1,
+                biometric_sid: -1,
+            })
+        }
+        KeyMintOperation::DeviceUpgradeKey => PerformOpReq::DeviceUpgradeKey(UpgradeKeyRequest {
+            key_blob_to_upgrade: keyblob(),
+            upgrade_params: vec![KeyParam::ApplicationId(b"appid".to_vec())],
+        }),
+        KeyMintOperation::DeviceDeleteKey => {
+            PerformOpReq::DeviceDeleteKey(DeleteKeyRequest { key_blob: keyblob() })
+        }
+        KeyMintOperation::DeviceDeleteAllKeys => {
+            PerformOpReq::DeviceDeleteAllKeys(DeleteAllKeysRequest {})
+        }
+        KeyMintOperation::DeviceDestroyAttestationIds => {
+            PerformOpReq::DeviceDestroyAttestationIds(DestroyAttestationIdsRequest {})
+        }
+        KeyMintOperation::DeviceBegin => PerformOpReq::DeviceBegin(BeginRequest {
+            purpose: KeyPurpose::Sign,
+            key_blob: keyblob(),
+            params: vec![KeyParam::Digest(Digest::Sha256)],
+            auth_token: Some(auth_token()),
+        }),
+        KeyMintOperation::DeviceEarlyBootEnded => {
+            PerformOpReq::DeviceEarlyBootEnded(EarlyBootEndedRequest {})
+        }
+        KeyMintOperation::DeviceConvertStorageKeyToEphemeral => {
+            PerformOpReq::DeviceConvertStorageKeyToEphemeral(ConvertStorageKeyToEphemeralRequest {
+                storage_key_blob: keyblob(),
+            })
+        }
+        KeyMintOperation::DeviceGetKeyCharacteristics => {
+            PerformOpReq::DeviceGetKeyCharacteristics(GetKeyCharacteristicsRequest {
+                key_blob: keyblob(),
+                app_id: b"appid".to_vec(),
+                app_data: vec![],
+            })
+        }
+        KeyMintOperation::OperationUpdateAad => {
+            PerformOpReq::OperationUpdateAad(UpdateAadRequest {
+                op_handle: OP_HANDLE,
+                input: b"aad".to_vec(),
+                auth_token: None,
+                timestamp_token: Some(timestamp_token()),
+            })
+        }
+        KeyMintOperation::OperationUpdate => PerformOpReq::OperationUpdate(UpdateRequest {
+            op_handle: OP_HANDLE,
+            input: b"input".to_vec(),
+            auth_token: Some(auth_token()),
+            timestamp_token: None,
+        }),
+        KeyMintOperation::OperationFinish => PerformOpReq::OperationFinish(FinishRequest {
+            op_handle: OP_HANDLE,
+            input: Some(b"input".to_vec()),
+            signature: Some(vec![0x5e; 8]),
+            auth_token: Some(auth_token()),
+            timestamp_token: Some(timestamp_token()),
+            confirmation_token: Some(vec![0xc0; 8]),
+        }),
+        KeyMintOperation::OperationAbort => {
+            PerformOpReq::OperationAbort(AbortRequest { op_handle: OP_HANDLE })
+        }
+        KeyMintOperation::OperationFinishDeriveKey => {
+            PerformOpReq::OperationFinishDeriveKey(FinishDeriveKeyRequest {
+                op_handle: OP_HANDLE,
+                input: Some(vec![0x04; 8]),
+                auth_token: None,
+                timestamp_token: None,
+                hkdf_salt: b"salt".to_vec(),
+                hkdf_info: b"info".to_vec(),
+                key_params: vec![
+                    KeyParam::Algorithm(Algorithm::Aes),
+                    KeyParam::KeySize(KeySizeInBits(256)),
+                ],
+            })
+        }
+        KeyMintOperation::RpcGetHardwareInfo => {
+            PerformOpReq::RpcGetHardwareInfo(GetRpcHardwareInfoRequest {})
+        }
+        KeyMintOperation::RpcGenerateEcdsaP256KeyPair => {
+            PerformOpReq::RpcGenerateEcdsaP256KeyPair(GenerateEcdsaP256KeyPairRequest {
+                test_mode: false,
+            })
+        }
+        KeyMintOperation::RpcGenerateCertificateRequest => {
+            PerformOpReq::RpcGenerateCertificateRequest(GenerateCertificateRequestRequest {
+                test_mode: true,
+                keys_to_sign: vec![maced_public_key()],
+                endpoint_encryption_cert_chain: vec![0x80],
+                challenge: b"challenge".to_vec(),
+            })
+        }
+        KeyMintOperation::RpcGenerateCertificateV2Request => {
+            PerformOpReq::RpcGenerateCertificateV2Request(GenerateCertificateRequestV2Request {
+                keys_to_sign: vec![maced_public_key()],
+                challenge: b"challenge".to_vec(),
+            })
+        }
+        KeyMintOperation::SharedSecretGetSharedSecretParameters => {
+            PerformOpReq::SharedSecretGetSharedSecretParameters(GetSharedSecretParametersRequest {})
+        }
+        KeyMintOperation::SharedSecretComputeSharedSecret => {
+            PerformOpReq::SharedSecretComputeSharedSecret(ComputeSharedSecretRequest {
+                params: vec![shared_secret_params(), shared_secret_params()],
+            })
+        }
+        KeyMintOperation::SecureClockGenerateTimeStamp => {
+            PerformOpReq::SecureClockGenerateTimeStamp(GenerateTimeStampRequest { challenge: 42 })
+        }
+        KeyMintOperation::GetRootOfTrustChallenge => {
+            PerformOpReq::GetRootOfTrustChallenge(GetRootOfTrustChallengeRequest {})
+        }
+        KeyMintOperation::GetRootOfTrust => {
+            PerformOpReq::GetRootOfTrust(GetRootOfTrustRequest { challenge: [0xcc; 16] })
+        }
+        KeyMintOperation::SendRootOfTrust => {
+            PerformOpReq::SendRootOfTrust(SendRootOfTrustRequest { root_of_trust: vec![0x84] })
+        }
+        KeyMintOperation::SetHalInfo => PerformOpReq::SetHalInfo(SetHalInfoRequest {
+            os_version: 150000,
+            os_patchlevel: 202601,
+            vendor_patchlevel: 20260105,
+        }),
+        KeyMintOperation::SetBootInfo => PerformOpReq::SetBootInfo(SetBootInfoRequest {
+            verified_boot_key: vec![0xbb; 32],
+            device_boot_locked: true,
+            verified_boot_state: 0,
+            verified_boot_hash: vec![0xbc; 32],
+            boot_patchlevel: 20260101,
+        }),
+        KeyMintOperation::SetAttestationIds => {
+            PerformOpReq::SetAttestationIds(SetAttestationIdsRequest {
+                ids: AttestationIdInfo {
+                    brand: b"brand".to_vec(),
+                    device: b"device".to_vec(),
+                    product: b"product".to_vec(),
+                    serial: b"serial".to_vec(),
+                    imei: b"imei".to_vec(),
+                    imei2: b"imei2".to_vec(),
+                    meid: b"meid".to_vec(),
+                    manufacturer: b"manufacturer".to_vec(),
+                    model: b"model".to_vec(),
+                },
+            })
+        }
+        KeyMintOperation::SetHalVersion => {
+            PerformOpReq::SetHalVersion(SetHalVersionRequest { aidl_version: 400 })
+        }
+        KeyMintOperation::SetAdditionalAttestationInfo => {
+            PerformOpReq::SetAdditionalAttestationInfo(SetAdditionalAttestationInfoRequest {
+                info: vec![KeyParam::ModuleHash(vec![0x4d; 32])],
+            })
+        }
+        KeyMintOperation::DrainAuditLog => PerformOpReq::DrainAuditLog(DrainAuditLogRequest {}),
+    }
+}
+
+/// Return a sample (successful) response for the given operation.
+pub fn sample_response(code: KeyMintOperation) -> PerformOpRsp {
+    match code {
+        KeyMintOperation::DeviceGetHardwareInfo => {
+            PerformOpRsp::DeviceGetHardwareInfo(GetHardwareInfoResponse {
+                ret: KeyMintHardwareInfo {
+                    version_number: 400,
+                    security_level: SecurityLevel::TrustedEnvironment,
+                    key_mint_name: "FakeKeyMintDevice".to_string(),
+                    key_mint_author_name: "Google".to_string(),
+                    timestamp_token_required: false,
+                },
+            })
+        }
+        KeyMintOperation::DeviceAddRngEntropy => {
+            PerformOpRsp::DeviceAddRngEntropy(AddRngEntropyResponse {})
+        }
+        KeyMintOperation::DeviceGenerateKey => {
+            PerformOpRsp::DeviceGenerateKey(GenerateKeyResponse { ret: creation_result() })
+        }
+        KeyMintOperation::DeviceImportKey => {
+            PerformOpRsp::DeviceImportKey(ImportKeyResponse { ret: creation_result() })
+        }
+        KeyMintOperation::DeviceImportWrappedKey => {
+            PerformOpRsp::DeviceImportWrappedKey(ImportWrappedKeyResponse {
+                ret: creation_result(),
+            })
+        }
+        KeyMintOperation::DeviceUpgradeKey => {
+            PerformOpRsp::DeviceUpgradeKey(UpgradeKeyResponse { ret: keyblob() })
+        }
+        KeyMintOperation::DeviceDeleteKey => PerformOpRsp::DeviceDeleteKey(DeleteKeyResponse {}),
+        KeyMintOperation::DeviceDeleteAllKeys => {
+            PerformOpRsp::DeviceDeleteAllKeys(DeleteAllKeysResponse {})
+        }
+        KeyMintOperation::DeviceDestroyAttestationIds => {
+            PerformOpRsp::DeviceDestroyAttestationIds(DestroyAttestationIdsResponse {})
+        }
+        KeyMintOperation::DeviceBegin => PerformOpRsp::DeviceBegin(BeginResponse {
+            ret: InternalBeginResult {
+                challenge: 0x1234,
+                params: vec![KeyParam::Nonce(vec![0x4e; 12])],
+                op_handle: OP_HANDLE,
+            },
+        }),
+        KeyMintOperation::DeviceEarlyBootEnded => {
+            PerformOpRsp::DeviceEarlyBootEnded(EarlyBootEndedResponse {})
+        }
+        KeyMintOperation::DeviceConvertStorageKeyToEphemeral => {
+            PerformOpRsp::DeviceConvertStorageKeyToEphemeral(ConvertStorageKeyToEphemeralResponse {
+                ret: vec![0xe0; 16],
+            })
+        }
+        KeyMintOperation::DeviceGetKeyCharacteristics => {
+            PerformOpRsp::DeviceGetKeyCharacteristics(GetKeyCharacteristicsResponse {
+                ret: characteristics(),
+            })
+        }
+        KeyMintOperation::OperationUpdateAad => {
+            PerformOpRsp::OperationUpdateAad(UpdateAadResponse {})
+        }
+        KeyMintOperation::OperationUpdate => {
+            PerformOpRsp::OperationUpdate(UpdateResponse { ret: b"output".to_vec() })
+        }
+        KeyMintOperation::OperationFinish => {
+            PerformOpRsp::OperationFinish(FinishResponse { ret: vec![0x5e; 8] })
+        }
+        KeyMintOperation::OperationAbort => PerformOpRsp::OperationAbort(AbortResponse {}),
+        KeyMintOperation::OperationFinishDeriveKey => {
+            PerformOpRsp::OperationFinishDeriveKey(FinishDeriveKeyResponse {
+                ret: KeyCreationResult {
+                    key_blob: keyblob(),
+                    key_characteristics: characteristics(),
+                    certificate_chain: vec![],
+                },
+            })
+        }
+        KeyMintOperation::RpcGetHardwareInfo => {
+            PerformOpRsp::RpcGetHardwareInfo(GetRpcHardwareInfoResponse {
+                ret: rpc::HardwareInfo {
+                    version_number: 3,
+                    rpc_author_name: "Google".to_string(),
+                    supported_eek_curve: rpc::EekCurve::None,
+                    unique_id: Some("FakeKeyMintDevice".to_string()),
+                    supported_num_keys_in_csr: rpc::MINIMUM_SUPPORTED_KEYS_IN_CSR,
+                },
+            })
+        }
+        KeyMintOperation::RpcGenerateEcdsaP256KeyPair => {
+            PerformOpRsp::RpcGenerateEcdsaP256KeyPair(GenerateEcdsaP256KeyPairResponse {
+                maced_public_key: maced_public_key(),
+                ret: keyblob(),
+            })
+        }
+        KeyMintOperation::RpcGenerateCertificateRequest => {
+            PerformOpRsp::RpcGenerateCertificateRequest(GenerateCertificateRequestResponse {
+                device_info: rpc::DeviceInfo { device_info: vec![0xa0] },
+                protected_data: rpc::ProtectedData { protected_data: vec![0x84] },
+                ret: vec![0x84],
+            })
+        }
+        KeyMintOperation::RpcGenerateCertificateV2Request => {
+            PerformOpRsp::RpcGenerateCertificateV2Request(GenerateCertificateRequestV2Response {
+                ret: vec![0x85],
+            })
+        }
+        KeyMintOperation::SharedSecretGetSharedSecretParameters => {
+            PerformOpRsp::SharedSecretGetSharedSecretParameters(GetSharedSecretParametersResponse {
+                ret: shared_secret_params(),
+            })
+        }
+        KeyMintOperation::SharedSecretComputeSharedSecret => {
+            PerformOpRsp::SharedSecretComputeSharedSecret(ComputeSharedSecretResponse {
+                ret: vec![0xcd; 32],
+            })
+        }
+        KeyMintOperation::SecureClockGenerateTimeStamp => {
+            PerformOpRsp::SecureClockGenerateTimeStamp(GenerateTimeStampResponse {
+                ret: timestamp_token(),
+            })
+        }
+        KeyMintOperation::GetRootOfTrustChallenge => {
+            PerformOpRsp::GetRootOfTrustChallenge(GetRootOfTrustChallengeResponse {
+                ret: [0xcc; 16],
+            })
+        }
+        KeyMintOperation::GetRootOfTrust => {
+            PerformOpRsp::GetRootOfTrust(GetRootOfTrustResponse { ret: vec![0x84] })
+        }
+        KeyMintOperation::SendRootOfTrust => {
+            PerformOpRsp::SendRootOfTrust(SendRootOfTrustResponse {})
+        }
+        KeyMintOperation::SetHalInfo => PerformOpRsp::SetHalInfo(SetHalInfoResponse {}),
+        KeyMintOperation::SetBootInfo => PerformOpRsp::SetBootInfo(SetBootInfoResponse {}),
+        KeyMintOperation::SetAttestationIds => {
+            PerformOpRsp::SetAttestationIds(SetAttestationIdsResponse {})
+        }
+        KeyMintOperation::SetHalVersion => PerformOpRsp::SetHalVersion(SetHalVersionResponse {}),
+        KeyMintOperation::SetAdditionalAttestationInfo => {
+            PerformOpRsp::SetAdditionalAttestationInfo(SetAdditionalAttestationInfoResponse {})
+        }
+        KeyMintOperation::DrainAuditLog => PerformOpRsp::DrainAuditLog(DrainAuditLogResponse {
+            records: vec![
+                AuditRecord {
+                    sequence: 7,
+                    timestamp_ms: Some(1_000),
+                    event: AuditEvent::KeyGenerated,
+                    key_id: Some(vec![0x1d; 32]),
+                    params: vec![KeyParam::Algorithm(Algorithm::Ec)],
+                    slot: Some(3),
+                    error: None,
+                },
+                AuditRecord {
+                    sequence: 8,
+                    timestamp_ms: None,
+                    event: AuditEvent::AuthFailed,
+                    key_id: None,
+                    params: vec![],
+                    slot: None,
+                    error: Some(ErrorCode::KeyUserNotAuthenticated),
+                },
+            ],
+            dropped: 2,
+        }),
+    }
+}
+
+/// Operation handle used in sample messages.
+const OP_HANDLE: i64 = 0x0102_0304_0506_0708;
+
+fn date(ms_since_epoch: i64) -> DateTime {
+    DateTime { ms_since_epoch }
+}
+
+fn keyblob() -> Vec<u8> {
+    vec![0x6b; 16]
+}
+
+fn key_params() -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+    ]
+}
+
+fn characteristics() -> Vec<KeyCharacteristics> {
+    vec![
+        KeyCharacteristics {
+            security_level: SecurityLevel::TrustedEnvironment,
+            authorizations: key_params(),
+        },
+        KeyCharacteristics {
+            security_level: SecurityLevel::Keystore,
+            authorizations: vec![KeyParam::CreationDatetime(date(1_600_000_000_000))],
+        },
+    ]
+}
+
+fn creation_result() -> KeyCreationResult {
+    KeyCreationResult {
+        key_blob: keyblob(),
+        key_characteristics: characteristics(),
+        certificate_chain: vec![
+            Certificate { encoded_certificate: vec![0x30, 0x01, 0x01] },
+            Certificate { encoded_certificate: vec![0x30, 0x01, 0x02] },
+        ],
+    }
+}
+
+fn attestation_key() -> AttestationKey {
+    AttestationKey {
+        key_blob: keyblob(),
+        attest_key_params: vec![],
+        issuer_subject_name: vec![0x30, 0x00],
+    }
+}
+
+fn auth_token() -> HardwareAuthToken {
+    HardwareAuthToken {
+        challenge: 0x1234,
+        user_id: 10,
+        authenticator_id: 20,
+        authenticator_type: HardwareAuthenticatorType::Password,
+        timestamp: Timestamp { milliseconds: 1_000 },
+        mac: vec![0x3a; 32],
+    }
+}
+
+fn timestamp_token() -> TimeStampToken {
+    TimeStampToken {
+        challenge: 0x1234,
+        timestamp: Timestamp { milliseconds: 2_000 },
+        mac: vec![0x3b; 32],
+    }
+}
+
+fn maced_public_key() -> rpc::MacedPublicKey {
+    rpc::MacedPublicKey { maced_key: vec![0x84, 0x40, 0xa0, 0x40, 0x40] }
+}
+
+fn shared_secret_params() -> SharedSecretParameters {
+    SharedSecretParameters { seed: vec![], nonce: vec![0x55; 32] }
+}
@@ -0,0 +1,487 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: a failed allocation while the TA is processing a request results in a
+// `MemoryAllocationFailed` error, rather than a panic or a leaked secure deletion slot.
+
+use kmr_common::{
+    crypto,
+    keyblob::{
+        sdd_mem::InMemorySlotManager, SecureDeletionData, SecureDeletionSecretManager,
+        SecureDeletionSlot, SlotPurpose,
+    },
+    Error,
+};
+use kmr_ta::{device, KeyMintTa};
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{
+    Algorithm, AttestationKey, BlockMode, Digest, EcCurve, ErrorCode, KeyParam, KeyPurpose,
+    PaddingMode, UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+use x509_cert::der::{Decode, Encode};
+
+/// Global allocator that fails the Nth allocation made by the current thread, once armed.
+///
+/// An infallible allocation (e.g. `Vec::push()`) that fails aborts the process, so the Nth
+/// allocation is only failed if it is made through `try_reserve()`, as used by the fallible
+/// allocation helpers in `kmr-wire` and `kmr-common`.  Otherwise the Nth allocation succeeds, and
+/// the code that made it is checked against [`INFALLIBLE_ALLOWED`].
+struct FailingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: FailingAllocator = FailingAllocator;
+
+/// Functions that are known to allocate infallibly while the TA processes the requests exercised
+/// here.  An entry matches if it occurs in the name of the innermost `kmr_*` function that called
+/// the allocator.  Any other infallible allocation makes the test fail.
+const INFALLIBLE_ALLOWED: &[&str] = &[
+    // CBOR encoding and decoding, which goes through `ciborium` and `coset`.
+    "kmr_wire::read_to_value",
+    "kmr_wire::AsCborValue::into_vec",
+    " as kmr_wire::AsCborValue>::",
+    "kmr_common::crypto::ec::<impl kmr_common::crypto::OpaqueOr<kmr_common::crypto::ec::Key>>::public_cose_key",
+    "kmr_ta::device::RetrieveRpcArtifacts::sign_data_in_cose_sign1",
+    "kmr_ta::KeyMintTa::get_dice_info",
+    "kmr_ta::rkp::",
+    // Certificate construction, which goes through the `der` and `x509-cert` crates.
+    "kmr_ta::cert::",
+    "kmr_common::crypto::traits::Ec::subject_public_key",
+    // Keyblob encryption and decryption.
+    "kmr_common::keyblob::encrypt",
+    "kmr_common::keyblob::decrypt",
+    "kmr_ta::keys::<impl kmr_ta::KeyMintTa>::finish_keyblob_creation",
+    // Copies of key characteristics, key material and boot information.
+    " as core::clone::Clone>::clone",
+    // The BoringSSL crypto implementation.
+    "kmr_crypto_boring::",
+    // Test fixtures.
+    "kmr_tests::",
+    "alloc_failure_test::",
+];
+
+thread_local! {
+    /// Number of allocations until the Nth, or zero if not armed.
+    static COUNTDOWN: Cell<usize> = const { Cell::new(0) };
+    /// What happened to the Nth allocation, if it has been reached since arming.
+    static NTH: RefCell<Option<Nth>> = const { RefCell::new(None) };
+    /// Set while examining the caller of an allocation, whose own allocations are not counted.
+    static EXAMINING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// What happened to the Nth allocation.
+enum Nth {
+    /// The allocation was fallible, and failed.
+    Failed,
+    /// The allocation was infallible, from a function in [`INFALLIBLE_ALLOWED`].
+    Allowed,
+    /// The allocation was infallible, from the given function.
+    Unexpected(String),
+    /// The backtrace had no symbols, so the caller could not be identified.
+    Unsymbolized,
+}
+
+/// Indicate whether the current allocation should fail.
+fn should_fail() -> bool {
+    let countdown = COUNTDOWN.get();
+    if countdown == 0 || EXAMINING.get() {
+        return false;
+    }
+    COUNTDOWN.set(countdown - 1);
+    if countdown > 1 {
+        return false;
+    }
+    EXAMINING.set(true);
+    let nth = examine(&format!("{:?}", Backtrace::force_capture()));
+    let fail = matches!(nth, Nth::Failed);
+    NTH.set(Some(nth));
+    EXAMINING.set(false);
+    fail
+}
+
+/// Classify an allocation from the (`Debug`-formatted) backtrace of the call to the allocator.
+fn examine(backtrace: &str) -> Nth {
+    // Function names appear as `fn: "<name>"`.
+    let mut fns = backtrace.split("fn: \"").skip(1).filter_map(|f| f.split('"').next());
+    if !fns.clone().any(|f| f.contains("should_fail")) {
+        return Nth::Unsymbolized;
+    }
+    if fns.clone().any(|f| f.contains("try_reserve")) {
+        return Nth::Failed;
+    }
+    match fns.find(|f| f.contains("kmr_")) {
+        Some(f) if INFALLIBLE_ALLOWED.iter().any(|allowed| f.contains(allowed)) => Nth::Allowed,
+        Some(f) => Nth::Unexpected(f.to_string()),
+        None => Nth::Unexpected("<no kmr_* caller>".to_string()),
+    }
+}
+
+unsafe impl GlobalAlloc for FailingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if should_fail() {
+            return std::ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if should_fail() {
+            return std::ptr::null_mut();
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if should_fail() {
+            return std::ptr::null_mut();
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Outcome of processing a request with the Nth allocation armed.
+enum Outcome {
+    /// The Nth allocation was never reached.
+    NotReached,
+    /// The Nth allocation was reached, but was not failed because it was infallible.
+    NotInjected(PerformOpResponse),
+    /// The Nth allocation failed.
+    Injected(PerformOpResponse),
+    /// The Nth allocation was an infallible allocation that is not in [`INFALLIBLE_ALLOWED`].
+    Unexpected(String),
+}
+
+/// Process a request with the `n`-th allocation made by the TA armed.
+fn process_failing(ta: &mut KeyMintTa, req: PerformOpReq, n: usize) -> Outcome {
+    let req_data = req.into_vec().unwrap();
+    NTH.set(None);
+    COUNTDOWN.set(n);
+    let result = catch_unwind(AssertUnwindSafe(|| ta.process(&req_data)));
+    COUNTDOWN.set(0);
+    let rsp_data = match result {
+        Ok(rsp_data) => rsp_data,
+        Err(_) => panic!("TA panicked with allocation {n} failing"),
+    };
+    let rsp = PerformOpResponse::from_slice(&rsp_data).unwrap();
+    match NTH.take() {
+        None => Outcome::NotReached,
+        Some(Nth::Failed) => Outcome::Injected(rsp),
+        Some(Nth::Allowed) => Outcome::NotInjected(rsp),
+        Some(Nth::Unexpected(site)) => Outcome::Unexpected(site),
+        Some(Nth::Unsymbolized) => {
+            panic!("backtraces have no symbols, so allocations can't be checked; build with debug info")
+        }
+    }
+}
+
+/// Run `flow` with the Nth allocation failing, for every N until the flow completes without
+/// reaching the Nth allocation.  Each run of the flow is given a fresh TA and context (as built by
+/// `setup`), and returns the outcome of the request that was processed with the failure armed.
+/// Any error must be `expected_err`.
+fn for_each_failure<T, S, F>(name: &str, expected_err: i32, setup: S, flow: F)
+where
+    S: Fn() -> (KeyMintTa, SlotCounter, T),
+    F: Fn(&mut KeyMintTa, &T, usize) -> Outcome,
+{
+    let mut injected = 0;
+    let mut unexpected = BTreeMap::new();
+    for n in 1.. {
+        let (mut km, slots, context) = setup();
+        let slots_before = slots.live();
+        let rsp = match flow(&mut km, &context, n) {
+            Outcome::NotReached => break,
+            Outcome::Unexpected(site) => {
+                unexpected.entry(site).or_insert(n);
+                continue;
+            }
+            Outcome::NotInjected(rsp) => {
+                assert_eq!(rsp.error_code, 0, "{name}: failed with no allocation failure");
+                rsp
+            }
+            Outcome::Injected(rsp) => {
+                injected += 1;
+                assert!(
+                    rsp.error_code == 0 || rsp.error_code == expected_err,
+                    "{name}: allocation {n} failing gave error {}",
+                    rsp.error_code
+                );
+                rsp
+            }
+        };
+        let created = match rsp.rsp {
+            Some(PerformOpRsp::DeviceGenerateKey(_)) => 1,
+            Some(PerformOpRsp::DeviceUpgradeKey(rsp)) if !rsp.ret.is_empty() => 1,
+            _ => 0,
+        };
+        assert_eq!(
+            slots.live(),
+            slots_before + created,
+            "{name}: secure deletion slot leaked with allocation {n} failing"
+        );
+    }
+    assert!(
+        unexpected.is_empty(),
+        "{name}: infallible allocations (with the first N that reached them): {unexpected:#?}"
+    );
+    assert!(injected > 0, "{name}: no fallible allocations exercised");
+}
+
+/// Secure deletion secret manager that keeps track of the number of slots in use, and which can
+/// be inspected while it is owned by a TA.
+#[derive(Clone, Default)]
+struct SlotCounter(Rc<RefCell<(InMemorySlotManager<16>, BTreeSet<u32>)>>);
+
+impl SlotCounter {
+    fn live(&self) -> usize {
+        self.0.borrow().1.len()
+    }
+}
+
+impl SecureDeletionSecretManager for SlotCounter {
+    fn get_or_create_factory_reset_secret(
+        &mut self,
+        rng: &mut dyn crypto::Rng,
+    ) -> Result<SecureDeletionData, Error> {
+        self.0.borrow_mut().0.get_or_create_factory_reset_secret(rng)
+    }
+
+    fn get_factory_reset_secret(&self) -> Result<SecureDeletionData, Error> {
+        self.0.borrow().0.get_factory_reset_secret()
+    }
+
+    fn new_secret(
+        &mut self,
+        rng: &mut dyn crypto::Rng,
+        purpose: SlotPurpose,
+    ) -> Result<(SecureDeletionSlot, SecureDeletionData), Error> {
+        let mut state = self.0.borrow_mut();
+        let (slot, sdd) = state.0.new_secret(rng, purpose)?;
+        state.1.insert(slot.0);
+        Ok((slot, sdd))
+    }
+
+    fn get_secret(&self, slot: SecureDeletionSlot) -> Result<SecureDeletionData, Error> {
+        self.0.borrow().0.get_secret(slot)
+    }
+
+    fn delete_secret(&mut self, slot: SecureDeletionSlot) -> Result<(), Error> {
+        let mut state = self.0.borrow_mut();
+        state.0.delete_secret(slot)?;
+        state.1.remove(&slot.0);
+        Ok(())
+    }
+
+    fn delete_all(&mut self) {
+        let mut state = self.0.borrow_mut();
+        state.0.delete_all();
+        state.1.clear();
+    }
+}
+
+/// Create a device implementation with secure storage (in `slots`) and IRPC artifacts.
+fn device_impl(slots: &SlotCounter) -> device::Implementation {
+    let mut dev = host::device_impl();
+    dev.sdd_mgr = Some(Box::new(slots.clone()));
+    dev.rpc = Box::new(host::TestRpcArtifacts);
+    dev
+}
+
+/// Create a TA with secure storage and IRPC artifacts.
+fn keymint_ta() -> (KeyMintTa, SlotCounter) {
+    let slots = SlotCounter::default();
+    let km = host::keymint_ta(host::crypto_impl(None), device_impl(&slots));
+    km.set_attestation_ids(AttestationIdInfo {
+        brand: b"brand".to_vec(),
+        device: b"device".to_vec(),
+        product: b"product".to_vec(),
+        serial: b"serial".to_vec(),
+        imei: b"imei".to_vec(),
+        imei2: b"imei2".to_vec(),
+        meid: b"meid".to_vec(),
+        manufacturer: b"manufacturer".to_vec(),
+        model: b"model".to_vec(),
+    });
+    (km, slots)
+}
+
+#[test]
+fn test_alloc_failure_keygen_attested() {
+    let setup = || {
+        let (mut km, slots) = keymint_ta();
+        let attest_key = host::generate_key(
+            &mut km,
+            vec![
+                KeyParam::Algorithm(Algorithm::Ec),
+                KeyParam::EcCurve(EcCurve::P256),
+                KeyParam::Purpose(KeyPurpose::AttestKey),
+                KeyParam::NoAuthRequired,
+                KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+                KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+            ],
+        )
+        .unwrap();
+        (km, slots, attest_key)
+    };
+    let (_, _, attest_key) = setup();
+    let attest_cert =
+        x509_cert::Certificate::from_der(&attest_key.certificate_chain[0].encoded_certificate)
+            .unwrap();
+    let issuer_subject_name = attest_cert.tbs_certificate.subject.to_der().unwrap();
+
+    for_each_failure(
+        "keygen",
+        ErrorCode::MemoryAllocationFailed as i32,
+        setup,
+        |km, attest_key, n| {
+            let req = PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+                key_params: vec![
+                    KeyParam::Algorithm(Algorithm::Ec),
+                    KeyParam::EcCurve(EcCurve::P256),
+                    KeyParam::Purpose(KeyPurpose::Sign),
+                    KeyParam::Digest(Digest::Sha256),
+                    KeyParam::NoAuthRequired,
+                    KeyParam::RollbackResistance,
+                    KeyParam::AttestationChallenge(b"challenge".to_vec()),
+                    KeyParam::AttestationApplicationId(b"app-id".to_vec()),
+                    KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+                    KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+                ],
+                attestation_key: Some(AttestationKey {
+                    key_blob: attest_key.key_blob.clone(),
+                    attest_key_params: vec![],
+                    issuer_subject_name: issuer_subject_name.clone(),
+                }),
+            });
+            process_failing(km, req, n)
+        },
+    );
+}
+
+#[test]
+fn test_alloc_failure_csr() {
+    for_each_failure(
+        "CSR",
+        rpc::ErrorCode::Failed as i32,
+        || {
+            let (mut km, slots) = keymint_ta();
+            match exec(
+                &mut km,
+                PerformOpReq::RpcGenerateEcdsaP256KeyPair(GenerateEcdsaP256KeyPairRequest {
+                    test_mode: false,
+                }),
+            ) {
+                Ok(PerformOpRsp::RpcGenerateEcdsaP256KeyPair(rsp)) => {
+                    (km, slots, rsp.maced_public_key)
+                }
+                _ => panic!("RKP key generation failed"),
+            }
+        },
+        |km, maced_key, n| {
+            let req = PerformOpReq::RpcGenerateCertificateV2Request(
+                GenerateCertificateRequestV2Request {
+                    keys_to_sign: vec![maced_key.clone()],
+                    challenge: b"challenge".to_vec(),
+                },
+            );
+            process_failing(km, req, n)
+        },
+    );
+}
+
+#[test]
+fn test_alloc_failure_large_update() {
+    for_each_failure(
+        "update",
+        ErrorCode::MemoryAllocationFailed as i32,
+        || {
+            let (mut km, slots) = keymint_ta();
+            let key = host::generate_key(
+                &mut km,
+                vec![
+                    KeyParam::Algorithm(Algorithm::Aes),
+                    KeyParam::KeySize(KeySizeInBits(256)),
+                    KeyParam::Purpose(KeyPurpose::Encrypt),
+                    KeyParam::BlockMode(BlockMode::Cbc),
+                    KeyParam::Padding(PaddingMode::None),
+                    KeyParam::NoAuthRequired,
+                ],
+            )
+            .unwrap();
+            let params =
+                vec![KeyParam::BlockMode(BlockMode::Cbc), KeyParam::Padding(PaddingMode::None)];
+            let op_handle =
+                host::begin(&mut km, KeyPurpose::Encrypt, &key.key_blob, params).unwrap().op_handle;
+            (km, slots, op_handle)
+        },
+        |km, op_handle, n| {
+            let req = PerformOpReq::OperationUpdate(UpdateRequest {
+                op_handle: *op_handle,
+                input: vec![0x42; 64 * 1024],
+                auth_token: None,
+                timestamp_token: None,
+            });
+            process_failing(km, req, n)
+        },
+    );
+}
+
+#[test]
+fn test_alloc_failure_upgrade() {
+    for_each_failure(
+        "upgrade",
+        ErrorCode::MemoryAllocationFailed as i32,
+        || {
+            // Create a key on a TA at an earlier patchlevel, so that the current TA (which shares
+            // the same secure storage) needs to upgrade it.
+            let slots = SlotCounter::default();
+            let mut old_km = host::keymint_ta_at_patchlevel(
+                host::crypto_impl(None),
+                device_impl(&slots),
+                20250101,
+            );
+            let key = host::generate_key(
+                &mut old_km,
+                vec![
+                    KeyParam::Algorithm(Algorithm::Aes),
+                    KeyParam::KeySize(KeySizeInBits(128)),
+                    KeyParam::Purpose(KeyPurpose::Encrypt),
+                    KeyParam::BlockMode(BlockMode::Ecb),
+                    KeyParam::Padding(PaddingMode::None),
+                    KeyParam::NoAuthRequired,
+                    KeyParam::RollbackResistance,
+                ],
+            )
+            .unwrap();
+            let km = host::keymint_ta(host::crypto_impl(None), device_impl(&slots));
+            (km, slots, key.key_blob)
+        },
+        |km, key_blob, n| {
+            let req = PerformOpReq::DeviceUpgradeKey(UpgradeKeyRequest {
+                key_blob_to_upgrade: key_blob.clone(),
+                upgrade_params: vec![],
+            });
+            process_failing(km, req, n)
+        },
+    );
+}
@@ -0,0 +1,202 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: security-relevant events are recorded in the TA's audit log (a vendor
+// extension), and passed to the device audit sink.
+
+use kmr_ta::{device, KeyMintTa};
+use kmr_tests::host::{self, exec, TestClock};
+use kmr_wire::audit::{AuditEvent, AuditRecord};
+use kmr_wire::keymint::{
+    Algorithm, ErrorCode, HardwareAuthenticatorType, KeyOrigin, KeyParam, VerifiedBootState,
+};
+use kmr_wire::*;
+use std::sync::{Arc, Mutex};
+
+const DATA: &[u8] = b"data";
+
+/// Audit sink that keeps a copy of every record.
+#[derive(Clone, Default)]
+struct TestSink(Arc<Mutex<Vec<AuditRecord>>>);
+
+impl device::AuditSink for TestSink {
+    fn record(&self, record: &AuditRecord) {
+        self.0.lock().unwrap().push(record.clone());
+    }
+}
+
+fn keymint_ta(sink: &TestSink) -> KeyMintTa {
+    let mut dev = host::sdd_device_impl();
+    dev.audit = Some(Box::new(sink.clone()));
+    host::keymint_ta(host::crypto_impl(Some(TestClock::default())), dev)
+}
+
+fn drain(km: &mut KeyMintTa) -> DrainAuditLogResponse {
+    match exec(km, PerformOpReq::DrainAuditLog(DrainAuditLogRequest {})) {
+        Ok(PerformOpRsp::DrainAuditLog(rsp)) => rsp,
+        _ => panic!("failed to drain audit log"),
+    }
+}
+
+/// Drain the audit log, returning the events recorded.
+fn drain_events(km: &mut KeyMintTa) -> Vec<AuditEvent> {
+    drain(km).records.iter().map(|r| r.event).collect()
+}
+
+#[test]
+fn test_audit_key_lifecycle() {
+    let sink = TestSink::default();
+    let mut km = keymint_ta(&sink);
+    let boot = drain(&mut km);
+    assert_eq!(boot.records.len(), 1);
+    assert_eq!(boot.records[0].event, AuditEvent::BootInfoChanged);
+    assert!(boot.records[0].timestamp_ms.is_some());
+
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::RollbackResistance])
+            .unwrap();
+    let rsp = drain(&mut km);
+    let events: Vec<_> = rsp.records.iter().map(|r| r.event).collect();
+    assert_eq!(events, vec![AuditEvent::SecureDeletionSlotCreated, AuditEvent::KeyGenerated]);
+    let created = &rsp.records[1];
+    let key_id = created.key_id.clone().expect("no key ID");
+    assert_eq!(key_id.len(), 32);
+    assert!(created.slot.is_some());
+    assert_eq!(rsp.records[0].slot, created.slot);
+    assert!(created.params.contains(&KeyParam::Algorithm(Algorithm::Hmac)));
+    assert!(created.params.contains(&KeyParam::Origin(KeyOrigin::Generated)));
+    assert!(created.params.contains(&KeyParam::RollbackResistance));
+    // Only a summary of the characteristics is recorded.
+    assert!(!created.params.contains(&KeyParam::NoAuthRequired));
+
+    exec(
+        &mut km,
+        PerformOpReq::DeviceDeleteKey(DeleteKeyRequest { key_blob: key.key_blob.clone() }),
+    )
+    .unwrap();
+    let rsp = drain(&mut km);
+    let events: Vec<_> = rsp.records.iter().map(|r| r.event).collect();
+    assert_eq!(events, vec![AuditEvent::SecureDeletionSlotDeleted, AuditEvent::KeyDeleted]);
+    assert_eq!(rsp.records[1].key_id.as_ref(), Some(&key_id));
+    assert_eq!(rsp.records[1].slot, created.slot);
+
+    exec(&mut km, PerformOpReq::DeviceDeleteAllKeys(DeleteAllKeysRequest {})).unwrap();
+    assert_eq!(drain_events(&mut km), vec![AuditEvent::AllKeysDeleted]);
+
+    // The sink saw everything that was drained, with increasing sequence numbers.
+    let seen = sink.0.lock().unwrap();
+    assert_eq!(seen.len(), 6);
+    assert!(seen.iter().enumerate().all(|(idx, r)| r.sequence == idx as u64));
+}
+
+#[test]
+fn test_audit_failures() {
+    let sink = TestSink::default();
+    let mut km = keymint_ta(&sink);
+    drain(&mut km);
+
+    // An auth-bound key used without an auth token.
+    let key = host::generate_hmac_key(
+        &mut km,
+        &[
+            KeyParam::UserSecureId(42),
+            KeyParam::UserAuthType(HardwareAuthenticatorType::Password as u32),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        host::hmac_sign(&mut km, &key.key_blob, &[], DATA),
+        Err(ErrorCode::KeyUserNotAuthenticated)
+    );
+    let rsp = drain(&mut km);
+    let failure = rsp.records.last().unwrap();
+    assert_eq!(failure.event, AuditEvent::AuthFailed);
+    assert_eq!(failure.error, Some(ErrorCode::KeyUserNotAuthenticated));
+
+    // A key with a per-boot use limit.
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::MaxUsesPerBoot(1)])
+            .unwrap();
+    let key_id = drain(&mut km).records.last().unwrap().key_id.clone();
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok());
+    assert_eq!(
+        host::hmac_sign(&mut km, &key.key_blob, &[], DATA),
+        Err(ErrorCode::KeyMaxOpsExceeded)
+    );
+    let rsp = drain(&mut km);
+    assert_eq!(rsp.records.len(), 1);
+    assert_eq!(rsp.records[0].event, AuditEvent::UseCountExhausted);
+    assert_eq!(rsp.records[0].key_id, key_id);
+    assert_eq!(rsp.records[0].params, vec![KeyParam::MaxUsesPerBoot(1)]);
+
+    // A single-use rollback-resistant key.
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::UsageCountLimit(1)])
+            .unwrap();
+    drain(&mut km);
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok());
+    assert_eq!(
+        drain_events(&mut km),
+        vec![AuditEvent::UseCountExhausted, AuditEvent::SecureDeletionSlotDeleted]
+    );
+
+    // Attestation IDs can't be destroyed on this device, but the attempt is still recorded.
+    assert_eq!(
+        exec(&mut km, PerformOpReq::DeviceDestroyAttestationIds(DestroyAttestationIdsRequest {}))
+            .err(),
+        Some(ErrorCode::Unimplemented)
+    );
+    let rsp = drain(&mut km);
+    assert_eq!(rsp.records[0].event, AuditEvent::AttestationIdsDestroyed);
+    assert_eq!(rsp.records[0].error, Some(ErrorCode::Unimplemented));
+
+    // An attempt to change the boot information.
+    let result = exec(
+        &mut km,
+        PerformOpReq::SetBootInfo(SetBootInfoRequest {
+            verified_boot_key: vec![1; 32],
+            device_boot_locked: true,
+            verified_boot_state: VerifiedBootState::Verified as i32,
+            verified_boot_hash: vec![1; 32],
+            boot_patchlevel: 20260101,
+        }),
+    );
+    assert_eq!(result.err(), Some(ErrorCode::RootOfTrustAlreadySet));
+    let rsp = drain(&mut km);
+    assert_eq!(rsp.records[0].event, AuditEvent::BootInfoChanged);
+    assert_eq!(rsp.records[0].error, Some(ErrorCode::RootOfTrustAlreadySet));
+}
+
+#[test]
+fn test_audit_log_overflow() {
+    let sink = TestSink::default();
+    let mut km = keymint_ta(&sink);
+    drain(&mut km);
+
+    // Each deletion of an unparseable keyblob records a single event.
+    for _ in 0..40 {
+        exec(&mut km, PerformOpReq::DeviceDeleteKey(DeleteKeyRequest { key_blob: vec![1, 2, 3] }))
+            .unwrap();
+    }
+    let rsp = drain(&mut km);
+    assert_eq!(rsp.records.len() as u64 + rsp.dropped, 40);
+    assert!(rsp.dropped > 0);
+    // The most recent events are retained.
+    let last = sink.0.lock().unwrap().last().unwrap().sequence;
+    assert_eq!(rsp.records.last().unwrap().sequence, last);
+
+    let rsp = drain(&mut km);
+    assert!(rsp.records.is_empty());
+    assert_eq!(rsp.dropped, 0);
+}
@@ -0,0 +1,180 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: the encodings of HAL<->TA messages and of keyblobs conform to the checked-in
+// CDDL schemas that are generated from the same types.
+
+use kmr_tests::cddl::Schema;
+use kmr_tests::host;
+use kmr_tests::wire_corpus::{self, PARAM_PREFIX, REQ_PREFIX, RSP_PREFIX};
+use kmr_wire::keymint::{Algorithm, KeyParam, KeyPurpose};
+use kmr_wire::*;
+
+const GENERATED_CDDL: &str = include_str!("../../common/generated.cddl");
+const KEYBLOB_CDDL: &str = include_str!("../../common/src/keyblob/keyblob.cddl");
+
+#[test]
+fn test_cddl_schemas_complete() {
+    for (name, text) in [("generated.cddl", GENERATED_CDDL), ("keyblob.cddl", KEYBLOB_CDDL)] {
+        let schema = Schema::parse(text).unwrap_or_else(|e| panic!("failed to parse {name}: {e}"));
+        assert_eq!(schema.undefined_refs(), Vec::<String>::new(), "undefined types in {name}");
+    }
+}
+
+#[test]
+fn test_cddl_wire_corpus() {
+    // If this fails after a change to the wire types, run `scripts/cddl-gen` to regenerate the
+    // schema.
+    let schema = Schema::parse(GENERATED_CDDL).unwrap();
+    for entry in wire_corpus::entries() {
+        let rule = if entry.name.starts_with(REQ_PREFIX) {
+            "PerformOpReq"
+        } else if entry.name.starts_with(RSP_PREFIX) {
+            "PerformOpResponse"
+        } else if entry.name.starts_with(PARAM_PREFIX) {
+            "KeyParam"
+        } else {
+            panic!("unexpected corpus entry {}", entry.name);
+        };
+        if let Err(e) = schema.validate_slice(rule, &entry.data) {
+            panic!("{} does not match schema: {e}", entry.name);
+        }
+    }
+}
+
+#[test]
+fn test_cddl_ta_traffic() {
+    let schema = Schema::parse(GENERATED_CDDL).unwrap();
+    let keyblob_schema = Schema::parse(KEYBLOB_CDDL).unwrap();
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+
+    let req = PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+        key_params: vec![
+            KeyParam::Algorithm(Algorithm::Aes),
+            KeyParam::KeySize(KeySizeInBits(128)),
+            KeyParam::Purpose(KeyPurpose::Encrypt),
+            KeyParam::NoAuthRequired,
+        ],
+        attestation_key: None,
+    })
+    .into_vec()
+    .unwrap();
+    schema.validate_slice("PerformOpReq", &req).unwrap();
+    let rsp = km.process(&req);
+    schema.validate_slice("PerformOpResponse", &rsp).unwrap();
+
+    let key_blob = match PerformOpResponse::from_slice(&rsp).unwrap().rsp {
+        Some(PerformOpRsp::DeviceGenerateKey(rsp)) => rsp.ret.key_blob,
+        _ => panic!("key generation failed"),
+    };
+    keyblob_schema.validate_slice("EncryptedKeyBlob", &key_blob).unwrap();
+
+    // An error response has no inner response.
+    let rsp = km.process(&[0x82, 0x00, 0x80]);
+    assert_ne!(PerformOpResponse::from_slice(&rsp).unwrap().error_code, 0);
+    schema.validate_slice("PerformOpResponse", &rsp).unwrap();
+}
+
+#[test]
+fn test_cddl_mismatch() {
+    let schema = Schema::parse(GENERATED_CDDL).unwrap();
+    let tests = [
+        // `DeviceAddRngEntropy` with a text string instead of a byte string.
+        ("8212816161", "PerformOpReq[1][0]"),
+        // Unknown operation code.
+        ("821901ff80", "PerformOpReq[0]"),
+        // `DeviceDeleteKey` with an extra field.
+        ("821782410000", "PerformOpReq[1][1]"),
+    ];
+    for (data, want_path) in tests {
+        let err = schema.validate_slice("PerformOpReq", &hex::decode(data).unwrap()).unwrap_err();
+        assert!(err.starts_with(&format!("{want_path}:")), "for {data} got {err}");
+    }
+
+    // Boolean key parameters must hold `true`.
+    let data = KeyParam::NoAuthRequired.into_vec().unwrap();
+    schema.validate_slice("KeyParam", &data).unwrap();
+    let mut data = data;
+    *data.last_mut().unwrap() = 0xf4; // false
+    assert!(schema.validate_slice("KeyParam", &data).is_err());
+}
+
+#[test]
+fn test_cddl_features() {
+    let schema = Schema::parse(
+        r#"
+        ; Comment.
+        Top = [
+            a: uint,
+            ? b: Choice,
+            * c: Fixed,
+        ]
+        Choice = tstr / nil
+        Fixed = bstr .size 2
+        Map = {
+            0 => int, ; Comment
+            ? 1 => Named,
+            * int => any,
+        }
+        Named = &(
+            Named_One: 0x01,
+            Named_Two: -2,
+        )
+        Tagged = #6.24(Map)
+        Pair = [Named_Two, true]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(schema.undefined_refs(), Vec::<String>::new());
+
+    let good = [
+        ("Top", "8100"),
+        ("Top", "8200f6"),
+        ("Top", "8300614142aaaa"),
+        ("Top", "8400f642aaaa42bbbb"),
+        ("Top", "820042aaaa"),
+        ("Map", "a10005"),
+        ("Map", "a3000501210a00"),
+        ("Map", "a3000501010a80"),
+        ("Tagged", "d818a10000"),
+        ("Pair", "8221f5"),
+    ];
+    for (rule, data) in good {
+        let data = hex::decode(data).unwrap();
+        schema.validate_slice(rule, &data).unwrap_or_else(|e| panic!("for {rule}: {e}"));
+    }
+    let bad = [
+        ("Top", "80", "Top[0]"),
+        ("Top", "8120", "Top[0]"),
+        ("Top", "8300616141aa", "Top[2]"),
+        ("Top", "83000142aaaa", "Top[1]"),
+        ("Map", "a0", "Map"),
+        ("Map", "a10101", "Map"),
+        ("Map", "a200050103", "Map{1}"),
+        ("Map", "a10060", "Map{0}"),
+        ("Map", "a20005616100", "Map"),
+        ("Tagged", "d819a10000", "Tagged"),
+        ("Pair", "8221f4", "Pair[1]"),
+        ("Missing", "00", "Missing"),
+    ];
+    for (rule, data, want_path) in bad {
+        let err = schema.validate_slice(rule, &hex::decode(data).unwrap()).unwrap_err();
+        assert!(err.starts_with(&format!("{want_path}:")), "for {rule} {data} got {err}");
+    }
+
+    assert!(Schema::parse("A = [int").is_err());
+    assert!(Schema::parse("A = bstr .cbor B").is_err());
+    assert!(Schema::parse("A = int\nA = bstr").is_err());
+    assert_eq!(Schema::parse("A = [* B]").unwrap().undefined_refs(), vec!["B".to_string()]);
+}
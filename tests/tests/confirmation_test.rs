@@ -0,0 +1,133 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: confirmation tokens issued by the ConfirmationUI companion are accepted by
+// KeyMint for keys that require trusted confirmation.
+
+use kmr_ta::confirmation::{
+    format_message, ConfirmationResult, ConfirmationTa, HeadlessConfirmationUi, UserResponse,
+};
+use kmr_ta::KeyMintTa;
+use kmr_tests::host;
+use kmr_wire::keymint::{
+    Algorithm, Digest, EcCurve, ErrorCode, KeyParam, KeyPurpose, UNDEFINED_NOT_AFTER,
+    UNDEFINED_NOT_BEFORE,
+};
+
+const PROMPT: &str = "Transfer 10 units to Bob?";
+const EXTRA: &[u8] = b"nonce-1234";
+
+/// Build KeyMint and ConfirmationUI TAs that have agreed an HMAC key, with the UI giving the
+/// provided responses.
+fn setup(responses: Vec<UserResponse>) -> (KeyMintTa, ConfirmationTa) {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let mut conf = ConfirmationTa::new(
+        host::crypto_impl(None),
+        Box::new(host::TestKeys),
+        Box::new(HeadlessConfirmationUi::new(responses)),
+    );
+    let params =
+        vec![host::get_shared_secret_params(&mut km), conf.get_shared_secret_params().unwrap()];
+    let km_check = host::compute_shared_secret(&mut km, &params);
+    assert_eq!(conf.compute_shared_secret(&params).unwrap(), km_check);
+    (km, conf)
+}
+
+fn generate_ec_key(km: &mut KeyMintTa) -> Vec<u8> {
+    let key_params = vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+        KeyParam::TrustedConfirmationRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ];
+    host::generate_key(km, key_params).expect("key generation failed").key_blob
+}
+
+fn sign(
+    km: &mut KeyMintTa,
+    key_blob: &[u8],
+    message: &[u8],
+    confirmation_token: Option<Vec<u8>>,
+) -> Result<Vec<u8>, ErrorCode> {
+    let params = vec![KeyParam::Digest(Digest::Sha256)];
+    let op_handle = host::begin(km, KeyPurpose::Sign, key_blob, params)?.op_handle;
+    host::finish_with_tokens(km, op_handle, message, None, None, confirmation_token)
+}
+
+#[test]
+fn test_format_message() {
+    let message = format_message("hi", &[0x01]).unwrap();
+    assert_eq!(
+        hex::encode(message),
+        concat!(
+            "a2",             // map(2)
+            "6670726f6d7074", // tstr "prompt"
+            "626869",         // tstr "hi"
+            "656578747261",   // tstr "extra"
+            "4101",           // bstr h'01'
+        )
+    );
+    assert!(matches!(
+        format_message("x", &[0; 7000]),
+        Err(kmr_common::Error::Hal(ErrorCode::InvalidInputLength, _))
+    ));
+}
+
+#[test]
+fn test_confirmed_signature() {
+    let (mut km, mut conf) = setup(vec![UserResponse::Confirmed]);
+    let key_blob = generate_ec_key(&mut km);
+
+    let (message, token) = match conf.prompt_user_confirmation(PROMPT, EXTRA, "en", &[]).unwrap() {
+        ConfirmationResult::Confirmed { message, token } => (message, token),
+        ConfirmationResult::Cancelled => panic!("unexpected cancellation"),
+    };
+    assert_eq!(message, format_message(PROMPT, EXTRA).unwrap());
+    assert!(sign(&mut km, &key_blob, &message, Some(token.clone())).is_ok());
+
+    // The token doesn't cover a different message.
+    let other = format_message(PROMPT, b"nonce-5678").unwrap();
+    assert_eq!(sign(&mut km, &key_blob, &other, Some(token)), Err(ErrorCode::NoUserConfirmation));
+
+    // A token is required.
+    assert_eq!(sign(&mut km, &key_blob, &message, None), Err(ErrorCode::NoUserConfirmation));
+}
+
+#[test]
+fn test_cancelled() {
+    let (_km, mut conf) = setup(vec![UserResponse::Cancelled]);
+    assert_eq!(
+        conf.prompt_user_confirmation(PROMPT, EXTRA, "en", &[]).unwrap(),
+        ConfirmationResult::Cancelled
+    );
+    // Script exhausted, so subsequent prompts are cancelled too.
+    assert_eq!(
+        conf.prompt_user_confirmation(PROMPT, EXTRA, "en", &[]).unwrap(),
+        ConfirmationResult::Cancelled
+    );
+}
+
+#[test]
+fn test_unagreed_hmac() {
+    let mut conf = ConfirmationTa::new(
+        host::crypto_impl(None),
+        Box::new(host::TestKeys),
+        Box::new(HeadlessConfirmationUi::new([UserResponse::Confirmed])),
+    );
+    assert!(conf.prompt_user_confirmation(PROMPT, EXTRA, "en", &[]).is_err());
+}
@@ -0,0 +1,52 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: error descriptions are only included in TA responses when the TA is built with
+// the `diagnostics` feature.
+
+use kmr_tests::host;
+use kmr_wire::keymint::{Algorithm, ErrorCode, KeyParam, KeyPurpose};
+use kmr_wire::*;
+
+/// Attempt to generate an AES key with an invalid key size, returning the raw response.
+fn failed_response() -> PerformOpResponse {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let req = PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+        key_params: vec![
+            KeyParam::Algorithm(Algorithm::Aes),
+            KeyParam::KeySize(KeySizeInBits(100)),
+            KeyParam::Purpose(KeyPurpose::Encrypt),
+            KeyParam::NoAuthRequired,
+        ],
+        attestation_key: None,
+    });
+    let rsp_data = km.process(&req.into_vec().unwrap());
+    let rsp = PerformOpResponse::from_slice(&rsp_data).expect("failed to parse response");
+    assert_eq!(rsp.error_code, ErrorCode::UnsupportedKeySize as i32);
+    assert!(rsp.rsp.is_none());
+    rsp
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_error_diagnostics() {
+    let diagnostics = failed_response().diagnostics.expect("no diagnostics in response");
+    assert!(diagnostics.contains("100"), "unexpected diagnostics: {diagnostics}");
+}
+
+#[cfg(not(feature = "diagnostics"))]
+#[test]
+fn test_no_error_diagnostics() {
+    assert_eq!(failed_response().diagnostics, None);
+}
@@ -0,0 +1,237 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: derivation of new keys from the output of a key agreement operation (a vendor
+// extension), without the shared secret leaving the TA.
+
+use kmr_common::crypto::{aes, ec, hmac, Aes, Ec, Hkdf, Hmac, KeyMaterial, SymmetricOperation};
+use kmr_crypto_boring::{aes::BoringAes, ec::BoringEc, hmac::BoringHmac, rng::BoringRng};
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, exec, generate_key, leaf_spki};
+use kmr_wire::audit::AuditEvent;
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, Digest, EcCurve, ErrorCode, KeyCreationResult, KeyOrigin, KeyParam,
+    KeyPurpose, PaddingMode, UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use x509_cert::der::Encode;
+
+const SALT: &[u8] = b"salt";
+const INFO: &[u8] = b"messaging session key";
+const DATA: &[u8] = b"sixteen byte msg";
+
+fn generate_agree_key(km: &mut KeyMintTa, curve: EcCurve) -> KeyCreationResult {
+    generate_key(
+        km,
+        vec![
+            KeyParam::Algorithm(Algorithm::Ec),
+            KeyParam::EcCurve(curve),
+            KeyParam::Purpose(KeyPurpose::AgreeKey),
+            KeyParam::NoAuthRequired,
+            KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+            KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+        ],
+    )
+    .unwrap()
+}
+
+fn aes_params() -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Aes),
+        KeyParam::KeySize(KeySizeInBits(256)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::BlockMode(BlockMode::Ecb),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::NoAuthRequired,
+    ]
+}
+
+fn hmac_params() -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Hmac),
+        KeyParam::KeySize(KeySizeInBits(256)),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::MinMacLength(256),
+        KeyParam::NoAuthRequired,
+    ]
+}
+
+fn finish_derive_key(
+    km: &mut KeyMintTa,
+    op_handle: i64,
+    peer_spki: &[u8],
+    key_params: Vec<KeyParam>,
+) -> Result<KeyCreationResult, ErrorCode> {
+    match exec(
+        km,
+        PerformOpReq::OperationFinishDeriveKey(FinishDeriveKeyRequest {
+            op_handle,
+            input: Some(peer_spki.to_vec()),
+            auth_token: None,
+            timestamp_token: None,
+            hkdf_salt: SALT.to_vec(),
+            hkdf_info: INFO.to_vec(),
+            key_params,
+        }),
+    )? {
+        PerformOpRsp::OperationFinishDeriveKey(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+/// Perform the peer side of the key agreement with the given public key, returning the peer's
+/// public key and the expected HKDF output of length `len`.
+fn peer_agree(curve: EcCurve, spki: &[u8], len: usize) -> (Vec<u8>, Vec<u8>) {
+    let ec = BoringEc::default();
+    let peer_key = match curve {
+        EcCurve::Curve25519 => ec.generate_x25519_key(&mut BoringRng, &[]).unwrap(),
+        _ => ec.generate_nist_key(&mut BoringRng, ec::NistCurve::P256, &[]).unwrap(),
+    };
+    let KeyMaterial::Ec(curve, curve_type, peer_key) = peer_key else {
+        panic!("unexpected key type")
+    };
+    let mut buf = Vec::new();
+    let peer_spki = peer_key
+        .subject_public_key_info(&mut buf, &ec, &curve, &curve_type)
+        .unwrap()
+        .to_der()
+        .unwrap();
+    let mut op = ec.begin_agree(peer_key).unwrap();
+    op.update(spki).unwrap();
+    let shared_secret = op.finish().unwrap();
+    (peer_spki, BoringHmac.hkdf(SALT, &shared_secret, INFO, len).unwrap())
+}
+
+fn check_derive_aes_key(curve: EcCurve) {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let agree_key = generate_agree_key(&mut km, curve);
+    let (peer_spki, want_key) = peer_agree(curve, &leaf_spki(&agree_key), 32);
+
+    let op_handle =
+        host::begin(&mut km, KeyPurpose::AgreeKey, &agree_key.key_blob, vec![]).unwrap().op_handle;
+    let derived = finish_derive_key(&mut km, op_handle, &peer_spki, aes_params()).unwrap();
+    assert!(derived.certificate_chain.is_empty());
+    assert!(derived.key_characteristics[0]
+        .authorizations
+        .contains(&KeyParam::Origin(KeyOrigin::Derived)));
+
+    // The audit log records the new key as derived, rather than generated.
+    let audit = match exec(&mut km, PerformOpReq::DrainAuditLog(DrainAuditLogRequest {})) {
+        Ok(PerformOpRsp::DrainAuditLog(rsp)) => rsp,
+        _ => panic!("audit log drain failed"),
+    };
+    let record = audit.records.last().unwrap();
+    assert_eq!(record.event, AuditEvent::KeyDerived);
+    assert!(record.params.contains(&KeyParam::Origin(KeyOrigin::Derived)));
+
+    // The derived key encrypts the same as the key derived by the peer.
+    let op_handle = host::begin(
+        &mut km,
+        KeyPurpose::Encrypt,
+        &derived.key_blob,
+        vec![KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::None)],
+    )
+    .unwrap()
+    .op_handle;
+    let got = host::finish(&mut km, op_handle, DATA, None).unwrap();
+    let mut op = BoringAes
+        .begin(
+            aes::Key::new(want_key).unwrap().into(),
+            aes::CipherMode::EcbNoPadding,
+            SymmetricOperation::Encrypt,
+        )
+        .unwrap();
+    let mut want = op.update(DATA).unwrap();
+    want.extend_from_slice(&op.finish().unwrap());
+    assert_eq!(got, want, "mismatch for {:?}", curve);
+}
+
+#[test]
+fn test_derive_aes_key() {
+    check_derive_aes_key(EcCurve::P256);
+}
+
+#[cfg(soong)]
+#[test]
+fn test_derive_aes_key_x25519() {
+    // X25519 is only supported by BoringSSL, not by OpenSSL in a Cargo build.
+    check_derive_aes_key(EcCurve::Curve25519);
+}
+
+#[test]
+fn test_derive_hmac_key() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let agree_key = generate_agree_key(&mut km, EcCurve::P256);
+    let (peer_spki, want_key) = peer_agree(EcCurve::P256, &leaf_spki(&agree_key), 32);
+
+    let op_handle =
+        host::begin(&mut km, KeyPurpose::AgreeKey, &agree_key.key_blob, vec![]).unwrap().op_handle;
+    let derived = finish_derive_key(&mut km, op_handle, &peer_spki, hmac_params()).unwrap();
+
+    let op_handle = host::begin(
+        &mut km,
+        KeyPurpose::Sign,
+        &derived.key_blob,
+        vec![KeyParam::Digest(Digest::Sha256), KeyParam::MacLength(256)],
+    )
+    .unwrap()
+    .op_handle;
+    let got = host::finish(&mut km, op_handle, DATA, None).unwrap();
+    let mut op = BoringHmac.begin(hmac::Key::new(want_key).into(), Digest::Sha256).unwrap();
+    op.update(DATA).unwrap();
+    assert_eq!(got, op.finish().unwrap());
+}
+
+#[test]
+fn test_derive_key_invalid() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let agree_key = generate_agree_key(&mut km, EcCurve::P256);
+    let (peer_spki, _) = peer_agree(EcCurve::P256, &leaf_spki(&agree_key), 32);
+
+    // Only symmetric keys can be derived.
+    let op_handle =
+        host::begin(&mut km, KeyPurpose::AgreeKey, &agree_key.key_blob, vec![]).unwrap().op_handle;
+    let ec_params = vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+    ];
+    assert_eq!(
+        finish_derive_key(&mut km, op_handle, &peer_spki, ec_params).err(),
+        Some(ErrorCode::UnsupportedAlgorithm)
+    );
+
+    // Only key agreement operations can be used for derivation, and a failed attempt terminates
+    // the operation.
+    let hmac_key = generate_key(&mut km, hmac_params()).unwrap();
+    let op_handle = host::begin(
+        &mut km,
+        KeyPurpose::Sign,
+        &hmac_key.key_blob,
+        vec![KeyParam::Digest(Digest::Sha256), KeyParam::MacLength(256)],
+    )
+    .unwrap()
+    .op_handle;
+    assert_eq!(
+        finish_derive_key(&mut km, op_handle, DATA, aes_params()).err(),
+        Some(ErrorCode::IncompatiblePurpose)
+    );
+    assert_eq!(
+        host::finish(&mut km, op_handle, DATA, None).err(),
+        Some(ErrorCode::InvalidOperation)
+    );
+}
@@ -0,0 +1,250 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: secure import of a key wrapped under an EC wrapping key (a vendor extension to
+// RSA-OAEP wrapping), with the transport key derived from an ephemeral ECDH agreement.
+
+use kmr_common::crypto::{aes, ec, Aes, Ec, Hkdf, KeyMaterial, OpaqueOr, Rng, SymmetricOperation};
+use kmr_crypto_boring::{aes::BoringAes, ec::BoringEc, hmac::BoringHmac, rng::BoringRng};
+use kmr_ta::keys::{
+    ecdh_wrapping_hkdf_info, KeyDescription, SecureKeyWrapper, ECDH_WRAPPING_HKDF_INFO,
+};
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, EcCurve, ErrorCode, KeyCreationResult, KeyParam, KeyPurpose, PaddingMode,
+    UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use x509_cert::der::{Decode, Encode};
+
+/// Key description for an AES-256 ECB/PKCS7 encryption key.
+const KEY_DESCRIPTION: &str = concat!(
+    "302e",     // SEQUENCE length 0x2e (KeyDescription) {
+    "020103",   // INTEGER length 1 value 0x03 (keyFormat = RAW)
+    "3029",     // SEQUENCE length 0x29 (AuthorizationList) {
+    "a108",     // [1] length 0x08 { (purpose)
+    "3106",     // SET length 0x06 {
+    "020100",   // INTEGER length 1 value 0x00 (Encrypt)
+    "020101",   // INTEGER length 1 value 0x01 (Decrypt) } }
+    "a203",     // [2] length 0x03 { (algorithm)
+    "020120",   // INTEGER length 1 value 0x20 (AES) }
+    "a304",     // [3] length 0x04 { (keySize)
+    "02020100", // INTEGER length 2 value 0x100 }
+    "a405",     // [4] length 0x05 { (blockMode)
+    "3103",     // SET length 0x03 {
+    "020101",   // INTEGER length 1 value 0x01 (ECB) } }
+    "a605",     // [6] length 0x05 { (padding)
+    "3103",     // SET length 0x03 {
+    "020140",   // INTEGER length 1 value 0x40 (PKCS7) } }
+    "bf837702", // [503] length 0x02 { (noAuthRequired)
+    "0500",     // NULL } } }
+);
+
+const PLAINTEXT: &[u8] = b"sixteen byte msg";
+
+fn generate_wrapping_key(
+    km: &mut KeyMintTa,
+    curve: EcCurve,
+    purpose: KeyPurpose,
+) -> Result<KeyCreationResult, ErrorCode> {
+    let key_params = vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(curve),
+        KeyParam::Purpose(purpose),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ];
+    host::generate_key(km, key_params)
+}
+
+/// Extract the DER-encoded `SubjectPublicKeyInfo` from the leaf certificate for a key.
+fn wrapping_spki(result: &KeyCreationResult) -> Vec<u8> {
+    let cert = x509_cert::Certificate::from_der(&result.certificate_chain[0].encoded_certificate)
+        .expect("failed to parse certificate");
+    cert.tbs_certificate.subject_public_key_info.to_der().unwrap()
+}
+
+/// Wrap `key_data` for the given wrapping public key, returning the `SecureKeyWrapper` and the
+/// masking key.
+fn wrap(key_data: &[u8], wrapping_spki: &[u8], x25519: bool) -> (Vec<u8>, Vec<u8>) {
+    wrap_with_info(key_data, wrapping_spki, x25519, None)
+}
+
+/// Wrap `key_data` for the given wrapping public key, deriving the transport key with the given
+/// HKDF info (or the correct info, if `None`).
+fn wrap_with_info(
+    key_data: &[u8],
+    wrapping_spki: &[u8],
+    x25519: bool,
+    hkdf_info: Option<&[u8]>,
+) -> (Vec<u8>, Vec<u8>) {
+    let ec = BoringEc::default();
+    let ephemeral_key = if x25519 {
+        ec.generate_x25519_key(&mut BoringRng, &[]).unwrap()
+    } else {
+        ec.generate_nist_key(&mut BoringRng, ec::NistCurve::P256, &[]).unwrap()
+    };
+    let KeyMaterial::Ec(curve, curve_type, ephemeral_key) = ephemeral_key else {
+        panic!("unexpected key type")
+    };
+    let mut buf = Vec::new();
+    let ephemeral_spki = ephemeral_key
+        .subject_public_key_info(&mut buf, &ec, &curve, &curve_type)
+        .unwrap()
+        .to_der()
+        .unwrap();
+
+    // Derive the masked transport key, and mask it.
+    let mut op = ec.begin_agree(ephemeral_key).unwrap();
+    op.update(wrapping_spki).unwrap();
+    let shared_secret = op.finish().unwrap();
+    let info = match hkdf_info {
+        Some(info) => info.to_vec(),
+        None => ecdh_wrapping_hkdf_info(&ephemeral_spki, wrapping_spki).unwrap(),
+    };
+    let masked_transport_key = BoringHmac.hkdf(&[], &shared_secret, &info, 32).unwrap();
+    let mut masking_key = vec![0; 32];
+    BoringRng.fill_bytes(&mut masking_key);
+    let transport_key: Vec<u8> =
+        masked_transport_key.iter().zip(&masking_key).map(|(x, y)| x ^ y).collect();
+
+    // Encrypt the key data under the transport key.
+    let key_description_data = hex::decode(KEY_DESCRIPTION).unwrap();
+    let key_description = KeyDescription::from_der(&key_description_data).unwrap();
+    let mut nonce = [0; aes::GCM_NONCE_SIZE];
+    BoringRng.fill_bytes(&mut nonce);
+    let mut op = BoringAes
+        .begin_aead(
+            aes::Key::new(transport_key).unwrap().into(),
+            aes::GcmMode::GcmTag16 { nonce },
+            SymmetricOperation::Encrypt,
+        )
+        .unwrap();
+    op.update_aad(&key_description_data).unwrap();
+    let mut ciphertext = op.update(key_data).unwrap();
+    ciphertext.extend_from_slice(&op.finish().unwrap());
+    let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+    let wrapper = SecureKeyWrapper {
+        version: 0,
+        encrypted_transport_key: &ephemeral_spki,
+        initialization_vector: &nonce,
+        key_description,
+        encrypted_key: &ciphertext,
+        tag: &tag,
+    };
+    (wrapper.to_der().unwrap(), masking_key)
+}
+
+fn import_wrapped(
+    km: &mut KeyMintTa,
+    wrapped_key_data: Vec<u8>,
+    wrapping_key_blob: &[u8],
+    masking_key: Vec<u8>,
+) -> Result<KeyCreationResult, ErrorCode> {
+    match exec(
+        km,
+        PerformOpReq::DeviceImportWrappedKey(ImportWrappedKeyRequest {
+            wrapped_key_data,
+            wrapping_key_blob: wrapping_key_blob.to_vec(),
+            masking_key,
+            unwrapping_params: vec![],
+            password_sid: 0,
+            biometric_sid: 0,
+        }),
+    )? {
+        PerformOpRsp::DeviceImportWrappedKey(rsp) => Ok(rsp.ret),
+        _ => panic!("unexpected response"),
+    }
+}
+
+fn encrypt(km: &mut KeyMintTa, key_blob: &[u8]) -> Vec<u8> {
+    let params = vec![KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::Pkcs7)];
+    host::run_op(km, KeyPurpose::Encrypt, key_blob, params, PLAINTEXT, None)
+        .expect("encryption failed")
+        .0
+}
+
+fn expected_ciphertext(key_data: &[u8]) -> Vec<u8> {
+    let mut op = BoringAes
+        .begin(
+            OpaqueOr::Explicit(aes::Key::new(key_data.to_vec()).unwrap()),
+            aes::CipherMode::EcbPkcs7Padding,
+            SymmetricOperation::Encrypt,
+        )
+        .unwrap();
+    let mut ciphertext = op.update(PLAINTEXT).unwrap();
+    ciphertext.extend_from_slice(&op.finish().unwrap());
+    ciphertext
+}
+
+fn check_wrapped_import(curve: EcCurve) {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let wrapping_key = generate_wrapping_key(&mut km, curve, KeyPurpose::WrapKey).unwrap();
+    let spki = wrapping_spki(&wrapping_key);
+    let x25519 = curve == EcCurve::Curve25519;
+
+    let mut key_data = vec![0; 32];
+    BoringRng.fill_bytes(&mut key_data);
+    let (wrapped, masking_key) = wrap(&key_data, &spki, x25519);
+    let result =
+        import_wrapped(&mut km, wrapped.clone(), &wrapping_key.key_blob, masking_key.clone())
+            .unwrap();
+    assert_eq!(encrypt(&mut km, &result.key_blob), expected_ciphertext(&key_data));
+
+    // The masking key is needed to recover the transport key.
+    let wrong_masking_key: Vec<u8> = masking_key.iter().map(|x| x ^ 0x01).collect();
+    assert!(import_wrapped(&mut km, wrapped, &wrapping_key.key_blob, wrong_masking_key).is_err());
+
+    // The transport key must be bound to the ephemeral and wrapping public keys.
+    let (wrapped, masking_key) =
+        wrap_with_info(&key_data, &spki, x25519, Some(ECDH_WRAPPING_HKDF_INFO));
+    assert!(import_wrapped(&mut km, wrapped, &wrapping_key.key_blob, masking_key).is_err());
+
+    // The data must be wrapped for this wrapping key.
+    let other_key = generate_wrapping_key(&mut km, curve, KeyPurpose::WrapKey).unwrap();
+    let (wrapped, masking_key) = wrap(&key_data, &wrapping_spki(&other_key), x25519);
+    assert!(import_wrapped(&mut km, wrapped, &wrapping_key.key_blob, masking_key).is_err());
+}
+
+#[test]
+fn test_p256_wrapped_import() {
+    check_wrapped_import(EcCurve::P256);
+}
+
+#[cfg(soong)]
+#[test]
+fn test_x25519_wrapped_import() {
+    // X25519 is only supported by BoringSSL, not by OpenSSL in a Cargo build.
+    check_wrapped_import(EcCurve::Curve25519);
+}
+
+#[test]
+fn test_invalid_wrapping_key() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    assert_eq!(
+        generate_wrapping_key(&mut km, EcCurve::P384, KeyPurpose::WrapKey).unwrap_err(),
+        ErrorCode::UnsupportedEcCurve
+    );
+
+    // An EC key without the WRAP_KEY purpose can't be used for wrapped import.
+    let agree_key = generate_wrapping_key(&mut km, EcCurve::P256, KeyPurpose::AgreeKey).unwrap();
+    let (wrapped, masking_key) = wrap(&[0; 32], &wrapping_spki(&agree_key), false);
+    assert_eq!(
+        import_wrapped(&mut km, wrapped, &agree_key.key_blob, masking_key).unwrap_err(),
+        ErrorCode::IncompatiblePurpose
+    );
+}
@@ -0,0 +1,215 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: auth tokens minted by the Gatekeeper companion are accepted by KeyMint.
+
+use kmr_crypto_boring::aes::BoringAes;
+use kmr_ta::gatekeeper::{GatekeeperTa, InMemoryFailureRecords, Response};
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, SoftAesCmac, TestClock};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, ErrorCode, HardwareAuthToken, HardwareAuthenticatorType, KeyParam,
+    KeyPurpose, PaddingMode,
+};
+use std::sync::Arc;
+
+const UID: u32 = 10;
+const PASSWORD: &[u8] = b"correct horse battery staple";
+
+/// Build KeyMint and Gatekeeper TAs that share a clock and have agreed an HMAC key.
+fn setup() -> (TestClock, KeyMintTa, GatekeeperTa) {
+    let clock = TestClock::default();
+    let mut km = host::keymint_ta(host::crypto_impl(Some(clock.clone())), host::device_impl());
+    let mut gk = GatekeeperTa::new(
+        host::crypto_impl(Some(clock.clone())),
+        Box::new(host::TestKeys),
+        Box::<InMemoryFailureRecords>::default(),
+    );
+
+    let km_params = host::get_shared_secret_params(&mut km);
+    let gk_params = gk.get_shared_secret_params().unwrap();
+    let params = vec![km_params, gk_params];
+    let km_check = host::compute_shared_secret(&mut km, &params);
+    let gk_check = gk.compute_shared_secret(&params).unwrap();
+    assert_eq!(km_check, gk_check);
+    (clock, km, gk)
+}
+
+fn enroll(gk: &mut GatekeeperTa) -> (Vec<u8>, u64) {
+    match gk.enroll(UID, None, None, PASSWORD).unwrap() {
+        Response::Ok(rsp) => (rsp.handle, rsp.secure_user_id),
+        rsp => panic!("unexpected enroll response {rsp:?}"),
+    }
+}
+
+fn verify(gk: &mut GatekeeperTa, challenge: i64, handle: &[u8]) -> HardwareAuthToken {
+    match gk.verify(UID, challenge, handle, PASSWORD).unwrap() {
+        Response::Ok(rsp) => rsp.auth_token,
+        rsp => panic!("unexpected verify response {rsp:?}"),
+    }
+}
+
+fn generate_aes_key(km: &mut KeyMintTa, sid: u64, timeout: Option<u32>) -> Vec<u8> {
+    let mut key_params = vec![
+        KeyParam::Algorithm(Algorithm::Aes),
+        KeyParam::KeySize(kmr_wire::KeySizeInBits(128)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::BlockMode(BlockMode::Ecb),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::UserSecureId(sid),
+        KeyParam::UserAuthType(HardwareAuthenticatorType::Password as u32),
+    ];
+    if let Some(timeout) = timeout {
+        key_params.push(KeyParam::AuthTimeout(timeout));
+    }
+    host::generate_key(km, key_params).expect("key generation failed").key_blob
+}
+
+fn begin(
+    km: &mut KeyMintTa,
+    key_blob: &[u8],
+    auth_token: Option<HardwareAuthToken>,
+) -> Result<i64, ErrorCode> {
+    let params = vec![KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::None)];
+    let rsp = host::begin_with_auth(km, KeyPurpose::Encrypt, key_blob, params, auth_token)?;
+    Ok(rsp.op_handle)
+}
+
+fn finish(
+    km: &mut KeyMintTa,
+    op_handle: i64,
+    auth_token: Option<HardwareAuthToken>,
+) -> Result<Vec<u8>, ErrorCode> {
+    host::finish_with_tokens(km, op_handle, &[0; 16], None, auth_token, None)
+}
+
+#[test]
+fn test_soft_aes_cmac() {
+    kmr_tests::test_aes_cmac(SoftAesCmac(Arc::new(BoringAes)));
+    kmr_tests::test_ckdf(SoftAesCmac(Arc::new(BoringAes)));
+}
+
+#[test]
+fn test_per_op_auth() {
+    let (_clock, mut km, mut gk) = setup();
+    let (handle, sid) = enroll(&mut gk);
+    let key_blob = generate_aes_key(&mut km, sid, None);
+
+    let op_handle = begin(&mut km, &key_blob, None).unwrap();
+    let token = verify(&mut gk, op_handle, &handle);
+    assert_eq!(token.user_id, sid as i64);
+    assert_eq!(finish(&mut km, op_handle, Some(token)).unwrap().len(), 16);
+
+    // A token bound to a different challenge is rejected.
+    let op_handle = begin(&mut km, &key_blob, None).unwrap();
+    let token = verify(&mut gk, op_handle.wrapping_add(1), &handle);
+    assert_eq!(finish(&mut km, op_handle, Some(token)), Err(ErrorCode::KeyUserNotAuthenticated));
+
+    // A token with a corrupted MAC is rejected.
+    let op_handle = begin(&mut km, &key_blob, None).unwrap();
+    let mut token = verify(&mut gk, op_handle, &handle);
+    token.mac[0] ^= 0x01;
+    assert_eq!(finish(&mut km, op_handle, Some(token)), Err(ErrorCode::KeyUserNotAuthenticated));
+
+    // No token at all is rejected.
+    let op_handle = begin(&mut km, &key_blob, None).unwrap();
+    assert_eq!(finish(&mut km, op_handle, None), Err(ErrorCode::KeyUserNotAuthenticated));
+}
+
+#[test]
+fn test_timeout_auth() {
+    let (clock, mut km, mut gk) = setup();
+    let (handle, sid) = enroll(&mut gk);
+    let key_blob = generate_aes_key(&mut km, sid, Some(60));
+
+    let token = verify(&mut gk, 0, &handle);
+    let op_handle = begin(&mut km, &key_blob, Some(token.clone())).unwrap();
+    assert_eq!(finish(&mut km, op_handle, None).unwrap().len(), 16);
+
+    clock.advance(61_000);
+    assert_eq!(begin(&mut km, &key_blob, Some(token)), Err(ErrorCode::KeyUserNotAuthenticated));
+}
+
+#[test]
+fn test_wrong_sid_rejected() {
+    let (_clock, mut km, mut gk) = setup();
+    let (handle, sid) = enroll(&mut gk);
+    let key_blob = generate_aes_key(&mut km, sid.wrapping_add(1), None);
+
+    let op_handle = begin(&mut km, &key_blob, None).unwrap();
+    let token = verify(&mut gk, op_handle, &handle);
+    assert_eq!(finish(&mut km, op_handle, Some(token)), Err(ErrorCode::KeyUserNotAuthenticated));
+}
+
+#[test]
+fn test_reenroll() {
+    let (_clock, _km, mut gk) = setup();
+    let (handle, sid) = enroll(&mut gk);
+
+    // Trusted re-enroll keeps the secure user ID.
+    let new_handle = match gk.enroll(UID, Some(&handle), Some(PASSWORD), b"new password").unwrap() {
+        Response::Ok(rsp) => {
+            assert_eq!(rsp.secure_user_id, sid);
+            rsp.handle
+        }
+        rsp => panic!("unexpected enroll response {rsp:?}"),
+    };
+    assert_eq!(gk.verify(UID, 0, &new_handle, PASSWORD).unwrap(), Response::Invalid);
+    assert!(matches!(gk.verify(UID, 0, &new_handle, b"new password").unwrap(), Response::Ok(_)));
+
+    // Re-enroll with the wrong current password fails.
+    assert_eq!(
+        gk.enroll(UID, Some(&new_handle), Some(PASSWORD), b"other").unwrap(),
+        Response::Invalid
+    );
+
+    // Untrusted re-enroll generates a new secure user ID.
+    let (_, new_sid) = enroll(&mut gk);
+    assert_ne!(new_sid, sid);
+}
+
+#[test]
+fn test_throttling() {
+    let (clock, _km, mut gk) = setup();
+    let (handle, _sid) = enroll(&mut gk);
+
+    for _ in 0..4 {
+        assert_eq!(gk.verify(UID, 0, &handle, b"wrong").unwrap(), Response::Invalid);
+        clock.advance(1);
+    }
+    assert_eq!(gk.verify(UID, 0, &handle, b"wrong").unwrap(), Response::Retry(30_000));
+
+    // Even the right password is throttled until the timeout expires.
+    clock.advance(10_000);
+    assert_eq!(gk.verify(UID, 0, &handle, PASSWORD).unwrap(), Response::Retry(20_000));
+    clock.advance(20_000);
+    let _ = verify(&mut gk, 0, &handle);
+
+    // Success resets the failure count.
+    assert_eq!(gk.verify(UID, 0, &handle, b"wrong").unwrap(), Response::Invalid);
+}
+
+#[test]
+fn test_unagreed_hmac() {
+    let mut gk = GatekeeperTa::new(
+        host::crypto_impl(Some(TestClock::default())),
+        Box::new(host::TestKeys),
+        Box::<InMemoryFailureRecords>::default(),
+    );
+    let handle = match gk.enroll(UID, None, None, PASSWORD).unwrap() {
+        Response::Ok(rsp) => rsp.handle,
+        rsp => panic!("unexpected enroll response {rsp:?}"),
+    };
+    assert!(gk.verify(UID, 0, &handle, PASSWORD).is_err());
+}
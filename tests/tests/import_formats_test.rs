@@ -0,0 +1,206 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: import of keys in the vendor-extension SEC1 and PKCS#1 formats, optionally
+// paired with a SubjectPublicKeyInfo, on a device whose key policy enables them.
+
+use kmr_common::crypto::{ec, rsa, Ec, KeyMaterial, OpaqueOr, Rsa};
+use kmr_crypto_boring::{ec::BoringEc, rng::BoringRng, rsa::BoringRsa};
+use kmr_ta::KeyMintTa;
+use kmr_tests::host;
+use kmr_wire::keymint::{
+    Algorithm, Digest, EcCurve, ErrorCode, KeyCreationResult, KeyFormat, KeyParam, KeyPurpose,
+    PaddingMode, UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use x509_cert::der::Encode;
+
+const MSG: &[u8] = b"data to sign";
+
+/// Generate a P-256 key, returning the SEC1 private key and the SubjectPublicKeyInfo.
+fn ec_key() -> (Vec<u8>, Vec<u8>) {
+    let imp = BoringEc::default();
+    let key = imp.generate_nist_key(&mut BoringRng, ec::NistCurve::P256, &[]).unwrap();
+    let KeyMaterial::Ec(curve, curve_type, key) = key else { panic!("unexpected key type") };
+    let mut buf = Vec::new();
+    let spki = key.subject_public_key_info(&mut buf, &imp, &curve, &curve_type).unwrap();
+    let OpaqueOr::Explicit(ec::Key::P256(ec::NistKey(der))) = &key else {
+        panic!("unexpected key variant")
+    };
+    (der.clone(), spki.to_der().unwrap())
+}
+
+/// Generate an RSA-2048 key, returning the PKCS#1 private key and the SubjectPublicKeyInfo.
+fn rsa_key() -> (Vec<u8>, Vec<u8>) {
+    let imp = BoringRsa::default();
+    let key =
+        imp.generate_key(&mut BoringRng, KeySizeInBits(2048), RsaExponent(65537), &[]).unwrap();
+    let KeyMaterial::Rsa(key) = key else { panic!("unexpected key type") };
+    let mut buf = Vec::new();
+    let spki = key.subject_public_key_info(&mut buf, &imp).unwrap();
+    let OpaqueOr::Explicit(rsa::Key(der)) = &key else { panic!("unexpected key variant") };
+    (der.clone(), spki.to_der().unwrap())
+}
+
+fn vendor_formats_ta() -> KeyMintTa {
+    let mut dev = host::device_impl();
+    dev.policy.vendor_import_formats = true;
+    host::keymint_ta(host::crypto_impl(None), dev)
+}
+
+fn concat(private_key: &[u8], spki: &[u8]) -> Vec<u8> {
+    [private_key, spki].concat()
+}
+
+fn ec_params() -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+fn rsa_params() -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Rsa),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::Padding(PaddingMode::RsaPkcs115Sign),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+fn has_param(result: &KeyCreationResult, param: &KeyParam) -> bool {
+    result.key_characteristics.iter().any(|chars| chars.authorizations.contains(param))
+}
+
+#[test]
+fn test_import_sec1() {
+    let mut km = vendor_formats_ta();
+    let (private_key, spki) = ec_key();
+
+    for key_data in [private_key.clone(), concat(&private_key, &spki)] {
+        let result = host::import_key(&mut km, ec_params(), KeyFormat::Sec1, key_data).unwrap();
+        // Curve and key size are deduced from the key material.
+        assert!(has_param(&result, &KeyParam::EcCurve(EcCurve::P256)));
+        assert!(has_param(&result, &KeyParam::KeySize(KeySizeInBits(256))));
+        assert!(host::sign(&mut km, &result.key_blob, vec![KeyParam::Digest(Digest::Sha256)], MSG)
+            .is_ok());
+    }
+
+    // SEC1 data is not PKCS#8.
+    assert!(host::import_key(&mut km, ec_params(), KeyFormat::Pkcs8, private_key.clone()).is_err());
+}
+
+#[test]
+fn test_import_sec1_mismatch() {
+    let mut km = vendor_formats_ta();
+    let (private_key, _spki) = ec_key();
+
+    let mut params = ec_params();
+    params.push(KeyParam::EcCurve(EcCurve::P384));
+    assert_eq!(
+        host::import_key(&mut km, params, KeyFormat::Sec1, private_key.clone()).unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+
+    // The SubjectPublicKeyInfo must hold the public key for the private key.
+    let (_other_private_key, other_spki) = ec_key();
+    assert_eq!(
+        host::import_key(&mut km, ec_params(), KeyFormat::Sec1, concat(&private_key, &other_spki))
+            .unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+    let (_rsa_private_key, rsa_spki) = rsa_key();
+    assert_eq!(
+        host::import_key(&mut km, ec_params(), KeyFormat::Sec1, concat(&private_key, &rsa_spki))
+            .unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+}
+
+#[test]
+fn test_import_pkcs1() {
+    let mut km = vendor_formats_ta();
+    let (private_key, spki) = rsa_key();
+
+    for key_data in [private_key.clone(), concat(&private_key, &spki)] {
+        let result = host::import_key(&mut km, rsa_params(), KeyFormat::Pkcs1, key_data).unwrap();
+        // Key size and public exponent are deduced from the key material.
+        assert!(has_param(&result, &KeyParam::KeySize(KeySizeInBits(2048))));
+        assert!(has_param(&result, &KeyParam::RsaPublicExponent(RsaExponent(65537))));
+        let params =
+            vec![KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaPkcs115Sign)];
+        assert!(host::sign(&mut km, &result.key_blob, params, MSG).is_ok());
+    }
+
+    // PKCS#1 data is not PKCS#8, and PKCS#1 is only for RSA keys.
+    assert!(host::import_key(&mut km, rsa_params(), KeyFormat::Pkcs8, private_key.clone()).is_err());
+    assert_eq!(
+        host::import_key(&mut km, ec_params(), KeyFormat::Pkcs1, private_key).unwrap_err(),
+        ErrorCode::UnsupportedKeyFormat
+    );
+}
+
+#[test]
+fn test_import_pkcs1_mismatch() {
+    let mut km = vendor_formats_ta();
+    let (private_key, _spki) = rsa_key();
+
+    let mut params = rsa_params();
+    params.push(KeyParam::KeySize(KeySizeInBits(3072)));
+    assert_eq!(
+        host::import_key(&mut km, params, KeyFormat::Pkcs1, private_key.clone()).unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+    let mut params = rsa_params();
+    params.push(KeyParam::RsaPublicExponent(RsaExponent(3)));
+    assert_eq!(
+        host::import_key(&mut km, params, KeyFormat::Pkcs1, private_key.clone()).unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+
+    // The SubjectPublicKeyInfo must hold the public key for the private key.
+    let (_other_private_key, other_spki) = rsa_key();
+    assert_eq!(
+        host::import_key(
+            &mut km,
+            rsa_params(),
+            KeyFormat::Pkcs1,
+            concat(&private_key, &other_spki)
+        )
+        .unwrap_err(),
+        ErrorCode::ImportParameterMismatch
+    );
+}
+
+#[test]
+fn test_import_vendor_formats_disabled() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let (ec_private_key, _spki) = ec_key();
+    assert_eq!(
+        host::import_key(&mut km, ec_params(), KeyFormat::Sec1, ec_private_key).unwrap_err(),
+        ErrorCode::UnsupportedKeyFormat
+    );
+    let (rsa_private_key, _spki) = rsa_key();
+    assert_eq!(
+        host::import_key(&mut km, rsa_params(), KeyFormat::Pkcs1, rsa_private_key).unwrap_err(),
+        ErrorCode::UnsupportedKeyFormat
+    );
+}
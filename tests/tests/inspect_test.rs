@@ -0,0 +1,119 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: captured HAL<->TA messages (including split responses and traffic logs) are
+// decoded and described, with secrets hidden on request.
+
+use kmr_tests::inspect::{describe, parse_log, Direction, Kind, Reassembler};
+use kmr_tests::wire_corpus::{self, PARAM_PREFIX};
+use kmr_wire::keymint::{Algorithm, KeyFormat, KeyParam, KeyPurpose};
+use kmr_wire::*;
+
+fn import_key_req() -> Vec<u8> {
+    PerformOpReq::DeviceImportKey(ImportKeyRequest {
+        key_params: vec![
+            KeyParam::Algorithm(Algorithm::Aes),
+            KeyParam::Purpose(KeyPurpose::Encrypt),
+            KeyParam::ApplicationId(vec![0xaa; 4]),
+        ],
+        key_format: KeyFormat::Raw,
+        key_data: vec![0x42; 16],
+        attestation_key: None,
+    })
+    .into_vec()
+    .unwrap()
+}
+
+#[test]
+fn test_describe_request() {
+    let data = import_key_req();
+    let text = describe(None, &data, false).unwrap();
+    assert!(text.starts_with("request DeviceImportKey"), "{text}");
+    assert!(text.contains("Algorithm(Aes)"), "{text}");
+    assert!(text.contains("Purpose(Encrypt)"), "{text}");
+    assert!(text.contains("ApplicationId(h'aaaaaaaa')"), "{text}");
+    assert!(text.contains(&hex::encode([0x42; 16])), "{text}");
+
+    let text = describe(Some(Kind::Request), &data, true).unwrap();
+    assert!(text.contains("Algorithm(Aes)"), "{text}");
+    assert!(text.contains("ApplicationId(<redacted 4 bytes>)"), "{text}");
+    assert!(text.contains("<redacted 16 bytes>"), "{text}");
+    assert!(!text.contains("4242"), "{text}");
+
+    assert!(describe(Some(Kind::Response), &data, false).is_err());
+}
+
+#[test]
+fn test_describe_response() {
+    let mut km = kmr_tests::host::keymint_ta(
+        kmr_tests::host::crypto_impl(None),
+        kmr_tests::host::device_impl(),
+    );
+    let req = PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {}).into_vec().unwrap();
+    let rsp = km.process(&req);
+    let text = describe(None, &rsp, false).unwrap();
+    assert!(text.starts_with("response error_code=Ok DeviceGetHardwareInfo"), "{text}");
+
+    let rsp = km.process(&[0x82, 0x00]);
+    let text = describe(Some(Kind::Response), &rsp, false).unwrap();
+    assert!(text.starts_with("response error_code="), "{text}");
+    assert!(!text.contains("error_code=Ok"), "{text}");
+}
+
+#[test]
+fn test_describe_corpus() {
+    for entry in wire_corpus::entries() {
+        if entry.name.starts_with(PARAM_PREFIX) {
+            continue;
+        }
+        for redact in [false, true] {
+            describe(None, &entry.data, redact)
+                .unwrap_or_else(|e| panic!("failed to describe {}: {e}", entry.name));
+        }
+    }
+}
+
+#[test]
+fn test_reassemble() {
+    let data = import_key_req();
+    let chunks = kmr_ta::split_rsp(&data, 8).unwrap();
+    assert!(chunks.len() > 1);
+    let mut reassembler = Reassembler::default();
+    for chunk in &chunks[..chunks.len() - 1] {
+        assert_eq!(reassembler.push(chunk), Ok(None));
+        assert!(reassembler.is_pending());
+    }
+    assert_eq!(reassembler.push(chunks.last().unwrap()), Ok(Some(data)));
+    assert!(!reassembler.is_pending());
+
+    assert!(reassembler.push(&[]).is_err());
+    assert!(reassembler.push(&[0x02, 0x00]).is_err());
+}
+
+#[test]
+fn test_parse_log() {
+    let log = "# capture\n\n12 > 821180\n  13 < 0100\n\n< 00\n";
+    let records = parse_log(log).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].timestamp_ms, Some(12));
+    assert_eq!(records[0].direction, Direction::Request);
+    assert_eq!(records[0].data, vec![0x82, 0x11, 0x80]);
+    assert_eq!(records[1].direction, Direction::Response);
+    assert_eq!(records[1].data, vec![0x01, 0x00]);
+    assert_eq!(records[2].timestamp_ms, None);
+    assert_eq!(records[2].data, vec![0x00]);
+
+    assert!(parse_log("12 821180\n").is_err());
+    assert!(parse_log("> 8211zz\n").is_err());
+}
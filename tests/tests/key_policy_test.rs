@@ -0,0 +1,148 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: a device-specific key policy restricts key generation, import and use.
+
+use kmr_common::tag::KeyPolicy;
+use kmr_ta::{device, KeyMintTa};
+use kmr_tests::host::{self, begin, generate_key, import_key};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, Digest, EcCurve, ErrorCode, KeyFormat, KeyParam, KeyPurpose, PaddingMode,
+    UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+
+fn policy() -> KeyPolicy {
+    KeyPolicy {
+        disallowed_algorithms: vec![Algorithm::TripleDes],
+        min_key_sizes: vec![(Algorithm::Rsa, KeySizeInBits(3072))],
+        disallowed_digests: vec![Digest::Sha1],
+        disallowed_padding_modes: vec![PaddingMode::RsaPkcs115Sign],
+        disallowed_purposes: vec![KeyPurpose::AgreeKey],
+        min_mac_length: Some(128),
+        ..Default::default()
+    }
+}
+
+fn restricted_ta() -> KeyMintTa {
+    let dev = device::Implementation { policy: policy(), ..host::device_impl() };
+    host::keymint_ta(host::crypto_impl(None), dev)
+}
+
+fn rsa_params(key_size: u32) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Rsa),
+        KeyParam::KeySize(KeySizeInBits(key_size)),
+        KeyParam::RsaPublicExponent(RsaExponent(65537)),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::Padding(PaddingMode::RsaPss),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+fn aes_gcm_params(min_mac_length: u32) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Aes),
+        KeyParam::KeySize(KeySizeInBits(128)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::BlockMode(BlockMode::Gcm),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::MinMacLength(min_mac_length),
+        KeyParam::NoAuthRequired,
+    ]
+}
+
+#[test]
+fn test_policy_key_generation() {
+    let mut km = restricted_ta();
+
+    let des_params = vec![
+        KeyParam::Algorithm(Algorithm::TripleDes),
+        KeyParam::KeySize(KeySizeInBits(168)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::BlockMode(BlockMode::Ecb),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::NoAuthRequired,
+    ];
+    assert_eq!(generate_key(&mut km, des_params).err(), Some(ErrorCode::UnsupportedAlgorithm));
+
+    assert_eq!(generate_key(&mut km, rsa_params(2048)).err(), Some(ErrorCode::UnsupportedKeySize));
+    assert!(generate_key(&mut km, rsa_params(3072)).is_ok());
+
+    let mut params = rsa_params(3072);
+    params.push(KeyParam::Digest(Digest::Sha1));
+    assert_eq!(generate_key(&mut km, params).err(), Some(ErrorCode::UnsupportedDigest));
+    let mut params = rsa_params(3072);
+    params.push(KeyParam::Padding(PaddingMode::RsaPkcs115Sign));
+    assert_eq!(generate_key(&mut km, params).err(), Some(ErrorCode::UnsupportedPaddingMode));
+
+    let ec_params = vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(KeyPurpose::AgreeKey),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ];
+    assert_eq!(generate_key(&mut km, ec_params).err(), Some(ErrorCode::UnsupportedPurpose));
+
+    assert_eq!(
+        generate_key(&mut km, aes_gcm_params(96)).err(),
+        Some(ErrorCode::UnsupportedMinMacLength)
+    );
+    assert!(generate_key(&mut km, aes_gcm_params(128)).is_ok());
+}
+
+#[test]
+fn test_policy_key_import() {
+    let mut km = restricted_ta();
+    let key_params = vec![
+        KeyParam::Algorithm(Algorithm::Hmac),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha1),
+        KeyParam::MinMacLength(160),
+        KeyParam::NoAuthRequired,
+    ];
+    let result = import_key(&mut km, key_params, KeyFormat::Raw, vec![0x42; 32]);
+    assert_eq!(result.err(), Some(ErrorCode::UnsupportedDigest));
+}
+
+#[test]
+fn test_policy_existing_keys() {
+    // Keys created before the policy was in force can no longer be used if they don't comply.
+    let mut unrestricted_km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let mut params = rsa_params(2048);
+    params.push(KeyParam::Padding(PaddingMode::RsaPkcs115Sign));
+    let small_key = generate_key(&mut unrestricted_km, params).unwrap();
+    let mut params = rsa_params(3072);
+    params.push(KeyParam::Padding(PaddingMode::RsaPkcs115Sign));
+    let large_key = generate_key(&mut unrestricted_km, params).unwrap();
+
+    let mut km = restricted_ta();
+    let pss = vec![KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaPss)];
+    let pkcs1 =
+        vec![KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaPkcs115Sign)];
+    assert_eq!(
+        begin(&mut km, KeyPurpose::Sign, &small_key.key_blob, pss.clone()).err(),
+        Some(ErrorCode::UnsupportedKeySize)
+    );
+    assert!(begin(&mut km, KeyPurpose::Sign, &large_key.key_blob, pss).is_ok());
+    assert_eq!(
+        begin(&mut km, KeyPurpose::Sign, &large_key.key_blob, pkcs1).err(),
+        Some(ErrorCode::UnsupportedPaddingMode)
+    );
+}
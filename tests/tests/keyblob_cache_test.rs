@@ -0,0 +1,97 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: the cache of decrypted keyblobs does not allow use of keys that would otherwise
+// be rejected.
+
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{ErrorCode, KeyParam};
+use kmr_wire::*;
+
+const DATA: &[u8] = b"data";
+
+/// Create a TA with secure storage, and the given keyblob cache size.
+fn keymint_ta(cache_size: usize) -> KeyMintTa {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::sdd_device_impl());
+    km.set_keyblob_cache_size(cache_size);
+    km
+}
+
+#[test]
+fn test_cached_key_use() {
+    for cache_size in [0, 1, 8] {
+        let mut km = keymint_ta(cache_size);
+        let keys: Vec<_> = (0..3)
+            .map(|_| host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired]).unwrap())
+            .collect();
+        for _ in 0..3 {
+            for key in &keys {
+                assert!(
+                    host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok(),
+                    "cache size {cache_size}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cached_key_hidden_params() {
+    let mut km = keymint_ta(8);
+    let app_id = KeyParam::ApplicationId(b"app-id".to_vec());
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, app_id.clone()]).unwrap();
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[app_id], DATA).is_ok());
+
+    // A cached copy of the keyblob is not used for the wrong application ID.
+    let wrong_app_id = KeyParam::ApplicationId(b"other-app-id".to_vec());
+    assert_eq!(
+        host::hmac_sign(&mut km, &key.key_blob, &[wrong_app_id], DATA),
+        Err(ErrorCode::InvalidKeyBlob)
+    );
+    assert_eq!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA), Err(ErrorCode::InvalidKeyBlob));
+}
+
+#[test]
+fn test_cached_key_deleted() {
+    let mut km = keymint_ta(8);
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::RollbackResistance])
+            .unwrap();
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok());
+    exec(
+        &mut km,
+        PerformOpReq::DeviceDeleteKey(DeleteKeyRequest { key_blob: key.key_blob.clone() }),
+    )
+    .unwrap();
+    assert_eq!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA), Err(ErrorCode::InvalidKeyBlob));
+
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::RollbackResistance])
+            .unwrap();
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok());
+    exec(&mut km, PerformOpReq::DeviceDeleteAllKeys(DeleteAllKeysRequest {})).unwrap();
+    assert_eq!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA), Err(ErrorCode::InvalidKeyBlob));
+}
+
+#[test]
+fn test_cached_single_use_key() {
+    let mut km = keymint_ta(8);
+    let key =
+        host::generate_hmac_key(&mut km, &[KeyParam::NoAuthRequired, KeyParam::UsageCountLimit(1)])
+            .unwrap();
+    assert!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA).is_ok());
+    assert_eq!(host::hmac_sign(&mut km, &key.key_blob, &[], DATA), Err(ErrorCode::InvalidKeyBlob));
+}
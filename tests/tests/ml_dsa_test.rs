@@ -0,0 +1,191 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: experimental ML-DSA keys (only built with the `pqc` feature, against OpenSSL
+// 3.5 or later).
+#![cfg(all(feature = "pqc", ossl350))]
+
+use kmr_common::crypto::mldsa;
+use kmr_tests::host::{
+    self, exec, generate_key, import_key, leaf_cert, leaf_public_key, sha256_hex,
+};
+use kmr_wire::keymint::{
+    Algorithm, AttestationKey, Digest, EcCurve, ErrorCode, KeyFormat, KeyParam, KeyPurpose,
+    UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use x509_cert::der::Encode;
+
+/// Message signed in the tests.
+const MSG: &[u8] = b"data to sign";
+
+/// Seed for known-answer tests.
+const SEED: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+/// PKCS#8 encoding of the ML-DSA-65 key derived from `SEED`, holding only the seed.
+const SEED_ONLY_PKCS8: &str = concat!(
+    "3034020100300b0609608648016503040312",
+    "04228020000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+);
+
+/// SHA-256 of the public key for the ML-DSA-65 key derived from `SEED`.
+const ML_DSA_65_PUBLIC_KEY_HASH: &str =
+    "d666806e11cee19a7c989f7445f90dd419cf4d2d51db8c0fdb4c0f0a542238c9";
+
+/// SHA-256 of the public key for the ML-DSA-87 key derived from `SEED`.
+const ML_DSA_87_PUBLIC_KEY_HASH: &str =
+    "91dc389cfaa01470b7f66eee45a4ae9026d154817c754dfe22298b3fa241ffcd";
+
+fn params(key_size: u32, purpose: KeyPurpose) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::MlDsa),
+        KeyParam::KeySize(KeySizeInBits(key_size)),
+        KeyParam::Purpose(purpose),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+#[test]
+fn test_mldsa_generate_and_sign() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let tests = [(65, mldsa::ML_DSA_65_OID, 1952, 3309), (87, mldsa::ML_DSA_87_OID, 2592, 4627)];
+    for (key_size, oid, pub_len, sig_len) in tests {
+        let result = generate_key(&mut km, params(key_size, KeyPurpose::Sign)).unwrap();
+        assert!(result.key_characteristics[0]
+            .authorizations
+            .contains(&KeyParam::KeySize(KeySizeInBits(key_size))));
+
+        // The key is self-signed, so the certificate has an ML-DSA signature.
+        let (key_oid, public_key) = leaf_public_key(&result);
+        assert_eq!(key_oid, oid.to_string());
+        assert_eq!(public_key.len(), pub_len);
+        let cert = leaf_cert(&result);
+        assert!(cert.tbs_certificate.subject_public_key_info.algorithm.parameters.is_none());
+        assert_eq!(cert.signature_algorithm.oid.to_string(), oid.to_string());
+
+        let sig = host::sign(&mut km, &result.key_blob, vec![], MSG).unwrap();
+        assert_eq!(sig.len(), sig_len);
+        let sig = host::sign(&mut km, &result.key_blob, vec![KeyParam::Digest(Digest::None)], MSG)
+            .unwrap();
+        assert_eq!(sig.len(), sig_len);
+    }
+}
+
+#[test]
+fn test_mldsa_import() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+
+    // PKCS#8 with seed only; the parameter set is deduced from the key.
+    let mut key_params = params(65, KeyPurpose::Sign);
+    key_params.retain(|p| !matches!(p, KeyParam::KeySize(_)));
+    let pkcs8 = hex::decode(SEED_ONLY_PKCS8).unwrap();
+    let result = import_key(&mut km, key_params.clone(), KeyFormat::Pkcs8, pkcs8).unwrap();
+    assert!(result.key_characteristics[0]
+        .authorizations
+        .contains(&KeyParam::KeySize(KeySizeInBits(65))));
+    let (_, public_key) = leaf_public_key(&result);
+    assert_eq!(sha256_hex(&public_key), ML_DSA_65_PUBLIC_KEY_HASH);
+    assert!(host::sign(&mut km, &result.key_blob, vec![], MSG).is_ok());
+
+    // PKCS#8 with both seed and expanded key; only the seed is used.
+    let seed = hex::decode(SEED).unwrap();
+    let mut both = vec![0x30, 0x28, 0x04, 0x20];
+    both.extend_from_slice(&seed);
+    both.extend_from_slice(&[0x04, 0x04, 0x01, 0x02, 0x03, 0x04]);
+    let mut pkcs8 = hex::decode("303c020100300b0609608648016503040312042a").unwrap();
+    pkcs8.extend_from_slice(&both);
+    let result = import_key(&mut km, key_params.clone(), KeyFormat::Pkcs8, pkcs8).unwrap();
+    let (_, public_key) = leaf_public_key(&result);
+    assert_eq!(sha256_hex(&public_key), ML_DSA_65_PUBLIC_KEY_HASH);
+
+    // Raw seed import needs the key size to identify the parameter set.
+    let result = import_key(&mut km, key_params, KeyFormat::Raw, seed.clone());
+    assert_eq!(result.err(), Some(ErrorCode::UnsupportedKeySize));
+    for (key_size, want) in [(65, ML_DSA_65_PUBLIC_KEY_HASH), (87, ML_DSA_87_PUBLIC_KEY_HASH)] {
+        let key_params = params(key_size, KeyPurpose::Sign);
+        let result = import_key(&mut km, key_params, KeyFormat::Raw, seed.clone()).unwrap();
+        let (_, public_key) = leaf_public_key(&result);
+        assert_eq!(sha256_hex(&public_key), want);
+    }
+
+    // A mismatched key size is rejected.
+    let pkcs8 = hex::decode(SEED_ONLY_PKCS8).unwrap();
+    let result = import_key(&mut km, params(87, KeyPurpose::Sign), KeyFormat::Pkcs8, pkcs8);
+    assert_eq!(result.err(), Some(ErrorCode::ImportParameterMismatch));
+}
+
+#[test]
+fn test_mldsa_invalid() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    assert_eq!(
+        generate_key(&mut km, params(44, KeyPurpose::Sign)).err(),
+        Some(ErrorCode::UnsupportedKeySize)
+    );
+    assert_eq!(
+        generate_key(&mut km, params(65, KeyPurpose::AgreeKey)).err(),
+        Some(ErrorCode::IncompatiblePurpose)
+    );
+    let mut key_params = params(65, KeyPurpose::Sign);
+    key_params.push(KeyParam::Digest(Digest::Sha256));
+    assert_eq!(generate_key(&mut km, key_params).err(), Some(ErrorCode::InvalidTag));
+
+    let result = generate_key(&mut km, params(65, KeyPurpose::Sign)).unwrap();
+    assert_eq!(
+        host::sign(&mut km, &result.key_blob, vec![KeyParam::Digest(Digest::Sha256)], MSG).err(),
+        Some(ErrorCode::UnsupportedDigest)
+    );
+}
+
+#[test]
+fn test_mldsa_attest_key() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let attest_key = generate_key(&mut km, params(87, KeyPurpose::AttestKey)).unwrap();
+    let attest_cert = leaf_cert(&attest_key);
+
+    // Generate an EC key that is attested by the ML-DSA key.
+    let key_params = vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+        KeyParam::AttestationChallenge(b"challenge".to_vec()),
+        KeyParam::AttestationApplicationId(b"app-id".to_vec()),
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ];
+    let result = match exec(
+        &mut km,
+        PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+            key_params,
+            attestation_key: Some(AttestationKey {
+                key_blob: attest_key.key_blob,
+                attest_key_params: vec![],
+                issuer_subject_name: attest_cert.tbs_certificate.subject.to_der().unwrap(),
+            }),
+        }),
+    ) {
+        Ok(PerformOpRsp::DeviceGenerateKey(rsp)) => rsp.ret,
+        _ => panic!("unexpected response"),
+    };
+
+    // The attested key's certificate is signed with ML-DSA.
+    assert_eq!(result.certificate_chain.len(), 1);
+    let cert = leaf_cert(&result);
+    assert_eq!(cert.signature_algorithm.oid.to_string(), mldsa::ML_DSA_87_OID.to_string());
+    assert!(cert.signature_algorithm.parameters.is_none());
+    assert_eq!(cert.signature.raw_bytes().len(), 4627);
+}
@@ -0,0 +1,143 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: experimental ML-KEM keys (only built with the `pqc` feature, against OpenSSL
+// 3.5 or later).
+#![cfg(all(feature = "pqc", ossl350))]
+
+use kmr_common::crypto::mlkem;
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, generate_key, import_key, leaf_public_key, sha256_hex};
+use kmr_wire::keymint::{
+    Algorithm, Digest, ErrorCode, KeyFormat, KeyParam, KeyPurpose, UNDEFINED_NOT_AFTER,
+    UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+
+/// Seed for known-answer tests.
+const SEED: &str = concat!(
+    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+    "202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f"
+);
+
+/// Prefix of the PKCS#8 encoding of the ML-KEM-768 key derived from `SEED`, holding only the seed.
+const SEED_ONLY_PKCS8_PREFIX: &str = "3054020100300b060960864801650304040204428040";
+
+/// SHA-256 of the encapsulation key for the ML-KEM-768 key derived from `SEED`.
+const ENCAPSULATION_KEY_HASH: &str =
+    "0b7934c83125c788995e2ba6bd761e33046b3e40571be53e023309a29f398cc9";
+
+/// Shared secret from decapsulating an all-zero ciphertext with the ML-KEM-768 key derived from
+/// `SEED` (which is an implicit rejection).
+const ZERO_CIPHERTEXT_SECRET: &str =
+    "c8fbeddafdacef2ffeb8b354ea644f11b5c150f3e2c4a74ce38abba8f854ae16";
+
+fn params(key_size: u32, purpose: KeyPurpose) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::MlKem),
+        KeyParam::KeySize(KeySizeInBits(key_size)),
+        KeyParam::Purpose(purpose),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+fn decapsulate(
+    km: &mut KeyMintTa,
+    key_blob: &[u8],
+    params: Vec<KeyParam>,
+    ciphertext: Vec<u8>,
+) -> Result<Vec<u8>, ErrorCode> {
+    host::run_op(km, KeyPurpose::AgreeKey, key_blob, params, &ciphertext, None)
+        .map(|(secret, _)| secret)
+}
+
+#[test]
+fn test_mlkem_generate_and_agree() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let result = generate_key(&mut km, params(768, KeyPurpose::AgreeKey)).unwrap();
+    assert!(result.key_characteristics[0]
+        .authorizations
+        .contains(&KeyParam::KeySize(mlkem::KEY_SIZE)));
+    let (oid, ek) = leaf_public_key(&result);
+    assert_eq!(oid, mlkem::ML_KEM_768_OID.to_string());
+    assert_eq!(ek.len(), mlkem::ENCAPSULATION_KEY_LEN);
+
+    // A peer encapsulates to the key in the certificate; decapsulation recovers the same secret.
+    let (ciphertext, want) = kmr_crypto_boring::mlkem::encapsulate(&ek).unwrap();
+    assert_eq!(ciphertext.len(), mlkem::CIPHERTEXT_LEN);
+    let got = decapsulate(&mut km, &result.key_blob, vec![], ciphertext).unwrap();
+    assert_eq!(got, want);
+    assert_eq!(got.len(), mlkem::SHARED_SECRET_LEN);
+
+    // A ciphertext of the wrong length is rejected.
+    assert_eq!(
+        decapsulate(&mut km, &result.key_blob, vec![], vec![0; 32]).err(),
+        Some(ErrorCode::InvalidInputLength)
+    );
+}
+
+#[test]
+fn test_mlkem_import() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let seed = hex::decode(SEED).unwrap();
+    let mut pkcs8 = hex::decode(SEED_ONLY_PKCS8_PREFIX).unwrap();
+    pkcs8.extend_from_slice(&seed);
+
+    // The key size is optional on import, as there is only one parameter set.
+    let mut key_params = params(768, KeyPurpose::AgreeKey);
+    key_params.retain(|p| !matches!(p, KeyParam::KeySize(_)));
+    for (key_format, key_data) in [(KeyFormat::Pkcs8, pkcs8.clone()), (KeyFormat::Raw, seed)] {
+        let result = import_key(&mut km, key_params.clone(), key_format, key_data).unwrap();
+        assert!(result.key_characteristics[0]
+            .authorizations
+            .contains(&KeyParam::KeySize(mlkem::KEY_SIZE)));
+        let (_, ek) = leaf_public_key(&result);
+        assert_eq!(sha256_hex(&ek), ENCAPSULATION_KEY_HASH);
+
+        let secret =
+            decapsulate(&mut km, &result.key_blob, vec![], vec![0; mlkem::CIPHERTEXT_LEN]).unwrap();
+        assert_eq!(hex::encode(secret), ZERO_CIPHERTEXT_SECRET);
+    }
+
+    // A mismatched key size is rejected.
+    let result = import_key(&mut km, params(512, KeyPurpose::AgreeKey), KeyFormat::Pkcs8, pkcs8);
+    assert_eq!(result.err(), Some(ErrorCode::ImportParameterMismatch));
+}
+
+#[test]
+fn test_mlkem_invalid() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    assert_eq!(
+        generate_key(&mut km, params(1024, KeyPurpose::AgreeKey)).err(),
+        Some(ErrorCode::UnsupportedKeySize)
+    );
+    assert_eq!(
+        generate_key(&mut km, params(768, KeyPurpose::Sign)).err(),
+        Some(ErrorCode::IncompatiblePurpose)
+    );
+
+    let result = generate_key(&mut km, params(768, KeyPurpose::AgreeKey)).unwrap();
+    assert_eq!(
+        decapsulate(
+            &mut km,
+            &result.key_blob,
+            vec![KeyParam::Digest(Digest::Sha256)],
+            vec![0; mlkem::CIPHERTEXT_LEN]
+        )
+        .err(),
+        Some(ErrorCode::InvalidTag)
+    );
+}
@@ -0,0 +1,300 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: key generation, import and use with a crypto implementation that only ever
+// hands out opaque key material, backed by the software secure element in `kmr_tests::opaque`.
+
+use kmr_ta::{self_test, KeyMintTa};
+use kmr_tests::host;
+use kmr_tests::opaque::{SeAes, SeCkdf, SeDes, SeHmac, SecureElement};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, Digest, EcCurve, KeyFormat, KeyParam, KeyPurpose, PaddingMode,
+    UNDEFINED_NOT_AFTER, UNDEFINED_NOT_BEFORE,
+};
+use kmr_wire::*;
+use std::sync::Arc;
+
+const MSG: &[u8] = b"opaque key test message";
+
+fn opaque_ta(se: &Arc<SecureElement>) -> KeyMintTa {
+    host::keymint_ta(host::opaque_crypto_impl(se, None), host::opaque_device_impl(se))
+}
+
+/// Encrypt and decrypt [`MSG`] with a symmetric key, checking that the round trip works.
+fn check_round_trip(km: &mut KeyMintTa, key_blob: &[u8], params: Vec<KeyParam>) {
+    let (ct, begin_params) =
+        host::run_op(km, KeyPurpose::Encrypt, key_blob, params.clone(), MSG, None).unwrap();
+    assert_ne!(ct, MSG);
+    let mut params = params;
+    params.extend(begin_params);
+    let (pt, _) = host::run_op(km, KeyPurpose::Decrypt, key_blob, params, &ct, None).unwrap();
+    assert_eq!(pt, MSG);
+}
+
+fn sym_params(algorithm: Algorithm, key_size: u32, block_mode: BlockMode) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(algorithm),
+        KeyParam::KeySize(KeySizeInBits(key_size)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::Purpose(KeyPurpose::Decrypt),
+        KeyParam::BlockMode(block_mode),
+        KeyParam::Padding(PaddingMode::Pkcs7),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::MinMacLength(128),
+        KeyParam::NoAuthRequired,
+    ]
+}
+
+fn ec_params(purpose: KeyPurpose) -> Vec<KeyParam> {
+    vec![
+        KeyParam::Algorithm(Algorithm::Ec),
+        KeyParam::EcCurve(EcCurve::P256),
+        KeyParam::Purpose(purpose),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ]
+}
+
+fn rsa_params(purpose: KeyPurpose) -> Vec<KeyParam> {
+    let mut params = vec![
+        KeyParam::Algorithm(Algorithm::Rsa),
+        KeyParam::Purpose(purpose),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::NoAuthRequired,
+        KeyParam::CertificateNotBefore(UNDEFINED_NOT_BEFORE),
+        KeyParam::CertificateNotAfter(UNDEFINED_NOT_AFTER),
+    ];
+    if purpose == KeyPurpose::Decrypt {
+        params.push(KeyParam::Padding(PaddingMode::RsaOaep));
+        params.push(KeyParam::RsaOaepMgfDigest(Digest::Sha256));
+    } else {
+        params.push(KeyParam::Padding(PaddingMode::RsaPkcs115Sign));
+    }
+    params
+}
+
+#[test]
+fn test_opaque_self_test() {
+    let se = SecureElement::new();
+    self_test::run(&host::opaque_crypto_impl(&se, None)).expect("self test failed");
+    // The known-answer tests import their keys, so all of them went through the secure element.
+    assert!(se.key_count() > 0);
+}
+
+#[test]
+fn test_opaque_trait_impls() {
+    // Run the smoke tests for individual trait implementations against the secure element
+    // wrappers that make up `host::opaque_crypto_impl()`.  The tests use explicit keys, which the
+    // wrappers pass through to the host implementation.  The RNG and SHA-256 implementations are
+    // not wrapped.
+    let se = SecureElement::new();
+    let imp = host::crypto_impl(None);
+    kmr_tests::test_aes_gcm(SeAes::new(&se, imp.aes));
+    kmr_tests::test_des(SeDes::new(&se, imp.des));
+    kmr_tests::test_ckdf(SeCkdf::new(&se, imp.ckdf));
+    let hmac = || SeHmac::new(&se, host::crypto_impl(None).hmac);
+    kmr_tests::test_hmac(hmac());
+    kmr_tests::test_hkdf(hmac());
+    kmr_tests::test_hmac_drbg(hmac());
+}
+
+#[test]
+fn test_opaque_symmetric() {
+    let se = SecureElement::new();
+    let mut km = opaque_ta(&se);
+    let initial = se.key_count();
+
+    let aes_modes = [
+        (BlockMode::Ecb, vec![KeyParam::Padding(PaddingMode::Pkcs7)]),
+        (BlockMode::Cbc, vec![KeyParam::Padding(PaddingMode::Pkcs7)]),
+        (BlockMode::Ctr, vec![KeyParam::Padding(PaddingMode::None)]),
+        (BlockMode::Gcm, vec![KeyParam::Padding(PaddingMode::None), KeyParam::MacLength(128)]),
+    ];
+    for (block_mode, op_params) in aes_modes {
+        let result =
+            host::generate_key(&mut km, sym_params(Algorithm::Aes, 256, block_mode)).unwrap();
+        let mut params = vec![KeyParam::BlockMode(block_mode)];
+        params.extend(op_params);
+        check_round_trip(&mut km, &result.key_blob, params);
+    }
+
+    let result =
+        host::generate_key(&mut km, sym_params(Algorithm::TripleDes, 168, BlockMode::Cbc)).unwrap();
+    check_round_trip(
+        &mut km,
+        &result.key_blob,
+        vec![KeyParam::BlockMode(BlockMode::Cbc), KeyParam::Padding(PaddingMode::Pkcs7)],
+    );
+
+    let hmac_params = vec![
+        KeyParam::Algorithm(Algorithm::Hmac),
+        KeyParam::KeySize(KeySizeInBits(256)),
+        KeyParam::Purpose(KeyPurpose::Sign),
+        KeyParam::Purpose(KeyPurpose::Verify),
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::MinMacLength(256),
+        KeyParam::NoAuthRequired,
+    ];
+    let result = host::generate_key(&mut km, hmac_params.clone()).unwrap();
+    let sign_params = vec![KeyParam::Digest(Digest::Sha256), KeyParam::MacLength(256)];
+    let (mac, _) =
+        host::run_op(&mut km, KeyPurpose::Sign, &result.key_blob, sign_params, MSG, None).unwrap();
+    let verify_params = vec![KeyParam::Digest(Digest::Sha256)];
+    host::run_op(&mut km, KeyPurpose::Verify, &result.key_blob, verify_params, MSG, Some(mac))
+        .unwrap();
+
+    // Imported HMAC keys are held in the secure element too, and give the same answers as the
+    // explicit implementation.
+    let case = &self_test::HMAC_TESTS[0];
+    let mut params = hmac_params;
+    params.retain(|p| !matches!(p, KeyParam::KeySize(_) | KeyParam::Digest(_)));
+    params.push(KeyParam::KeySize(KeySizeInBits(case.key.len() as u32 * 8)));
+    params.push(KeyParam::Digest(case.digest));
+    let result = host::import_key(&mut km, params, KeyFormat::Raw, case.key.to_vec()).unwrap();
+    let sign_params =
+        vec![KeyParam::Digest(case.digest), KeyParam::MacLength(case.tag_size as u32 * 8)];
+    let (mac, _) =
+        host::run_op(&mut km, KeyPurpose::Sign, &result.key_blob, sign_params, case.data, None)
+            .unwrap();
+    assert_eq!(hex::encode(mac), case.expected_mac);
+
+    // Four AES keys, one 3DES key and two HMAC keys, plus the keyblob encryption keys derived
+    // (from the opaque root KEK) for each request that touches a keyblob.
+    assert!(se.key_count() > initial + 7);
+}
+
+#[test]
+fn test_opaque_rsa() {
+    let se = SecureElement::new();
+    let mut km = opaque_ta(&se);
+
+    // A generated key gets a self-signed certificate, which needs the public key.
+    let mut params = rsa_params(KeyPurpose::Sign);
+    params.push(KeyParam::KeySize(KeySizeInBits(2048)));
+    params.push(KeyParam::RsaPublicExponent(RsaExponent(65537)));
+    let result = host::generate_key(&mut km, params).unwrap();
+    assert_eq!(result.certificate_chain.len(), 1);
+    let sign_params =
+        vec![KeyParam::Digest(Digest::Sha256), KeyParam::Padding(PaddingMode::RsaPkcs115Sign)];
+    host::run_op(&mut km, KeyPurpose::Sign, &result.key_blob, sign_params.clone(), MSG, None)
+        .unwrap();
+
+    // Known-answer checks on an imported key.
+    let key = hex::decode(self_test::RSA_KEY).unwrap();
+    let result =
+        host::import_key(&mut km, rsa_params(KeyPurpose::Sign), KeyFormat::Pkcs8, key.clone())
+            .unwrap();
+    let (sig, _) = host::run_op(
+        &mut km,
+        KeyPurpose::Sign,
+        &result.key_blob,
+        sign_params,
+        self_test::RSA_MSG,
+        None,
+    )
+    .unwrap();
+    assert_eq!(hex::encode(sig), self_test::RSA_SIG);
+    let decrypt_params = vec![
+        KeyParam::Digest(Digest::Sha256),
+        KeyParam::Padding(PaddingMode::RsaOaep),
+        KeyParam::RsaOaepMgfDigest(Digest::Sha256),
+    ];
+    let result =
+        host::import_key(&mut km, rsa_params(KeyPurpose::Decrypt), KeyFormat::Pkcs8, key).unwrap();
+    let ct = hex::decode(self_test::RSA_OAEP_CT).unwrap();
+    let (pt, _) =
+        host::run_op(&mut km, KeyPurpose::Decrypt, &result.key_blob, decrypt_params, &ct, None)
+            .unwrap();
+    assert_eq!(pt, self_test::RSA_MSG);
+}
+
+#[test]
+fn test_opaque_ec() {
+    let se = SecureElement::new();
+    let mut km = opaque_ta(&se);
+
+    let result = host::generate_key(&mut km, ec_params(KeyPurpose::Sign)).unwrap();
+    assert_eq!(result.certificate_chain.len(), 1);
+    let sign_params = vec![KeyParam::Digest(Digest::Sha256)];
+    host::run_op(&mut km, KeyPurpose::Sign, &result.key_blob, sign_params, MSG, None).unwrap();
+
+    // Two generated keys agree on the same shared secret.
+    let a = host::generate_key(&mut km, ec_params(KeyPurpose::AgreeKey)).unwrap();
+    let b = host::generate_key(&mut km, ec_params(KeyPurpose::AgreeKey)).unwrap();
+    let (secret_a, _) = host::run_op(
+        &mut km,
+        KeyPurpose::AgreeKey,
+        &a.key_blob,
+        vec![],
+        &host::leaf_spki(&b),
+        None,
+    )
+    .unwrap();
+    let (secret_b, _) = host::run_op(
+        &mut km,
+        KeyPurpose::AgreeKey,
+        &b.key_blob,
+        vec![],
+        &host::leaf_spki(&a),
+        None,
+    )
+    .unwrap();
+    assert_eq!(secret_a, secret_b);
+
+    // Known-answer check on an imported key.
+    let key = hex::decode(self_test::EC_KEY).unwrap();
+    let result =
+        host::import_key(&mut km, ec_params(KeyPurpose::AgreeKey), KeyFormat::Pkcs8, key).unwrap();
+    assert_eq!(host::leaf_spki(&result)[26..], hex::decode(self_test::EC_PUBLIC_KEY).unwrap());
+    let peer = hex::decode(self_test::EC_PEER_SPKI).unwrap();
+    let (secret, _) =
+        host::run_op(&mut km, KeyPurpose::AgreeKey, &result.key_blob, vec![], &peer, None).unwrap();
+    assert_eq!(hex::encode(secret), self_test::EC_SHARED_SECRET);
+}
+
+#[test]
+fn test_opaque_handles_not_transferable() {
+    let se = SecureElement::new();
+    let mut km = opaque_ta(&se);
+    let result = host::generate_key(&mut km, ec_params(KeyPurpose::Sign)).unwrap();
+
+    // A TA with a different secure element (but the same device keys) can't find the key.
+    let other_se = SecureElement::new();
+    let mut other_km = opaque_ta(&other_se);
+    let sign_params = vec![KeyParam::Digest(Digest::Sha256)];
+    assert!(host::run_op(
+        &mut other_km,
+        KeyPurpose::Sign,
+        &result.key_blob,
+        sign_params,
+        MSG,
+        None
+    )
+    .is_err());
+
+    // Nor can a TA with explicit device keys, because the keyblob encryption key differs.
+    let mut explicit_km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let sign_params = vec![KeyParam::Digest(Digest::Sha256)];
+    assert!(host::run_op(
+        &mut explicit_km,
+        KeyPurpose::Sign,
+        &result.key_blob,
+        sign_params,
+        MSG,
+        None
+    )
+    .is_err());
+}
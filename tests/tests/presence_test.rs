@@ -0,0 +1,162 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: operations on TRUSTED_USER_PRESENCE_REQUIRED keys wait for, and verify, a
+// trusted user presence event.
+
+use kmr_common::crypto::MonotonicClock;
+use kmr_ta::device::TRUSTED_PRESENCE_TIMEOUT_MS;
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, exec, PresenceAction, SoftPresence, TestClock};
+use kmr_wire::keymint::{
+    Algorithm, BlockMode, ErrorCode, HardwareAuthenticatorType, KeyParam, KeyPurpose, PaddingMode,
+};
+use kmr_wire::*;
+
+fn setup() -> (TestClock, SoftPresence, KeyMintTa, Vec<u8>) {
+    let clock = TestClock::default();
+    let presence = SoftPresence::new(clock.clone());
+    let mut dev = host::device_impl();
+    dev.tup = Box::new(presence.clone());
+    let mut km = host::keymint_ta(host::crypto_impl(Some(clock.clone())), dev);
+    let key_blob = generate_key(&mut km, KeyParam::NoAuthRequired);
+    (clock, presence, km, key_blob)
+}
+
+fn generate_key(km: &mut KeyMintTa, auth: KeyParam) -> Vec<u8> {
+    let mut key_params = vec![
+        KeyParam::Algorithm(Algorithm::Aes),
+        KeyParam::KeySize(KeySizeInBits(128)),
+        KeyParam::Purpose(KeyPurpose::Encrypt),
+        KeyParam::BlockMode(BlockMode::Ecb),
+        KeyParam::Padding(PaddingMode::None),
+        KeyParam::TrustedUserPresenceRequired,
+    ];
+    key_params.push(auth);
+    if matches!(key_params.last(), Some(KeyParam::UserSecureId(_))) {
+        key_params.push(KeyParam::UserAuthType(HardwareAuthenticatorType::Password as u32));
+    }
+    host::generate_key(km, key_params).expect("key generation failed").key_blob
+}
+
+fn begin(km: &mut KeyMintTa, key_blob: &[u8]) -> Result<i64, ErrorCode> {
+    let params = vec![KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::None)];
+    Ok(host::begin(km, KeyPurpose::Encrypt, key_blob, params)?.op_handle)
+}
+
+fn update(km: &mut KeyMintTa, op_handle: i64) -> Result<Vec<u8>, ErrorCode> {
+    host::update(km, op_handle, &[0; 16])
+}
+
+fn finish(km: &mut KeyMintTa, op_handle: i64) -> Result<Vec<u8>, ErrorCode> {
+    host::finish(km, op_handle, &[0; 16], None)
+}
+
+fn abort(km: &mut KeyMintTa, op_handle: i64) {
+    exec(km, PerformOpReq::OperationAbort(AbortRequest { op_handle })).expect("abort failed");
+}
+
+#[test]
+fn test_presence_detected() {
+    let (clock, presence, mut km, key_blob) = setup();
+    presence.push(PresenceAction::Press { delay_ms: 2_000 });
+    let start = clock.now().0;
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(finish(&mut km, op_handle).unwrap().len(), 16);
+    // Finishing the operation blocked until the button was pressed.
+    assert_eq!(clock.now().0, start + 2_000);
+}
+
+#[test]
+fn test_presence_checked_once() {
+    let (_clock, presence, mut km, key_blob) = setup();
+    presence.push(PresenceAction::Press { delay_ms: 10 });
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(update(&mut km, op_handle).unwrap().len(), 16);
+    // The presence event seen on update covers the rest of the operation.
+    assert_eq!(finish(&mut km, op_handle).unwrap().len(), 16);
+}
+
+#[test]
+fn test_presence_timeout() {
+    let (clock, presence, mut km, key_blob) = setup();
+    presence.push(PresenceAction::Ignore);
+    let start = clock.now().0;
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::ProofOfPresenceRequired));
+    assert_eq!(clock.now().0, start + TRUSTED_PRESENCE_TIMEOUT_MS as i64);
+
+    // A timeout on update destroys the operation.
+    presence.push(PresenceAction::Ignore);
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(update(&mut km, op_handle), Err(ErrorCode::ProofOfPresenceRequired));
+    assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::InvalidOperation));
+}
+
+#[test]
+fn test_presence_outside_window() {
+    let (_clock, presence, mut km, key_blob) = setup();
+    let late = TRUSTED_PRESENCE_TIMEOUT_MS as i64 + 1;
+    for delay_ms in [-1, late] {
+        presence.push(PresenceAction::Press { delay_ms });
+        let op_handle = begin(&mut km, &key_blob).unwrap();
+        assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::ProofOfPresenceRequired));
+    }
+}
+
+#[test]
+fn test_presence_concurrent() {
+    let (_clock, presence, mut km, key_blob) = setup();
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(begin(&mut km, &key_blob), Err(ErrorCode::ConcurrentProofOfPresenceRequested));
+
+    // Aborting the first operation cancels its wait and allows another.
+    abort(&mut km, op_handle);
+    assert_eq!(presence.cancelled(), 1);
+    presence.push(PresenceAction::Press { delay_ms: 0 });
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(finish(&mut km, op_handle).unwrap().len(), 16);
+}
+
+#[test]
+fn test_presence_unsupported() {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    let key_blob = generate_key(&mut km, KeyParam::NoAuthRequired);
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::ProofOfPresenceRequired));
+}
+
+#[test]
+fn test_presence_after_auth_failure() {
+    let (clock, presence, mut km, _key_blob) = setup();
+    let key_blob = generate_key(&mut km, KeyParam::UserSecureId(1));
+    let start = clock.now().0;
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+
+    // An unauthenticated update does not wait for (or use up) the presence event.
+    assert_eq!(update(&mut km, op_handle), Err(ErrorCode::KeyUserNotAuthenticated));
+    assert_eq!(clock.now().0, start);
+
+    // An unauthenticated finish destroys the operation, and cancels its wait.
+    assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::KeyUserNotAuthenticated));
+    assert_eq!(clock.now().0, start);
+    assert_eq!(presence.cancelled(), 1);
+    assert_eq!(finish(&mut km, op_handle), Err(ErrorCode::InvalidOperation));
+
+    // So another operation that needs presence can begin.
+    let key_blob = generate_key(&mut km, KeyParam::NoAuthRequired);
+    presence.push(PresenceAction::Press { delay_ms: 0 });
+    let op_handle = begin(&mut km, &key_blob).unwrap();
+    assert_eq!(finish(&mut km, op_handle).unwrap().len(), 16);
+}
@@ -0,0 +1,131 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: recorded HAL<->TA traffic replays identically against a TA with the same
+// deterministic random number generator, and differences are reported otherwise.
+
+use kmr_tests::replay::{parse_recording, replay, replay_ta, Exchange};
+use kmr_wire::keymint::{Algorithm, BlockMode, KeyParam, KeyPurpose, PaddingMode};
+use kmr_wire::*;
+
+/// Index of the `DeviceGenerateKey` exchange in the recording.
+const GENERATE_IDX: usize = 2;
+/// Index of the final `OperationFinish` exchange in the recording.
+const FINISH_IDX: usize = 5;
+
+/// Record an AES encryption session with a TA seeded with `seed`, in the format written by
+/// `kmr_hal::record::RecordingChannel`.
+fn record(seed: u64) -> String {
+    let (mut ta, clock) = replay_ta(seed);
+    let mut recording = String::from("# recording\n");
+    let mut now_ms = 0;
+    let mut send = |req: PerformOpReq| -> PerformOpRsp {
+        let req = req.into_vec().unwrap();
+        now_ms += 7;
+        clock.advance(7);
+        let rsp = ta.process(&req);
+        recording +=
+            &format!("{} > {}\n{} < {}\n", now_ms, hex::encode(&req), now_ms, hex::encode(&rsp));
+        PerformOpResponse::from_slice(&rsp).unwrap().rsp.expect("request failed")
+    };
+
+    send(PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {}));
+    send(PerformOpReq::DeviceAddRngEntropy(AddRngEntropyRequest { data: vec![1, 2, 3] }));
+    let key_blob = match send(PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+        key_params: vec![
+            KeyParam::Algorithm(Algorithm::Aes),
+            KeyParam::KeySize(KeySizeInBits(128)),
+            KeyParam::Purpose(KeyPurpose::Encrypt),
+            KeyParam::BlockMode(BlockMode::Ecb),
+            KeyParam::Padding(PaddingMode::None),
+            KeyParam::NoAuthRequired,
+        ],
+        attestation_key: None,
+    })) {
+        PerformOpRsp::DeviceGenerateKey(rsp) => rsp.ret.key_blob,
+        _ => panic!("unexpected response"),
+    };
+    let op_handle = match send(PerformOpReq::DeviceBegin(BeginRequest {
+        purpose: KeyPurpose::Encrypt,
+        key_blob,
+        params: vec![KeyParam::BlockMode(BlockMode::Ecb), KeyParam::Padding(PaddingMode::None)],
+        auth_token: None,
+    })) {
+        PerformOpRsp::DeviceBegin(rsp) => rsp.ret.op_handle,
+        _ => panic!("unexpected response"),
+    };
+    send(PerformOpReq::OperationUpdate(UpdateRequest {
+        op_handle,
+        input: vec![0x42; 16],
+        auth_token: None,
+        timestamp_token: None,
+    }));
+    send(PerformOpReq::OperationFinish(FinishRequest {
+        op_handle,
+        input: Some(vec![0x43; 16]),
+        signature: None,
+        auth_token: None,
+        timestamp_token: None,
+        confirmation_token: None,
+    }));
+    recording
+}
+
+#[test]
+fn test_replay_identical() {
+    let exchanges = parse_recording(&record(1)).unwrap();
+    assert_eq!(exchanges.len(), FINISH_IDX + 1);
+    assert_eq!(exchanges[GENERATE_IDX].timestamp_ms, Some(21));
+
+    let (mut ta, clock) = replay_ta(1);
+    let differences = replay(&mut ta, &clock, &exchanges);
+    assert!(differences.is_empty(), "{}", differences[0]);
+}
+
+#[test]
+fn test_replay_different_seed() {
+    let exchanges = parse_recording(&record(1)).unwrap();
+    let (mut ta, clock) = replay_ta(2);
+    let differences = replay(&mut ta, &clock, &exchanges);
+    assert_eq!(differences.first().map(|d| d.index), Some(GENERATE_IDX));
+}
+
+#[test]
+fn test_replay_tampered() {
+    let mut exchanges = parse_recording(&record(1)).unwrap();
+    let rsp = exchanges[FINISH_IDX].rsp.as_mut().unwrap();
+    *rsp.last_mut().unwrap() ^= 0x01;
+
+    let (mut ta, clock) = replay_ta(1);
+    let differences = replay(&mut ta, &clock, &exchanges);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].index, FINISH_IDX);
+    let text = differences[0].to_string();
+    assert!(text.contains("request OperationFinish"), "{text}");
+    assert!(text.contains("recorded response error_code=Ok OperationFinish"), "{text}");
+}
+
+#[test]
+fn test_parse_recording() {
+    let text = "10 > 821180\n# 12 channel error: failed\n20 > 821180\n21 < 00\n";
+    assert_eq!(
+        parse_recording(text).unwrap(),
+        vec![
+            Exchange { timestamp_ms: Some(10), req: vec![0x82, 0x11, 0x80], rsp: None },
+            Exchange { timestamp_ms: Some(20), req: vec![0x82, 0x11, 0x80], rsp: Some(vec![0]) },
+        ]
+    );
+    assert!(parse_recording("10 < 00\n").is_err());
+    assert!(parse_recording("10 > 821180\n11 < 00\n12 < 00\n").is_err());
+}
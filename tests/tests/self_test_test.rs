@@ -0,0 +1,167 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: power-on known-answer self tests of a crypto implementation, and latching of
+// crypto failures in the TA.
+
+use kmr_common::{
+    crypto::{ec, AccumulatingOperation, Ec, KeyMaterial, OpaqueOr, Rng, Sha256},
+    Error,
+};
+use kmr_crypto_boring::ec::BoringEc;
+use kmr_ta::self_test;
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{Algorithm, Digest, ErrorCode, KeyParam, KeyPurpose};
+use kmr_wire::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// SHA-256 implementation that gets the wrong answer.
+struct BrokenSha256;
+
+impl Sha256 for BrokenSha256 {
+    fn hash(&self, _data: &[u8]) -> Result<[u8; 32], Error> {
+        Ok([0x42; 32])
+    }
+}
+
+/// EC implementation that relies on the default (unimplemented) signature verification.
+struct NoVerifyEc(BoringEc);
+
+impl Ec for NoVerifyEc {
+    fn generate_nist_key(
+        &self,
+        rng: &mut dyn Rng,
+        curve: ec::NistCurve,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.0.generate_nist_key(rng, curve, params)
+    }
+    fn generate_ed25519_key(
+        &self,
+        rng: &mut dyn Rng,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.0.generate_ed25519_key(rng, params)
+    }
+    fn generate_x25519_key(
+        &self,
+        rng: &mut dyn Rng,
+        params: &[KeyParam],
+    ) -> Result<KeyMaterial, Error> {
+        self.0.generate_x25519_key(rng, params)
+    }
+    fn nist_public_key(&self, key: &ec::NistKey, curve: ec::NistCurve) -> Result<Vec<u8>, Error> {
+        self.0.nist_public_key(key, curve)
+    }
+    fn ed25519_public_key(&self, key: &ec::Ed25519Key) -> Result<Vec<u8>, Error> {
+        self.0.ed25519_public_key(key)
+    }
+    fn x25519_public_key(&self, key: &ec::X25519Key) -> Result<Vec<u8>, Error> {
+        self.0.x25519_public_key(key)
+    }
+    fn begin_agree(&self, key: OpaqueOr<ec::Key>) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.0.begin_agree(key)
+    }
+    fn begin_sign(
+        &self,
+        key: OpaqueOr<ec::Key>,
+        digest: Digest,
+    ) -> Result<Box<dyn AccumulatingOperation>, Error> {
+        self.0.begin_sign(key, digest)
+    }
+}
+
+/// Random number generator that fails on the next request for random data once armed.
+struct FailingRng {
+    armed: Arc<AtomicBool>,
+    failed: bool,
+}
+
+impl Rng for FailingRng {
+    fn add_entropy(&mut self, _data: &[u8]) {}
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.failed |= self.armed.load(Ordering::SeqCst);
+        dest.fill(if self.failed { 0 } else { 0x42 });
+    }
+    fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+#[test]
+fn test_self_tests_pass() {
+    let imp = host::crypto_impl(None);
+    self_test::run(&imp).expect("self tests failed");
+
+    let mut km = host::keymint_ta(imp, host::device_impl());
+    km.run_self_tests().expect("self tests failed");
+    assert!(exec(&mut km, PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {})).is_ok());
+}
+
+#[test]
+fn test_self_tests_without_ecdsa_verify() {
+    // An `Ec` implementation that cannot verify signatures skips the ECDSA check.
+    let mut imp = host::crypto_impl(None);
+    imp.ec = Box::new(NoVerifyEc(BoringEc::default()));
+    self_test::run(&imp).expect("self tests failed");
+}
+
+#[test]
+fn test_self_test_failure_latched() {
+    let mut imp = host::crypto_impl(None);
+    imp.sha256 = Box::new(BrokenSha256);
+    assert!(self_test::run(&imp).is_err());
+
+    let mut km = host::keymint_ta(imp, host::device_impl());
+    assert!(km.run_self_tests().is_err());
+
+    // All subsequent requests are rejected.
+    for req in [
+        PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {}),
+        PerformOpReq::DeviceAddRngEntropy(AddRngEntropyRequest { data: vec![1, 2, 3] }),
+        PerformOpReq::SharedSecretGetSharedSecretParameters(GetSharedSecretParametersRequest {}),
+    ] {
+        assert_eq!(exec(&mut km, req).err(), Some(ErrorCode::HardwareNotYetAvailable));
+    }
+}
+
+#[test]
+fn test_rng_failure_latched() {
+    let armed = Arc::new(AtomicBool::new(false));
+    let mut imp = host::crypto_impl(None);
+    imp.rng = Box::new(FailingRng { armed: armed.clone(), failed: false });
+    let mut km = host::keymint_ta(imp, host::device_impl());
+    let generate = || {
+        PerformOpReq::DeviceGenerateKey(GenerateKeyRequest {
+            key_params: vec![
+                KeyParam::Algorithm(Algorithm::Aes),
+                KeyParam::KeySize(KeySizeInBits(128)),
+                KeyParam::Purpose(KeyPurpose::Encrypt),
+                KeyParam::NoAuthRequired,
+            ],
+            attestation_key: None,
+        })
+    };
+    assert!(exec(&mut km, generate()).is_ok());
+
+    // A key generated while the generator fails is discarded, and all subsequent requests are
+    // rejected.
+    armed.store(true, Ordering::SeqCst);
+    for req in [generate(), PerformOpReq::DeviceGetHardwareInfo(GetHardwareInfoRequest {})] {
+        assert_eq!(exec(&mut km, req).err(), Some(ErrorCode::HardwareNotYetAvailable));
+    }
+}
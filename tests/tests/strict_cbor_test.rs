@@ -0,0 +1,81 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: a TA with strict CBOR decoding enabled rejects requests and keyblobs that are
+// not deterministically encoded, but still accepts everything that it emits itself.
+
+use kmr_ta::KeyMintTa;
+use kmr_tests::host::{self, exec};
+use kmr_wire::keymint::{Algorithm, ErrorCode, KeyParam, KeyPurpose};
+use kmr_wire::*;
+
+fn keymint_ta(strict: bool) -> KeyMintTa {
+    let mut km = host::keymint_ta(host::crypto_impl(None), host::device_impl());
+    km.set_strict_cbor(strict);
+    km
+}
+
+fn get_characteristics(km: &mut KeyMintTa, key_blob: Vec<u8>) -> Result<(), ErrorCode> {
+    let req = PerformOpReq::DeviceGetKeyCharacteristics(GetKeyCharacteristicsRequest {
+        key_blob,
+        app_id: vec![],
+        app_data: vec![],
+    });
+    exec(km, req).map(|_| ())
+}
+
+#[test]
+fn test_strict_cbor_request() {
+    // `DeviceGetHardwareInfo` request, with the operation code encoded canonically and
+    // non-canonically.
+    let canonical = hex::decode("821180").unwrap();
+    let non_canonical = hex::decode("82181180").unwrap();
+
+    for strict in [false, true] {
+        let mut km = keymint_ta(strict);
+        let rsp = PerformOpResponse::from_slice(&km.process(&canonical)).unwrap();
+        assert_eq!(rsp.error_code, 0, "strict={strict}");
+
+        let rsp = PerformOpResponse::from_slice(&km.process(&non_canonical)).unwrap();
+        let want = if strict { ErrorCode::EncodingError as i32 } else { 0 };
+        assert_eq!(rsp.error_code, want, "strict={strict}");
+    }
+}
+
+#[test]
+fn test_strict_cbor_keyblob() {
+    for strict in [false, true] {
+        let mut km = keymint_ta(strict);
+        let key_blob = host::generate_key(
+            &mut km,
+            vec![
+                KeyParam::Algorithm(Algorithm::Aes),
+                KeyParam::KeySize(KeySizeInBits(128)),
+                KeyParam::Purpose(KeyPurpose::Encrypt),
+                KeyParam::NoAuthRequired,
+            ],
+        )
+        .unwrap()
+        .key_blob;
+        assert!(check_canonical(&key_blob).is_ok());
+        assert_eq!(get_characteristics(&mut km, key_blob.clone()), Ok(()), "strict={strict}");
+
+        // Re-encode the leading version number (0) of the keyblob non-minimally.
+        assert_eq!(&key_blob[..2], &[0x82, 0x00]);
+        let mut non_canonical = vec![0x82, 0x18, 0x00];
+        non_canonical.extend_from_slice(&key_blob[2..]);
+        let want = if strict { Err(ErrorCode::InvalidKeyBlob) } else { Ok(()) };
+        assert_eq!(get_characteristics(&mut km, non_canonical), want, "strict={strict}");
+    }
+}
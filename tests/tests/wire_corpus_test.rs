@@ -0,0 +1,86 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Integration test: the checked-in corpus of CBOR encodings for HAL<->TA messages is canonical,
+// still decodes and re-encodes byte-for-byte, and matches the encodings of the current sample
+// values.
+
+use kmr_tests::wire_corpus::{
+    all_codes, all_tags, corpus_text, parse, sample_key_param, PARAM_PREFIX, REQ_PREFIX, RSP_PREFIX,
+};
+use kmr_wire::keymint::KeyParam;
+use kmr_wire::{check_canonical, AsCborValue, PerformOpReq, PerformOpResponse};
+use std::collections::BTreeSet;
+
+const GOLDEN: &str = include_str!("../testdata/wire_corpus.txt");
+
+#[test]
+fn test_wire_corpus_round_trip() {
+    for entry in parse(GOLDEN) {
+        let name = &entry.name;
+        check_canonical(&entry.data)
+            .unwrap_or_else(|e| panic!("non-canonical encoding for {name}: {e:?}"));
+        let reencoded = if let Some(code) = name.strip_prefix(REQ_PREFIX) {
+            let req = PerformOpReq::from_slice(&entry.data)
+                .unwrap_or_else(|e| panic!("failed to decode {name}: {e:?}"));
+            assert_eq!(format!("{:?}", req.code()), code, "wrong request code for {name}");
+            req.into_vec().unwrap()
+        } else if name.starts_with(RSP_PREFIX) {
+            let rsp = PerformOpResponse::from_slice(&entry.data)
+                .unwrap_or_else(|e| panic!("failed to decode {name}: {e:?}"));
+            rsp.into_vec().unwrap()
+        } else if let Some(tag) = name.strip_prefix(PARAM_PREFIX) {
+            let param = KeyParam::from_slice(&entry.data)
+                .unwrap_or_else(|e| panic!("failed to decode {name}: {e:?}"));
+            assert_eq!(format!("{:?}", param.tag()), tag, "wrong tag for {name}");
+            param.into_vec().unwrap()
+        } else {
+            panic!("unexpected corpus entry {name}");
+        };
+        assert_eq!(hex::encode(reencoded), hex::encode(&entry.data), "for {name}");
+    }
+}
+
+#[test]
+fn test_wire_corpus_complete() {
+    let names: BTreeSet<String> = parse(GOLDEN).into_iter().map(|entry| entry.name).collect();
+    for code in all_codes() {
+        for prefix in [REQ_PREFIX, RSP_PREFIX] {
+            let name = format!("{prefix}{code:?}");
+            assert!(names.contains(&name), "corpus has no entry for {name}");
+        }
+    }
+    for tag in all_tags() {
+        let name = format!("{PARAM_PREFIX}{tag:?}");
+        match sample_key_param(tag) {
+            Some(param) => {
+                assert_eq!(param.tag(), tag);
+                assert!(names.contains(&name), "corpus has no entry for {name}");
+            }
+            None => assert!(!names.contains(&name), "corpus has entry for non-param {name}"),
+        }
+    }
+}
+
+#[test]
+fn test_wire_corpus_up_to_date() {
+    // A mismatch here means that either the wire format or the sample values have changed.  If the
+    // wire format change is intentional (e.g. for a new HAL version), run `scripts/wire-corpus-gen`
+    // to regenerate the corpus.
+    let want = corpus_text();
+    for (got_line, want_line) in GOLDEN.lines().zip(want.lines()) {
+        assert_eq!(got_line, want_line, "corpus is out of date");
+    }
+    assert_eq!(GOLDEN.lines().count(), want.lines().count(), "corpus is out of date");
+}
@@ -0,0 +1,77 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Types for the audit log of security-relevant events in the TA (a vendor extension).
+
+use crate::keymint::{ErrorCode, KeyParam};
+use crate::{cbor_type_error, try_from_n, AsCborValue, CborError};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use enumn::N;
+use kmr_derive::AsCborValue;
+
+/// Type of a security-relevant event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, AsCborValue, N)]
+#[repr(i32)]
+pub enum AuditEvent {
+    /// A key was generated.
+    KeyGenerated = 1,
+    /// A key was imported (including import of a wrapped key).
+    KeyImported = 2,
+    /// A key was upgraded; the key ID identifies the new keyblob.
+    KeyUpgraded = 3,
+    /// A key was deleted.
+    KeyDeleted = 4,
+    /// All keys were deleted.
+    AllKeysDeleted = 5,
+    /// An operation was rejected because of a missing or invalid auth token.
+    AuthFailed = 6,
+    /// A key with a usage limit was used up.
+    UseCountExhausted = 7,
+    /// A secure deletion slot was allocated for a rollback-resistant key.
+    SecureDeletionSlotCreated = 8,
+    /// A secure deletion slot was released, making the corresponding key unusable.
+    SecureDeletionSlotDeleted = 9,
+    /// Device attestation IDs were (or failed to be) permanently destroyed.
+    AttestationIdsDestroyed = 10,
+    /// Boot information was set, or an attempt to change it was rejected.
+    BootInfoChanged = 11,
+    /// A key was derived from the output of a key agreement.
+    KeyDerived = 12,
+}
+try_from_n!(AuditEvent);
+
+/// Record of a single security-relevant event.  Fields that are not relevant to the event are
+/// left empty.
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
+pub struct AuditRecord {
+    /// Sequence number of the record, which increases by one for each event recorded since the TA
+    /// started.
+    pub sequence: u64,
+    /// Time of the event in milliseconds, if the TA has a clock.
+    pub timestamp_ms: Option<i64>,
+    /// Type of event, which determines which of the remaining fields are filled in.
+    pub event: AuditEvent,
+    /// Identifier of the keyblob involved, as HMAC-SHA256 of the keyblob with an all-zero key.
+    pub key_id: Option<Vec<u8>>,
+    /// Summary of the key characteristics, or other parameters that describe the event.
+    pub params: Vec<KeyParam>,
+    /// Secure deletion slot involved.
+    pub slot: Option<u32>,
+    /// Error code for events that record a failure.
+    pub error: Option<ErrorCode>,
+}
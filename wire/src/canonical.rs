@@ -0,0 +1,212 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Check for deterministically encoded CBOR, as described in RFC 8949 section 4.2.1.
+//!
+//! The check is made on the raw data (before it is converted to a [`cbor::value::Value`]), because
+//! the conversion discards the details of how each item was encoded.
+
+use crate::{cbor, CborError, EndOfFile};
+use core::cmp::Ordering;
+
+/// Maximum nesting depth of the data that is checked.  This only bounds the recursion of the
+/// check; decoding into a [`cbor::value::Value`] applies its own (lower) limit.
+const MAX_DEPTH: usize = 64;
+
+/// Check that `data` holds a single CBOR item that is encoded deterministically:
+/// - integers, lengths and tags use the shortest possible encoding of their argument
+/// - floating point values use the shortest encoding that preserves their value
+/// - there are no indefinite-length strings, arrays or maps
+/// - the keys of each map are unique and sorted by the bytewise lexicographic order of their
+///   encodings.
+///
+/// Nested CBOR that is held inside a byte string is not checked.
+pub fn check_canonical(data: &[u8]) -> Result<(), CborError> {
+    let mut reader = Reader { data, pos: 0 };
+    reader.item(0)?;
+    if reader.pos == data.len() {
+        Ok(())
+    } else {
+        Err(CborError::ExtraneousData)
+    }
+}
+
+/// Reader that walks over the raw encoding of CBOR items.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: u64) -> Result<&'a [u8], CborError> {
+        let remaining = self.data.len() - self.pos;
+        let len = match usize::try_from(len) {
+            Ok(len) if len <= remaining => len,
+            _ => return Err(CborError::DecodeFailed(cbor::de::Error::Io(EndOfFile))),
+        };
+        let result = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(result)
+    }
+
+    fn byte(&mut self) -> Result<u8, CborError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn uint(&mut self, len: u64) -> Result<u64, CborError> {
+        Ok(self.bytes(len)?.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+    }
+
+    fn syntax_error(&self) -> CborError {
+        CborError::DecodeFailed(cbor::de::Error::Syntax(self.pos - 1))
+    }
+
+    /// Read the argument that follows an initial byte with additional information `info`,
+    /// checking that it is minimally encoded.
+    fn argument(&mut self, info: u8) -> Result<u64, CborError> {
+        let (value, min) = match info {
+            0..=23 => return Ok(info as u64),
+            24 => (self.uint(1)?, 24),
+            25 => (self.uint(2)?, 0x100),
+            26 => (self.uint(4)?, 0x1_0000),
+            27 => (self.uint(8)?, 0x1_0000_0000),
+            31 => return Err(CborError::NonCanonical("indefinite-length item")),
+            _ => return Err(self.syntax_error()),
+        };
+        if value < min {
+            Err(CborError::NonCanonical("non-minimal argument"))
+        } else {
+            Ok(value)
+        }
+    }
+
+    fn item(&mut self, depth: usize) -> Result<(), CborError> {
+        if depth > MAX_DEPTH {
+            return Err(CborError::DecodeFailed(cbor::de::Error::RecursionLimitExceeded));
+        }
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        if major == 7 {
+            return self.simple_or_float(info);
+        }
+        let arg = self.argument(info)?;
+        match major {
+            // Unsigned and negative integers.
+            0 | 1 => {}
+            // Byte and text strings.
+            2 | 3 => {
+                self.bytes(arg)?;
+            }
+            // Array.  Every item occupies at least one byte, so a bogus length fails promptly.
+            4 => {
+                for _ in 0..arg {
+                    self.item(depth + 1)?;
+                }
+            }
+            // Map.
+            5 => {
+                let mut prev_key: Option<&[u8]> = None;
+                for _ in 0..arg {
+                    let start = self.pos;
+                    self.item(depth + 1)?;
+                    let key = &self.data[start..self.pos];
+                    if let Some(prev_key) = prev_key {
+                        match prev_key.cmp(key) {
+                            Ordering::Less => {}
+                            Ordering::Equal => {
+                                return Err(CborError::NonCanonical("duplicate map key"))
+                            }
+                            Ordering::Greater => {
+                                return Err(CborError::NonCanonical("unsorted map keys"))
+                            }
+                        }
+                    }
+                    prev_key = Some(key);
+                    self.item(depth + 1)?;
+                }
+            }
+            // Tag.
+            _ => self.item(depth + 1)?,
+        }
+        Ok(())
+    }
+
+    fn simple_or_float(&mut self, info: u8) -> Result<(), CborError> {
+        match info {
+            0..=23 => Ok(()),
+            24 => {
+                // Simple values below 32 must use the single byte encoding.
+                if self.byte()? < 32 {
+                    Err(CborError::NonCanonical("non-minimal simple value"))
+                } else {
+                    Ok(())
+                }
+            }
+            25 => self.bytes(2).map(|_| ()),
+            26 => {
+                if f32_fits_f16(self.uint(4)? as u32) {
+                    Err(CborError::NonCanonical("non-minimal float"))
+                } else {
+                    Ok(())
+                }
+            }
+            27 => {
+                if f64_fits_f32(self.uint(8)?) {
+                    Err(CborError::NonCanonical("non-minimal float"))
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Err(self.syntax_error()),
+        }
+    }
+}
+
+/// Indicate whether the single-precision float with the given bits can be represented exactly as
+/// a half-precision float.
+fn f32_fits_f16(bits: u32) -> bool {
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    match exp {
+        // Infinity or NaN, for which any payload must fit in the 10-bit half-precision mantissa.
+        0xff => mantissa & 0x1fff == 0,
+        // Zero; single-precision subnormals are too small for half-precision.
+        0 => mantissa == 0,
+        _ => {
+            let exp = exp - 127;
+            if (-14..=15).contains(&exp) {
+                // Normal half-precision value.
+                mantissa & 0x1fff == 0
+            } else if (-24..-14).contains(&exp) {
+                // Subnormal half-precision value, which is a multiple of 2^-24.
+                let full = mantissa | 0x80_0000;
+                full & ((1 << (-exp - 1)) - 1) == 0
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// Indicate whether the double-precision float with the given bits can be represented exactly as
+/// a single-precision float.
+fn f64_fits_f32(bits: u64) -> bool {
+    let value = f64::from_bits(bits);
+    if value.is_nan() {
+        // The NaN payload must fit in the 23-bit single-precision mantissa.
+        bits & 0x1fff_ffff == 0
+    } else {
+        (value as f32) as f64 == value
+    }
+}
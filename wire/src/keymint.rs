@@ -27,7 +27,7 @@
 //! - `KeyParameterValue` is not included here.
 
 use crate::{
-    cbor, cbor_type_error, try_from_n, vec_try, AsCborValue, CborError, KeySizeInBits, RsaExponent,
+    cbor, cbor_type_error, try_from_n, AsCborValue, CborError, KeySizeInBits, RsaExponent,
 };
 use alloc::format;
 use alloc::string::{String, ToString};
@@ -134,6 +134,14 @@ pub enum Algorithm {
     Aes = 32,
     TripleDes = 33,
     Hmac = 128,
+    /// Vendor extension (experimental): ML-DSA (FIPS 204) signing key, with the parameter set
+    /// indicated by a `KEY_SIZE` of 65 (ML-DSA-65) or 87 (ML-DSA-87).  Only supported by
+    /// implementations built with the `pqc` feature.
+    MlDsa = 0x1000_0001,
+    /// Vendor extension (experimental): ML-KEM-768 (FIPS 203) key encapsulation key, indicated by
+    /// a `KEY_SIZE` of 768 and used with `KeyPurpose::AgreeKey`.  Only supported by
+    /// implementations built with the `pqc` feature.
+    MlKem = 0x1000_0002,
 }
 try_from_n!(Algorithm);
 
@@ -320,6 +328,14 @@ pub enum KeyFormat {
     X509 = 0,
     Pkcs8 = 1,
     Raw = 3,
+    /// Vendor extension: DER-encoded SEC1 `ECPrivateKey` (RFC 5915), optionally followed by a
+    /// DER-encoded `SubjectPublicKeyInfo` for the corresponding public key.  Only accepted if the
+    /// device's key policy enables vendor import formats.
+    Sec1 = 0x1000_0001,
+    /// Vendor extension: DER-encoded PKCS#1 `RSAPrivateKey` (RFC 8017 A.1.2), optionally followed
+    /// by a DER-encoded `SubjectPublicKeyInfo` for the corresponding public key.  Only accepted if
+    /// the device's key policy enables vendor import formats.
+    Pkcs1 = 0x1000_0002,
 }
 try_from_n!(KeyFormat);
 
@@ -343,70 +359,132 @@ pub enum KeyOrigin {
 }
 try_from_n!(KeyOrigin);
 
-/// Rust exhaustive enum for all key parameters.
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Rust exhaustive enum for all key parameters, encoded to match the serialization of the HAL
+/// `Tag` / `KeyParameterValue` types.  Boolean parameters are represented by a `true` value (false
+/// values are represented by the absence of the tag).
+#[derive(Clone, Debug, PartialEq, Eq, AsCborValue)]
 pub enum KeyParam {
+    #[cbor(tag = Tag::Purpose)]
     Purpose(KeyPurpose),
+    #[cbor(tag = Tag::Algorithm)]
     Algorithm(Algorithm),
+    #[cbor(tag = Tag::KeySize)]
     KeySize(KeySizeInBits),
+    #[cbor(tag = Tag::BlockMode)]
     BlockMode(BlockMode),
+    #[cbor(tag = Tag::Digest)]
     Digest(Digest),
+    #[cbor(tag = Tag::Padding)]
     Padding(PaddingMode),
+    #[cbor(tag = Tag::CallerNonce, flag)]
     CallerNonce,
+    #[cbor(tag = Tag::MinMacLength)]
     MinMacLength(u32),
+    #[cbor(tag = Tag::EcCurve)]
     EcCurve(EcCurve),
+    #[cbor(tag = Tag::RsaPublicExponent)]
     RsaPublicExponent(RsaExponent),
+    #[cbor(tag = Tag::IncludeUniqueId, flag)]
     IncludeUniqueId,
+    #[cbor(tag = Tag::RsaOaepMgfDigest)]
     RsaOaepMgfDigest(Digest),
+    #[cbor(tag = Tag::BootloaderOnly, flag)]
     BootloaderOnly,
+    #[cbor(tag = Tag::RollbackResistance, flag)]
     RollbackResistance,
+    #[cbor(tag = Tag::EarlyBootOnly, flag)]
     EarlyBootOnly,
+    #[cbor(tag = Tag::ActiveDatetime)]
     ActiveDatetime(DateTime),
+    #[cbor(tag = Tag::OriginationExpireDatetime)]
     OriginationExpireDatetime(DateTime),
+    #[cbor(tag = Tag::UsageExpireDatetime)]
     UsageExpireDatetime(DateTime),
+    #[cbor(tag = Tag::MaxUsesPerBoot)]
     MaxUsesPerBoot(u32),
+    #[cbor(tag = Tag::UsageCountLimit)]
     UsageCountLimit(u32),
+    #[cbor(tag = Tag::UserId)]
     UserId(u32),
+    #[cbor(tag = Tag::UserSecureId)]
     UserSecureId(u64),
+    #[cbor(tag = Tag::NoAuthRequired, flag)]
     NoAuthRequired,
+    #[cbor(tag = Tag::UserAuthType)]
     UserAuthType(u32),
+    #[cbor(tag = Tag::AuthTimeout)]
     AuthTimeout(u32),
+    #[cbor(tag = Tag::AllowWhileOnBody, flag)]
     AllowWhileOnBody,
+    #[cbor(tag = Tag::TrustedUserPresenceRequired, flag)]
     TrustedUserPresenceRequired,
+    #[cbor(tag = Tag::TrustedConfirmationRequired, flag)]
     TrustedConfirmationRequired,
+    #[cbor(tag = Tag::UnlockedDeviceRequired, flag)]
     UnlockedDeviceRequired,
+    #[cbor(tag = Tag::ApplicationId)]
     ApplicationId(Vec<u8>),
+    #[cbor(tag = Tag::ApplicationData)]
     ApplicationData(Vec<u8>),
+    #[cbor(tag = Tag::CreationDatetime)]
     CreationDatetime(DateTime),
+    #[cbor(tag = Tag::Origin)]
     Origin(KeyOrigin),
+    #[cbor(tag = Tag::RootOfTrust)]
     RootOfTrust(Vec<u8>),
+    #[cbor(tag = Tag::OsVersion)]
     OsVersion(u32),
+    #[cbor(tag = Tag::OsPatchlevel)]
     OsPatchlevel(u32),
+    #[cbor(tag = Tag::AttestationChallenge)]
     AttestationChallenge(Vec<u8>),
+    #[cbor(tag = Tag::AttestationApplicationId)]
     AttestationApplicationId(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdBrand)]
     AttestationIdBrand(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdDevice)]
     AttestationIdDevice(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdProduct)]
     AttestationIdProduct(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdSerial)]
     AttestationIdSerial(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdImei)]
     AttestationIdImei(Vec<u8>),
     #[cfg(feature = "hal_v3")]
+    #[cbor(tag = Tag::AttestationIdSecondImei)]
     AttestationIdSecondImei(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdMeid)]
     AttestationIdMeid(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdManufacturer)]
     AttestationIdManufacturer(Vec<u8>),
+    #[cbor(tag = Tag::AttestationIdModel)]
     AttestationIdModel(Vec<u8>),
+    #[cbor(tag = Tag::VendorPatchlevel)]
     VendorPatchlevel(u32),
+    #[cbor(tag = Tag::BootPatchlevel)]
     BootPatchlevel(u32),
+    #[cbor(tag = Tag::DeviceUniqueAttestation, flag)]
     DeviceUniqueAttestation,
+    #[cbor(tag = Tag::StorageKey, flag)]
     StorageKey,
+    #[cbor(tag = Tag::Nonce)]
     Nonce(Vec<u8>),
+    #[cbor(tag = Tag::MacLength)]
     MacLength(u32),
+    #[cbor(tag = Tag::ResetSinceIdRotation, flag)]
     ResetSinceIdRotation,
+    #[cbor(tag = Tag::CertificateSerial)]
     CertificateSerial(Vec<u8>),
+    #[cbor(tag = Tag::CertificateSubject)]
     CertificateSubject(Vec<u8>),
+    #[cbor(tag = Tag::CertificateNotBefore)]
     CertificateNotBefore(DateTime),
+    #[cbor(tag = Tag::CertificateNotAfter)]
     CertificateNotAfter(DateTime),
+    #[cbor(tag = Tag::MaxBootLevel)]
     MaxBootLevel(u32),
     #[cfg(feature = "hal_v4")]
+    #[cbor(tag = Tag::ModuleHash)]
     ModuleHash(Vec<u8>),
 }
 
@@ -479,606 +557,6 @@ impl KeyParam {
     }
 }
 
-/// Check that a `bool` value is true (false values are represented by the absence of a tag).
-fn check_bool(value: cbor::value::Value) -> Result<(), crate::CborError> {
-    match value {
-        cbor::value::Value::Bool(true) => Ok(()),
-        cbor::value::Value::Bool(false) => Err(crate::CborError::UnexpectedItem("false", "true")),
-        _ => crate::cbor_type_error(&value, "true"),
-    }
-}
-
-/// Manual implementation of [`crate::AsCborValue`] for the [`KeyParam`] enum that
-/// matches the serialization of the HAL `Tag` / `KeyParameterValue` types.
-impl crate::AsCborValue for KeyParam {
-    fn from_cbor_value(value: cbor::value::Value) -> Result<Self, crate::CborError> {
-        let mut a = match value {
-            cbor::value::Value::Array(a) => a,
-            _ => return crate::cbor_type_error(&value, "arr"),
-        };
-        if a.len() != 2 {
-            return Err(crate::CborError::UnexpectedItem("arr", "arr len 2"));
-        }
-
-        // Need to know the tag value to completely parse the value.
-        let raw = a.remove(1);
-        let tag = <Tag>::from_cbor_value(a.remove(0))?;
-
-        Ok(match tag {
-            Tag::Algorithm => KeyParam::Algorithm(<Algorithm>::from_cbor_value(raw)?),
-            Tag::BlockMode => KeyParam::BlockMode(<BlockMode>::from_cbor_value(raw)?),
-            Tag::Padding => KeyParam::Padding(<PaddingMode>::from_cbor_value(raw)?),
-            Tag::Digest => KeyParam::Digest(<Digest>::from_cbor_value(raw)?),
-            Tag::EcCurve => KeyParam::EcCurve(<EcCurve>::from_cbor_value(raw)?),
-            Tag::Origin => KeyParam::Origin(<KeyOrigin>::from_cbor_value(raw)?),
-            Tag::Purpose => KeyParam::Purpose(<KeyPurpose>::from_cbor_value(raw)?),
-            Tag::KeySize => KeyParam::KeySize(<KeySizeInBits>::from_cbor_value(raw)?),
-            Tag::CallerNonce => KeyParam::CallerNonce,
-            Tag::MinMacLength => KeyParam::MinMacLength(<u32>::from_cbor_value(raw)?),
-            Tag::RsaPublicExponent => {
-                KeyParam::RsaPublicExponent(<RsaExponent>::from_cbor_value(raw)?)
-            }
-            Tag::IncludeUniqueId => {
-                check_bool(raw)?;
-                KeyParam::IncludeUniqueId
-            }
-            Tag::RsaOaepMgfDigest => KeyParam::RsaOaepMgfDigest(<Digest>::from_cbor_value(raw)?),
-            Tag::BootloaderOnly => {
-                check_bool(raw)?;
-                KeyParam::BootloaderOnly
-            }
-            Tag::RollbackResistance => {
-                check_bool(raw)?;
-                KeyParam::RollbackResistance
-            }
-            Tag::EarlyBootOnly => {
-                check_bool(raw)?;
-                KeyParam::EarlyBootOnly
-            }
-            Tag::ActiveDatetime => KeyParam::ActiveDatetime(<DateTime>::from_cbor_value(raw)?),
-            Tag::OriginationExpireDatetime => {
-                KeyParam::OriginationExpireDatetime(<DateTime>::from_cbor_value(raw)?)
-            }
-            Tag::UsageExpireDatetime => {
-                KeyParam::UsageExpireDatetime(<DateTime>::from_cbor_value(raw)?)
-            }
-            Tag::MaxUsesPerBoot => KeyParam::MaxUsesPerBoot(<u32>::from_cbor_value(raw)?),
-            Tag::UsageCountLimit => KeyParam::UsageCountLimit(<u32>::from_cbor_value(raw)?),
-            Tag::UserId => KeyParam::UserId(<u32>::from_cbor_value(raw)?),
-            Tag::UserSecureId => KeyParam::UserSecureId(<u64>::from_cbor_value(raw)?),
-            Tag::NoAuthRequired => {
-                check_bool(raw)?;
-                KeyParam::NoAuthRequired
-            }
-            Tag::UserAuthType => KeyParam::UserAuthType(<u32>::from_cbor_value(raw)?),
-            Tag::AuthTimeout => KeyParam::AuthTimeout(<u32>::from_cbor_value(raw)?),
-            Tag::AllowWhileOnBody => KeyParam::AllowWhileOnBody,
-            Tag::TrustedUserPresenceRequired => {
-                check_bool(raw)?;
-                KeyParam::TrustedUserPresenceRequired
-            }
-            Tag::TrustedConfirmationRequired => {
-                check_bool(raw)?;
-                KeyParam::TrustedConfirmationRequired
-            }
-            Tag::UnlockedDeviceRequired => {
-                check_bool(raw)?;
-                KeyParam::UnlockedDeviceRequired
-            }
-            Tag::ApplicationId => KeyParam::ApplicationId(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::ApplicationData => KeyParam::ApplicationData(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::CreationDatetime => KeyParam::CreationDatetime(<DateTime>::from_cbor_value(raw)?),
-            Tag::RootOfTrust => KeyParam::RootOfTrust(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::OsVersion => KeyParam::OsVersion(<u32>::from_cbor_value(raw)?),
-            Tag::OsPatchlevel => KeyParam::OsPatchlevel(<u32>::from_cbor_value(raw)?),
-            Tag::AttestationChallenge => {
-                KeyParam::AttestationChallenge(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationApplicationId => {
-                KeyParam::AttestationApplicationId(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdBrand => {
-                KeyParam::AttestationIdBrand(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdDevice => {
-                KeyParam::AttestationIdDevice(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdProduct => {
-                KeyParam::AttestationIdProduct(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdSerial => {
-                KeyParam::AttestationIdSerial(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdImei => KeyParam::AttestationIdImei(<Vec<u8>>::from_cbor_value(raw)?),
-            #[cfg(feature = "hal_v3")]
-            Tag::AttestationIdSecondImei => {
-                KeyParam::AttestationIdSecondImei(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdMeid => KeyParam::AttestationIdMeid(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::AttestationIdManufacturer => {
-                KeyParam::AttestationIdManufacturer(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::AttestationIdModel => {
-                KeyParam::AttestationIdModel(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::VendorPatchlevel => KeyParam::VendorPatchlevel(<u32>::from_cbor_value(raw)?),
-            Tag::BootPatchlevel => KeyParam::BootPatchlevel(<u32>::from_cbor_value(raw)?),
-            Tag::DeviceUniqueAttestation => {
-                check_bool(raw)?;
-                KeyParam::DeviceUniqueAttestation
-            }
-            Tag::StorageKey => KeyParam::StorageKey,
-            Tag::Nonce => KeyParam::Nonce(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::MacLength => KeyParam::MacLength(<u32>::from_cbor_value(raw)?),
-            Tag::ResetSinceIdRotation => {
-                check_bool(raw)?;
-                KeyParam::ResetSinceIdRotation
-            }
-            Tag::CertificateSerial => KeyParam::CertificateSerial(<Vec<u8>>::from_cbor_value(raw)?),
-            Tag::CertificateSubject => {
-                KeyParam::CertificateSubject(<Vec<u8>>::from_cbor_value(raw)?)
-            }
-            Tag::CertificateNotBefore => {
-                KeyParam::CertificateNotBefore(<DateTime>::from_cbor_value(raw)?)
-            }
-            Tag::CertificateNotAfter => {
-                KeyParam::CertificateNotAfter(<DateTime>::from_cbor_value(raw)?)
-            }
-            Tag::MaxBootLevel => KeyParam::MaxBootLevel(<u32>::from_cbor_value(raw)?),
-            #[cfg(feature = "hal_v4")]
-            Tag::ModuleHash => KeyParam::ModuleHash(<Vec<u8>>::from_cbor_value(raw)?),
-
-            _ => return Err(crate::CborError::UnexpectedItem("tag", "known tag")),
-        })
-    }
-    fn to_cbor_value(self) -> Result<cbor::value::Value, crate::CborError> {
-        let (tag, val) = match self {
-            KeyParam::Algorithm(v) => (Tag::Algorithm, v.to_cbor_value()?),
-            KeyParam::BlockMode(v) => (Tag::BlockMode, v.to_cbor_value()?),
-            KeyParam::Padding(v) => (Tag::Padding, v.to_cbor_value()?),
-            KeyParam::Digest(v) => (Tag::Digest, v.to_cbor_value()?),
-            KeyParam::EcCurve(v) => (Tag::EcCurve, v.to_cbor_value()?),
-            KeyParam::Origin(v) => (Tag::Origin, v.to_cbor_value()?),
-            KeyParam::Purpose(v) => (Tag::Purpose, v.to_cbor_value()?),
-            KeyParam::KeySize(v) => (Tag::KeySize, v.to_cbor_value()?),
-            KeyParam::CallerNonce => (Tag::CallerNonce, true.to_cbor_value()?),
-            KeyParam::MinMacLength(v) => (Tag::MinMacLength, v.to_cbor_value()?),
-            KeyParam::RsaPublicExponent(v) => (Tag::RsaPublicExponent, v.to_cbor_value()?),
-            KeyParam::IncludeUniqueId => (Tag::IncludeUniqueId, true.to_cbor_value()?),
-            KeyParam::RsaOaepMgfDigest(v) => (Tag::RsaOaepMgfDigest, v.to_cbor_value()?),
-            KeyParam::BootloaderOnly => (Tag::BootloaderOnly, true.to_cbor_value()?),
-            KeyParam::RollbackResistance => (Tag::RollbackResistance, true.to_cbor_value()?),
-            KeyParam::EarlyBootOnly => (Tag::EarlyBootOnly, true.to_cbor_value()?),
-            KeyParam::ActiveDatetime(v) => (Tag::ActiveDatetime, v.to_cbor_value()?),
-            KeyParam::OriginationExpireDatetime(v) => {
-                (Tag::OriginationExpireDatetime, v.to_cbor_value()?)
-            }
-            KeyParam::UsageExpireDatetime(v) => (Tag::UsageExpireDatetime, v.to_cbor_value()?),
-            KeyParam::MaxUsesPerBoot(v) => (Tag::MaxUsesPerBoot, v.to_cbor_value()?),
-            KeyParam::UsageCountLimit(v) => (Tag::UsageCountLimit, v.to_cbor_value()?),
-            KeyParam::UserId(v) => (Tag::UserId, v.to_cbor_value()?),
-            KeyParam::UserSecureId(v) => (Tag::UserSecureId, v.to_cbor_value()?),
-            KeyParam::NoAuthRequired => (Tag::NoAuthRequired, true.to_cbor_value()?),
-            KeyParam::UserAuthType(v) => (Tag::UserAuthType, v.to_cbor_value()?),
-            KeyParam::AuthTimeout(v) => (Tag::AuthTimeout, v.to_cbor_value()?),
-            KeyParam::AllowWhileOnBody => (Tag::AllowWhileOnBody, true.to_cbor_value()?),
-            KeyParam::TrustedUserPresenceRequired => {
-                (Tag::TrustedUserPresenceRequired, true.to_cbor_value()?)
-            }
-            KeyParam::TrustedConfirmationRequired => {
-                (Tag::TrustedConfirmationRequired, true.to_cbor_value()?)
-            }
-            KeyParam::UnlockedDeviceRequired => {
-                (Tag::UnlockedDeviceRequired, true.to_cbor_value()?)
-            }
-            KeyParam::ApplicationId(v) => (Tag::ApplicationId, v.to_cbor_value()?),
-            KeyParam::ApplicationData(v) => (Tag::ApplicationData, v.to_cbor_value()?),
-            KeyParam::CreationDatetime(v) => (Tag::CreationDatetime, v.to_cbor_value()?),
-            KeyParam::RootOfTrust(v) => (Tag::RootOfTrust, v.to_cbor_value()?),
-            KeyParam::OsVersion(v) => (Tag::OsVersion, v.to_cbor_value()?),
-            KeyParam::OsPatchlevel(v) => (Tag::OsPatchlevel, v.to_cbor_value()?),
-            KeyParam::AttestationChallenge(v) => (Tag::AttestationChallenge, v.to_cbor_value()?),
-            KeyParam::AttestationApplicationId(v) => {
-                (Tag::AttestationApplicationId, v.to_cbor_value()?)
-            }
-            KeyParam::AttestationIdBrand(v) => (Tag::AttestationIdBrand, v.to_cbor_value()?),
-            KeyParam::AttestationIdDevice(v) => (Tag::AttestationIdDevice, v.to_cbor_value()?),
-            KeyParam::AttestationIdProduct(v) => (Tag::AttestationIdProduct, v.to_cbor_value()?),
-            KeyParam::AttestationIdSerial(v) => (Tag::AttestationIdSerial, v.to_cbor_value()?),
-            KeyParam::AttestationIdImei(v) => (Tag::AttestationIdImei, v.to_cbor_value()?),
-            #[cfg(feature = "hal_v3")]
-            KeyParam::AttestationIdSecondImei(v) => {
-                (Tag::AttestationIdSecondImei, v.to_cbor_value()?)
-            }
-            KeyParam::AttestationIdMeid(v) => (Tag::AttestationIdMeid, v.to_cbor_value()?),
-            KeyParam::AttestationIdManufacturer(v) => {
-                (Tag::AttestationIdManufacturer, v.to_cbor_value()?)
-            }
-            KeyParam::AttestationIdModel(v) => (Tag::AttestationIdModel, v.to_cbor_value()?),
-            KeyParam::VendorPatchlevel(v) => (Tag::VendorPatchlevel, v.to_cbor_value()?),
-            KeyParam::BootPatchlevel(v) => (Tag::BootPatchlevel, v.to_cbor_value()?),
-            KeyParam::DeviceUniqueAttestation => {
-                (Tag::DeviceUniqueAttestation, true.to_cbor_value()?)
-            }
-            KeyParam::StorageKey => (Tag::StorageKey, true.to_cbor_value()?),
-            KeyParam::Nonce(v) => (Tag::Nonce, v.to_cbor_value()?),
-            KeyParam::MacLength(v) => (Tag::MacLength, v.to_cbor_value()?),
-            KeyParam::ResetSinceIdRotation => (Tag::ResetSinceIdRotation, true.to_cbor_value()?),
-            KeyParam::CertificateSerial(v) => (Tag::CertificateSerial, v.to_cbor_value()?),
-            KeyParam::CertificateSubject(v) => (Tag::CertificateSubject, v.to_cbor_value()?),
-            KeyParam::CertificateNotBefore(v) => (Tag::CertificateNotBefore, v.to_cbor_value()?),
-            KeyParam::CertificateNotAfter(v) => (Tag::CertificateNotAfter, v.to_cbor_value()?),
-            KeyParam::MaxBootLevel(v) => (Tag::MaxBootLevel, v.to_cbor_value()?),
-            #[cfg(feature = "hal_v4")]
-            KeyParam::ModuleHash(v) => (Tag::ModuleHash, v.to_cbor_value()?),
-        };
-        Ok(cbor::value::Value::Array(vec_try![tag.to_cbor_value()?, val]?))
-    }
-    fn cddl_typename() -> Option<String> {
-        Some("KeyParam".to_string())
-    }
-    fn cddl_schema() -> Option<String> {
-        let mut result = "&(\n".to_string();
-
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::Algorithm as i32,
-            Algorithm::cddl_ref(),
-            "Tag_Algorithm"
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::BlockMode as i32,
-            BlockMode::cddl_ref(),
-            "Tag_BlockMode",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::Padding as i32,
-            PaddingMode::cddl_ref(),
-            "Tag_Padding",
-        );
-        result +=
-            &format!("    [{}, {}], ; {}\n", Tag::Digest as i32, Digest::cddl_ref(), "Tag_Digest",);
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::EcCurve as i32,
-            EcCurve::cddl_ref(),
-            "Tag_EcCurve",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::Origin as i32,
-            KeyOrigin::cddl_ref(),
-            "Tag_Origin",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::Purpose as i32,
-            KeyPurpose::cddl_ref(),
-            "Tag_Purpose",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::KeySize as i32,
-            KeySizeInBits::cddl_ref(),
-            "Tag_KeySize",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CallerNonce as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_CallerNonce",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::MinMacLength as i32,
-            u32::cddl_ref(),
-            "Tag_MinMacLength",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::RsaPublicExponent as i32,
-            RsaExponent::cddl_ref(),
-            "Tag_RsaPublicExponent",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::IncludeUniqueId as i32,
-            "true",
-            "Tag_IncludeUniqueId",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::RsaOaepMgfDigest as i32,
-            Digest::cddl_ref(),
-            "Tag_RsaOaepMgfDigest",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::BootloaderOnly as i32,
-            "true",
-            "Tag_BootloaderOnly",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::RollbackResistance as i32,
-            "true",
-            "Tag_RollbackResistance",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::EarlyBootOnly as i32,
-            "true",
-            "Tag_EarlyBootOnly",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::ActiveDatetime as i32,
-            DateTime::cddl_ref(),
-            "Tag_ActiveDatetime",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::OriginationExpireDatetime as i32,
-            DateTime::cddl_ref(),
-            "Tag_OriginationExpireDatetime",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::UsageExpireDatetime as i32,
-            DateTime::cddl_ref(),
-            "Tag_UsageExpireDatetime",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::MaxUsesPerBoot as i32,
-            u32::cddl_ref(),
-            "Tag_MaxUsesPerBoot",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::UsageCountLimit as i32,
-            u32::cddl_ref(),
-            "Tag_UsageCountLimit",
-        );
-        result +=
-            &format!("    [{}, {}], ; {}\n", Tag::UserId as i32, u32::cddl_ref(), "Tag_UserId",);
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::UserSecureId as i32,
-            u64::cddl_ref(),
-            "Tag_UserSecureId",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::NoAuthRequired as i32,
-            "true",
-            "Tag_NoAuthRequired",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::UserAuthType as i32,
-            u32::cddl_ref(),
-            "Tag_UserAuthType",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AuthTimeout as i32,
-            u32::cddl_ref(),
-            "Tag_AuthTimeout",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AllowWhileOnBody as i32,
-            "true",
-            "Tag_AllowWhileOnBody",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::TrustedUserPresenceRequired as i32,
-            "true",
-            "Tag_TrustedUserPresenceRequired",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::TrustedConfirmationRequired as i32,
-            "true",
-            "Tag_TrustedConfirmationRequired",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::UnlockedDeviceRequired as i32,
-            "true",
-            "Tag_UnlockedDeviceRequired",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::ApplicationId as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_ApplicationId",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::ApplicationData as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_ApplicationData",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CreationDatetime as i32,
-            DateTime::cddl_ref(),
-            "Tag_CreationDatetime",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::RootOfTrust as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_RootOfTrust",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::OsVersion as i32,
-            u32::cddl_ref(),
-            "Tag_OsVersion",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::OsPatchlevel as i32,
-            u32::cddl_ref(),
-            "Tag_OsPatchlevel",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationChallenge as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationChallenge",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationApplicationId as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationApplicationId",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdBrand as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdBrand",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdDevice as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdDevice",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdProduct as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdProduct",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdSerial as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdSerial",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdImei as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdImei",
-        );
-        #[cfg(feature = "hal_v3")]
-        {
-            result += &format!(
-                "    [{}, {}], ; {}\n",
-                Tag::AttestationIdSecondImei as i32,
-                Vec::<u8>::cddl_ref(),
-                "Tag_AttestationIdSecondImei",
-            );
-        }
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdMeid as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdMeid",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdManufacturer as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdManufacturer",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::AttestationIdModel as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_AttestationIdModel",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::VendorPatchlevel as i32,
-            u32::cddl_ref(),
-            "Tag_VendorPatchlevel",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::BootPatchlevel as i32,
-            u32::cddl_ref(),
-            "Tag_BootPatchlevel",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::DeviceUniqueAttestation as i32,
-            "true",
-            "Tag_DeviceUniqueAttestation",
-        );
-        result +=
-            &format!("    [{}, {}], ; {}\n", Tag::StorageKey as i32, "true", "Tag_StorageKey",);
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::Nonce as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_Nonce",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::MacLength as i32,
-            u32::cddl_ref(),
-            "Tag_MacLength",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::ResetSinceIdRotation as i32,
-            "true",
-            "Tag_ResetSinceIdRotation",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CertificateSerial as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_CertificateSerial",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CertificateSubject as i32,
-            Vec::<u8>::cddl_ref(),
-            "Tag_CertificateSubject",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CertificateNotBefore as i32,
-            DateTime::cddl_ref(),
-            "Tag_CertificateNotBefore",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::CertificateNotAfter as i32,
-            DateTime::cddl_ref(),
-            "Tag_CertificateNotAfter",
-        );
-        result += &format!(
-            "    [{}, {}], ; {}\n",
-            Tag::MaxBootLevel as i32,
-            u32::cddl_ref(),
-            "Tag_MaxBootLevel",
-        );
-        #[cfg(feature = "hal_v4")]
-        {
-            result += &format!(
-                "    [{}, {}], ; {}\n",
-                Tag::ModuleHash as i32,
-                Vec::<u8>::cddl_ref(),
-                "Tag_ModuleHash",
-            );
-        }
-        result += ")";
-        Some(result)
-    }
-}
-
 /// Determine the tag type for a tag, based on the top 4 bits of the tag number.
 pub fn tag_type(tag: Tag) -> TagType {
     match ((tag as u32) & 0xf0000000u32) as i32 {
@@ -60,7 +60,7 @@ use crate::{
 };
 use alloc::vec::Vec;
 use enumn::N;
-use kmr_derive::LegacySerialize;
+use kmr_derive::{LegacyDeserialize, LegacySerialize};
 use zeroize::ZeroizeOnDrop;
 
 /// This bit is set in the `u32` command value for response messages.
@@ -286,13 +286,18 @@ pub fn serialize_trusty_secure_error_rsp(
     })
 }
 
-/// Trait that serializes an inner message to/from the format used by the legacy C++ Keymaster code.
-pub trait InnerSerialize: Sized {
-    fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error>;
+/// Trait that serializes an inner message to the format used by the legacy C++ Keymaster code.
+pub trait InnerSerialize {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error>;
 }
 
-impl InnerSerialize for u64 {
+/// Trait that deserializes an inner message from the format used by the legacy C++ Keymaster code,
+/// returning the message and any remaining data.
+pub trait InnerDeserialize: Sized {
+    fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error>;
+}
+
+impl InnerDeserialize for u64 {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         if data.len() < 8 {
             return Err(Error::DataTruncated);
@@ -300,6 +305,9 @@ impl InnerSerialize for u64 {
         let int_data: [u8; 8] = data[..8].try_into().map_err(|_e| Error::DataTruncated)?;
         Ok((<u64>::from_ne_bytes(int_data), &data[8..]))
     }
+}
+
+impl InnerSerialize for u64 {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(8).map_err(|_e| Error::AllocationFailed)?;
         buf.extend_from_slice(&self.to_ne_bytes());
@@ -307,7 +315,7 @@ impl InnerSerialize for u64 {
     }
 }
 
-impl InnerSerialize for u32 {
+impl InnerDeserialize for u32 {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         if data.len() < 4 {
             return Err(Error::DataTruncated);
@@ -315,6 +323,9 @@ impl InnerSerialize for u32 {
         let int_data: [u8; 4] = data[..4].try_into().map_err(|_e| Error::DataTruncated)?;
         Ok((<u32>::from_ne_bytes(int_data), &data[4..]))
     }
+}
+
+impl InnerSerialize for u32 {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(4).map_err(|_e| Error::AllocationFailed)?;
         buf.extend_from_slice(&self.to_ne_bytes());
@@ -322,13 +333,16 @@ impl InnerSerialize for u32 {
     }
 }
 
-impl InnerSerialize for u8 {
+impl InnerDeserialize for u8 {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         if data.is_empty() {
             return Err(Error::DataTruncated);
         }
         Ok((data[0], &data[1..]))
     }
+}
+
+impl InnerSerialize for u8 {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(1).map_err(|_e| Error::AllocationFailed)?;
         buf.push(*self);
@@ -336,17 +350,20 @@ impl InnerSerialize for u8 {
     }
 }
 
-impl InnerSerialize for bool {
+impl InnerDeserialize for bool {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (v, rest) = <u32>::deserialize(data)?;
         Ok((v != 0, rest))
     }
+}
+
+impl InnerSerialize for bool {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         (*self as u32).serialize_into(buf)
     }
 }
 
-impl InnerSerialize for Vec<u8> {
+impl InnerDeserialize for Vec<u8> {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (len, rest) = <u32>::deserialize(data)?;
         let len = len as usize;
@@ -358,6 +375,9 @@ impl InnerSerialize for Vec<u8> {
         buf.extend_from_slice(&rest[..len]);
         Ok((buf, &rest[len..]))
     }
+}
+
+impl InnerSerialize for Vec<u8> {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(4 + self.len()).map_err(|_e| Error::AllocationFailed)?;
         let len = self.len() as u32;
@@ -367,31 +387,40 @@ impl InnerSerialize for Vec<u8> {
     }
 }
 
-impl InnerSerialize for KmVersion {
+impl InnerDeserialize for KmVersion {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (v, rest) = <u32>::deserialize(data)?;
         Ok((Self::try_from(v as i32).map_err(|_e| Error::InvalidEnumValue(v))?, rest))
     }
+}
+
+impl InnerSerialize for KmVersion {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         (*self as u32).serialize_into(buf)
     }
 }
 
-impl InnerSerialize for Algorithm {
+impl InnerDeserialize for Algorithm {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (v, rest) = <u32>::deserialize(data)?;
         Ok((Self::try_from(v as i32).map_err(|_e| Error::InvalidEnumValue(v))?, rest))
     }
+}
+
+impl InnerSerialize for Algorithm {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         (*self as u32).serialize_into(buf)
     }
 }
 
-impl InnerSerialize for VerifiedBootState {
+impl InnerDeserialize for VerifiedBootState {
     fn deserialize(data: &[u8]) -> Result<(Self, &[u8]), Error> {
         let (v, rest) = <u32>::deserialize(data)?;
         Ok((Self::try_from(v as i32).map_err(|_e| Error::InvalidEnumValue(v))?, rest))
     }
+}
+
+impl InnerSerialize for VerifiedBootState {
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         (*self as u32).serialize_into(buf)
     }
@@ -399,43 +428,43 @@ impl InnerSerialize for VerifiedBootState {
 
 // Legacy messages of interest from `android_keymaster_messages.h`.
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetVersionRequest {}
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetVersionResponse {
     pub major_ver: u8,
     pub minor_ver: u8,
     pub subminor_ver: u8,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetVersion2Request {
     pub max_message_version: u32,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetVersion2Response {
     pub max_message_version: u32,
     pub km_version: KmVersion,
     pub km_date: u32,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ConfigureBootPatchlevelRequest {
     pub boot_patchlevel: u32, // YYYMMDD
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ConfigureBootPatchlevelResponse {}
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ConfigureVerifiedBootInfoRequest {
     pub boot_state: Vec<u8>,
     pub bootloader_state: Vec<u8>,
     pub vbmeta_digest: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ConfigureVerifiedBootInfoResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct SetAttestationIdsRequest {
     pub brand: Vec<u8>,
     pub device: Vec<u8>,
@@ -446,20 +475,20 @@ pub struct SetAttestationIdsRequest {
     pub manufacturer: Vec<u8>,
     pub model: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetAttestationIdsResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct SetAttestationIdsKM3Request {
     pub base: SetAttestationIdsRequest,
     pub second_imei: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetAttestationIdsKM3Response {}
 
 // Legacy messages of interest from `trusty_keymaster_messages.h`.
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetAuthTokenKeyRequest {}
 #[derive(Clone, PartialEq, Eq, ZeroizeOnDrop)]
 pub struct GetAuthTokenKeyResponse {
@@ -468,11 +497,8 @@ pub struct GetAuthTokenKeyResponse {
 
 /// The serialization of a `GET_AUTH_TOKEN_KEY` response does not include a length field before the
 /// contents of the key, so the auto-derive implementation can't be used. (This also means that
-/// `deserialize()` can't be implemented, because there is no length information available.)
+/// [`InnerDeserialize`] can't be implemented, because there is no length information available.)
 impl InnerSerialize for GetAuthTokenKeyResponse {
-    fn deserialize(_data: &[u8]) -> Result<(Self, &[u8]), Error> {
-        Err(Error::UnexpectedResponse)
-    }
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(self.key_material.len()).map_err(|_e| Error::AllocationFailed)?;
         buf.extend_from_slice(&self.key_material);
@@ -480,7 +506,7 @@ impl InnerSerialize for GetAuthTokenKeyResponse {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetDeviceInfoRequest {}
 #[derive(Clone, PartialEq, Eq, ZeroizeOnDrop)]
 pub struct GetDeviceInfoResponse {
@@ -489,12 +515,9 @@ pub struct GetDeviceInfoResponse {
 }
 
 /// The serialization of a `GET_DEVICE_INFO` response does not include a length field before the
-/// contents, so the auto-derive implementation can't be used. (This also means that `deserialize()`
-/// can't be implemented, because there is no length information available.)
+/// contents, so the auto-derive implementation can't be used. (This also means that
+/// [`InnerDeserialize`] can't be implemented, because there is no length information available.)
 impl InnerSerialize for GetDeviceInfoResponse {
-    fn deserialize(_data: &[u8]) -> Result<(Self, &[u8]), Error> {
-        Err(Error::UnexpectedResponse)
-    }
     fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
         buf.try_reserve(self.device_ids.len()).map_err(|_e| Error::AllocationFailed)?;
         buf.extend_from_slice(&self.device_ids);
@@ -502,14 +525,14 @@ impl InnerSerialize for GetDeviceInfoResponse {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetUdsCertsRequest {}
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct GetUdsCertsResponse {
     pub uds_certs: Vec<u8>,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetBootParamsRequest {
     pub os_version: u32,
     pub os_patchlevel: u32, // YYYYMM
@@ -518,53 +541,53 @@ pub struct SetBootParamsRequest {
     pub verified_boot_key: Vec<u8>,
     pub verified_boot_hash: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetBootParamsResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct SetAttestationKeyRequest {
     #[zeroize(skip)]
     pub algorithm: Algorithm,
     pub key_data: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetAttestationKeyResponse {}
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct AppendAttestationCertChainRequest {
     pub algorithm: Algorithm,
     pub cert_data: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct AppendAttestationCertChainResponse {}
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ClearAttestationCertChainRequest {
     pub algorithm: Algorithm,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ClearAttestationCertChainResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct SetWrappedAttestationKeyRequest {
     #[zeroize(skip)]
     pub algorithm: Algorithm,
     pub key_data: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct SetWrappedAttestationKeyResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct AppendUdsCertificateRequest {
     pub cert_data: Vec<u8>,
 }
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct AppendUdsCertificateResponse {}
 
-#[derive(Clone, PartialEq, Eq, LegacySerialize, ZeroizeOnDrop)]
+#[derive(Clone, PartialEq, Eq, LegacySerialize, LegacyDeserialize, ZeroizeOnDrop)]
 pub struct ClearUdsCertificateRequest {}
 
-#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize)]
+#[derive(Clone, PartialEq, Eq, Debug, LegacySerialize, LegacyDeserialize)]
 pub struct ClearUdsCertificateResponse {}
 
 macro_rules! declare_req_rsp_enums {
@@ -816,4 +839,195 @@ mod tests {
         let got_data = serialize_trusty_secure_rsp(msg).unwrap();
         assert_eq!(hex::encode(got_data), data);
     }
+
+    /// Deterministic pseudo-random source (xorshift64) for the round-trip property tests.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn choose<T: Copy>(&mut self, vals: &[T]) -> T {
+            vals[(self.next() % vals.len() as u64) as usize]
+        }
+    }
+
+    /// Generate a random instance of a type.
+    trait Random {
+        fn random(rng: &mut Rng) -> Self;
+    }
+
+    impl Random for u8 {
+        fn random(rng: &mut Rng) -> Self {
+            rng.next() as u8
+        }
+    }
+    impl Random for u32 {
+        fn random(rng: &mut Rng) -> Self {
+            rng.next() as u32
+        }
+    }
+    impl Random for u64 {
+        fn random(rng: &mut Rng) -> Self {
+            rng.next()
+        }
+    }
+    impl Random for bool {
+        fn random(rng: &mut Rng) -> Self {
+            rng.next() & 1 == 1
+        }
+    }
+    impl Random for Vec<u8> {
+        fn random(rng: &mut Rng) -> Self {
+            let len = rng.next() % 40;
+            (0..len).map(|_| rng.next() as u8).collect()
+        }
+    }
+    impl Random for KmVersion {
+        fn random(rng: &mut Rng) -> Self {
+            rng.choose(&[
+                KmVersion::Keymaster1,
+                KmVersion::Keymaster11,
+                KmVersion::Keymaster2,
+                KmVersion::Keymaster3,
+                KmVersion::Keymaster4,
+                KmVersion::Keymaster41,
+                KmVersion::KeyMint1,
+                KmVersion::KeyMint2,
+                KmVersion::KeyMint3,
+            ])
+        }
+    }
+    impl Random for Algorithm {
+        fn random(rng: &mut Rng) -> Self {
+            rng.choose(&[
+                Algorithm::Rsa,
+                Algorithm::Ec,
+                Algorithm::Aes,
+                Algorithm::TripleDes,
+                Algorithm::Hmac,
+            ])
+        }
+    }
+    impl Random for VerifiedBootState {
+        fn random(rng: &mut Rng) -> Self {
+            rng.choose(&[
+                VerifiedBootState::Verified,
+                VerifiedBootState::SelfSigned,
+                VerifiedBootState::Unverified,
+                VerifiedBootState::Failed,
+            ])
+        }
+    }
+
+    macro_rules! random_structs {
+        { $( $typ:ident { $( $field:ident ),* } )* } => {
+            $(
+                impl Random for $typ {
+                    #[allow(unused_variables)]
+                    fn random(rng: &mut Rng) -> Self {
+                        Self { $( $field: Random::random(rng), )* }
+                    }
+                }
+            )*
+            /// Check that random instances of every listed message type round-trip.
+            fn check_all_round_trip(rng: &mut Rng) {
+                $( check_round_trip::<$typ>(rng); )*
+            }
+        };
+    }
+
+    random_structs! {
+        GetVersionRequest {}
+        GetVersionResponse { major_ver, minor_ver, subminor_ver }
+        GetVersion2Request { max_message_version }
+        GetVersion2Response { max_message_version, km_version, km_date }
+        ConfigureBootPatchlevelRequest { boot_patchlevel }
+        ConfigureBootPatchlevelResponse {}
+        ConfigureVerifiedBootInfoRequest { boot_state, bootloader_state, vbmeta_digest }
+        ConfigureVerifiedBootInfoResponse {}
+        SetAttestationIdsRequest {
+            brand, device, product, serial, imei, meid, manufacturer, model
+        }
+        SetAttestationIdsResponse {}
+        SetAttestationIdsKM3Request { base, second_imei }
+        SetAttestationIdsKM3Response {}
+        GetAuthTokenKeyRequest {}
+        GetDeviceInfoRequest {}
+        GetUdsCertsRequest {}
+        GetUdsCertsResponse { uds_certs }
+        SetBootParamsRequest {
+            os_version,
+            os_patchlevel,
+            device_locked,
+            verified_boot_state,
+            verified_boot_key,
+            verified_boot_hash
+        }
+        SetBootParamsResponse {}
+        SetAttestationKeyRequest { algorithm, key_data }
+        SetAttestationKeyResponse {}
+        AppendAttestationCertChainRequest { algorithm, cert_data }
+        AppendAttestationCertChainResponse {}
+        ClearAttestationCertChainRequest { algorithm }
+        ClearAttestationCertChainResponse {}
+        SetWrappedAttestationKeyRequest { algorithm, key_data }
+        SetWrappedAttestationKeyResponse {}
+        AppendUdsCertificateRequest { cert_data }
+        AppendUdsCertificateResponse {}
+        ClearUdsCertificateRequest {}
+        ClearUdsCertificateResponse {}
+    }
+
+    fn check_round_trip<T: Random + InnerSerialize + InnerDeserialize + PartialEq>(rng: &mut Rng) {
+        for _ in 0..50 {
+            let msg = T::random(rng);
+            let mut data = Vec::new();
+            msg.serialize_into(&mut data).unwrap();
+
+            let (got, rest) = T::deserialize(&data).unwrap();
+            assert!(rest.is_empty());
+            assert!(got == msg);
+
+            // Trailing data is left for the caller.
+            let mut extended = data.clone();
+            extended.extend_from_slice(&[0xaa, 0xbb]);
+            let (got, rest) = T::deserialize(&extended).unwrap();
+            assert_eq!(rest, &[0xaa, 0xbb]);
+            assert!(got == msg);
+
+            // Every truncation of the data is rejected.
+            for len in 0..data.len() {
+                assert!(T::deserialize(&data[..len]).is_err(), "truncated to {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_inner_round_trip() {
+        check_all_round_trip(&mut Rng(0x0123_4567_89ab_cdef));
+    }
+
+    #[test]
+    fn test_trusty_req_random_data() {
+        // Arbitrary data following a valid command code must be rejected cleanly (or accepted)
+        // rather than causing a panic.
+        let mut rng = Rng(0xfedc_ba98_7654_3210);
+        let codes: [u32; 10] =
+            [7, 28, 0x1000, 0x2000, 0x3000, 0xa000, 0xb000, 0xc000, 0xc001, 0xd0000];
+        let secure_codes: [u32; 4] = [0, 1, 2, 0xc000];
+        for _ in 0..1000 {
+            let random = <Vec<u8>>::random(&mut rng);
+            let mut data = (rng.choose(&codes) << TRUSTY_CMD_SHIFT).to_ne_bytes().to_vec();
+            data.extend_from_slice(&random);
+            let _ = deserialize_trusty_req(&data);
+
+            let mut data = (rng.choose(&secure_codes) << TRUSTY_CMD_SHIFT).to_ne_bytes().to_vec();
+            data.extend_from_slice(&random);
+            let _ = deserialize_trusty_secure_req(&data);
+        }
+    }
 }
@@ -32,6 +32,8 @@ pub use ciborium as cbor;
 /// Re-export of crate used for COSE encoding.
 pub use coset;
 
+pub mod audit;
+mod canonical;
 pub mod keymint;
 pub mod legacy;
 pub mod rpc;
@@ -40,6 +42,8 @@ pub mod sharedsecret;
 pub mod types;
 pub use types::*;
 
+pub use canonical::check_canonical;
+
 #[cfg(test)]
 mod tests;
 
@@ -184,6 +188,8 @@ pub enum CborError {
     InvalidValue,
     /// Allocation failure.
     AllocationFailed,
+    /// CBOR input was not deterministically encoded, when strict decoding was requested.
+    NonCanonical(&'static str),
 }
 
 // Can only implement `Into` due to orphan trait rule.
@@ -204,6 +210,7 @@ impl Into<coset::CoseError> for CborError {
             CborError::UnexpectedItem(got, want) => coset::CoseError::UnexpectedItem(got, want),
             CborError::InvalidValue => coset::CoseError::EncodeFailed,
             CborError::AllocationFailed => coset::CoseError::EncodeFailed,
+            CborError::NonCanonical(got) => coset::CoseError::UnexpectedItem(got, "canonical CBOR"),
         }
     }
 }
@@ -273,6 +280,7 @@ impl core::fmt::Debug for CborError {
             CborError::UnexpectedItem(got, want) => write!(f, "got {}, expected {}", got, want),
             CborError::InvalidValue => write!(f, "invalid CBOR value"),
             CborError::AllocationFailed => write!(f, "allocation failed"),
+            CborError::NonCanonical(msg) => write!(f, "non-canonical CBOR: {}", msg),
         }
     }
 }
@@ -306,6 +314,13 @@ pub fn read_to_value(mut slice: &[u8]) -> Result<cbor::value::Value, CborError>
     }
 }
 
+/// Read a [`cbor::value::Value`] from a byte slice, as for [`read_to_value`], but also failing if
+/// the data is not deterministically encoded (see [`check_canonical`]).
+pub fn read_to_value_strict(slice: &[u8]) -> Result<cbor::value::Value, CborError> {
+    check_canonical(slice)?;
+    read_to_value(slice)
+}
+
 /// Trait for types that can be converted to/from a [`cbor::value::Value`].
 pub trait AsCborValue: Sized {
     /// Convert a [`cbor::value::Value`] into an instance of the type.
@@ -319,6 +334,12 @@ pub trait AsCborValue: Sized {
         Self::from_cbor_value(read_to_value(slice)?)
     }
 
+    /// Create an object instance from serialized CBOR data in a slice, rejecting data that is not
+    /// deterministically encoded.
+    fn from_slice_strict(slice: &[u8]) -> Result<Self, CborError> {
+        Self::from_cbor_value(read_to_value_strict(slice)?)
+    }
+
     /// Serialize this object to a vector, consuming it along the way.
     fn into_vec(self) -> Result<Vec<u8>, CborError> {
         let mut data = Vec::new();
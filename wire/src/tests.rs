@@ -43,3 +43,93 @@ fn test_read_to_value_fail() {
         assert_eq!(format!("{:?}", got_err), format!("{:?}", want_err), "failed for {}", hexdata);
     }
 }
+
+#[test]
+fn test_perform_op_response_diagnostics() {
+    let tests = vec![
+        // Without diagnostics, the response is encoded as a 2-array (as for older TAs).
+        (None, "82382080"),
+        (Some("bad".to_string()), "8338208063626164"),
+    ];
+    for (diagnostics, want_hex) in tests {
+        let rsp =
+            PerformOpResponse { error_code: -33, rsp: None, diagnostics: diagnostics.clone() };
+        let data = rsp.into_vec().unwrap();
+        assert_eq!(hex::encode(&data), want_hex);
+        let got = PerformOpResponse::from_slice(&data).unwrap();
+        assert_eq!(got.error_code, -33);
+        assert!(got.rsp.is_none());
+        assert_eq!(got.diagnostics, diagnostics);
+    }
+
+    // A diagnostics element must be a string, and there can't be anything after it.
+    for hexdata in ["8338208001", "833820806362616401"] {
+        let data = hex::decode(hexdata).unwrap();
+        assert!(PerformOpResponse::from_slice(&data).is_err(), "for {hexdata}");
+    }
+}
+
+#[test]
+fn test_check_canonical_ok() {
+    let tests = [
+        "00",                 // 0
+        "17",                 // 23
+        "1818",               // 24
+        "1901f4",             // 500
+        "3903e7",             // -1000
+        "4401020304",         // h'01020304'
+        "6161",               // "a"
+        "8301820203820405",   // [1, [2, 3], [4, 5]]
+        "a201020304",         // {1: 2, 3: 4}
+        "a2012018ff00",       // {1: -1, 255: 0}, sorted by encoded key
+        "a2616101616202",     // {"a": 1, "b": 2}
+        "c0636e6f77",         // tag 0
+        "f4",                 // false
+        "f93c00",             // 1.0
+        "fa47c35000",         // 100000.0
+        "fb3fb999999999999a", // 0.1
+        "f97e00",             // NaN
+    ];
+    for hexdata in tests {
+        let data = hex::decode(hexdata).unwrap();
+        assert!(check_canonical(&data).is_ok(), "failed for {}", hexdata);
+        // Anything that is canonical must also be valid.
+        let _ = read_to_value_strict(&data).unwrap();
+    }
+}
+
+#[test]
+fn test_check_canonical_fail() {
+    let tests = [
+        ("1817", CborError::NonCanonical("non-minimal argument")),
+        ("190017", CborError::NonCanonical("non-minimal argument")),
+        ("1a000000ff", CborError::NonCanonical("non-minimal argument")),
+        ("1b00000000ffffffff", CborError::NonCanonical("non-minimal argument")),
+        ("3800", CborError::NonCanonical("non-minimal argument")),
+        ("5801ff", CborError::NonCanonical("non-minimal argument")),
+        ("81980101", CborError::NonCanonical("non-minimal argument")),
+        ("5f41014102ff", CborError::NonCanonical("indefinite-length item")),
+        ("9f01ff", CborError::NonCanonical("indefinite-length item")),
+        ("bf0102ff", CborError::NonCanonical("indefinite-length item")),
+        ("a203040102", CborError::NonCanonical("unsorted map keys")),
+        ("a218ff000120", CborError::NonCanonical("unsorted map keys")),
+        ("a201020103", CborError::NonCanonical("duplicate map key")),
+        ("81a201020103", CborError::NonCanonical("duplicate map key")),
+        ("f814", CborError::NonCanonical("non-minimal simple value")),
+        ("fa3f800000", CborError::NonCanonical("non-minimal float")),
+        ("fb3ff0000000000000", CborError::NonCanonical("non-minimal float")),
+        ("fb7ff8000000000000", CborError::NonCanonical("non-minimal float")),
+        ("0101", CborError::ExtraneousData),
+        ("43", CborError::DecodeFailed(cbor::de::Error::Io(EndOfFile))),
+        ("82", CborError::DecodeFailed(cbor::de::Error::Io(EndOfFile))),
+        ("1c", CborError::DecodeFailed(cbor::de::Error::Syntax(0))),
+    ];
+    for (hexdata, want_err) in tests {
+        let data = hex::decode(hexdata).unwrap();
+        let got_err = check_canonical(&data).expect_err("check expected to fail");
+        assert_eq!(format!("{:?}", got_err), format!("{:?}", want_err), "failed for {}", hexdata);
+        assert!(read_to_value_strict(&data).is_err(), "strict decode succeeded for {}", hexdata);
+    }
+    // The lenient decoder accepts non-canonical data.
+    assert!(read_to_value(&hex::decode("a203040102").unwrap()).is_ok());
+}
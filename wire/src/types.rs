@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::audit::AuditRecord;
 use crate::keymint::{
     AttestationKey, HardwareAuthToken, KeyCharacteristics, KeyCreationResult, KeyFormat,
     KeyMintHardwareInfo, KeyParam, KeyPurpose,
@@ -62,6 +63,7 @@ pub enum ValueNotRecognized {
     KmVersion,
     EekCurve,
     Origin,
+    AuditEvent,
     // Non-enum types.
     Bool,
     Blob,
@@ -279,6 +281,22 @@ pub struct FinishRequest {
 pub struct FinishResponse {
     pub ret: Vec<u8>,
 }
+/// Vendor extension: finish a key agreement operation, deriving a new AES or HMAC key from the
+/// shared secret (using HKDF-SHA256) rather than returning the shared secret.
+#[derive(Debug, AsCborValue)]
+pub struct FinishDeriveKeyRequest {
+    pub op_handle: i64, // Extra for internal use, from `InternalBeginResult`.
+    pub input: Option<Vec<u8>>,
+    pub auth_token: Option<HardwareAuthToken>,
+    pub timestamp_token: Option<TimeStampToken>,
+    pub hkdf_salt: Vec<u8>,
+    pub hkdf_info: Vec<u8>,
+    pub key_params: Vec<KeyParam>,
+}
+#[derive(AsCborValue)]
+pub struct FinishDeriveKeyResponse {
+    pub ret: KeyCreationResult,
+}
 #[derive(Debug, AsCborValue)]
 pub struct AbortRequest {
     pub op_handle: i64, // Extra for internal use, from `InternalBeginResult`.
@@ -385,6 +403,16 @@ pub struct SetBootInfoRequest {
 #[derive(Debug, AsCborValue)]
 pub struct SetBootInfoResponse {}
 
+// HAL->TA as needed (vendor extension): retrieve and clear the contents of the TA's audit log.
+#[derive(Debug, AsCborValue)]
+pub struct DrainAuditLogRequest {}
+#[derive(Debug, AsCborValue)]
+pub struct DrainAuditLogResponse {
+    pub records: Vec<AuditRecord>,
+    /// Number of records discarded because the audit log was full since it was last drained.
+    pub dropped: u64,
+}
+
 /// Attestation ID information.
 #[derive(Clone, Debug, AsCborValue, PartialEq, Eq, Default)]
 pub struct AttestationIdInfo {
@@ -410,10 +438,44 @@ pub struct SetAttestationIdsResponse {}
 
 // Result of an operation, as an error code and a response message (only present when
 // `error_code` is zero).
-#[derive(AsCborValue)]
 pub struct PerformOpResponse {
     pub error_code: i32,
     pub rsp: Option<PerformOpRsp>,
+    /// Diagnostic message describing a failure.  Only emitted by TAs built for debugging, and
+    /// encoded as an optional trailing array element so that HAL and TA versions that don't know
+    /// about it can still interoperate.
+    pub diagnostics: Option<String>,
+}
+
+impl AsCborValue for PerformOpResponse {
+    fn from_cbor_value(value: cbor::value::Value) -> Result<Self, CborError> {
+        let mut a = match value {
+            cbor::value::Value::Array(a) if a.len() == 2 || a.len() == 3 => a,
+            _ => return cbor_type_error(&value, "arr len 2 or 3"),
+        };
+        let diagnostics =
+            if a.len() == 3 { Some(<String>::from_cbor_value(a.remove(2))?) } else { None };
+        let rsp = <Option<PerformOpRsp>>::from_cbor_value(a.remove(1))?;
+        let error_code = <i32>::from_cbor_value(a.remove(0))?;
+        Ok(Self { error_code, rsp, diagnostics })
+    }
+    fn to_cbor_value(self) -> Result<cbor::value::Value, CborError> {
+        let mut v = vec_try![self.error_code.to_cbor_value()?, self.rsp.to_cbor_value()?]?;
+        if let Some(diagnostics) = self.diagnostics {
+            v.try_reserve(1).map_err(|_e| CborError::AllocationFailed)?;
+            v.push(diagnostics.to_cbor_value()?);
+        }
+        Ok(cbor::value::Value::Array(v))
+    }
+    fn cddl_typename() -> Option<String> {
+        Some("PerformOpResponse".to_string())
+    }
+    fn cddl_schema() -> Option<String> {
+        Some(format!(
+            "[\n    error_code: int,\n    rsp: {},\n    ? diagnostics: tstr,\n]",
+            <Option<PerformOpRsp>>::cddl_ref()
+        ))
+    }
 }
 
 /// Declare a collection of related enums for a code and a pair of types.
@@ -532,6 +594,14 @@ macro_rules! declare_req_rsp_enums {
             $( $cname($rsptyp), )*
         }
 
+        impl $rspenum {
+            pub fn code(&self) -> $cenum {
+                match self {
+                    $( Self::$cname(_) => $cenum::$cname, )*
+                }
+            }
+        }
+
         impl AsCborValue for $reqenum {
             fn from_cbor_value(value: cbor::value::Value) -> Result<Self, CborError> {
                 let mut a = match value {
@@ -645,6 +715,8 @@ declare_req_rsp_enums! { KeyMintOperation  =>    (PerformOpReq, PerformOpRsp) {
     OperationUpdate = 0x32 =>                          (UpdateRequest, UpdateResponse),
     OperationFinish = 0x33 =>                          (FinishRequest, FinishResponse),
     OperationAbort = 0x34 =>                           (AbortRequest, AbortResponse),
+    // Vendor extension messages use values above 0x10000000.
+    OperationFinishDeriveKey = 0x10000033 =>           (FinishDeriveKeyRequest, FinishDeriveKeyResponse),
     RpcGetHardwareInfo = 0x41 =>                       (GetRpcHardwareInfoRequest, GetRpcHardwareInfoResponse),
     RpcGenerateEcdsaP256KeyPair = 0x42 =>              (GenerateEcdsaP256KeyPairRequest, GenerateEcdsaP256KeyPairResponse),
     RpcGenerateCertificateRequest = 0x43 =>            (GenerateCertificateRequestRequest, GenerateCertificateRequestResponse),
@@ -660,6 +732,7 @@ declare_req_rsp_enums! { KeyMintOperation  =>    (PerformOpReq, PerformOpRsp) {
     SetAttestationIds = 0x83 =>                        (SetAttestationIdsRequest, SetAttestationIdsResponse),
     SetHalVersion = 0x84 =>                            (SetHalVersionRequest, SetHalVersionResponse),
     SetAdditionalAttestationInfo = 0x91 =>             (SetAdditionalAttestationInfoRequest, SetAdditionalAttestationInfoResponse),
+    DrainAuditLog = 0x10000085 =>                      (DrainAuditLogRequest, DrainAuditLogResponse),
 } }
 
 /// Indicate whether an operation is part of the `IRemotelyProvisionedComponent` HAL.